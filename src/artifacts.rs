@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The branch prebuilt artifacts are committed to with [`ArtifactMode::OrphanBranch`], one directory per published [`Version`]
+///
+/// [`Version`]: crate::version::Version
+pub const ARTIFACTS_BRANCH: &str = "knapsac-artifacts";
+
+/// The directory inside an archive prebuilt artifacts are packed into with [`ArtifactMode::Archive`]
+pub const ARTIFACTS_DIRECTORY: &str = ".artifacts";
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`ArtifactMode`] tells where publishing a [`Package`] stores the prebuilt outputs of its [`Module`]s,
+/// see [`Package::set_artifact_mode`]
+/// Only outputs of [`Module`]s with an ABI tag are stored, installing prefers them when the tag matches the host
+///
+/// [`Module`]: crate::module::Module
+/// [`Package`]: crate::package::Package
+/// [`Package::set_artifact_mode`]: crate::package::Package::set_artifact_mode
+pub enum ArtifactMode {
+    /// The outputs are committed to [`ARTIFACTS_BRANCH`], a branch without history in common with the sources
+    OrphanBranch,
+    /// The outputs are committed on their own and tagged `artifacts/<version>`, next to the tag of the [`Version`]
+    ///
+    /// [`Version`]: crate::version::Version
+    Tag,
+    /// The outputs are packed into [`ARTIFACTS_DIRECTORY`] of the archive made by [`Package::pack`]
+    ///
+    /// [`Package::pack`]: crate::package::Package::pack
+    Archive,
+}
+
+impl ArtifactMode {
+    /// The git reference holding the prebuilt artifacts of the given tag, and the directory they are stored in below it
+    pub(crate) fn reference(&self, tag: &str) -> Option<(String, PathBuf)> {
+        match self {
+            ArtifactMode::OrphanBranch => Some((format!("refs/heads/{}", ARTIFACTS_BRANCH), PathBuf::from(tag))),
+            ArtifactMode::Tag => Some((format!("refs/tags/artifacts/{}", tag), PathBuf::new())),
+            ArtifactMode::Archive => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`PrebuiltReport`] lists which [`Module`]s [`Registry::restore_prebuilt`] restored prebuilt artifacts for
+///
+/// [`Module`]: crate::module::Module
+/// [`Registry::restore_prebuilt`]: crate::registry::Registry::restore_prebuilt
+pub struct PrebuiltReport {
+    pub(crate) restored: Vec<PathBuf>,
+    pub(crate) from_source: Vec<PathBuf>,
+}
+
+impl PrebuiltReport {
+    /// Returns the absolute source files of the [`Module`]s whose outputs were restored from prebuilt artifacts
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn restored(&self) -> &[PathBuf] {
+        &self.restored
+    }
+
+    /// Returns the absolute source files of the [`Module`]s that were published with prebuilt artifacts,
+    /// but need to be built from source as no artifact matches the host or it could not be retrieved
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn from_source(&self) -> &[PathBuf] {
+        &self.from_source
+    }
+
+    pub(crate) fn record(&mut self, source: &Path, restored: bool) {
+        match restored {
+            true => self.restored.push(source.to_path_buf()),
+            false => self.from_source.push(source.to_path_buf()),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::utils::sha256_hex;
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{read_to_string, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`AuditEntry`] records a single operation performed on a [`Registry`]
+///
+/// [`Registry`]: crate::registry::Registry
+pub struct AuditEntry {
+    pub(crate) user: String,
+    pub(crate) timestamp: u64,
+    pub(crate) operation: String,
+    pub(crate) arguments: Vec<String>,
+    pub(crate) result: String,
+    pub(crate) previous_hash: String,
+    pub(crate) hash: String,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> String {
+        let timestamp = self.timestamp.to_string();
+        let fields = [&self.previous_hash, &self.user, &timestamp, &self.operation]
+            .into_iter()
+            .chain(&self.arguments)
+            .chain([&self.result]);
+        let mut data = Vec::new();
+        for field in fields {
+            data.extend(field.as_bytes());
+            data.push(0);
+        }
+        sha256_hex(data)
+    }
+
+    /// Returns the name of the user that performed the operation
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// Returns when the operation was performed, in seconds since the unix epoch
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Returns the name of the operation
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Returns the arguments the operation was performed with
+    pub fn arguments(&self) -> &[String] {
+        &self.arguments
+    }
+
+    /// Returns the outcome of the operation
+    pub fn result(&self) -> &str {
+        &self.result
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`AuditLog`] is an append-only file of [`AuditEntry`]s, one JSON object per line
+/// Every [`AuditEntry`] contains a hash over its contents and the hash of the previous [`AuditEntry`],
+/// so changing or removing an [`AuditEntry`] breaks the chain
+pub struct AuditLog {
+    pub(crate) location: PathBuf,
+}
+
+impl AuditLog {
+    /// Opens the [`AuditLog`] at the given [`Path`], the file is created on the first append
+    ///
+    /// # Arguments
+    /// * `path` - A [`Path`] pointing to the file the [`AuditLog`] is stored in
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        AuditLog {
+            location: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Appends an [`AuditEntry`] performed by the current user
+    ///
+    /// # Arguments
+    /// * `operation` - The name of the operation
+    /// * `arguments` - The arguments the operation was performed with
+    /// * `result` - The outcome of the operation
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use knapsac_lib::audit::AuditLog;
+    ///
+    /// let path = env::temp_dir().join("audit_append.jsonl");
+    /// # let _ = fs::remove_file(&path);
+    /// let log = AuditLog::open(&path);
+    /// log.append("add", vec!["/tmp/json".to_string()], "ok");
+    /// log.append("remove", vec!["/tmp/json".to_string()], "ok");
+    /// assert_eq!(log.entries().unwrap().len(), 2);
+    /// assert!(log.verify().is_ok());
+    /// assert_eq!(log.query(|e| e.operation() == "remove").unwrap().len(), 1);
+    /// ```
+    pub fn append(&self, operation: &str, arguments: Vec<String>, result: &str) {
+        let previous_hash = self.last_line()
+            .and_then(|l| serde_json::from_str::<AuditEntry>(&l).ok())
+            .map(|e| e.hash)
+            .unwrap_or_default();
+        let mut entry = AuditEntry {
+            user: env::var("USER").or_else(|_| env::var("USERNAME")).unwrap_or_else(|_| String::from("unknown")),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            operation: operation.to_string(),
+            arguments,
+            result: result.to_string(),
+            previous_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.location).unwrap();
+        writeln!(file, "{}", serde_json::to_string(&entry).unwrap()).unwrap();
+    }
+
+    /// Returns the last line of the file, reading it backwards so appending does not read the whole [`AuditLog`]
+    fn last_line(&self) -> Option<String> {
+        let mut file = File::open(&self.location).ok()?;
+        let mut end = file.metadata().ok()?.len();
+        let mut tail = Vec::new();
+        loop {
+            let start = end.saturating_sub(4096);
+            let mut chunk = vec![0; (end - start) as usize];
+            file.seek(SeekFrom::Start(start)).ok()?;
+            file.read_exact(&mut chunk).ok()?;
+            chunk.extend(tail);
+            tail = chunk;
+
+            let line = tail.iter().rposition(|b| *b != b'\n').map_or(&tail[..0], |last| &tail[..=last]);
+            match line.iter().rposition(|b| *b == b'\n') {
+                Some(newline) => return Some(String::from_utf8_lossy(&line[newline + 1..]).to_string()),
+                None if start == 0 => return (!line.is_empty()).then(|| String::from_utf8_lossy(line).to_string()),
+                None => end = start,
+            }
+        }
+    }
+
+    /// Returns all [`AuditEntry`]s in the order they were appended
+    ///
+    /// # Errors
+    /// Returns the index of the first line that is not a valid [`AuditEntry`]
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, usize> {
+        match read_to_string(&self.location) {
+            Ok(data) => data
+                .lines()
+                .filter(|l| !l.is_empty())
+                .enumerate()
+                .map(|(index, l)| serde_json::from_str(l).map_err(|_| index))
+                .collect(),
+            Err(_) => Ok(vec![]),
+        }
+    }
+
+    /// Returns the [`AuditEntry`]s matching the given predicate
+    ///
+    /// # Arguments
+    /// * `predicate` - A function deciding which [`AuditEntry`]s are returned
+    ///
+    /// # Errors
+    /// Returns the index of the first line that is not a valid [`AuditEntry`], see [`AuditLog::entries`]
+    pub fn query<F: Fn(&AuditEntry) -> bool>(&self, predicate: F) -> Result<Vec<AuditEntry>, usize> {
+        Ok(self.entries()?.into_iter().filter(|e| predicate(e)).collect())
+    }
+
+    /// Checks whether the hash chain is intact
+    /// Returns the index of the first [`AuditEntry`] that was tampered with otherwise,
+    /// a line that is not a valid [`AuditEntry`] counts as tampered with
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use knapsac_lib::audit::AuditLog;
+    ///
+    /// let path = env::temp_dir().join("audit_verify.jsonl");
+    /// # let _ = fs::remove_file(&path);
+    /// let log = AuditLog::open(&path);
+    /// log.append("add", vec!["/tmp/a".to_string()], "ok");
+    /// log.append("add", vec!["/tmp/b".to_string()], "ok");
+    ///
+    /// let tampered = fs::read_to_string(&path).unwrap().replace("/tmp/b", "/tmp/c");
+    /// fs::write(&path, tampered).unwrap();
+    /// assert_eq!(log.verify(), Err(1));
+    ///
+    /// let truncated = fs::read_to_string(&path).unwrap().replacen("}", "", 1);
+    /// fs::write(&path, truncated).unwrap();
+    /// assert_eq!(log.verify(), Err(0));
+    /// assert_eq!(log.entries(), Err(0));
+    /// ```
+    pub fn verify(&self) -> Result<(), usize> {
+        let data = read_to_string(&self.location).unwrap_or_default();
+        let mut previous_hash = String::new();
+        for (index, line) in data.lines().filter(|l| !l.is_empty()).enumerate() {
+            let entry: AuditEntry = serde_json::from_str(line).map_err(|_| index)?;
+            if entry.previous_hash != previous_hash || entry.hash != entry.compute_hash() {
+                return Err(index);
+            }
+            previous_hash = entry.hash;
+        }
+        Ok(())
+    }
+}
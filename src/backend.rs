@@ -0,0 +1,125 @@
+use crate::fetcher::Fetcher;
+use crate::utils::unpack_archive;
+use crate::version::Version;
+
+use std::path::Path;
+use url::Url;
+
+/// A [`RegistryBackend`] is the transport used to publish packaged [`Package`]s to a package registry
+/// and to download them again
+///
+/// [`Package`]: crate::package::Package
+pub trait RegistryBackend {
+    /// A short name identifying the kind of registry, recorded as part of a [`Provenance`]
+    ///
+    /// [`Provenance`]: crate::fetcher::Provenance
+    fn name(&self) -> &str;
+
+    /// Publishes a packaged [`Package`]
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier the [`Package`] is published under
+    /// * `version` - The [`Version`] being published
+    /// * `archive` - The contents of the [`Package`] as a gzipped tarball
+    /// * `manifest` - The contents of the [`Package`]'s manifest
+    ///
+    /// [`Package`]: crate::package::Package
+    fn upload(&self, identifier: &str, version: &Version, archive: &[u8], manifest: &str) -> Result<(), String>;
+
+    /// Downloads the gzipped tarball found at `source`
+    ///
+    /// # Arguments
+    /// * `source` - An [`Url`] pointing to a packaged [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    fn download(&self, source: &Url) -> Result<Vec<u8>, String>;
+}
+
+/// A [`Fetcher`] that downloads packaged [`Package`]s through a [`RegistryBackend`] and unpacks them
+/// Archives are not content addressed, so a checksum is required, see [`Package::checksum`]
+///
+/// [`Package`]: crate::package::Package
+/// [`Package::checksum`]: crate::package::Package::checksum
+pub struct ArchiveFetcher<B: RegistryBackend>(pub B);
+
+impl<B: RegistryBackend> Fetcher for ArchiveFetcher<B> {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn fetch(&self, source: &Url, destination: &Path) -> Result<(), String> {
+        let archive = self.0.download(source)?;
+        unpack_archive(&archive, destination)
+    }
+}
+
+#[cfg(feature = "http")]
+/// A [`RegistryBackend`] talking to a package registry over HTTP(S), only available with the `http` feature enabled
+///
+/// Archives are uploaded with `PUT <endpoint>/packages/<identifier>/<version>.tar.gz`
+/// and manifests with `PUT <endpoint>/packages/<identifier>/<version>/manifest`
+///
+/// # Examples
+/// ```no_run
+/// # use std::env;
+/// # use url::Url;
+/// # use knapsac_lib::backend::{ArchiveFetcher, HttpBackend};
+/// # use knapsac_lib::registry::Registry;
+///
+/// let backend = HttpBackend::create(Url::parse("https://packages.example.com/api").unwrap(), None);
+/// let mut registry = Registry::initialize(env::temp_dir().join("registry_http.json"));
+/// let source = Url::parse("https://packages.example.com/api/packages/json/1.0.0.tar.gz").unwrap();
+/// registry.install(&ArchiveFetcher(backend), source, env::temp_dir(), Some("<checksum>"));
+/// ```
+pub struct HttpBackend {
+    endpoint: Url,
+    token: Option<String>,
+}
+
+#[cfg(feature = "http")]
+impl HttpBackend {
+    /// Creates a new [`HttpBackend`]
+    ///
+    /// # Arguments
+    /// * `endpoint` - The base [`Url`] of the registry API
+    /// * `token` - A bearer token sent along with every upload, if the registry requires one
+    pub fn create(endpoint: Url, token: Option<String>) -> Self {
+        HttpBackend { endpoint, token }
+    }
+
+    /// Returns the base [`Url`] of the registry API
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    fn put(&self, path: &str, content_type: &str, body: &[u8]) -> Result<(), String> {
+        let url = format!("{}/{}", self.endpoint.as_str().trim_end_matches('/'), path);
+        let mut request = ureq::put(&url).set("Content-Type", content_type);
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        request.send_bytes(body).map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "http")]
+impl RegistryBackend for HttpBackend {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    fn upload(&self, identifier: &str, version: &Version, archive: &[u8], manifest: &str) -> Result<(), String> {
+        self.put(&format!("packages/{}/{}.tar.gz", identifier, version), "application/gzip", archive)?;
+        self.put(&format!("packages/{}/{}/manifest", identifier, version), "application/octet-stream", manifest.as_bytes())
+    }
+
+    fn download(&self, source: &Url) -> Result<Vec<u8>, String> {
+        if !matches!(source.scheme(), "http" | "https") {
+            return Err(format!("`{}` is not an HTTP(S) url", source));
+        }
+        let response = ureq::get(source.as_str()).call().map_err(|e| e.to_string())?;
+        let mut archive = vec![];
+        std::io::Read::read_to_end(&mut response.into_reader(), &mut archive).map_err(|e| e.to_string())?;
+        Ok(archive)
+    }
+}
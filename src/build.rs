@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// The format a [`BuildPlan`] is emitted in, see [`Registry::emit_build_plan`]
+///
+/// [`Registry::emit_build_plan`]: crate::registry::Registry::emit_build_plan
+pub enum BuildPlanFormat {
+    /// A JSON document listing every [`BuildStep`]
+    Json,
+    /// A ninja build file, so the steps can be run by `ninja`
+    Ninja,
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`BuildStep`] is a single command of a [`BuildPlan`], compiling a [`Module`] or linking an [`Executable`]
+///
+/// [`Executable`]: crate::executable::Executable
+/// [`Module`]: crate::module::Module
+pub struct BuildStep {
+    pub(crate) id: PathBuf,
+    pub(crate) command: Vec<String>,
+    pub(crate) inputs: Vec<PathBuf>,
+    pub(crate) outputs: Vec<PathBuf>,
+    pub(crate) dependencies: Vec<PathBuf>,
+}
+
+impl BuildStep {
+    /// Returns the absolute source file of the [`Module`] or [`Executable`] the [`BuildStep`] builds, identifying the step
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Module`]: crate::module::Module
+    pub fn id(&self) -> &Path {
+        &self.id
+    }
+
+    /// Returns the command to run, the program first
+    pub fn command(&self) -> &[String] {
+        &self.command
+    }
+
+    /// Returns the source files the command reads
+    pub fn inputs(&self) -> &[PathBuf] {
+        &self.inputs
+    }
+
+    /// Returns the files or directories the command produces, empty when they are unknown
+    pub fn outputs(&self) -> &[PathBuf] {
+        &self.outputs
+    }
+
+    /// Returns the ids of the [`BuildStep`]s that need to run first
+    pub fn dependencies(&self) -> &[PathBuf] {
+        &self.dependencies
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`BuildPlan`] lists the [`BuildStep`]s building a [`Module`] or [`Executable`] and everything it depends on,
+/// every [`BuildStep`] after the ones it depends on
+///
+/// [`Executable`]: crate::executable::Executable
+/// [`Module`]: crate::module::Module
+pub struct BuildPlan {
+    pub(crate) steps: Vec<BuildStep>,
+}
+
+impl BuildPlan {
+    /// Returns the [`BuildStep`]s, every one after the ones it depends on
+    pub fn steps(&self) -> &[BuildStep] {
+        &self.steps
+    }
+
+    /// Renders the [`BuildPlan`] in the given [`BuildPlanFormat`]
+    ///
+    /// # Arguments
+    /// * `format` - The [`BuildPlanFormat`] to render
+    pub fn render(&self, format: BuildPlanFormat) -> String {
+        match format {
+            BuildPlanFormat::Json => serde_json::to_string_pretty(self).unwrap(),
+            BuildPlanFormat::Ninja => self.render_ninja(),
+        }
+    }
+
+    /// The files ninja considers produced by the given step, a stamp named after the step when its outputs are unknown
+    fn ninja_outputs(step: &BuildStep) -> Vec<PathBuf> {
+        match step.outputs.is_empty() {
+            true => {
+                let mut stamp = step.id.as_os_str().to_os_string();
+                stamp.push(".stamp");
+                vec![PathBuf::from(stamp)]
+            }
+            false => step.outputs.clone(),
+        }
+    }
+
+    fn render_ninja(&self) -> String {
+        let mut ninja = String::from("rule knapsac\n  command = $command\n  description = $description\n");
+        for step in &self.steps {
+            let outputs: Vec<String> = BuildPlan::ninja_outputs(step).iter().map(|p| escape_path(p)).collect();
+            let inputs: Vec<String> = step.inputs.iter().map(|p| escape_path(p)).collect();
+            let order: Vec<String> = self.steps.iter()
+                .filter(|s| step.dependencies.contains(&s.id))
+                .flat_map(BuildPlan::ninja_outputs)
+                .map(|p| escape_path(&p))
+                .collect();
+            let _ = write!(ninja, "\nbuild {}: knapsac {}", outputs.join(" "), inputs.join(" "));
+            if !order.is_empty() {
+                let _ = write!(ninja, " | {}", order.join(" "));
+            }
+            let command: Vec<String> = step.command.iter().map(|a| quote(a)).collect();
+            let _ = writeln!(ninja, "\n  command = {}", command.join(" ").replace('$', "$$"));
+            let _ = writeln!(ninja, "  description = build {}", step.id.display().to_string().replace('$', "$$"));
+        }
+        ninja
+    }
+}
+
+/// Escapes the characters ninja treats specially in paths
+fn escape_path(path: &Path) -> String {
+    let mut escaped = String::new();
+    for character in path.display().to_string().chars() {
+        if matches!(character, '$' | ' ' | ':') {
+            escaped.push('$');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
+
+/// Quotes an argument for the shell ninja runs commands with, when it contains anything but plain characters
+fn quote(argument: &str) -> String {
+    let plain = !argument.is_empty() && argument.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=+,:@%".contains(c));
+    match plain {
+        true => argument.to_string(),
+        false => format!("'{}'", argument.replace('\'', "'\\''")),
+    }
+}
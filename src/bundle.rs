@@ -0,0 +1,157 @@
+use crate::fetcher::Provenance;
+use crate::language::Language;
+use crate::module::Module;
+
+use serde::{Deserialize, Serialize};
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`BundledPackage`] is the slice of a [`Registry`] describing a single [`Package`] in a [`FailureBundle`]
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry`]: crate::registry::Registry
+pub struct BundledPackage {
+    pub(crate) local_location: PathBuf,
+    pub(crate) remote_location: Option<Url>,
+    pub(crate) dependencies: Vec<Url>,
+    pub(crate) modules: Vec<Module>,
+    pub(crate) language: Option<String>,
+    pub(crate) provenance: Option<Provenance>,
+}
+
+impl BundledPackage {
+    /// Returns the [`PathBuf`] the [`Package`] is located at
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn local_location(&self) -> &Path {
+        &self.local_location
+    }
+
+    /// Returns the remote location of the [`Package`], if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn remote_location(&self) -> Option<&Url> {
+        self.remote_location.as_ref()
+    }
+
+    /// Returns the remote locations of the [`Package`]s the [`Package`] depends on
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn dependencies(&self) -> &[Url] {
+        &self.dependencies
+    }
+
+    /// Returns the [`Module`]s the [`Package`] provides
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    /// Returns the name of the [`Language`] of the [`Package`], if set
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Returns where and how the [`Package`] was obtained, if it was installed
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`FailureBundle`] collects everything needed to reproduce a failed build of a [`Module`] in a single file,
+/// so it can be attached to a bug report
+pub struct FailureBundle {
+    pub(crate) source: PathBuf,
+    pub(crate) created: u64,
+    pub(crate) knapsac_version: String,
+    pub(crate) language: Option<Language>,
+    pub(crate) compiler_version: Option<String>,
+    pub(crate) invocation: Vec<String>,
+    pub(crate) output: Option<String>,
+    pub(crate) diagnostics: Vec<String>,
+    pub(crate) packages: Vec<BundledPackage>,
+}
+
+impl FailureBundle {
+    /// Loads a [`FailureBundle`] that was written by [`Registry::failure_bundle`]
+    ///
+    /// # Arguments
+    /// * `path` - A [`Path`] pointing to the file of the [`FailureBundle`]
+    ///
+    /// # Panics
+    /// Panics when the file does not exist or does not contain a [`FailureBundle`]
+    ///
+    /// [`Registry::failure_bundle`]: crate::registry::Registry::failure_bundle
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let data = read_to_string(&path)
+            .unwrap_or_else(|_| panic!("No failure bundle found @ {}", path.as_ref().display()));
+        serde_json::from_str(&data).unwrap()
+    }
+
+    pub(crate) fn save<P: AsRef<Path>>(&self, path: P) {
+        write(path, serde_json::to_string_pretty(self).unwrap()).unwrap();
+    }
+
+    /// Returns the [`PathBuf`] of the source file that failed to build
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Returns when the [`FailureBundle`] was created, in seconds since the unix epoch
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    /// Returns the version of KnapSaC that created the [`FailureBundle`]
+    pub fn knapsac_version(&self) -> &str {
+        &self.knapsac_version
+    }
+
+    /// Returns the resolved [`Language`] of the [`Module`], if known
+    pub fn language(&self) -> Option<&Language> {
+        self.language.as_ref()
+    }
+
+    /// Returns the version of the compiler that was detected, if any
+    pub fn compiler_version(&self) -> Option<&str> {
+        self.compiler_version.as_deref()
+    }
+
+    /// Returns the compiler invocation the [`Module`] is built with, the compiler first
+    pub fn invocation(&self) -> &[String] {
+        &self.invocation
+    }
+
+    /// Returns the output the failed build produced, if it was captured
+    pub fn output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    /// Returns the problems [`Registry::check`] found for the [`Module`]
+    ///
+    /// [`Registry::check`]: crate::registry::Registry::check
+    pub fn diagnostics(&self) -> &[String] {
+        &self.diagnostics
+    }
+
+    /// Returns the [`Package`] providing the [`Module`] followed by all [`Package`]s it depends on
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn packages(&self) -> &[BundledPackage] {
+        &self.packages
+    }
+}
@@ -0,0 +1,124 @@
+use crate::package::Package;
+use crate::utils::{list_all_files, pack_directory, sha256_hex};
+use crate::version::{Version, VersionReq};
+
+use nanoid::nanoid;
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`CacheEntry`] records a downloaded [`Package`] stored in a [`PackageCache`]
+pub struct CacheEntry {
+    pub(crate) source: Url,
+    pub(crate) identifier: String,
+    pub(crate) version: Option<Version>,
+    pub(crate) checksum: String,
+    pub(crate) archive: String,
+}
+
+impl CacheEntry {
+    /// Returns the [`Url`] the [`Package`] was downloaded from
+    pub fn source(&self) -> &Url {
+        &self.source
+    }
+
+    /// Returns the identifier of the [`Package`]
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Returns the [`Version`] of the [`Package`], if it has one
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// Returns the checksum of the contents of the [`Package`], see [`Package::checksum`]
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`PackageCache`] is a directory of downloaded [`Package`]s, so they can be installed again without network access
+///
+/// Every [`Package`] is stored as a gzipped tarball including its git repository, named after the SHA-256 hash of the tarball.
+/// The file `index.json` maps the source, identifier and [`Version`] of every [`Package`] to its tarball
+pub struct PackageCache {
+    pub(crate) location: PathBuf,
+}
+
+impl PackageCache {
+    /// Opens the [`PackageCache`] in the given directory, which is created on the first store
+    ///
+    /// # Arguments
+    /// * `location` - An absolute [`Path`] pointing to a directory
+    pub fn open<P: AsRef<Path>>(location: P) -> Self {
+        PackageCache {
+            location: location.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the [`Path`] of the directory the [`PackageCache`] is stored in
+    pub fn location(&self) -> &Path {
+        &self.location
+    }
+
+    /// Returns every [`CacheEntry`] in the [`PackageCache`]
+    pub fn entries(&self) -> Vec<CacheEntry> {
+        read_to_string(self.location.join("index.json"))
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Retrieves the [`CacheEntry`] with the highest [`Version`] downloaded from the given source
+    ///
+    /// # Arguments
+    /// * `source` - The [`Url`] the [`Package`] was downloaded from
+    /// * `requirement` - The [`VersionReq`] the [`Version`] needs to satisfy, if any
+    /// * `checksum` - The checksum the contents need to match, if any
+    pub fn lookup(&self, source: &Url, requirement: Option<&VersionReq>, checksum: Option<&str>) -> Option<CacheEntry> {
+        self.entries()
+            .into_iter()
+            .filter(|e| &e.source == source)
+            .filter(|e| checksum.is_none_or(|c| c == e.checksum))
+            .filter(|e| match (requirement, &e.version) {
+                (None, _) => true,
+                (Some(requirement), Some(version)) => requirement.matches(version),
+                (Some(_), None) => false,
+            })
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+
+    /// Stores a downloaded [`Package`] in the [`PackageCache`], replacing an earlier copy of the same source and [`Version`]
+    pub(crate) fn store(&self, package: &Package, source: &Url) {
+        create_dir_all(&self.location).unwrap();
+        let archive = pack_directory(&package.local_location, list_all_files(&package.local_location));
+        let name = format!("{}.tar.gz", sha256_hex(&archive));
+        write(self.location.join(&name), archive).unwrap();
+
+        let version = package.version();
+        let mut entries = self.entries();
+        entries.retain(|e| !(&e.source == source && e.version == version));
+        entries.push(CacheEntry {
+            source: source.clone(),
+            identifier: package.identifier(),
+            version,
+            checksum: Package::checksum(&package.local_location),
+            archive: name,
+        });
+        write(self.location.join("index.json"), serde_json::to_string_pretty(&entries).unwrap()).unwrap();
+    }
+
+    /// Restores the [`Package`] of the given [`CacheEntry`] into a new directory in the given directory
+    pub(crate) fn restore<P: AsRef<Path>>(&self, entry: &CacheEntry, path: P) -> Package {
+        Package::unpack(self.location.join(&entry.archive), path.as_ref().join(nanoid!()))
+    }
+}
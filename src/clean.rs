@@ -0,0 +1,61 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// Selects what [`Registry::clean`] removes the build artifacts of
+///
+/// [`Registry::clean`]: crate::registry::Registry::clean
+pub enum CleanTarget {
+    /// The [`Module`] or [`Executable`] at the given absolute source [`PathBuf`]
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Module`]: crate::module::Module
+    Item(PathBuf),
+    /// Every item of the [`Package`] at the given `local_location`
+    ///
+    /// [`Package`]: crate::package::Package
+    Package(PathBuf),
+    /// Every item of every registered [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    All,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`CleanReport`] lists the build artifacts that were (or would be) removed by [`Registry::clean`]
+///
+/// [`Registry::clean`]: crate::registry::Registry::clean
+pub struct CleanReport {
+    pub(crate) removed: Vec<PathBuf>,
+    pub(crate) bytes: u64,
+}
+
+impl CleanReport {
+    /// Returns the absolute [`Path`]s of the removed files, sorted
+    pub fn removed(&self) -> &[PathBuf] {
+        &self.removed
+    }
+
+    /// Returns the total size in bytes of the removed files
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Checks whether nothing was removed
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+
+    pub(crate) fn add(&mut self, path: &Path) {
+        if let Some(metadata) = path.metadata().ok().filter(|m| m.is_file()) {
+            if !self.removed.iter().any(|r| r == path) {
+                self.bytes += metadata.len();
+                self.removed.push(path.to_path_buf());
+            }
+        }
+    }
+}
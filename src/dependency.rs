@@ -1,18 +1,283 @@
+use crate::package_id::PackageId;
+use crate::version::VersionReq;
+
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 use url::Url;
 
 #[derive(Deserialize, Serialize)]
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq, Hash)]
+#[derive(PartialOrd, Ord)]
+/// A [`DependencyKind`] tells when a [`Dependency`] is needed
+pub enum DependencyKind {
+    /// The [`Package`] is needed to build and use the dependent
+    ///
+    /// [`Package`]: crate::package::Package
+    #[default]
+    Normal,
+    /// The [`Package`] is only needed to test the dependent, it is not needed by anything depending on the dependent
+    ///
+    /// [`Package`]: crate::package::Package
+    Dev,
+    /// The [`Package`] is only needed for some uses of the dependent
+    ///
+    /// [`Package`]: crate::package::Package
+    Optional,
+}
+
+impl DependencyKind {
+    /// Every [`DependencyKind`]
+    pub const ALL: [DependencyKind; 3] = [DependencyKind::Normal, DependencyKind::Dev, DependencyKind::Optional];
+
+    pub(crate) fn is_normal(&self) -> bool {
+        *self == DependencyKind::Normal
+    }
+}
+
+impl Display for DependencyKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependencyKind::Normal => write!(f, "normal"),
+            DependencyKind::Dev => write!(f, "dev"),
+            DependencyKind::Optional => write!(f, "optional"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
 #[derive(Clone)]
-#[derive(Hash)]
+#[derive(Debug)]
+/// A [`Dependency`] declares that a [`Package`] needs the [`Package`] hosted at `git_url`
+/// Two [`Dependency`]s on the same `git_url` are equal, regardless of what else they declare
+///
+/// [`Package`]: crate::package::Package
 pub struct Dependency {
     pub(crate) git_url: Url,
+    #[serde(default)]
+    pub(crate) identifier: Option<PackageId>,
+    #[serde(default)]
+    pub(crate) requirement: Option<VersionReq>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) features: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) alias: Option<String>,
+    #[serde(default, skip_serializing_if = "DependencyKind::is_normal")]
+    pub(crate) kind: DependencyKind,
 }
 
 impl Dependency {
     pub fn create(url: Url) -> Self {
         Dependency {
             git_url: url,
+            identifier: None,
+            requirement: None,
+            features: BTreeSet::new(),
+            alias: None,
+            kind: DependencyKind::Normal,
+        }
+    }
+
+    /// Creates a [`Dependency`] that also declares the identifier of the [`Package`] and the versions it accepts
+    ///
+    /// # Arguments
+    /// * `url` - An [`Url`] pointing to the git repository of the [`Package`]
+    /// * `identifier` - The identifier of the [`Package`], see [`PackageId`]
+    /// * `requirement` - The [`VersionReq`] describing the versions of the [`Package`] that are accepted
+    ///
+    /// # Examples
+    /// ```
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    ///
+    /// let url = Url::parse("https://github.com/jcuppen/JSON").unwrap();
+    /// let declared = Dependency::declare(url.clone(), "json", Some("^1.0".parse().unwrap()));
+    /// assert_eq!(declared.identifier(), Some("json"));
+    /// assert_eq!(declared.requirement().unwrap().to_string(), "^1.0");
+    /// assert_eq!(declared, Dependency::create(url));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the identifier is not a valid [`PackageId`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn declare<S: AsRef<str>>(url: Url, identifier: S, requirement: Option<VersionReq>) -> Self {
+        let identifier: PackageId = identifier.as_ref().parse().unwrap_or_else(|e| panic!("{}", e));
+        Dependency {
+            git_url: url,
+            identifier: Some(identifier),
+            requirement,
+            features: BTreeSet::new(),
+            alias: None,
+            kind: DependencyKind::Normal,
+        }
+    }
+
+    /// Returns the [`Url`] pointing to the git repository of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn git_url(&self) -> &Url {
+        &self.git_url
+    }
+
+    /// Returns the identifier of the [`Package`], if declared
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn identifier(&self) -> Option<&str> {
+        self.identifier.as_ref().map(PackageId::as_str)
+    }
+
+    /// Returns the [`PackageId`] of the [`Package`], if declared
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn package_id(&self) -> Option<&PackageId> {
+        self.identifier.as_ref()
+    }
+
+    /// Returns the [`VersionReq`] describing the versions of the [`Package`] that are accepted, if declared
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn requirement(&self) -> Option<&VersionReq> {
+        self.requirement.as_ref()
+    }
+
+    /// Returns the names of the features of the [`Package`] that are enabled, see [`Package::add_feature`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Package::add_feature`]: crate::package::Package::add_feature
+    pub fn features(&self) -> &BTreeSet<String> {
+        &self.features
+    }
+
+    /// Enables the given features of the [`Package`], in addition to its [`Module`]s that are not optional
+    ///
+    /// # Arguments
+    /// * `features` - The names of the features to enable
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    pub fn set_features(&mut self, features: Vec<String>) {
+        self.features = features.into_iter().collect();
+    }
+
+    /// Returns the local name the [`Package`] is referred to by instead of its identifier, if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn alias(&self) -> Option<&str> {
+        self.alias.as_deref()
+    }
+
+    /// Sets the local name the [`Package`] is referred to by instead of its identifier,
+    /// e.g. when the identifiers of two [`Dependency`]s collide or a shorter name is desired
+    ///
+    /// # Arguments
+    /// * `alias` - The local name, or `None` to refer to the [`Package`] by its identifier
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_alias<S: Into<String>>(&mut self, alias: Option<S>) {
+        self.alias = alias.map(Into::into);
+    }
+
+    /// Returns the [`DependencyKind`] telling when the [`Package`] is needed
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn kind(&self) -> DependencyKind {
+        self.kind
+    }
+
+    /// Sets the [`DependencyKind`] telling when the [`Package`] is needed
+    ///
+    /// # Arguments
+    /// * `kind` - The [`DependencyKind`] of the [`Dependency`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::{Dependency, DependencyKind};
+    ///
+    /// let mut dependency = Dependency::create(Url::parse("https://github.com/jcuppen/JSON").unwrap());
+    /// assert_eq!(dependency.kind(), DependencyKind::Normal);
+    /// dependency.set_kind(DependencyKind::Dev);
+    /// assert_eq!(dependency.kind(), DependencyKind::Dev);
+    /// ```
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_kind(&mut self, kind: DependencyKind) {
+        self.kind = kind;
+    }
+
+    /// Returns the name the [`Package`] is referred to by locally, its alias or else its declared identifier
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn local_name(&self) -> Option<&str> {
+        self.alias().or(self.identifier())
+    }
+}
+
+impl PartialEq for Dependency {
+    fn eq(&self, other: &Self) -> bool {
+        self.git_url == other.git_url
+    }
+}
+
+impl Eq for Dependency {}
+
+impl Hash for Dependency {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.git_url.hash(state);
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`DependencyError`] explains why the dependencies of a [`Package`] or [`Module`] could not be resolved or changed
+///
+/// [`Module`]: crate::module::Module
+/// [`Package`]: crate::package::Package
+pub enum DependencyError {
+    /// No registered [`Package`] contains the given source [`PathBuf`]
+    ///
+    /// [`Package`]: crate::package::Package
+    UnknownSource(PathBuf),
+    /// The [`Package`] declares no [`Dependency`] on the given [`Url`]
+    ///
+    /// [`Package`]: crate::package::Package
+    UnknownDependency(Url),
+    /// The given output directory of a stray dependency is relative or no longer exists, see [`Package::add_stray`]
+    ///
+    /// [`Package::add_stray`]: crate::package::Package::add_stray
+    StrayNotResolvable(PathBuf),
+    /// Another [`Dependency`] of the [`Package`] is already referred to by the given local name
+    ///
+    /// [`Package`]: crate::package::Package
+    AliasConflict(String),
+    /// No registered version of the [`Package`] hosted at the given [`Url`] satisfies the given [`VersionReq`]
+    ///
+    /// [`Package`]: crate::package::Package
+    UnsatisfiedRequirement(Url, VersionReq),
+    /// The dependency file at the given [`PathBuf`] could not be read or parsed, see [`Registry::import_depfile`]
+    ///
+    /// [`Registry::import_depfile`]: crate::registry::Registry::import_depfile
+    Depfile(PathBuf, String),
+}
+
+impl Display for DependencyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependencyError::UnknownSource(source) => write!(f, "no package registered for {}", source.display()),
+            DependencyError::UnknownDependency(url) => write!(f, "no dependency on {} is declared", url),
+            DependencyError::StrayNotResolvable(output) => write!(f, "stray dependency {} is not an existing absolute directory", output.display()),
+            DependencyError::AliasConflict(alias) => write!(f, "another dependency is already named {}", alias),
+            DependencyError::UnsatisfiedRequirement(url, requirement) => write!(f, "no registered version of {} satisfies {}", url, requirement),
+            DependencyError::Depfile(path, message) => write!(f, "could not import dependency file {}: {}", path.display(), message),
         }
     }
 }
+
+impl std::error::Error for DependencyError {}
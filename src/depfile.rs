@@ -0,0 +1,110 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`DepfileFormat`] tells how a dependency file emitted by a compiler is written, see [`Registry::import_depfile`]
+///
+/// [`Registry::import_depfile`]: crate::registry::Registry::import_depfile
+pub enum DepfileFormat {
+    /// Make rules, `target: prerequisite prerequisite \`, as emitted by e.g. `gcc -MD`
+    /// Spaces in paths are escaped with a backslash
+    Make,
+    /// A JSON array of paths, or a JSON object listing them in its `dependencies` array
+    Json,
+}
+
+impl DepfileFormat {
+    /// Returns the paths listed in the contents of a dependency file
+    /// For [`DepfileFormat::Make`] only the prerequisites are listed, not the targets
+    pub(crate) fn parse(&self, contents: &str) -> Result<Vec<PathBuf>, String> {
+        match self {
+            DepfileFormat::Make => Ok(parse_make(contents)),
+            DepfileFormat::Json => parse_json(contents),
+        }
+    }
+}
+
+fn parse_make(contents: &str) -> Vec<PathBuf> {
+    let joined = contents.replace("\\\r\n", " ").replace("\\\n", " ");
+    let mut paths = vec![];
+    for line in joined.lines() {
+        let mut words = vec![];
+        let mut word = String::new();
+        let mut characters = line.chars().peekable();
+        while let Some(character) = characters.next() {
+            match character {
+                '\\' if characters.peek().is_some_and(|c| *c == ' ' || *c == '#') => word.push(characters.next().unwrap()),
+                '#' => break,
+                c if c.is_whitespace() => words.push(std::mem::take(&mut word)),
+                c => word.push(c),
+            }
+        }
+        words.push(word);
+        let words: Vec<String> = words.into_iter().filter(|w| !w.is_empty()).collect();
+        let Some(separator) = words.iter().position(|w| w.ends_with(':')) else {
+            continue;
+        };
+        paths.extend(words[separator + 1..].iter().map(PathBuf::from));
+    }
+    paths
+}
+
+fn parse_json(contents: &str) -> Result<Vec<PathBuf>, String> {
+    let value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let list = match &value {
+        Value::Object(object) => object.get("dependencies").ok_or("missing `dependencies` array")?,
+        value => value,
+    };
+    list.as_array()
+        .ok_or("expected an array of paths")?
+        .iter()
+        .map(|p| p.as_str().map(PathBuf::from).ok_or_else(|| format!("expected a path, found {}", p)))
+        .collect()
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`DepfileImport`] lists what [`Registry::import_depfile`] registered
+///
+/// [`Registry::import_depfile`]: crate::registry::Registry::import_depfile
+pub struct DepfileImport {
+    pub(crate) dependencies: Vec<Url>,
+    pub(crate) strays: Vec<PathBuf>,
+    pub(crate) skipped: Vec<PathBuf>,
+}
+
+impl DepfileImport {
+    /// Returns the remote locations of the registered [`Package`]s that were declared as [`Dependency`]s
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    pub fn dependencies(&self) -> &[Url] {
+        &self.dependencies
+    }
+
+    /// Returns the directories that were added as stray dependencies, see [`Package::add_stray`]
+    ///
+    /// [`Package::add_stray`]: crate::package::Package::add_stray
+    pub fn strays(&self) -> &[PathBuf] {
+        &self.strays
+    }
+
+    /// Returns the listed paths that did not lead to a dependency, because they lie in the importing [`Package`] itself,
+    /// no longer exist or belong to a registered [`Package`] without a remote location
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn skipped(&self) -> &[PathBuf] {
+        &self.skipped
+    }
+
+    pub(crate) fn skip(&mut self, path: &Path) {
+        if !self.skipped.iter().any(|p| p == path) {
+            self.skipped.push(path.to_path_buf());
+        }
+    }
+}
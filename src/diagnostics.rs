@@ -0,0 +1,150 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A single problem found while checking a [`Registry`]
+///
+/// [`Registry`]: crate::registry::Registry
+pub enum Diagnostic {
+    /// No registered [`Package`] contains the given source [`PathBuf`]
+    ///
+    /// [`Package`]: crate::package::Package
+    UnregisteredPackage(PathBuf),
+    /// The [`Package`] does not provide a [`Module`] at the given source [`PathBuf`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Module`]: crate::module::Module
+    UnregisteredModule(PathBuf),
+    /// No registered [`Package`] is located at the given remote [`Url`]
+    ///
+    /// [`Package`]: crate::package::Package
+    UnresolvedDependency(Url),
+    /// The [`Package`]s located at the given [`PathBuf`]s depend on each other in a cycle
+    ///
+    /// [`Package`]: crate::package::Package
+    DependencyCycle(Vec<PathBuf>),
+    /// A registered [`Module`] points to a file that does not exist
+    ///
+    /// [`Module`]: crate::module::Module
+    MissingModuleFile(PathBuf),
+    /// A [`Language`] is referred to by name but not registered
+    ///
+    /// [`Language`]: crate::language::Language
+    UnknownLanguage(String),
+    /// The local compiler version lies outside the range of compiler versions
+    /// the [`Package`] located at the given [`PathBuf`] was built or tested with
+    ///
+    /// [`Package`]: crate::package::Package
+    IncompatibleCompiler(PathBuf, String),
+    /// The artifact of the [`Module`] at the given source [`PathBuf`] is tagged with the first ABI tag,
+    /// while the checked [`Module`] needs artifacts tagged with the second ABI tag
+    ///
+    /// [`Module`]: crate::module::Module
+    IncompatibleArtifact(PathBuf, String, String),
+    /// The output directory at the given [`PathBuf`] is set for a [`Module`] but does not exist
+    ///
+    /// [`Module`]: crate::module::Module
+    MissingOutputDirectory(PathBuf),
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::UnregisteredPackage(path) => write!(
+                f,
+                "`{}` is not part of a registered package, register its package first",
+                path.display()
+            ),
+            Diagnostic::UnregisteredModule(path) => write!(
+                f,
+                "`{}` is not a registered module, add it to its package first",
+                path.display()
+            ),
+            Diagnostic::UnresolvedDependency(url) => write!(
+                f,
+                "dependency `{}` does not resolve to a registered package, download it first",
+                url
+            ),
+            Diagnostic::DependencyCycle(cycle) => {
+                let paths: Vec<String> = cycle.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "dependency cycle detected: {}", paths.join(" -> "))
+            }
+            Diagnostic::MissingModuleFile(path) => write!(
+                f,
+                "module file `{}` does not exist",
+                path.display()
+            ),
+            Diagnostic::UnknownLanguage(name) => write!(
+                f,
+                "language `{}` is not registered",
+                name
+            ),
+            Diagnostic::IncompatibleCompiler(path, version) => write!(
+                f,
+                "package `{}` was not built or tested with compiler version {}",
+                path.display(),
+                version
+            ),
+            Diagnostic::IncompatibleArtifact(path, found, expected) => write!(
+                f,
+                "artifact of `{}` was built as {} but {} is needed, rebuild needed",
+                path.display(),
+                found,
+                expected
+            ),
+            Diagnostic::MissingOutputDirectory(path) => write!(
+                f,
+                "output directory `{}` does not exist, build the module first",
+                path.display()
+            ),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`DiagnosticsReport`] collects the errors and warnings found while checking a [`Registry`]
+///
+/// [`Registry`]: crate::registry::Registry
+pub struct DiagnosticsReport {
+    pub(crate) errors: Vec<Diagnostic>,
+    pub(crate) warnings: Vec<Diagnostic>,
+}
+
+impl DiagnosticsReport {
+    /// Returns the problems that prevent the checked [`Module`] from being built
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn errors(&self) -> &[Diagnostic] {
+        &self.errors
+    }
+
+    /// Returns the problems that do not prevent the checked [`Module`] from being built
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// Checks whether no errors were found
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub(crate) fn error(&mut self, diagnostic: Diagnostic) {
+        if !self.errors.contains(&diagnostic) {
+            self.errors.push(diagnostic);
+        }
+    }
+
+    pub(crate) fn warning(&mut self, diagnostic: Diagnostic) {
+        if !self.warnings.contains(&diagnostic) {
+            self.warnings.push(diagnostic);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::snapshot::{PackageView, RegistrySnapshot};
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`DiffReport`] lists what differs between two states of a [`Registry`], see [`Registry::diff`]
+/// [`Package`]s are identified by `local_location`, items by their absolute source [`Path`]
+/// and dependency edges by the `local_location` of the dependent [`Package`] and the [`Url`] depended on
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::diff`]: crate::registry::Registry::diff
+pub struct DiffReport {
+    pub(crate) added_packages: Vec<PathBuf>,
+    pub(crate) removed_packages: Vec<PathBuf>,
+    pub(crate) changed_packages: Vec<PathBuf>,
+    pub(crate) added_items: Vec<PathBuf>,
+    pub(crate) removed_items: Vec<PathBuf>,
+    pub(crate) changed_items: Vec<PathBuf>,
+    pub(crate) added_edges: Vec<(PathBuf, Url)>,
+    pub(crate) removed_edges: Vec<(PathBuf, Url)>,
+    pub(crate) changed_edges: Vec<(PathBuf, Url)>,
+}
+
+impl DiffReport {
+    /// Compares two [`RegistrySnapshot`]s, `before` is the old state and `after` the new one
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::diff::DiffReport;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_diff_snapshots.json"));
+    /// let package_path = env::temp_dir().join("mock_package_diff_snapshots");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// registry.add(package.clone());
+    ///
+    /// let before = registry.snapshot();
+    /// package.add_module(Module::create("a.sac", None));
+    /// let report = DiffReport::compare(&before, &registry.snapshot());
+    /// assert_eq!(report.added_items(), &[package_path.join("a.sac")]);
+    /// assert_eq!(report.changed_packages(), &[package_path]);
+    /// ```
+    pub fn compare(before: &RegistrySnapshot, after: &RegistrySnapshot) -> Self {
+        let before: BTreeMap<&Path, &PackageView> = before.packages.iter().map(|p| (p.local_location(), p)).collect();
+        let after: BTreeMap<&Path, &PackageView> = after.packages.iter().map(|p| (p.local_location(), p)).collect();
+        let mut report = DiffReport::default();
+
+        for (location, old) in &before {
+            if !after.contains_key(location) {
+                report.removed_packages.push(location.to_path_buf());
+                report.removed_items.extend(old.items.iter().map(|i| i.source.clone()));
+                report.removed_edges.extend(old.dependencies.iter().map(|d| (location.to_path_buf(), d.git_url.clone())));
+            }
+        }
+        for (location, new) in &after {
+            let old = match before.get(location) {
+                Some(old) => old,
+                None => {
+                    report.added_packages.push(location.to_path_buf());
+                    report.added_items.extend(new.items.iter().map(|i| i.source.clone()));
+                    report.added_edges.extend(new.dependencies.iter().map(|d| (location.to_path_buf(), d.git_url.clone())));
+                    continue;
+                }
+            };
+            if old != new {
+                report.changed_packages.push(location.to_path_buf());
+            }
+
+            let old_items: BTreeMap<&Path, _> = old.items.iter().map(|i| (i.source(), i)).collect();
+            let new_items: BTreeMap<&Path, _> = new.items.iter().map(|i| (i.source(), i)).collect();
+            report.removed_items.extend(old_items.keys().filter(|s| !new_items.contains_key(*s)).map(|s| s.to_path_buf()));
+            for (source, item) in &new_items {
+                match old_items.get(source) {
+                    None => report.added_items.push(source.to_path_buf()),
+                    Some(old) if old != item => report.changed_items.push(source.to_path_buf()),
+                    Some(_) => {}
+                }
+            }
+
+            let old_edges: BTreeMap<&Url, _> = old.dependencies.iter().map(|d| (d.git_url(), d)).collect();
+            let new_edges: BTreeMap<&Url, _> = new.dependencies.iter().map(|d| (d.git_url(), d)).collect();
+            report.removed_edges.extend(old_edges.keys().filter(|u| !new_edges.contains_key(*u)).map(|u| (location.to_path_buf(), (*u).clone())));
+            for (url, dependency) in &new_edges {
+                match old_edges.get(url) {
+                    None => report.added_edges.push((location.to_path_buf(), (*url).clone())),
+                    Some(old) if old != dependency => report.changed_edges.push((location.to_path_buf(), (*url).clone())),
+                    Some(_) => {}
+                }
+            }
+        }
+        report
+    }
+
+    /// Returns the `local_location`s of [`Package`]s that were registered
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn added_packages(&self) -> &[PathBuf] {
+        &self.added_packages
+    }
+
+    /// Returns the `local_location`s of [`Package`]s that were removed
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn removed_packages(&self) -> &[PathBuf] {
+        &self.removed_packages
+    }
+
+    /// Returns the `local_location`s of [`Package`]s that remain registered but differ in any way,
+    /// including their items and dependency edges
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn changed_packages(&self) -> &[PathBuf] {
+        &self.changed_packages
+    }
+
+    /// Returns the sources of items that were added
+    pub fn added_items(&self) -> &[PathBuf] {
+        &self.added_items
+    }
+
+    /// Returns the sources of items that were removed
+    pub fn removed_items(&self) -> &[PathBuf] {
+        &self.removed_items
+    }
+
+    /// Returns the sources of items whose kind, name, language or output changed
+    pub fn changed_items(&self) -> &[PathBuf] {
+        &self.changed_items
+    }
+
+    /// Returns the dependency edges that were added
+    pub fn added_edges(&self) -> &[(PathBuf, Url)] {
+        &self.added_edges
+    }
+
+    /// Returns the dependency edges that were removed
+    pub fn removed_edges(&self) -> &[(PathBuf, Url)] {
+        &self.removed_edges
+    }
+
+    /// Returns the dependency edges whose identifier, requirement or resolved [`Package`] changed
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn changed_edges(&self) -> &[(PathBuf, Url)] {
+        &self.changed_edges
+    }
+
+    /// Checks whether both states are the same
+    pub fn is_empty(&self) -> bool {
+        self.added_packages.is_empty()
+            && self.removed_packages.is_empty()
+            && self.changed_packages.is_empty()
+            && self.added_items.is_empty()
+            && self.removed_items.is_empty()
+            && self.changed_items.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_edges.is_empty()
+    }
+}
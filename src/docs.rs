@@ -0,0 +1,90 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// A [`DocExtractor`] pulls the description of a [`Module`] from the contents of its source file
+/// A [`Registry`] uses it when [`Module`]s without a description are registered, see [`Registry::set_doc_extractor`]
+///
+/// [`Module`]: crate::module::Module
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::set_doc_extractor`]: crate::registry::Registry::set_doc_extractor
+pub trait DocExtractor {
+    /// Returns the description found in the given source, if any
+    ///
+    /// # Arguments
+    /// * `source` - The contents of the source file of a [`Module`]
+    ///
+    /// [`Module`]: crate::module::Module
+    fn extract(&self, source: &str) -> Option<String>;
+}
+
+/// A [`DocExtractor`] that takes the comment block at the top of a source file,
+/// either consecutive `//` lines or a single `/* */` comment, ignoring leading blank lines
+/// Comment markers and the leading `*` of block comment lines are stripped
+///
+/// # Examples
+/// ```
+/// # use knapsac_lib::docs::{CommentBlockExtractor, DocExtractor};
+///
+/// let source = "\n/// Parses JSON\n/// into values\nmodule JSON;\n";
+/// assert_eq!(CommentBlockExtractor.extract(source), Some("Parses JSON\ninto values".to_string()));
+///
+/// let source = "/**\n * Parses JSON\n */\nmodule JSON;\n";
+/// assert_eq!(CommentBlockExtractor.extract(source), Some("Parses JSON".to_string()));
+///
+/// assert_eq!(CommentBlockExtractor.extract("module JSON;\n"), None);
+/// ```
+pub struct CommentBlockExtractor;
+
+impl DocExtractor for CommentBlockExtractor {
+    fn extract(&self, source: &str) -> Option<String> {
+        let mut lines = source.lines().map(str::trim).skip_while(|l| l.is_empty()).peekable();
+        let mut block: Vec<&str> = vec![];
+        if lines.peek()?.starts_with("/*") {
+            for line in lines {
+                let (line, end) = match line.split_once("*/") {
+                    Some((line, _)) => (line, true),
+                    None => (line, false),
+                };
+                let line = line.trim_start_matches('/').trim_start_matches('*').trim_start_matches('!').trim();
+                block.push(line);
+                if end {
+                    break;
+                }
+            }
+        } else {
+            block.extend(
+                lines
+                    .take_while(|l| l.starts_with("//"))
+                    .map(|l| l.trim_start_matches('/').trim_start_matches('!').trim()),
+            );
+        }
+        let description = block.join("\n").trim().to_string();
+        match description.is_empty() {
+            true => None,
+            false => Some(description),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Default)]
+/// Holds the [`DocExtractor`] set on a [`Registry`], which takes no part in comparing or printing it
+/// Clones of the [`Registry`] share it
+///
+/// [`Registry`]: crate::registry::Registry
+pub(crate) struct Extractor(pub(crate) Option<Arc<dyn DocExtractor + Send + Sync>>);
+
+impl Debug for Extractor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "Extractor(Some(..))"),
+            None => write!(f, "Extractor(None)"),
+        }
+    }
+}
+
+impl PartialEq for Extractor {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
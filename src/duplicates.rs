@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`DuplicateGroup`] lists registered [`Module`]s with identical contents, see [`Registry::find_duplicates`]
+///
+/// [`Module`]: crate::module::Module
+/// [`Registry::find_duplicates`]: crate::registry::Registry::find_duplicates
+pub struct DuplicateGroup {
+    pub(crate) content_hash: String,
+    pub(crate) modules: Vec<(PathBuf, String)>,
+    pub(crate) diverged: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Returns the content hash the [`Module`]s share, see [`Module::content_hash`]
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Module::content_hash`]: crate::module::Module::content_hash
+    pub fn content_hash(&self) -> &str {
+        &self.content_hash
+    }
+
+    /// Returns the absolute source file and identifier of every [`Module`] in the group, ordered by source file
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn modules(&self) -> &[(PathBuf, String)] {
+        &self.modules
+    }
+
+    /// Returns the source files of the [`Module`]s whose contents changed since their content hash was recorded,
+    /// copies that started out identical but diverged since
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn diverged(&self) -> &[PathBuf] {
+        &self.diverged
+    }
+
+    /// Returns the source file of the [`Module`] suggested to keep when consolidating the group,
+    /// the first one whose contents did not diverge
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn suggested(&self) -> &Path {
+        self.modules.iter()
+            .map(|(source, _)| source)
+            .find(|s| !self.diverged.contains(s))
+            .unwrap_or(&self.modules[0].0)
+    }
+}
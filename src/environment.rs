@@ -0,0 +1,238 @@
+use crate::language::Language;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::env::consts::{ARCH, OS};
+use std::fmt::{Display, Formatter};
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+
+/// The suffix appended to an output to name the file its [`BuildEnvironment`] is recorded in,
+/// e.g. `build/liba.so` is described by `build/liba.so.knapsac-build.json`
+pub const BUILD_METADATA_SUFFIX: &str = ".knapsac-build.json";
+
+/// The environment variables that influence how a compiler finds its inputs and are recorded in every [`BuildEnvironment`]
+/// Every variable starting with `KNAPSAC_` is recorded as well
+pub const RECORDED_VARIABLES: &[&str] = &[
+    "PATH",
+    "LD_LIBRARY_PATH",
+    "DYLD_LIBRARY_PATH",
+    "LIBRARY_PATH",
+    "CPATH",
+    "CC",
+    "CFLAGS",
+    "LDFLAGS",
+    "SAC2CRC",
+];
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`BuildEnvironment`] records how an output was produced: the toolchain, the resolved [`Language`] and the
+/// relevant environment variables, so differences between machines can be found, see [`Registry::explain_artifact`]
+///
+/// [`Registry::explain_artifact`]: crate::registry::Registry::explain_artifact
+pub struct BuildEnvironment {
+    pub(crate) source: PathBuf,
+    pub(crate) output: PathBuf,
+    pub(crate) created: u64,
+    pub(crate) knapsac_version: String,
+    pub(crate) host: String,
+    pub(crate) target: Option<String>,
+    pub(crate) language: Option<Language>,
+    pub(crate) toolchain: Option<String>,
+    pub(crate) invocation: Vec<String>,
+    pub(crate) variables: BTreeMap<String, String>,
+}
+
+impl BuildEnvironment {
+    /// Loads the [`BuildEnvironment`] recorded for the given output, if any
+    ///
+    /// # Arguments
+    /// * `output` - A [`Path`] pointing to a file or directory that was produced by a build
+    pub fn load<P: AsRef<Path>>(output: P) -> Option<Self> {
+        let data = read_to_string(metadata_path(output)).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    pub(crate) fn save(&self) {
+        write(metadata_path(&self.output), serde_json::to_string_pretty(self).unwrap()).unwrap();
+    }
+
+    /// Returns the absolute source file of the [`Module`] or [`Executable`] that was built
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Module`]: crate::module::Module
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Returns the file or directory that was produced
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    /// Returns when the output was recorded, in seconds since the unix epoch
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    /// Returns the version of KnapSaC that recorded the [`BuildEnvironment`]
+    pub fn knapsac_version(&self) -> &str {
+        &self.knapsac_version
+    }
+
+    /// Returns the architecture and operating system of the machine that produced the output, e.g. `x86_64-linux`
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Returns the target the [`Registry`] requested when the output was produced, if any, see [`Registry::set_target`]
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    /// [`Registry::set_target`]: crate::registry::Registry::set_target
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Returns the resolved [`Language`] the output was produced with, if known
+    pub fn language(&self) -> Option<&Language> {
+        self.language.as_ref()
+    }
+
+    /// Returns what the compiler printed when invoked with `--version`, if it could be invoked
+    pub fn toolchain(&self) -> Option<&str> {
+        self.toolchain.as_deref()
+    }
+
+    /// Returns the command line that produces the output, the compiler first
+    pub fn invocation(&self) -> &[String] {
+        &self.invocation
+    }
+
+    /// Returns the recorded environment variables that were set, see [`RECORDED_VARIABLES`]
+    pub fn variables(&self) -> &BTreeMap<String, String> {
+        &self.variables
+    }
+
+    /// Lists the [`EnvironmentChange`]s between this [`BuildEnvironment`] and the given one, usually the current one
+    ///
+    /// # Arguments
+    /// * `current` - The [`BuildEnvironment`] to compare against
+    pub fn changes(&self, current: &BuildEnvironment) -> Vec<EnvironmentChange> {
+        let mut changes = vec![];
+        if self.host != current.host {
+            changes.push(EnvironmentChange::Host(self.host.clone(), current.host.clone()));
+        }
+        if self.target != current.target {
+            changes.push(EnvironmentChange::Target(self.target.clone(), current.target.clone()));
+        }
+        if self.toolchain != current.toolchain {
+            changes.push(EnvironmentChange::Toolchain(self.toolchain.clone(), current.toolchain.clone()));
+        }
+        if self.language != current.language {
+            changes.push(EnvironmentChange::Language);
+        }
+        if self.invocation != current.invocation {
+            changes.push(EnvironmentChange::Invocation);
+        }
+        let mut names: Vec<&String> = self.variables.keys().chain(current.variables.keys()).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let (recorded, now) = (self.variables.get(name), current.variables.get(name));
+            if recorded != now {
+                changes.push(EnvironmentChange::Variable(name.clone(), recorded.cloned(), now.cloned()));
+            }
+        }
+        changes
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`EnvironmentChange`] is a difference between the [`BuildEnvironment`] an output was produced in and another one
+pub enum EnvironmentChange {
+    /// The host changed from the first to the second
+    Host(String, String),
+    /// The requested target changed from the first to the second
+    Target(Option<String>, Option<String>),
+    /// The output of `--version` of the compiler changed from the first to the second
+    Toolchain(Option<String>, Option<String>),
+    /// The resolved [`Language`] changed
+    Language,
+    /// The command line changed
+    Invocation,
+    /// The environment variable with the given name changed from the first value to the second, `None` when it is not set
+    Variable(String, Option<String>, Option<String>),
+}
+
+impl Display for EnvironmentChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let display = |value: &Option<String>| value.as_deref().map_or("none".to_string(), |v| v.lines().next().unwrap_or_default().to_string());
+        match self {
+            EnvironmentChange::Host(recorded, current) => write!(f, "host changed from {} to {}", recorded, current),
+            EnvironmentChange::Target(recorded, current) => write!(f, "target changed from {} to {}", display(recorded), display(current)),
+            EnvironmentChange::Toolchain(recorded, current) => write!(f, "toolchain changed from {} to {}", display(recorded), display(current)),
+            EnvironmentChange::Language => write!(f, "language configuration changed"),
+            EnvironmentChange::Invocation => write!(f, "compiler invocation changed"),
+            EnvironmentChange::Variable(name, recorded, current) => write!(f, "${} changed from {} to {}", name, display(recorded), display(current)),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`ArtifactExplanation`] tells how an artifact was produced, see [`Registry::explain_artifact`]
+///
+/// [`Registry::explain_artifact`]: crate::registry::Registry::explain_artifact
+pub struct ArtifactExplanation {
+    pub(crate) artifact: PathBuf,
+    pub(crate) recorded: BuildEnvironment,
+    pub(crate) changes: Vec<EnvironmentChange>,
+}
+
+impl ArtifactExplanation {
+    /// Returns the artifact that was explained
+    pub fn artifact(&self) -> &Path {
+        &self.artifact
+    }
+
+    /// Returns the [`BuildEnvironment`] the artifact, or the output directory containing it, was produced in
+    pub fn recorded(&self) -> &BuildEnvironment {
+        &self.recorded
+    }
+
+    /// Returns how the current environment differs from the recorded one, empty when building now gives the same result
+    pub fn changes(&self) -> &[EnvironmentChange] {
+        &self.changes
+    }
+
+    /// Checks whether the current environment differs from the recorded one
+    pub fn is_stale(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+/// The file the [`BuildEnvironment`] of the given output is recorded in
+pub(crate) fn metadata_path<P: AsRef<Path>>(output: P) -> PathBuf {
+    let mut path = output.as_ref().as_os_str().to_os_string();
+    path.push(BUILD_METADATA_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// The architecture and operating system of the current machine
+pub(crate) fn host() -> String {
+    format!("{}-{}", ARCH, OS)
+}
+
+/// The [`RECORDED_VARIABLES`] and `KNAPSAC_` variables that are currently set
+pub(crate) fn recorded_variables() -> BTreeMap<String, String> {
+    env::vars()
+        .filter(|(name, _)| RECORDED_VARIABLES.contains(&name.as_str()) || name.starts_with("KNAPSAC_"))
+        .collect()
+}
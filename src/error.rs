@@ -0,0 +1,373 @@
+use crate::dependency::DependencyError;
+use crate::hooks::HookError;
+use crate::index::IndexError;
+use crate::install::InstallError;
+use crate::module::ModuleError;
+use crate::package_id::ParsePackageIdError;
+use crate::packaging::PackagingError;
+use crate::removal::RemovalError;
+use crate::run::RunError;
+use crate::signing::SignatureError;
+use crate::sync::{SchemaViolation, SyncError};
+use crate::version::ParseVersionError;
+#[cfg(feature = "watch")]
+use crate::watch::WatchError;
+
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`RegistryError`] explains why a [`Registry`] could not be loaded or saved
+///
+/// [`Registry`]: crate::registry::Registry
+pub enum RegistryError {
+    /// There is no file at the given [`PathBuf`]
+    NotFound(PathBuf),
+    /// The file at the given [`PathBuf`] does not contain a valid [`Registry`]
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    Malformed(PathBuf, String),
+    /// The file at the given [`PathBuf`] does not match its checksum, it was only partially written
+    PartialWriteDetected(PathBuf),
+    /// The lock file at the given [`PathBuf`] is held by another process, see [`SharedRegistry::write`]
+    ///
+    /// [`SharedRegistry::write`]: crate::shared::SharedRegistry::write
+    Locked(PathBuf),
+    /// The [`Registry`] could not be written to the given [`PathBuf`], for the given reason
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    Unwritable(PathBuf, String),
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::NotFound(path) => write!(f, "No registry found @ {}", path.display()),
+            RegistryError::Malformed(path, message) => write!(f, "Invalid registry @ {}: {}", path.display(), message),
+            RegistryError::PartialWriteDetected(path) => write!(
+                f,
+                "Registry @ {} was only partially written and no valid backup exists",
+                path.display()
+            ),
+            RegistryError::Locked(path) => write!(f, "Registry is locked by {}", path.display()),
+            RegistryError::Unwritable(path, reason) => write!(f, "Cannot save registry @ {}: {}", path.display(), reason),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`ManifestError`] explains why the manifest of a [`Package`] is not valid
+/// Errors found while parsing carry the line and column they were found at, both starting at 1
+///
+/// [`Package`]: crate::package::Package
+pub enum ManifestError {
+    /// There is no manifest at the given [`PathBuf`]
+    NotFound(PathBuf),
+    /// The manifest at the given [`PathBuf`] is not valid JSON or TOML at the given line and column, for the given reason
+    Syntax(PathBuf, usize, usize, String),
+    /// The manifest at the given [`PathBuf`] lacks the field with the given name in the object ending at the given line and column
+    MissingField(PathBuf, usize, usize, String),
+    /// The manifest at the given [`PathBuf`] contains the given string at the given line and column,
+    /// where a [`Version`] or [`VersionReq`] is expected
+    ///
+    /// [`Version`]: crate::version::Version
+    /// [`VersionReq`]: crate::version::VersionReq
+    InvalidVersion(PathBuf, usize, usize, String),
+    /// The manifest at the given [`PathBuf`] contains a string at the given line and column where an [`Url`] is expected,
+    /// the given reason explains why it is not one
+    ///
+    /// [`Url`]: url::Url
+    InvalidUrl(PathBuf, usize, usize, String),
+    /// The manifest at the given [`PathBuf`] contains a value at the given line and column that does not fit the field,
+    /// e.g. a number where a list is expected
+    Mismatch(PathBuf, usize, usize, String),
+    /// The manifest at the given [`PathBuf`] parses, but contains the given [`SchemaViolation`]s
+    Invalid(PathBuf, Vec<SchemaViolation>),
+}
+
+impl ManifestError {
+    /// Returns the location of the manifest
+    pub fn path(&self) -> &Path {
+        match self {
+            ManifestError::NotFound(path)
+            | ManifestError::Syntax(path, ..)
+            | ManifestError::MissingField(path, ..)
+            | ManifestError::InvalidVersion(path, ..)
+            | ManifestError::InvalidUrl(path, ..)
+            | ManifestError::Mismatch(path, ..)
+            | ManifestError::Invalid(path, _) => path,
+        }
+    }
+
+    /// Returns the line and column the error was found at, if it was found while parsing
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            ManifestError::Syntax(_, line, column, _)
+            | ManifestError::MissingField(_, line, column, _)
+            | ManifestError::InvalidVersion(_, line, column, _)
+            | ManifestError::InvalidUrl(_, line, column, _)
+            | ManifestError::Mismatch(_, line, column, _) => Some((*line, *column)),
+            ManifestError::NotFound(_) | ManifestError::Invalid(..) => None,
+        }
+    }
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let location = match self.position() {
+            Some((line, column)) => format!("{}:{}:{}", self.path().display(), line, column),
+            None => self.path().display().to_string(),
+        };
+        match self {
+            ManifestError::NotFound(_) => write!(f, "No manifest found @ {}", location),
+            ManifestError::Syntax(.., reason) => write!(f, "Invalid manifest @ {}: {}", location, reason),
+            ManifestError::MissingField(.., field) => write!(f, "Invalid manifest @ {}: missing field `{}`", location, field),
+            ManifestError::InvalidVersion(.., version) => write!(f, "Invalid manifest @ {}: `{}` is not a valid version", location, version),
+            ManifestError::InvalidUrl(.., reason) => write!(f, "Invalid manifest @ {}: invalid URL, {}", location, reason),
+            ManifestError::Mismatch(.., reason) => write!(f, "Invalid manifest @ {}: {}", location, reason),
+            ManifestError::Invalid(_, violations) => {
+                let violations: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+                write!(f, "Invalid manifest @ {}: {}", location, violations.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`Error`] is any error the crate returns, re-exported as `knapsac_lib::Error`
+/// Every error type of the crate converts into it, so `?` can be used across modules
+/// The wrapped error is available through [`std::error::Error::source`]
+///
+/// # Examples
+/// ```
+/// # use std::error::Error as _;
+/// # use knapsac_lib::Error;
+/// # use knapsac_lib::version::Version;
+///
+/// fn parse(version: &str) -> Result<Version, Error> {
+///     Ok(version.parse::<Version>()?)
+/// }
+///
+/// let error = parse("one").unwrap_err();
+/// assert!(matches!(error, Error::ParseVersion(_)));
+/// assert!(error.source().is_some());
+/// ```
+pub enum Error {
+    /// See [`RegistryError`]
+    Registry(RegistryError),
+    /// See [`ManifestError`]
+    Manifest(ManifestError),
+    /// See [`InstallError`]
+    Install(InstallError),
+    /// See [`RunError`]
+    Run(RunError),
+    /// See [`DependencyError`]
+    Dependency(DependencyError),
+    /// See [`ModuleError`]
+    Module(ModuleError),
+    /// See [`RemovalError`]
+    Removal(RemovalError),
+    /// See [`PackagingError`]
+    Packaging(PackagingError),
+    /// See [`IndexError`]
+    Index(IndexError),
+    /// See [`SignatureError`]
+    Signature(SignatureError),
+    /// See [`HookError`]
+    Hook(HookError),
+    /// See [`SyncError`]
+    Sync(SyncError),
+    /// See [`ParseVersionError`]
+    ParseVersion(ParseVersionError),
+    /// See [`ParsePackageIdError`]
+    ParsePackageId(ParsePackageIdError),
+    /// See [`WatchError`]
+    #[cfg(feature = "watch")]
+    Watch(WatchError),
+    /// A git operation on the repository at the given [`PathBuf`] failed for the given reason
+    Git(PathBuf, String),
+    /// A [`RegistryBackend`] failed for the given reason
+    ///
+    /// [`RegistryBackend`]: crate::backend::RegistryBackend
+    Backend(String),
+    /// The [`Package`] at the given [`PathBuf`] has no [`Version`] yet
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Version`]: crate::version::Version
+    Unpublished(PathBuf),
+    /// The given [`PathBuf`] lies outside the allowed roots of a [`Registry`], see [`Registry::set_allowed_roots`]
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    /// [`Registry::set_allowed_roots`]: crate::registry::Registry::set_allowed_roots
+    OutsideAllowedRoots(PathBuf),
+    /// No [`Package`] is registered for the given path or [`Url`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Url`]: url::Url
+    UnknownPackage(String),
+    /// The file or directory at the given [`PathBuf`] could not be read or written, for the given reason
+    Io(PathBuf, String),
+    /// The given [`PathBuf`] is relative, while an absolute one is required
+    RelativePath(PathBuf),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Registry(error) => error.fmt(f),
+            Error::Manifest(error) => error.fmt(f),
+            Error::Install(error) => error.fmt(f),
+            Error::Run(error) => error.fmt(f),
+            Error::Dependency(error) => error.fmt(f),
+            Error::Module(error) => error.fmt(f),
+            Error::Removal(error) => error.fmt(f),
+            Error::Packaging(error) => error.fmt(f),
+            Error::Index(error) => error.fmt(f),
+            Error::Signature(error) => error.fmt(f),
+            Error::Hook(error) => error.fmt(f),
+            Error::Sync(error) => error.fmt(f),
+            Error::ParseVersion(error) => error.fmt(f),
+            Error::ParsePackageId(error) => error.fmt(f),
+            #[cfg(feature = "watch")]
+            Error::Watch(error) => error.fmt(f),
+            Error::Git(path, reason) => write!(f, "git failed for {}: {}", path.display(), reason),
+            Error::Backend(reason) => write!(f, "registry backend failed: {}", reason),
+            Error::Unpublished(path) => write!(f, "{} has no version, publish it first", path.display()),
+            Error::OutsideAllowedRoots(path) => write!(f, "{} lies outside the allowed roots of the registry", path.display()),
+            Error::UnknownPackage(location) => write!(f, "No package registered for {}", location),
+            Error::Io(path, reason) => write!(f, "Cannot access {}: {}", path.display(), reason),
+            Error::RelativePath(path) => write!(f, "{} is relative", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Registry(error) => Some(error),
+            Error::Manifest(error) => Some(error),
+            Error::Install(error) => Some(error),
+            Error::Run(error) => Some(error),
+            Error::Dependency(error) => Some(error),
+            Error::Module(error) => Some(error),
+            Error::Removal(error) => Some(error),
+            Error::Packaging(error) => Some(error),
+            Error::Index(error) => Some(error),
+            Error::Signature(error) => Some(error),
+            Error::Hook(error) => Some(error),
+            Error::Sync(error) => Some(error),
+            Error::ParseVersion(error) => Some(error),
+            Error::ParsePackageId(error) => Some(error),
+            #[cfg(feature = "watch")]
+            Error::Watch(error) => Some(error),
+            Error::Git(..)
+            | Error::Backend(_)
+            | Error::Unpublished(_)
+            | Error::OutsideAllowedRoots(_)
+            | Error::UnknownPackage(_)
+            | Error::Io(..)
+            | Error::RelativePath(_) => None,
+        }
+    }
+}
+
+impl From<RegistryError> for Error {
+    fn from(error: RegistryError) -> Self {
+        Error::Registry(error)
+    }
+}
+
+impl From<ManifestError> for Error {
+    fn from(error: ManifestError) -> Self {
+        Error::Manifest(error)
+    }
+}
+
+impl From<InstallError> for Error {
+    fn from(error: InstallError) -> Self {
+        Error::Install(error)
+    }
+}
+
+impl From<RunError> for Error {
+    fn from(error: RunError) -> Self {
+        Error::Run(error)
+    }
+}
+
+impl From<DependencyError> for Error {
+    fn from(error: DependencyError) -> Self {
+        Error::Dependency(error)
+    }
+}
+
+impl From<ModuleError> for Error {
+    fn from(error: ModuleError) -> Self {
+        Error::Module(error)
+    }
+}
+
+impl From<RemovalError> for Error {
+    fn from(error: RemovalError) -> Self {
+        Error::Removal(error)
+    }
+}
+
+impl From<PackagingError> for Error {
+    fn from(error: PackagingError) -> Self {
+        Error::Packaging(error)
+    }
+}
+
+impl From<IndexError> for Error {
+    fn from(error: IndexError) -> Self {
+        Error::Index(error)
+    }
+}
+
+impl From<SignatureError> for Error {
+    fn from(error: SignatureError) -> Self {
+        Error::Signature(error)
+    }
+}
+
+impl From<HookError> for Error {
+    fn from(error: HookError) -> Self {
+        Error::Hook(error)
+    }
+}
+
+impl From<SyncError> for Error {
+    fn from(error: SyncError) -> Self {
+        Error::Sync(error)
+    }
+}
+
+impl From<ParseVersionError> for Error {
+    fn from(error: ParseVersionError) -> Self {
+        Error::ParseVersion(error)
+    }
+}
+
+impl From<ParsePackageIdError> for Error {
+    fn from(error: ParsePackageIdError) -> Self {
+        Error::ParsePackageId(error)
+    }
+}
+
+#[cfg(feature = "watch")]
+impl From<WatchError> for Error {
+    fn from(error: WatchError) -> Self {
+        Error::Watch(error)
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Serialize)]
+#[derive(Hash)]
+#[derive(Eq, PartialEq)]
+#[derive(Clone)]
+#[derive(Debug)]
+/// An [`Executable`] is a program provided by a [`Package`]
+///
+/// It is either built from the source file at its location, producing a binary named after it,
+/// or prebuilt, in which case the file at its location is the binary itself
+///
+/// [`Package`]: crate::package::Package
+pub struct Executable {
+    pub(crate) location: PathBuf,
+    #[serde(default)]
+    pub(crate) binary_name: Option<String>,
+    #[serde(default)]
+    pub(crate) link_flags: Vec<String>,
+    #[serde(default)]
+    pub(crate) language: Option<String>,
+    #[serde(default)]
+    pub(crate) output: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) prebuilt: bool,
+}
+
+impl Executable {
+    /// Creates a new [`Executable`] built from the source file at the given [`Path`]
+    ///
+    /// # Arguments
+    /// * `path` - A relative [`Path`] that points to a file within a [`Package`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use knapsac_lib::executable::Executable;
+    ///
+    /// let executable = Executable::create(["src", "main.sac"].iter().collect::<PathBuf>());
+    /// assert_eq!(executable.binary_name(), "main");
+    /// assert!(!executable.is_prebuilt());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when received path is absolute
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn create<P: AsRef<Path>>(path: P) -> Self {
+        if path.as_ref().is_absolute() {
+            panic!("Path is absolute")
+        }
+        Executable {
+            location: path.as_ref().to_path_buf(),
+            binary_name: None,
+            link_flags: vec![],
+            language: None,
+            output: None,
+            prebuilt: false,
+        }
+    }
+
+    /// Creates a new [`Executable`] for the prebuilt binary at the given [`Path`]
+    ///
+    /// # Arguments
+    /// * `path` - A relative [`Path`] that points to a file within a [`Package`]
+    ///
+    /// # Panics
+    /// Panics when received path is absolute
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn prebuilt<P: AsRef<Path>>(path: P) -> Self {
+        let mut executable = Executable::create(path);
+        executable.prebuilt = true;
+        executable
+    }
+
+    /// Returns the [`Path`] of the source file, or of the binary when the [`Executable`] is prebuilt,
+    /// relative to the [`Package`]'s `local_location`
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn location(&self) -> &Path {
+        &self.location
+    }
+
+    /// Checks whether the file at the location of the [`Executable`] is the binary itself
+    pub fn is_prebuilt(&self) -> bool {
+        self.prebuilt
+    }
+
+    /// Returns the file name of the binary, which defaults to the file name of the source file without its extension
+    pub fn binary_name(&self) -> String {
+        match &self.binary_name {
+            Some(name) => name.clone(),
+            None if self.prebuilt => self.location.file_name().unwrap().to_string_lossy().to_string(),
+            None => self.location.file_stem().unwrap().to_string_lossy().to_string(),
+        }
+    }
+
+    /// Sets the file name of the binary, `None` to derive it from the source file
+    pub fn set_binary_name(&mut self, name: Option<String>) {
+        self.binary_name = name;
+    }
+
+    /// Returns the flags passed to the linker when building the [`Executable`]
+    pub fn link_flags(&self) -> &[String] {
+        &self.link_flags
+    }
+
+    /// Sets the flags passed to the linker when building the [`Executable`]
+    pub fn set_link_flags(&mut self, flags: Vec<String>) {
+        self.link_flags = flags;
+    }
+
+    /// Returns the name of the [`Language`] the [`Executable`] is written in, if set
+    /// When no name is set the [`Executable`] uses the [`Language`] of its [`Package`]
+    ///
+    /// [`Language`]: crate::language::Language
+    /// [`Package`]: crate::package::Package
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Sets the name of the [`Language`] the [`Executable`] is written in
+    ///
+    /// [`Language`]: crate::language::Language
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    /// Returns the directory the binary is written to as it is stored, if set
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// Sets the directory the binary is written to
+    /// A relative [`Path`] is resolved against the directory of the source file
+    pub fn set_output<P: AsRef<Path>>(&mut self, output: Option<P>) {
+        self.output = output.map(|o| o.as_ref().to_path_buf());
+    }
+
+    /// Returns the [`Path`] of the binary relative to the [`Package`]'s `local_location`
+    /// A prebuilt binary is the file at its location, otherwise it is named [`Executable::binary_name`]
+    /// and placed in the output directory, or next to the source file when there is none
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::path::Path;
+    /// # use knapsac_lib::executable::Executable;
+    ///
+    /// let mut executable = Executable::create("src/main.sac");
+    /// assert_eq!(executable.binary_path(), Path::new("src/main"));
+    /// executable.set_output(Some("bin"));
+    /// executable.set_binary_name(Some("tool".to_string()));
+    /// assert_eq!(executable.binary_path(), Path::new("src/bin/tool"));
+    /// ```
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn binary_path(&self) -> PathBuf {
+        if self.prebuilt {
+            return self.location.clone();
+        }
+        let directory = self.location.parent().unwrap_or(Path::new(""));
+        match &self.output {
+            Some(output) => directory.join(output).join(self.binary_name()),
+            None => directory.join(self.binary_name()),
+        }
+    }
+}
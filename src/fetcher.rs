@@ -0,0 +1,480 @@
+use crate::format::Format;
+use crate::manifest::Manifest;
+use crate::package::Package;
+
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{FetchOptions, RemoteCallbacks, Repository};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::collections::HashMap;
+use std::fs::{copy, create_dir_all, read_dir, remove_dir_all};
+use std::path::Path;
+use std::process::Command;
+use url::Url;
+
+/// A [`Fetcher`] obtains the contents of a [`Package`] from a source and places them in a directory
+///
+/// [`Package`]: crate::package::Package
+pub trait Fetcher {
+    /// A short name identifying the kind of source, recorded as part of a [`Provenance`]
+    fn name(&self) -> &str;
+
+    /// Whether packages obtained by this [`Fetcher`] must be verified against a checksum
+    /// Sources that are not content addressed, unlike git, should require one
+    fn requires_checksum(&self) -> bool {
+        true
+    }
+
+    /// Places the contents found at `source` in the existing, empty directory at `destination`
+    ///
+    /// # Arguments
+    /// * `source` - An [`Url`] pointing to the contents of a [`Package`]
+    /// * `destination` - A [`Path`] pointing to the directory the contents need to be placed in
+    ///
+    /// [`Package`]: crate::package::Package
+    fn fetch(&self, source: &Url, destination: &Path) -> Result<(), String>;
+
+    /// Like [`Fetcher::fetch`], reporting the number of objects received and the total number of objects while transferring
+    /// By default this calls [`Fetcher::fetch`] without reporting progress
+    ///
+    /// # Arguments
+    /// * `source` - An [`Url`] pointing to the contents of a [`Package`]
+    /// * `destination` - A [`Path`] pointing to the directory the contents need to be placed in
+    /// * `progress` - Called with the number of objects received and the total number of objects
+    ///
+    /// [`Package`]: crate::package::Package
+    fn fetch_with_progress(&self, source: &Url, destination: &Path, progress: &mut dyn FnMut(usize, usize)) -> Result<(), String> {
+        let _ = progress;
+        self.fetch(source, destination)
+    }
+
+    /// The [`GitRef`] this [`Fetcher`] checks out, recorded as part of a [`Provenance`]
+    /// By default [`Fetcher`]s check out whatever the source provides by default, e.g. the default branch
+    fn reference(&self) -> Option<GitRef> {
+        None
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`GitRef`] selects what to check out of a git repository
+pub enum GitRef {
+    /// The latest commit of the branch with the given name
+    Branch(String),
+    /// The commit the tag with the given name points to
+    Tag(String),
+    /// The commit with the given SHA, which may be abbreviated
+    Commit(String),
+}
+
+impl Display for GitRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitRef::Branch(branch) => write!(f, "branch {}", branch),
+            GitRef::Tag(tag) => write!(f, "tag {}", tag),
+            GitRef::Commit(sha) => write!(f, "commit {}", sha),
+        }
+    }
+}
+
+/// A [`Fetcher`] that clones git repositories
+pub struct GitFetcher;
+
+impl Fetcher for GitFetcher {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn requires_checksum(&self) -> bool {
+        false
+    }
+
+    fn fetch(&self, source: &Url, destination: &Path) -> Result<(), String> {
+        Repository::clone(source.as_str(), destination)
+            .map(|_| ())
+            .map_err(|e| e.message().to_string())
+    }
+
+    fn fetch_with_progress(&self, source: &Url, destination: &Path, progress: &mut dyn FnMut(usize, usize)) -> Result<(), String> {
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.transfer_progress(|p| {
+            progress(p.received_objects(), p.total_objects());
+            true
+        });
+        let mut options = FetchOptions::new();
+        options.remote_callbacks(callbacks);
+        RepoBuilder::new()
+            .fetch_options(options)
+            .clone(source.as_str(), destination)
+            .map(|_| ())
+            .map_err(|e| e.message().to_string())
+    }
+}
+
+/// A [`Fetcher`] that clones git repositories and checks out the given [`GitRef`], so installs can be pinned
+/// A branch is checked out as a local branch, a tag or commit leaves the repository in a detached state
+///
+/// # Examples
+/// ```
+/// # use std::{env, fs};
+/// # use git2::Repository;
+/// # use url::Url;
+/// # use knapsac_lib::fetcher::{GitRef, PinnedGitFetcher};
+/// # use knapsac_lib::package::Package;
+/// # use knapsac_lib::registry::Registry;
+/// # use knapsac_lib::version::SemVerIncrement;
+///
+/// let origin = env::temp_dir().join("mock_package_pinned_origin");
+/// # let _ = fs::remove_dir_all(&origin);
+/// Repository::init(&origin);
+/// let package = Package::create(&origin);
+/// package.publish(SemVerIncrement::Major, vec![]);
+/// package.publish(SemVerIncrement::Major, vec![]);
+///
+/// let mut registry = Registry::initialize(env::temp_dir().join("registry_pinned.json"));
+/// let destination = env::temp_dir().join("mock_package_pinned_install");
+/// # let _ = fs::remove_dir_all(&destination);
+/// # fs::create_dir_all(&destination).unwrap();
+/// let fetcher = PinnedGitFetcher::create(GitRef::Tag("1.0.0".to_string()));
+/// let installed = registry.install(&fetcher, Url::from_directory_path(&origin).unwrap(), &destination, None);
+/// assert_eq!(installed.version().unwrap().to_string(), "1.0.0");
+///
+/// let provenance = registry.get_provenance(&installed).unwrap();
+/// assert_eq!(provenance.reference(), Some(&GitRef::Tag("1.0.0".to_string())));
+/// assert_eq!(provenance.revision().unwrap().len(), 40);
+/// ```
+pub struct PinnedGitFetcher {
+    pub(crate) reference: GitRef,
+}
+
+impl PinnedGitFetcher {
+    /// Creates a new [`PinnedGitFetcher`] checking out the given [`GitRef`]
+    pub fn create(reference: GitRef) -> Self {
+        PinnedGitFetcher { reference }
+    }
+}
+
+impl Fetcher for PinnedGitFetcher {
+    fn name(&self) -> &str {
+        "git-pinned"
+    }
+
+    fn requires_checksum(&self) -> bool {
+        false
+    }
+
+    fn fetch(&self, source: &Url, destination: &Path) -> Result<(), String> {
+        let repository = Repository::clone(source.as_str(), destination).map_err(|e| e.message().to_string())?;
+        let specification = match &self.reference {
+            GitRef::Branch(branch) => format!("refs/remotes/origin/{}", branch),
+            GitRef::Tag(tag) => format!("refs/tags/{}", tag),
+            GitRef::Commit(sha) => sha.clone(),
+        };
+        let commit = repository.revparse_single(&specification)
+            .and_then(|o| o.peel_to_commit())
+            .map_err(|_| format!("{} not found in {}", self.reference, source))?;
+        repository.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+            .map_err(|e| e.message().to_string())?;
+        match &self.reference {
+            GitRef::Branch(branch) => {
+                repository.branch(branch, &commit, true).map_err(|e| e.message().to_string())?;
+                repository.set_head(&format!("refs/heads/{}", branch))
+            }
+            _ => repository.set_head_detached(commit.id()),
+        }.map_err(|e| e.message().to_string())
+    }
+
+    fn reference(&self) -> Option<GitRef> {
+        Some(self.reference.clone())
+    }
+}
+
+/// A [`Fetcher`] that clones only what is needed of a git repository by invoking the `git` command line tool
+///
+/// The clone is shallow: it contains only the commit at the requested tag, or the latest commit.
+/// A sparse clone also checks out only the manifest, the [`Module`] source files and their output directories,
+/// as declared by the manifest at that commit
+///
+/// [`Module`]: crate::module::Module
+pub struct ShallowGitFetcher {
+    pub(crate) tag: Option<String>,
+    pub(crate) sparse: bool,
+}
+
+impl ShallowGitFetcher {
+    /// Creates a new [`ShallowGitFetcher`]
+    ///
+    /// # Arguments
+    /// * `tag` - The tag to clone, e.g. a published [`Version`], or `None` to clone the latest commit
+    /// * `sparse` - Whether to check out only the files the manifest refers to
+    ///
+    /// [`Version`]: crate::version::Version
+    pub fn create(tag: Option<String>, sparse: bool) -> Self {
+        ShallowGitFetcher { tag, sparse }
+    }
+
+    fn git(directory: &Path, arguments: &[&str]) -> Result<Vec<u8>, String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(directory)
+            .args(arguments)
+            .output()
+            .map_err(|e| format!("Failed to invoke git: {}", e))?;
+        match output.status.success() {
+            true => Ok(output.stdout),
+            false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        }
+    }
+
+    /// Lists the sparse checkout patterns covering the manifest and the files and directories it refers to
+    fn sparse_patterns(destination: &Path) -> Result<Vec<String>, String> {
+        let mut patterns = vec![String::from("/manifest.*")];
+        let format = [Format::Json, #[cfg(feature = "toml")] Format::Toml]
+            .into_iter()
+            .find(|f| ShallowGitFetcher::git(destination, &["cat-file", "-e", &format!("HEAD:manifest.{}", f.extension())]).is_ok());
+        let format = match format {
+            Some(format) => format,
+            None => return Ok(patterns),
+        };
+        let contents = ShallowGitFetcher::git(destination, &["show", &format!("HEAD:manifest.{}", format.extension())])?;
+        let manifest: Manifest = format.deserialize(&String::from_utf8_lossy(&contents))?;
+        for module in manifest.modules {
+            patterns.push(format!("/{}", module.location.display()));
+            for output in module.all_outputs().filter(|o| o.is_relative()) {
+                let directory = module.location.parent().map(|p| p.join(output)).unwrap_or_else(|| output.clone());
+                patterns.push(format!("/{}/", directory.display()));
+            }
+        }
+        Ok(patterns)
+    }
+}
+
+impl Fetcher for ShallowGitFetcher {
+    fn name(&self) -> &str {
+        "git-shallow"
+    }
+
+    fn requires_checksum(&self) -> bool {
+        false
+    }
+
+    fn fetch(&self, source: &Url, destination: &Path) -> Result<(), String> {
+        let mut clone = vec!["clone", "--quiet", "--depth", "1"];
+        if let Some(tag) = &self.tag {
+            clone.extend(["--branch", tag]);
+        }
+        if self.sparse {
+            clone.push("--no-checkout");
+        }
+        let destination_argument = destination.to_string_lossy();
+        clone.extend([source.as_str(), &destination_argument]);
+        ShallowGitFetcher::git(destination, &clone)?;
+
+        if self.sparse {
+            let patterns = ShallowGitFetcher::sparse_patterns(destination)?;
+            let mut set = vec!["sparse-checkout", "set", "--no-cone"];
+            set.extend(patterns.iter().map(String::as_str));
+            ShallowGitFetcher::git(destination, &set)?;
+            ShallowGitFetcher::git(destination, &["checkout", "--quiet"])?;
+        }
+        Ok(())
+    }
+}
+
+/// A [`Fetcher`] that copies directories from the local file system, e.g. a mounted network share
+/// Sources are `file://` [`Url`]s
+pub struct LocalFetcher;
+
+impl LocalFetcher {
+    fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+        create_dir_all(to)?;
+        for entry in read_dir(from)? {
+            let entry = entry?;
+            let target = to.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                LocalFetcher::copy_dir(&entry.path(), &target)?;
+            } else {
+                copy(entry.path(), target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Fetcher for LocalFetcher {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn fetch(&self, source: &Url, destination: &Path) -> Result<(), String> {
+        let path = source
+            .to_file_path()
+            .map_err(|_| format!("`{}` is not a local path", source))?;
+        if !path.is_dir() {
+            return Err(format!("No directory found @ {}", path.display()));
+        }
+        LocalFetcher::copy_dir(&path, destination).map_err(|e| e.to_string())
+    }
+}
+
+/// A [`Fetcher`] that tries the mirrors of a source in order when fetching from the source itself fails,
+/// fetching each location with the wrapped [`Fetcher`]
+/// The [`Provenance`] of a [`Package`] obtained from a mirror still records the original source
+///
+/// # Examples
+/// ```
+/// # use std::{env, fs};
+/// # use url::Url;
+/// # use knapsac_lib::fetcher::{LocalFetcher, MirrorFetcher};
+/// # use knapsac_lib::package::Package;
+/// # use knapsac_lib::registry::Registry;
+///
+/// let mirror = env::temp_dir().join("mock_share_mirror");
+/// fs::create_dir_all(&mirror).unwrap();
+/// fs::write(mirror.join("a.sac"), "module a;").unwrap();
+/// let checksum = Package::checksum(&mirror);
+/// let unreachable = Url::from_directory_path(env::temp_dir().join("mock_share_unreachable")).unwrap();
+///
+/// let mut fetcher = MirrorFetcher::create(LocalFetcher);
+/// fetcher.add_mirror(unreachable.clone(), Url::from_directory_path(&mirror).unwrap());
+///
+/// let mut registry = Registry::initialize(env::temp_dir().join("registry_mirror_fetcher.json"));
+/// let package = registry.install(&fetcher, unreachable.clone(), env::temp_dir(), Some(&checksum));
+/// let provenance = registry.get_provenance(&package).unwrap();
+/// assert_eq!(provenance.source(), &unreachable);
+/// assert_eq!(provenance.fetcher(), "local");
+/// ```
+///
+/// [`Package`]: crate::package::Package
+pub struct MirrorFetcher<F: Fetcher> {
+    pub(crate) fetcher: F,
+    pub(crate) mirrors: HashMap<Url, Vec<Url>>,
+}
+
+impl<F: Fetcher> MirrorFetcher<F> {
+    /// Creates a new [`MirrorFetcher`] without any mirrors, wrapping the given [`Fetcher`]
+    pub fn create(fetcher: F) -> Self {
+        MirrorFetcher {
+            fetcher,
+            mirrors: HashMap::new(),
+        }
+    }
+
+    /// Adds a mirror of the given source, tried after the source and the mirrors added before
+    ///
+    /// # Arguments
+    /// * `source` - An [`Url`] pointing to the contents of a [`Package`]
+    /// * `mirror` - An [`Url`] pointing to a copy of the same contents
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn add_mirror(&mut self, source: Url, mirror: Url) {
+        let mirrors = self.mirrors.entry(source).or_default();
+        if !mirrors.contains(&mirror) {
+            mirrors.push(mirror);
+        }
+    }
+
+    /// Adds the mirrors recorded by the given [`Package`] for its primary remote location, see [`Package::add_mirror`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Package::add_mirror`]: crate::package::Package::add_mirror
+    pub fn add_mirrors_of(&mut self, package: &Package) {
+        if let Some(source) = &package.remote_location {
+            for mirror in package.mirrors().into_iter().filter(|m| m != source) {
+                self.add_mirror(source.clone(), mirror);
+            }
+        }
+    }
+
+    /// Returns the mirrors of the given source, in the order they are tried
+    pub fn mirrors(&self, source: &Url) -> &[Url] {
+        self.mirrors.get(source).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+impl<F: Fetcher> Fetcher for MirrorFetcher<F> {
+    fn name(&self) -> &str {
+        self.fetcher.name()
+    }
+
+    fn requires_checksum(&self) -> bool {
+        self.fetcher.requires_checksum()
+    }
+
+    fn fetch(&self, source: &Url, destination: &Path) -> Result<(), String> {
+        self.fetch_with_progress(source, destination, &mut |_, _| {})
+    }
+
+    fn fetch_with_progress(&self, source: &Url, destination: &Path, progress: &mut dyn FnMut(usize, usize)) -> Result<(), String> {
+        let mut failures = vec![];
+        for location in std::iter::once(source).chain(self.mirrors(source)) {
+            match self.fetcher.fetch_with_progress(location, destination, progress) {
+                Ok(()) => return Ok(()),
+                Err(e) => failures.push(format!("{}: {}", location, e)),
+            }
+            let _ = remove_dir_all(destination);
+            create_dir_all(destination).map_err(|e| e.to_string())?;
+        }
+        Err(failures.join(", "))
+    }
+
+    fn reference(&self) -> Option<GitRef> {
+        self.fetcher.reference()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// [`Provenance`] records where and how a [`Package`] was obtained
+///
+/// [`Package`]: crate::package::Package
+pub struct Provenance {
+    pub(crate) source: Url,
+    pub(crate) fetcher: String,
+    pub(crate) checksum: Option<String>,
+    #[serde(default)]
+    pub(crate) reference: Option<GitRef>,
+    #[serde(default)]
+    pub(crate) revision: Option<String>,
+}
+
+impl Provenance {
+    /// Returns the [`Url`] the [`Package`] was obtained from
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn source(&self) -> &Url {
+        &self.source
+    }
+
+    /// Returns the name of the [`Fetcher`] that obtained the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn fetcher(&self) -> &str {
+        &self.fetcher
+    }
+
+    /// Returns the checksum the [`Package`]'s contents were verified against, if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn checksum(&self) -> Option<&str> {
+        self.checksum.as_deref()
+    }
+
+    /// Returns the [`GitRef`] the [`Package`] was pinned to when it was installed, if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn reference(&self) -> Option<&GitRef> {
+        self.reference.as_ref()
+    }
+
+    /// Returns the SHA of the commit that was checked out when the [`Package`] was installed, if it is a git repository
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+}
@@ -0,0 +1,123 @@
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// The file format a [`Registry`] or manifest is persisted in, selected by file extension
+///
+/// [`Registry`]: crate::registry::Registry
+pub enum Format {
+    /// A `.json` file
+    Json,
+    /// A `.toml` file, only available with the `toml` feature enabled
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// The layout a [`Registry`] is written in, see [`Registry::set_style`]
+/// Either way keys and sets are written in a deterministic order, so saved files diff cleanly
+///
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::set_style`]: crate::registry::Registry::set_style
+pub enum Style {
+    /// Indented, one value per line
+    #[default]
+    Pretty,
+    /// As few characters as possible
+    Compact,
+}
+
+impl Format {
+    /// Returns the [`Format`] matching the extension of the given [`Path`], if it is supported
+    ///
+    /// # Arguments
+    /// * `path` - A [`Path`] pointing to a file
+    ///
+    /// # Examples
+    /// ```
+    /// # use knapsac_lib::format::Format;
+    ///
+    /// assert_eq!(Format::from_path("/tmp/registry.json"), Some(Format::Json));
+    /// assert_eq!(Format::from_path("/tmp/registry.txt"), None);
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            #[cfg(feature = "toml")]
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    /// Returns the file extension belonging to the [`Format`]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            #[cfg(feature = "toml")]
+            Format::Toml => "toml",
+        }
+    }
+
+    pub(crate) fn serialize<T: Serialize>(&self, value: &T, style: Style) -> String {
+        match (self, style) {
+            (Format::Json, Style::Pretty) => serde_json::to_string_pretty(value).unwrap(),
+            (Format::Json, Style::Compact) => serde_json::to_string(value).unwrap(),
+            #[cfg(feature = "toml")]
+            (Format::Toml, Style::Pretty) => toml::to_string_pretty(value).unwrap(),
+            #[cfg(feature = "toml")]
+            (Format::Toml, Style::Compact) => toml::to_string(value).unwrap(),
+        }
+    }
+
+    /// Deserializes like [`Format::deserialize`], also returning where deserialization failed
+    pub(crate) fn deserialize_located<T: DeserializeOwned>(&self, data: &str) -> Result<T, LocatedError> {
+        match self {
+            Format::Json => serde_json::from_str(data).map_err(|e| {
+                let suffix = format!(" at line {} column {}", e.line(), e.column());
+                let message = e.to_string();
+                LocatedError {
+                    line: e.line(),
+                    column: e.column(),
+                    message: message.strip_suffix(&suffix).unwrap_or(&message).to_string(),
+                    syntax: matches!(e.classify(), Category::Syntax | Category::Eof),
+                }
+            }),
+            #[cfg(feature = "toml")]
+            Format::Toml => toml::from_str(data).map_err(|e| {
+                let offset = e.span().map_or(0, |s| s.start);
+                let before = &data[..offset];
+                LocatedError {
+                    line: before.matches('\n').count() + 1,
+                    column: before.rsplit('\n').next().unwrap_or_default().chars().count() + 1,
+                    message: e.message().trim().to_string(),
+                    syntax: toml::from_str::<toml::Table>(data).is_err(),
+                }
+            }),
+        }
+    }
+
+    pub(crate) fn deserialize<T: DeserializeOwned>(&self, data: &str) -> Result<T, String> {
+        match self {
+            Format::Json => serde_json::from_str(data).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            Format::Toml => toml::from_str(data).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// An error found while deserializing, with the line and column it was found at, both starting at 1
+pub(crate) struct LocatedError {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) message: String,
+    /// Whether the data is not valid JSON or TOML at all, rather than not fitting the deserialized type
+    pub(crate) syntax: bool,
+}
@@ -0,0 +1,160 @@
+use crate::utils::discover_git_repository;
+
+use git2::{Repository, StatusOptions};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// [`GitMetadata`] describes the state of a git repository at the time it was read
+pub struct GitMetadata {
+    pub(crate) remotes: Vec<String>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) dirty: bool,
+}
+
+impl GitMetadata {
+    fn read(repository: &Repository) -> Self {
+        let remotes = repository.remotes().unwrap();
+        let remotes = remotes
+            .iter()
+            .flatten()
+            .filter_map(|name| repository.find_remote(name).ok())
+            .filter_map(|remote| remote.url().map(String::from))
+            .collect();
+        let tags = repository.tag_names(None).unwrap();
+        let tags = tags.iter().flatten().map(String::from).collect();
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).include_ignored(false);
+        let dirty = !repository.statuses(Some(&mut options)).unwrap().is_empty();
+
+        GitMetadata { remotes, tags, dirty }
+    }
+
+    /// Returns the urls of the repository's remotes
+    pub fn remotes(&self) -> &[String] {
+        &self.remotes
+    }
+
+    /// Returns the names of the repository's tags
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Checks whether the repository's working directory has uncommitted changes
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+#[derive(Default)]
+struct GitCache {
+    workdirs: HashMap<PathBuf, PathBuf>,
+    metadata: HashMap<PathBuf, GitMetadata>,
+}
+
+fn cache() -> MutexGuard<'static, GitCache> {
+    static CACHE: OnceLock<Mutex<GitCache>> = OnceLock::new();
+    CACHE.get_or_init(Default::default).lock().unwrap()
+}
+
+/// Returns the root of the working directory of the git repository containing the given [`Path`]
+/// The result is cached until [`invalidate`] or [`invalidate_all`] is called
+///
+/// # Arguments
+/// * `path` - A [`Path`] pointing to somewhere inside a git repository
+///
+/// # Panics
+/// Panics when the given [`Path`] is not inside a git repository with a working directory
+pub fn workdir<P: AsRef<Path>>(path: P) -> PathBuf {
+    if let Some(workdir) = cache().workdirs.get(path.as_ref()) {
+        return workdir.clone();
+    }
+    let workdir = discover_git_repository(&path)
+        .workdir()
+        .unwrap_or_else(|| panic!(
+            "Failed to find root of local repository for path '{}'",
+            path.as_ref().display(),
+        ))
+        .to_path_buf();
+    cache().workdirs.insert(path.as_ref().to_path_buf(), workdir.clone());
+    workdir
+}
+
+/// Returns the [`GitMetadata`] of the git repository containing the given [`Path`]
+/// The result is cached until [`invalidate`] or [`invalidate_all`] is called
+///
+/// # Arguments
+/// * `path` - A [`Path`] pointing to somewhere inside a git repository
+///
+/// # Examples
+/// ```
+/// # use std::{env, fs};
+/// # use git2::Repository;
+/// # use knapsac_lib::git_cache;
+///
+/// let path = env::temp_dir().join("mock_repository_git_cache");
+/// # let _ = fs::remove_dir_all(&path);
+/// let repository = Repository::init(&path).unwrap();
+/// assert!(git_cache::metadata(&path).remotes().is_empty());
+///
+/// repository.remote("origin", "https://example.com/a").unwrap();
+/// assert!(git_cache::metadata(&path).remotes().is_empty());
+///
+/// git_cache::invalidate(&path);
+/// assert_eq!(git_cache::metadata(&path).remotes(), &["https://example.com/a".to_string()]);
+/// ```
+///
+/// # Panics
+/// Panics when the given [`Path`] is not inside a git repository with a working directory
+pub fn metadata<P: AsRef<Path>>(path: P) -> GitMetadata {
+    let workdir = workdir(&path);
+    if let Some(metadata) = cache().metadata.get(&workdir) {
+        return metadata.clone();
+    }
+    let metadata = GitMetadata::read(&discover_git_repository(&workdir));
+    cache().metadata.insert(workdir, metadata.clone());
+    metadata
+}
+
+/// Drops the cached [`GitMetadata`] and working directories of the git repository containing the given [`Path`]
+/// Call this after changing a repository's remotes, tags or working directory, or after moving or removing it
+///
+/// # Arguments
+/// * `path` - A [`Path`] pointing to somewhere inside a git repository
+///
+/// # Examples
+/// ```
+/// # use std::{env, fs};
+/// # use git2::Repository;
+/// # use knapsac_lib::git_cache;
+///
+/// let path = env::temp_dir().join("mock_repository_git_cache_invalidate");
+/// # let _ = fs::remove_dir_all(&path);
+/// Repository::init(&path).unwrap();
+/// let nested = path.join("nested");
+/// fs::create_dir_all(&nested).unwrap();
+/// assert_eq!(git_cache::workdir(&nested), path);
+///
+/// Repository::init(&nested).unwrap();
+/// git_cache::invalidate(&nested);
+/// assert_eq!(git_cache::workdir(&nested), nested);
+/// ```
+pub fn invalidate<P: AsRef<Path>>(path: P) {
+    let cached = cache().workdirs.get(path.as_ref()).cloned();
+    let workdir = cached
+        .or_else(|| Repository::discover(&path).ok()?.workdir().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.as_ref().to_path_buf());
+    let mut cache = cache();
+    cache.metadata.remove(&workdir);
+    cache.workdirs.retain(|p, w| p != path.as_ref() && w != &workdir && !p.starts_with(&workdir));
+}
+
+/// Drops everything that was cached
+pub fn invalidate_all() {
+    let mut cache = cache();
+    cache.workdirs.clear();
+    cache.metadata.clear();
+}
@@ -0,0 +1,93 @@
+use std::fmt::Write;
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// The text format a dependency graph is exported to
+pub enum GraphFormat {
+    /// GraphViz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub(crate) enum NodeKind {
+    Package,
+    Module,
+    Unresolved,
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+pub(crate) enum EdgeKind {
+    Provides,
+    DependsOn,
+}
+
+/// A format independent description of a dependency graph
+#[derive(Default)]
+pub(crate) struct Graph {
+    pub(crate) nodes: Vec<(String, String, NodeKind)>,
+    pub(crate) edges: Vec<(String, String, EdgeKind)>,
+}
+
+impl Graph {
+    pub(crate) fn node<S: Into<String>>(&mut self, id: S, label: S, kind: NodeKind) {
+        self.nodes.push((id.into(), label.into(), kind));
+    }
+
+    pub(crate) fn edge<S: Into<String>>(&mut self, from: S, to: S, kind: EdgeKind) {
+        self.edges.push((from.into(), to.into(), kind));
+    }
+
+    pub(crate) fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.render_dot(),
+            GraphFormat::Mermaid => self.render_mermaid(),
+        }
+    }
+
+    fn render_dot(&self) -> String {
+        let mut out = String::from("digraph knapsac {\n");
+        for (id, label, kind) in &self.nodes {
+            let style = match kind {
+                NodeKind::Package => "shape=box, style=bold",
+                NodeKind::Module => "shape=ellipse",
+                NodeKind::Unresolved => "shape=box, style=dashed, color=red",
+            };
+            writeln!(out, "    {} [label=\"{}\", {}];", id, label.replace('"', "\\\""), style).unwrap();
+        }
+        for (from, to, kind) in &self.edges {
+            let style = match kind {
+                EdgeKind::Provides => " [style=dotted, arrowhead=none]",
+                EdgeKind::DependsOn => "",
+            };
+            writeln!(out, "    {} -> {}{};", from, to, style).unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn render_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for (id, label, kind) in &self.nodes {
+            let label = label.replace('"', "#quot;");
+            match kind {
+                NodeKind::Package => writeln!(out, "    {}[\"{}\"]", id, label),
+                NodeKind::Module => writeln!(out, "    {}([\"{}\"])", id, label),
+                NodeKind::Unresolved => writeln!(out, "    {}{{{{\"{}\"}}}}", id, label),
+            }
+            .unwrap();
+        }
+        for (from, to, kind) in &self.edges {
+            let arrow = match kind {
+                EdgeKind::Provides => "-.-",
+                EdgeKind::DependsOn => "-->",
+            };
+            writeln!(out, "    {} {} {}", from, arrow, to).unwrap();
+        }
+        out
+    }
+}
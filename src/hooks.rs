@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Set to the name of the [`HookKind`] being run, e.g. `pre_build`
+pub const HOOK_VARIABLE: &str = "KNAPSAC_HOOK";
+/// Set to the absolute `local_location` of the [`Package`] whose hook is run
+///
+/// [`Package`]: crate::package::Package
+pub const PACKAGE_ROOT_VARIABLE: &str = "KNAPSAC_PACKAGE_ROOT";
+/// Set to the [`Version`] of the [`Package`] whose hook is run, empty when it has none
+///
+/// [`Package`]: crate::package::Package
+/// [`Version`]: crate::version::Version
+pub const PACKAGE_VERSION_VARIABLE: &str = "KNAPSAC_PACKAGE_VERSION";
+/// Set to the absolute output directories of the [`Module`]s of the [`Package`] whose hook is run,
+/// joined like the `PATH` variable of the platform
+///
+/// [`Module`]: crate::module::Module
+/// [`Package`]: crate::package::Package
+pub const OUTPUT_DIRECTORIES_VARIABLE: &str = "KNAPSAC_OUTPUT_DIRS";
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+/// The moment in the lifecycle of a [`Package`] a hook declared in its manifest is run at
+///
+/// [`Package`]: crate::package::Package
+pub enum HookKind {
+    /// Before an [`Executable`] of the [`Package`] is built, see [`Registry::run`]
+    /// A failing hook aborts the build
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Package`]: crate::package::Package
+    /// [`Registry::run`]: crate::registry::Registry::run
+    PreBuild,
+    /// After an [`Executable`] of the [`Package`] was built successfully, see [`Registry::run`]
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Package`]: crate::package::Package
+    /// [`Registry::run`]: crate::registry::Registry::run
+    PostBuild,
+    /// Before the [`Package`] is published, see [`Package::publish`]
+    /// A failing hook aborts publishing
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Package::publish`]: crate::package::Package::publish
+    PrePublish,
+    /// After the [`Package`] was installed and registered, see [`Registry::install`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Registry::install`]: crate::registry::Registry::install
+    PostInstall,
+}
+
+impl HookKind {
+    /// Returns the name of the [`HookKind`] as it is written in the manifest, e.g. `pre_build`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookKind::PreBuild => "pre_build",
+            HookKind::PostBuild => "post_build",
+            HookKind::PrePublish => "pre_publish",
+            HookKind::PostInstall => "post_install",
+        }
+    }
+}
+
+impl Display for HookKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`HookPolicy`] decides which hooks a [`Registry`] runs, see [`Registry::set_hook_policy`]
+///
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::set_hook_policy`]: crate::registry::Registry::set_hook_policy
+pub enum HookPolicy {
+    /// Hooks of every [`Package`] are run, including those of installed [`Package`]s
+    ///
+    /// [`Package`]: crate::package::Package
+    All,
+    /// Only hooks of [`Package`]s without a [`Provenance`] are run, so code obtained from elsewhere is not executed
+    /// [`Package`]s installed through a [`Fetcher`], unpacked from an archive or imported from an export all have one
+    ///
+    /// [`Fetcher`]: crate::fetcher::Fetcher
+    /// [`Package`]: crate::package::Package
+    /// [`Provenance`]: crate::fetcher::Provenance
+    #[default]
+    Local,
+    /// No hooks are run
+    None,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`HookError`] explains why a hook of a [`Package`] failed
+///
+/// [`Package`]: crate::package::Package
+pub struct HookError {
+    pub(crate) kind: HookKind,
+    pub(crate) package: PathBuf,
+    pub(crate) command: String,
+    pub(crate) reason: String,
+}
+
+impl HookError {
+    /// Returns the [`HookKind`] of the hook that failed
+    pub fn kind(&self) -> HookKind {
+        self.kind
+    }
+
+    /// Returns the `local_location` of the [`Package`] declaring the hook
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn package(&self) -> &Path {
+        &self.package
+    }
+
+    /// Returns the command of the hook
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Returns why the hook failed: why it could not be started, or its exit status and error output
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for HookError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} hook `{}` of {} failed: {}", self.kind, self.command, self.package.display(), self.reason)
+    }
+}
+
+impl std::error::Error for HookError {}
+
+/// Runs a hook command from the given `local_location`, with the variables describing the [`Package`] set
+///
+/// [`Package`]: crate::package::Package
+pub(crate) fn run_hook(kind: HookKind, command: &[String], root: &Path, version: Option<String>, outputs: &[PathBuf]) -> Result<(), HookError> {
+    let error = |reason: String| HookError {
+        kind,
+        package: root.to_path_buf(),
+        command: command.join(" "),
+        reason,
+    };
+    let Some((program, args)) = command.split_first() else { return Ok(()) };
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .env(HOOK_VARIABLE, kind.name())
+        .env(PACKAGE_ROOT_VARIABLE, root)
+        .env(PACKAGE_VERSION_VARIABLE, version.unwrap_or_default())
+        .env(OUTPUT_DIRECTORIES_VARIABLE, env::join_paths(outputs).unwrap_or_default())
+        .output()
+        .map_err(|e| error(e.to_string()))?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(error(format!("{}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()))),
+    }
+}
@@ -0,0 +1,136 @@
+use crate::utils::glob_matches;
+
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// The file in the root of a [`Package`] listing the files sweeps leave out, one pattern per line, see [`IgnoreRules`]
+///
+/// [`Package`]: crate::package::Package
+pub const IGNORE_FILE: &str = ".knapsacignore";
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+struct IgnoreRule {
+    pattern: String,
+    negated: bool,
+    directory: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (directory, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let pattern = match line.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if line.contains('/') => line.to_string(),
+            None => format!("**/{}", line),
+        };
+        Some(IgnoreRule { pattern, negated, directory })
+    }
+
+    /// Checks whether the given relative [`Path`], or one of the directories containing it, matches the rule
+    fn matches(&self, path: &Path) -> bool {
+        let components: Vec<_> = path.components().collect();
+        (1..=components.len()).any(|n| {
+            let prefix: PathBuf = components[..n].iter().collect();
+            (n < components.len() || !self.directory) && glob_matches(&self.pattern, prefix)
+        })
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// [`IgnoreRules`] select the files that sweeping a directory into a [`Package`] leaves out, e.g. editor backups or vendored code
+///
+/// Every rule is a glob pattern as accepted by [`Registry::add_items_matching`], relative to the root of the [`Package`]
+/// - A pattern without `/` matches a file or directory with that name anywhere, e.g. `*~`
+/// - A pattern starting with `/` or containing `/` is matched from the root, e.g. `/vendor`
+/// - A pattern ending with `/` only matches directories
+/// - A pattern starting with `!` includes files again that an earlier rule ignored
+/// - Empty lines and lines starting with `#` are skipped
+///
+/// A file is ignored when the last rule matching it, or one of the directories containing it, is not negated
+///
+/// # Examples
+/// ```
+/// # use knapsac_lib::ignore::IgnoreRules;
+///
+/// let rules = IgnoreRules::parse("# editor backups\n*~\nvendor/\n!vendor/keep.sac\n");
+/// assert!(rules.is_ignored("src/a.sac~"));
+/// assert!(rules.is_ignored("vendor/json/json.sac"));
+/// assert!(!rules.is_ignored("vendor/keep.sac"));
+/// assert!(!rules.is_ignored("src/vendor"));
+/// assert!(!rules.is_ignored("src/a.sac"));
+/// ```
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry::add_items_matching`]: crate::registry::Registry::add_items_matching
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Parses [`IgnoreRules`] from the contents of an [`IGNORE_FILE`], one rule per line
+    ///
+    /// # Arguments
+    /// * `contents` - The rules, separated by newlines
+    pub fn parse(contents: &str) -> Self {
+        IgnoreRules {
+            rules: contents.lines().filter_map(IgnoreRule::parse).collect(),
+        }
+    }
+
+    /// Loads the [`IgnoreRules`] from the [`IGNORE_FILE`] in the given directory, no rules when it does not exist
+    ///
+    /// # Arguments
+    /// * `root` - A [`Path`] pointing to the root of a [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn load<P: AsRef<Path>>(root: P) -> Self {
+        match read_to_string(root.as_ref().join(IGNORE_FILE)) {
+            Ok(contents) => IgnoreRules::parse(&contents),
+            Err(_) => IgnoreRules::default(),
+        }
+    }
+
+    /// Adds a rule after the existing ones, so it takes precedence over them
+    ///
+    /// # Arguments
+    /// * `rule` - A single line of an [`IGNORE_FILE`]
+    pub fn add(&mut self, rule: &str) {
+        self.rules.extend(IgnoreRule::parse(rule));
+    }
+
+    /// Adds the given [`IgnoreRules`] after the existing ones, so they take precedence over them
+    pub fn extend(&mut self, other: IgnoreRules) {
+        self.rules.extend(other.rules);
+    }
+
+    /// Checks whether the [`IgnoreRules`] leave out the file at the given [`Path`], relative to the root of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn is_ignored<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.rules.iter()
+            .rev()
+            .find(|r| r.matches(path.as_ref()))
+            .is_some_and(|r| !r.negated)
+    }
+
+    /// Checks whether there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
@@ -0,0 +1,182 @@
+use crate::package_id::PackageId;
+use crate::utils::list_files;
+use crate::version::{Version, VersionReq};
+
+use git2::build::CheckoutBuilder;
+use git2::{Repository, ResetType};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`IndexEntry`] describes a [`Package`] published in a [`RemoteIndex`]
+/// Every entry is stored in the index repository as `<identifier>.json`
+///
+/// [`Package`]: crate::package::Package
+pub struct IndexEntry {
+    pub(crate) identifier: PackageId,
+    pub(crate) git_url: Url,
+    #[serde(default)]
+    pub(crate) versions: Vec<Version>,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+}
+
+impl IndexEntry {
+    /// Returns the identifier the [`Package`] is published under
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+
+    /// Returns the [`Url`] of the git repository of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn git_url(&self) -> &Url {
+        &self.git_url
+    }
+
+    /// Returns the published [`Version`]s of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn versions(&self) -> &[Version] {
+        &self.versions
+    }
+
+    /// Returns the description of the [`Package`], if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the highest published [`Version`] satisfying the given [`VersionReq`]
+    ///
+    /// # Arguments
+    /// * `requirement` - The [`VersionReq`] to satisfy, or `None` to return the highest published [`Version`]
+    pub fn latest(&self, requirement: Option<&VersionReq>) -> Option<&Version> {
+        self.versions
+            .iter()
+            .filter(|v| requirement.is_none_or(|r| r.matches(v)))
+            .max()
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`IndexError`] explains why a [`RemoteIndex`] could not be used
+pub enum IndexError {
+    /// The [`Registry`] has no [`RemoteIndex`] configured
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    NoIndex,
+    /// The index repository at the given [`Url`] could not be cloned or fetched, for the given reason
+    Unreachable(String, String),
+    /// The [`Registry`] is offline, so the index repository cannot be fetched
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    Offline,
+}
+
+impl Display for IndexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexError::NoIndex => write!(f, "no remote index is configured"),
+            IndexError::Unreachable(url, message) => write!(f, "remote index {} could not be updated: {}", url, message),
+            IndexError::Offline => write!(f, "remote index cannot be updated while offline"),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`RemoteIndex`] is a git repository mapping [`Package`] identifiers to git [`Url`]s and published [`Version`]s
+/// It is cloned to a local checkout, which is searched without network access
+///
+/// [`Package`]: crate::package::Package
+pub struct RemoteIndex {
+    pub(crate) remote: Url,
+    pub(crate) checkout: PathBuf,
+}
+
+impl RemoteIndex {
+    /// Creates a new [`RemoteIndex`]
+    ///
+    /// # Arguments
+    /// * `remote` - The [`Url`] of the index repository
+    /// * `checkout` - An absolute [`Path`] the index repository is cloned to
+    ///
+    /// # Panics
+    /// Panics when the given [`Path`] is relative
+    pub fn create<P: AsRef<Path>>(remote: Url, checkout: P) -> Self {
+        if checkout.as_ref().is_relative() {
+            panic!("Path is relative")
+        }
+        RemoteIndex {
+            remote,
+            checkout: checkout.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the [`Url`] of the index repository
+    pub fn remote(&self) -> &Url {
+        &self.remote
+    }
+
+    /// Returns the [`Path`] the index repository is cloned to
+    pub fn checkout(&self) -> &Path {
+        &self.checkout
+    }
+
+    /// Clones the index repository, or fetches and checks out its latest state when it was cloned before
+    pub(crate) fn update(&self) -> Result<(), IndexError> {
+        let unreachable = |e: git2::Error| IndexError::Unreachable(self.remote.to_string(), e.message().to_string());
+        if !self.checkout.join(".git").is_dir() {
+            Repository::clone(self.remote.as_str(), &self.checkout).map_err(unreachable)?;
+            return Ok(());
+        }
+
+        let repository = Repository::open(&self.checkout).map_err(unreachable)?;
+        repository
+            .remote_anonymous(self.remote.as_str())
+            .and_then(|mut r| r.fetch(&["HEAD"], None, None))
+            .map_err(unreachable)?;
+        let head = repository
+            .find_reference("FETCH_HEAD")
+            .and_then(|r| r.peel(git2::ObjectType::Commit))
+            .map_err(unreachable)?;
+        repository
+            .reset(&head, ResetType::Hard, Some(CheckoutBuilder::new().force()))
+            .map_err(unreachable)
+    }
+
+    /// Returns every [`IndexEntry`] in the local checkout
+    pub fn entries(&self) -> Vec<IndexEntry> {
+        list_files(&self.checkout)
+            .into_iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "json"))
+            .filter_map(|f| serde_json::from_str(&read_to_string(self.checkout.join(f)).ok()?).ok())
+            .collect()
+    }
+
+    /// Retrieves the [`IndexEntry`] with the given identifier from the local checkout
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier the [`Package`] is published under
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn get(&self, identifier: &str) -> Option<IndexEntry> {
+        self.entries().into_iter().find(|e| e.identifier == *identifier)
+    }
+}
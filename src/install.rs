@@ -0,0 +1,253 @@
+use crate::dependency::{Dependency, DependencyKind};
+use crate::error::ManifestError;
+use crate::fetcher::Fetcher;
+use crate::git_cache;
+use crate::hooks::{HookError, HookKind};
+use crate::index::IndexError;
+use crate::integrity::IntegrityIssue;
+use crate::operation::{Operation, OperationEvent};
+use crate::package::Package;
+use crate::registry::Registry;
+use crate::signing::SignatureError;
+
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::fs::remove_dir_all;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`InstallError`] explains why a [`Package`] and the [`Package`]s it depends on could not be installed
+///
+/// [`Package`]: crate::package::Package
+pub enum InstallError {
+    /// A [`Dependency`] on the [`Package`] with the given identifier points to the first [`Url`],
+    /// while the registered [`Package`] with that identifier is located at the second [`Url`], if it has one
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    /// [`Url`]: url::Url
+    ConflictingSources(String, String, Option<String>),
+    /// A [`Dependency`] on the [`Package`] with the given identifier requires the given versions,
+    /// while the [`Package`] that is registered or was fetched has the given version
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    ConflictingRequirement(String, String, Option<String>),
+    /// The given [`PathBuf`] to install to lies outside the allowed roots of the [`Registry`]
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    OutsideAllowedRoots(PathBuf),
+    /// The [`Package`] with the given identifier is needed as a dependency, but its given version was yanked
+    ///
+    /// [`Package`]: crate::package::Package
+    YankedVersion(String, String),
+    /// No [`Package`] with the given identifier is published in the remote index
+    ///
+    /// [`Package`]: crate::package::Package
+    UnknownPackage(String),
+    /// The remote index could not be used, see [`IndexError`]
+    Index(IndexError),
+    /// The manifest of the fetched [`Package`] is not valid, the fetched [`Package`] is removed again
+    ///
+    /// [`Package`]: crate::package::Package
+    Manifest(ManifestError),
+    /// Files of the fetched [`Package`] with the given identifier do not match the hashes recorded when it was published
+    ///
+    /// [`Package`]: crate::package::Package
+    IntegrityViolation(String, Vec<IntegrityIssue>),
+    /// The signature of the fetched [`Package`] with the given identifier was rejected by the [`Registry`]'s trust store
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Registry`]: crate::registry::Registry
+    Signature(String, SignatureError),
+    /// The [`Package`] at the given source is not cached and cannot be downloaded while the [`Registry`] is offline
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Registry`]: crate::registry::Registry
+    Offline(String),
+    /// The [`HookKind::PostInstall`] hook of an installed [`Package`] failed, the installation is rolled back
+    ///
+    /// [`HookKind::PostInstall`]: crate::hooks::HookKind::PostInstall
+    /// [`Package`]: crate::package::Package
+    Hook(HookError),
+    /// The [`Package`] could not be fetched, for the given reason; the directory created for it is removed again
+    ///
+    /// [`Package`]: crate::package::Package
+    Fetch(String),
+    /// The [`Fetcher`] with the given name requires a checksum to fetch the given source, but none was given
+    ///
+    /// [`Fetcher`]: crate::fetcher::Fetcher
+    ChecksumRequired(String, String),
+    /// The contents fetched from the given source have the second checksum instead of the first, expected one
+    ChecksumMismatch(String, String, String),
+}
+
+impl Display for InstallError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallError::ConflictingSources(identifier, requested, registered) => match registered {
+                Some(registered) => write!(f, "package `{}` is requested from {} but registered from {}", identifier, requested, registered),
+                None => write!(f, "package `{}` is requested from {} but a local package with that identifier is registered", identifier, requested),
+            },
+            InstallError::ConflictingRequirement(identifier, requirement, version) => match version {
+                Some(version) => write!(f, "package `{}` is required at `{}` but version {} is installed", identifier, requirement, version),
+                None => write!(f, "package `{}` is required at `{}` but has no version", identifier, requirement),
+            },
+            InstallError::OutsideAllowedRoots(path) => write!(f, "{} lies outside the allowed roots of the registry", path.display()),
+            InstallError::YankedVersion(identifier, version) => write!(f, "version {} of package `{}` was yanked", version, identifier),
+            InstallError::UnknownPackage(identifier) => write!(f, "package `{}` is not published in the remote index", identifier),
+            InstallError::Index(error) => error.fmt(f),
+            InstallError::Manifest(error) => error.fmt(f),
+            InstallError::IntegrityViolation(identifier, issues) => {
+                let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+                write!(f, "package `{}` failed verification: {}", identifier, issues.join(", "))
+            }
+            InstallError::Offline(source) => write!(f, "package at {} is not cached and the registry is offline", source),
+            InstallError::Signature(identifier, error) => write!(f, "package `{}` failed signature verification: {}", identifier, error),
+            InstallError::Hook(error) => error.fmt(f),
+            InstallError::Fetch(reason) => write!(f, "{}", reason),
+            InstallError::ChecksumRequired(source, fetcher) => write!(f, "a checksum is required to fetch `{}` using {}", source, fetcher),
+            InstallError::ChecksumMismatch(source, expected, actual) => write!(f, "checksum mismatch for `{}`: expected {}, found {}", source, expected, actual),
+        }
+    }
+}
+
+impl std::error::Error for InstallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InstallError::Index(error) => Some(error),
+            InstallError::Manifest(error) => Some(error),
+            InstallError::Signature(_, error) => Some(error),
+            InstallError::Hook(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Installs a [`Package`] and the [`Package`]s it depends on, one [`Package`] per step
+///
+/// [`Package`]: crate::package::Package
+pub(crate) struct InstallOperation<'f, F: Fetcher> {
+    pub(crate) fetcher: &'f F,
+    pub(crate) path: PathBuf,
+    pub(crate) pending: VecDeque<(Url, Option<String>, Option<Dependency>)>,
+    pub(crate) installed: Vec<Package>,
+    pub(crate) root: Option<Package>,
+}
+
+impl<'f, F: Fetcher> InstallOperation<'f, F> {
+    pub(crate) fn create<P: AsRef<Path>>(fetcher: &'f F, source: Url, path: P, checksum: Option<&str>) -> Self {
+        InstallOperation {
+            fetcher,
+            path: path.as_ref().to_path_buf(),
+            pending: VecDeque::from([(source, checksum.map(String::from), None)]),
+            installed: vec![],
+            root: None,
+        }
+    }
+
+    /// Installs the [`Package`] a [`Dependency`] refers to, unless a registered [`Package`] satisfies it already
+    pub(crate) fn declared<P: AsRef<Path>>(fetcher: &'f F, dependency: Dependency, path: P) -> Self {
+        InstallOperation {
+            fetcher,
+            path: path.as_ref().to_path_buf(),
+            pending: VecDeque::from([(dependency.git_url.clone(), None, Some(dependency))]),
+            installed: vec![],
+            root: None,
+        }
+    }
+}
+
+impl<'f, F: Fetcher> Operation<Package, InstallError> for InstallOperation<'f, F> {
+    fn step(&mut self, registry: &mut Registry, events: &mut Vec<OperationEvent>) -> Result<Option<Package>, InstallError> {
+        let (source, checksum, declared) = self.pending.pop_front().unwrap();
+        let resolved = match &declared {
+            Some(dependency) => registry.resolve_declared(dependency)?.cloned(),
+            None => None,
+        };
+        let fetched = resolved.is_none();
+        let package = match resolved {
+            Some(package) => package,
+            None => {
+                let requirement = declared.as_ref().and_then(|d| d.requirement.as_ref());
+                let package = registry.fetch_and_register(self.fetcher, source, &self.path, checksum.as_deref(), requirement)?;
+                if let Some(requirement) = declared.as_ref().and_then(|d| d.requirement.as_ref()) {
+                    if !package.version().is_some_and(|v| requirement.matches(&v)) {
+                        package.checkout_version(requirement);
+                    }
+                }
+                events.push(OperationEvent::Step(format!("installed {}", package.local_location.display())));
+                self.installed.push(package.clone());
+                if let Some(trust_store) = &registry.trust_store {
+                    package.verify_signature(trust_store).map_err(|e| InstallError::Signature(package.identifier(), e))?;
+                }
+                let prebuilt = registry.restore_prebuilt(&package);
+                for source in prebuilt.restored() {
+                    events.push(OperationEvent::Step(format!("restored prebuilt artifacts of {}", source.display())));
+                }
+                for source in prebuilt.from_source() {
+                    events.push(OperationEvent::Step(format!("{} needs to be built from source", source.display())));
+                }
+                let unbuilt: Vec<PathBuf> = prebuilt.from_source().iter()
+                    .filter_map(|s| package.get_module_by_location(package.strip_prefix(s))?.output().map(|o| package.local_location.join(o)))
+                    .collect();
+                let issues: Vec<IntegrityIssue> = package.verify_integrity()
+                    .into_iter()
+                    .filter(|i| !matches!(i, IntegrityIssue::Missing(path) if unbuilt.iter().any(|o| path.starts_with(o))))
+                    .collect();
+                if !issues.is_empty() {
+                    return Err(InstallError::IntegrityViolation(package.identifier(), issues));
+                }
+                if let Some(version) = package.version() {
+                    if package.is_yanked(&version) {
+                        match &declared {
+                            Some(_) => return Err(InstallError::YankedVersion(package.identifier(), version.to_string())),
+                            None => events.push(OperationEvent::Warning(format!("version {} of package `{}` was yanked", version, package.identifier()))),
+                        }
+                    }
+                    if let Some(message) = package.deprecation(&version) {
+                        events.push(OperationEvent::Warning(format!("version {} of package `{}` is deprecated: {}", version, package.identifier(), message)));
+                    }
+                }
+                for dependency in package.dependencies().into_iter().filter(|d| d.kind != DependencyKind::Dev) {
+                    match registry.resolve_declared(&dependency)? {
+                        Some(resolved) => Registry::check_requirement(&dependency, resolved)?,
+                        None => self.pending.push_back((dependency.git_url.clone(), None, Some(dependency))),
+                    }
+                }
+                package
+            }
+        };
+        if let Some(dependency) = &declared {
+            Registry::check_requirement(dependency, &package)?;
+        }
+        if fetched {
+            registry.run_hook(&package, HookKind::PostInstall).map_err(InstallError::Hook)?;
+        }
+        if self.root.is_none() {
+            self.root = Some(package);
+        }
+
+        match self.pending.is_empty() {
+            true => Ok(self.root.clone()),
+            false => Ok(None),
+        }
+    }
+
+    fn progress(&self) -> (usize, usize) {
+        (self.installed.len(), self.installed.len() + self.pending.len())
+    }
+
+    fn rollback(&mut self, registry: &mut Registry) {
+        for package in self.installed.drain(..) {
+            registry.packages.remove(&package);
+            registry.provenance.remove(&package.local_location);
+            git_cache::invalidate(&package.local_location);
+            let _ = remove_dir_all(&package.local_location);
+        }
+        registry.persist();
+    }
+}
@@ -0,0 +1,24 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`IntegrityIssue`] is a file of a [`Package`] that no longer matches the hash recorded when it was published
+///
+/// [`Package`]: crate::package::Package
+pub enum IntegrityIssue {
+    /// The file at the given [`PathBuf`] was changed, it was tampered with or is corrupt
+    Modified(PathBuf),
+    /// The file at the given [`PathBuf`] no longer exists
+    Missing(PathBuf),
+}
+
+impl Display for IntegrityIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityIssue::Modified(path) => write!(f, "{} does not match its published hash", path.display()),
+            IntegrityIssue::Missing(path) => write!(f, "{} is missing", path.display()),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::executable::Executable;
+use crate::module::Module;
+
+use std::path::{Path, PathBuf};
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+/// A [`RegistryItem`] is anything a [`Package`] provides that is registered by its source file
+///
+/// [`Package`]: crate::package::Package
+pub enum RegistryItem {
+    /// A [`Module`] other [`Package`]s can import
+    ///
+    /// [`Package`]: crate::package::Package
+    Module(Module),
+    /// An [`Executable`] that can be run
+    Executable(Executable),
+}
+
+impl RegistryItem {
+    /// Returns the [`Path`] of the file of the [`RegistryItem`], relative to the [`Package`]'s `local_location`
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn location(&self) -> &Path {
+        match self {
+            RegistryItem::Module(module) => &module.location,
+            RegistryItem::Executable(executable) => &executable.location,
+        }
+    }
+
+    /// Returns the [`Module`], if the [`RegistryItem`] is one
+    pub fn as_module(&self) -> Option<&Module> {
+        match self {
+            RegistryItem::Module(module) => Some(module),
+            RegistryItem::Executable(_) => None,
+        }
+    }
+
+    /// Returns the [`Executable`], if the [`RegistryItem`] is one
+    pub fn as_executable(&self) -> Option<&Executable> {
+        match self {
+            RegistryItem::Module(_) => None,
+            RegistryItem::Executable(executable) => Some(executable),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`ImportKind`] tells where the [`Module`] an identifier resolves to comes from, see [`ImportTarget`]
+pub enum ImportKind {
+    /// The [`Module`] belongs to the same [`Package`] as the importing source file
+    ///
+    /// [`Package`]: crate::package::Package
+    Local,
+    /// The [`Module`] belongs to a [`Package`] the [`Package`] of the importing source file depends on
+    ///
+    /// [`Package`]: crate::package::Package
+    Dependency,
+    /// The identifier was found in the output directory of a stray dependency, see [`Package::add_stray`]
+    ///
+    /// [`Package::add_stray`]: crate::package::Package::add_stray
+    Stray,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`ImportTarget`] describes what an identifier imported by a source file resolves to, see [`Registry::resolve_import`]
+///
+/// [`Registry::resolve_import`]: crate::registry::Registry::resolve_import
+pub struct ImportTarget {
+    pub(crate) kind: ImportKind,
+    pub(crate) source: Option<PathBuf>,
+    pub(crate) output: Option<PathBuf>,
+    pub(crate) package: Option<PathBuf>,
+}
+
+impl ImportTarget {
+    /// Returns where the imported [`Module`] comes from
+    pub fn kind(&self) -> ImportKind {
+        self.kind
+    }
+
+    /// Returns the absolute path of the source file defining the imported [`Module`], unknown for stray dependencies
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    /// Returns the directory the imported [`Module`] is compiled to, if known
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// Returns the `local_location` of the [`Package`] providing the imported [`Module`], unknown for stray dependencies
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn package(&self) -> Option<&Path> {
+        self.package.as_deref()
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`JournalEntry`] holds the state of a [`Registry`] right before it was changed,
+/// together with the contents of the manifests the change touched
+///
+/// [`Registry`]: crate::registry::Registry
+pub struct JournalEntry {
+    pub(crate) created: u64,
+    pub(crate) snapshot: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) manifests: Vec<(PathBuf, String)>,
+}
+
+impl JournalEntry {
+    /// Returns when the [`Registry`] was changed, in seconds since the unix epoch
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`Journal`] keeps the states of a [`Registry`] before its most recent changes in a file, so they can be undone
+/// Only the newest `retention` states are kept, older ones are discarded
+///
+/// The [`Journal`] covers what is stored in the [`Registry`] itself, and the manifests of its [`Package`]s
+/// as far as they are changed through the [`Registry`], e.g. by [`Registry::set_output`]
+/// Changes made directly through a [`Package`] are not recorded
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::set_output`]: crate::registry::Registry::set_output
+pub struct Journal {
+    pub(crate) location: PathBuf,
+    pub(crate) retention: usize,
+}
+
+impl Journal {
+    /// Creates a new [`Journal`]
+    ///
+    /// # Arguments
+    /// * `location` - An absolute [`Path`] pointing to the file the [`Journal`] is stored in
+    /// * `retention` - The number of states to keep
+    ///
+    /// # Panics
+    /// Panics when the given [`Path`] is relative
+    pub fn create<P: AsRef<Path>>(location: P, retention: usize) -> Self {
+        if location.as_ref().is_relative() {
+            panic!("Path is relative")
+        }
+        Journal {
+            location: location.as_ref().to_path_buf(),
+            retention,
+        }
+    }
+
+    /// Returns the [`Path`] of the file the [`Journal`] is stored in
+    pub fn location(&self) -> &Path {
+        &self.location
+    }
+
+    /// Returns the number of states the [`Journal`] keeps
+    pub fn retention(&self) -> usize {
+        self.retention
+    }
+
+    /// Returns every [`JournalEntry`], oldest first
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        read_to_string(&self.location)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn record(&self, snapshot: String, manifests: Vec<(PathBuf, String)>) {
+        let mut entries = self.entries();
+        entries.push(JournalEntry {
+            created: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            snapshot,
+            manifests,
+        });
+        let excess = entries.len().saturating_sub(self.retention);
+        entries.drain(..excess);
+        self.write(&entries);
+    }
+
+    pub(crate) fn pop(&self) -> Option<JournalEntry> {
+        let mut entries = self.entries();
+        let entry = entries.pop()?;
+        self.write(&entries);
+        Some(entry)
+    }
+
+    fn write(&self, entries: &[JournalEntry]) {
+        write(&self.location, serde_json::to_string(entries).unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::env::consts::{ARCH, OS};
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(Hash)]
+/// A [`Language`] describes the toolchain used to build [`Module`]s
+///
+/// A [`Language`] can extend another [`Language`] registered in the same [`Registry`]
+/// Every setting it leaves empty is then inherited from that base [`Language`]
+///
+/// [`Module`]: crate::module::Module
+/// [`Registry`]: crate::registry::Registry
+pub struct Language {
+    pub(crate) compiler: String,
+    #[serde(default)]
+    pub(crate) flags: Option<Vec<String>>,
+    #[serde(default)]
+    pub(crate) extends: Option<String>,
+    #[serde(default)]
+    pub(crate) include_template: Option<String>,
+    #[serde(default)]
+    pub(crate) output_template: Option<String>,
+    #[serde(default)]
+    pub(crate) linker: Option<String>,
+    #[serde(default)]
+    pub(crate) link_template: Option<String>,
+}
+
+impl Language {
+    /// Creates a new [`Language`] that invokes the given `compiler` with the given `flags`
+    ///
+    /// # Arguments
+    /// * `compiler` - The command used to invoke the compiler
+    /// * `flags` - Flags that are passed to the compiler on every invocation
+    ///
+    /// # Examples
+    /// ```
+    /// # use knapsac_lib::language::Language;
+    ///
+    /// let language = Language::create("sac2c", vec![String::from("-O3")]);
+    /// assert_eq!(language.compiler(), "sac2c");
+    /// assert_eq!(language.flags(), &[String::from("-O3")]);
+    /// ```
+    pub fn create<S: Into<String>>(compiler: S, flags: Vec<String>) -> Self {
+        Language {
+            compiler: compiler.into(),
+            flags: Some(flags),
+            extends: None,
+            include_template: None,
+            output_template: None,
+            linker: None,
+            link_template: None,
+        }
+    }
+
+    /// Creates a new [`Language`] that inherits every setting from the [`Language`] registered under `base`
+    /// Settings can then be overridden selectively
+    ///
+    /// # Arguments
+    /// * `base` - The name of the [`Language`] to inherit from
+    ///
+    /// # Examples
+    /// ```
+    /// # use knapsac_lib::language::Language;
+    ///
+    /// let base = Language::create("sac2c", vec![String::from("-O3")]);
+    /// let mut debug = Language::extend("sac");
+    /// debug.set_flags(Some(vec![String::from("-g")]));
+    ///
+    /// let resolved = debug.inherit(&base);
+    /// assert_eq!(resolved.compiler(), "sac2c");
+    /// assert_eq!(resolved.flags(), &[String::from("-g")]);
+    ///
+    /// let mut plain = Language::extend("sac");
+    /// assert_eq!(plain.inherit(&base).flags(), &[String::from("-O3")]);
+    /// plain.set_flags(Some(vec![]));
+    /// assert!(plain.inherit(&base).flags().is_empty());
+    /// ```
+    pub fn extend<S: Into<String>>(base: S) -> Self {
+        let mut language = Language::create("", vec![]);
+        language.flags = None;
+        language.extends = Some(base.into());
+        language
+    }
+
+    /// Returns a copy of this [`Language`] where every setting that is left empty is taken from `base`
+    ///
+    /// # Arguments
+    /// * `base` - The [`Language`] to inherit from
+    pub fn inherit(&self, base: &Language) -> Language {
+        Language {
+            compiler: match self.compiler.is_empty() {
+                true => base.compiler.clone(),
+                false => self.compiler.clone(),
+            },
+            flags: self.flags.clone().or_else(|| base.flags.clone()),
+            extends: base.extends.clone(),
+            include_template: self.include_template.clone().or_else(|| base.include_template.clone()),
+            output_template: self.output_template.clone().or_else(|| base.output_template.clone()),
+            linker: self.linker.clone().or_else(|| base.linker.clone()),
+            link_template: self.link_template.clone().or_else(|| base.link_template.clone()),
+        }
+    }
+
+    /// Returns the command used to invoke the compiler
+    pub fn compiler(&self) -> &str {
+        &self.compiler
+    }
+
+    /// Returns the flags that are passed to the compiler
+    pub fn flags(&self) -> &[String] {
+        self.flags.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the name of the [`Language`] this [`Language`] inherits from, if any
+    pub fn extends(&self) -> Option<&str> {
+        self.extends.as_deref()
+    }
+
+    /// Returns the template of the flag pointing the compiler at a dependency, e.g. `-I{path}`
+    pub fn include_template(&self) -> Option<&str> {
+        self.include_template.as_deref()
+    }
+
+    /// Returns the template of the name of a compiled [`Module`], e.g. `lib{identifier}.so`
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn output_template(&self) -> Option<&str> {
+        self.output_template.as_deref()
+    }
+
+    /// Returns the command used to link [`Executable`]s, if it differs from the compiler
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    pub fn linker(&self) -> Option<&str> {
+        self.linker.as_deref()
+    }
+
+    /// Returns the template of the flag pointing the linker at the output directory of a dependency, e.g. `-L{path}`
+    pub fn link_template(&self) -> Option<&str> {
+        self.link_template.as_deref()
+    }
+
+    /// Sets the command used to invoke the compiler, an empty command is inherited
+    pub fn set_compiler<S: Into<String>>(&mut self, compiler: S) {
+        self.compiler = compiler.into();
+    }
+
+    /// Sets the flags that are passed to the compiler, `None` is inherited while empty flags pass no flags at all
+    pub fn set_flags(&mut self, flags: Option<Vec<String>>) {
+        self.flags = flags;
+    }
+
+    /// Sets the template of the flag pointing the compiler at a dependency, `None` is inherited
+    pub fn set_include_template(&mut self, template: Option<String>) {
+        self.include_template = template;
+    }
+
+    /// Sets the template of the name of a compiled [`Module`], `None` is inherited
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn set_output_template(&mut self, template: Option<String>) {
+        self.output_template = template;
+    }
+
+    /// Sets the command used to link [`Executable`]s, `None` is inherited and links with the compiler
+    /// See [`Registry::link`]
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Registry::link`]: crate::registry::Registry::link
+    pub fn set_linker(&mut self, linker: Option<String>) {
+        self.linker = linker;
+    }
+
+    /// Sets the template of the flag pointing the linker at the output directory of a dependency, `None` is inherited
+    ///
+    /// # Examples
+    /// ```
+    /// # use knapsac_lib::language::Language;
+    ///
+    /// let mut language = Language::create("sac2c", vec![]);
+    /// language.set_link_template(Some(String::from("-L{path}")));
+    /// assert_eq!(language.link_template(), Some("-L{path}"));
+    /// ```
+    pub fn set_link_template(&mut self, template: Option<String>) {
+        self.link_template = template;
+    }
+
+    /// Returns the tag identifying the format of artifacts the compiler produces at the given version for the current target
+    /// Artifacts with different tags cannot be linked together
+    ///
+    /// # Arguments
+    /// * `compiler_version` - The version of the compiler, see [`Language::detect_version`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env::consts::{ARCH, OS};
+    /// # use knapsac_lib::language::Language;
+    ///
+    /// let language = Language::create("/usr/bin/sac2c", vec![]);
+    /// assert_eq!(language.abi_tag("1.3.3"), format!("sac2c-1.3.3-{}-{}", ARCH, OS));
+    /// ```
+    pub fn abi_tag(&self, compiler_version: &str) -> String {
+        let compiler = Path::new(&self.compiler)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(&self.compiler);
+        format!("{}-{}-{}-{}", compiler, compiler_version, ARCH, OS)
+    }
+
+    /// Invokes the locally installed compiler with `--version` and returns what it printed, trimmed
+    /// Returns `None` when the compiler cannot be invoked
+    pub fn version_output(&self) -> Option<String> {
+        let output = Command::new(&self.compiler).arg("--version").output().ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Detects the version of the locally installed compiler by invoking it with `--version`
+    /// Returns the first dot separated number in its output, if any
+    pub fn detect_version(&self) -> Option<String> {
+        let stdout = self.version_output()?;
+        stdout
+            .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .map(|token| token.trim_matches('.'))
+            .find(|token| token.contains('.') && token.starts_with(|c: char| c.is_ascii_digit()))
+            .map(String::from)
+    }
+}
@@ -1,9 +1,61 @@
 extern crate core;
 
+pub mod artifacts;
+pub mod audit;
+pub mod backend;
+pub mod build;
+pub mod bundle;
+pub mod cache;
+pub mod clean;
 pub mod dependency;
+pub mod depfile;
+pub mod diagnostics;
+pub mod diff;
+pub mod docs;
+pub mod duplicates;
+pub mod environment;
+pub mod error;
+pub mod executable;
+pub mod fetcher;
+pub mod format;
+pub mod git_cache;
+pub mod graph;
+pub mod hooks;
+pub mod ignore;
+pub mod index;
+pub mod install;
+pub mod integrity;
+pub mod item;
+pub mod journal;
+pub mod language;
+pub mod metadata;
+pub mod migration;
 pub mod package;
+pub mod package_id;
+pub mod paths;
+pub mod packaging;
+pub mod progress;
+pub mod publish;
+pub mod prune;
 pub mod registry;
+pub mod removal;
+pub mod run;
+pub mod search;
+pub mod shared;
+pub mod signing;
+pub mod snapshot;
+pub mod stack;
+pub mod stats;
+pub mod sync;
+pub mod testing;
+pub mod vendor;
+pub mod version;
+#[cfg(feature = "watch")]
+pub mod watch;
 pub mod module;
+pub mod operation;
 
 mod manifest;
 mod utils;
+
+pub use error::Error;
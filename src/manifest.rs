@@ -1,16 +1,105 @@
+use crate::artifacts::ArtifactMode;
 use crate::dependency::Dependency;
+use crate::executable::Executable;
+use crate::error::ManifestError;
+use crate::format::{Format, LocatedError, Style};
+use crate::git_cache;
+use crate::hooks::HookKind;
+use crate::language::Language;
+use crate::metadata::PackageMetadata;
 use crate::module::Module;
+use crate::publish::DirtyPolicy;
+use crate::sync::SchemaViolation;
+use crate::testing::TestModule;
+use crate::utils::{sorted_set, write_atomic};
+use crate::version::Version;
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::fs::{read_to_string, write};
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::read_to_string;
+use std::path::{Component, Path, PathBuf};
+use url::{ParseError, Url};
 
 #[derive(Deserialize, Serialize)]
 #[derive(Clone)]
 pub(crate) struct Manifest {
+    #[serde(serialize_with = "sorted_set")]
     pub(crate) dependencies: HashSet<Dependency>,
+    #[serde(serialize_with = "sorted_set")]
     pub(crate) modules: HashSet<Module>,
+    #[serde(default)]
+    pub(crate) language: Option<String>,
+    #[serde(default)]
+    pub(crate) compiler_versions: BTreeSet<String>,
+    #[serde(default)]
+    pub(crate) language_override: Option<Language>,
+    #[serde(default)]
+    pub(crate) metadata: PackageMetadata,
+    #[serde(default)]
+    pub(crate) version: Option<Version>,
+    #[serde(default)]
+    pub(crate) yanked: BTreeSet<Version>,
+    #[serde(default)]
+    pub(crate) deprecated: BTreeMap<Version, String>,
+    #[serde(default)]
+    pub(crate) hashes: BTreeMap<PathBuf, String>,
+    #[serde(default)]
+    pub(crate) flags: Vec<String>,
+    #[serde(default, serialize_with = "sorted_set")]
+    pub(crate) executables: HashSet<Executable>,
+    #[serde(default, serialize_with = "sorted_set")]
+    pub(crate) tests: HashSet<TestModule>,
+    #[serde(default)]
+    pub(crate) strays: BTreeSet<PathBuf>,
+    #[serde(default)]
+    pub(crate) features: BTreeMap<String, BTreeSet<PathBuf>>,
+    #[serde(default)]
+    pub(crate) hooks: BTreeMap<HookKind, Vec<String>>,
+    #[serde(default)]
+    pub(crate) dirty_policy: DirtyPolicy,
+    #[serde(default)]
+    pub(crate) mirrors: Vec<Url>,
+    #[serde(default)]
+    pub(crate) vendor_strays: bool,
+    #[serde(default)]
+    pub(crate) vendored: BTreeMap<PathBuf, PathBuf>,
+    #[serde(default)]
+    pub(crate) artifact_mode: Option<ArtifactMode>,
+    #[serde(default)]
+    pub(crate) prebuilt: BTreeMap<PathBuf, String>,
+}
+
+/// The reasons [`Url`] gives for not parsing a string, which its deserializer prefixes its error messages with
+const URL_ERRORS: [ParseError; 10] = [
+    ParseError::EmptyHost,
+    ParseError::IdnaError,
+    ParseError::InvalidPort,
+    ParseError::InvalidIpv4Address,
+    ParseError::InvalidIpv6Address,
+    ParseError::InvalidDomainCharacter,
+    ParseError::RelativeUrlWithoutBase,
+    ParseError::RelativeUrlWithCannotBeABaseBase,
+    ParseError::SetHostOnCannotBeABaseUrl,
+    ParseError::Overflow,
+];
+
+/// Turns an error found while deserializing the manifest at the given path into the matching [`ManifestError`]
+fn classify(path: &Path, error: LocatedError) -> ManifestError {
+    let LocatedError { line, column, message, syntax } = error;
+    let path = path.to_path_buf();
+    if syntax {
+        return ManifestError::Syntax(path, line, column, message);
+    }
+    if let Some(field) = message.strip_prefix("missing field `").and_then(|m| m.split('`').next()) {
+        return ManifestError::MissingField(path, line, column, field.to_string());
+    }
+    if let Some(version) = message.strip_prefix("invalid version: ") {
+        return ManifestError::InvalidVersion(path, line, column, version.to_string());
+    }
+    match URL_ERRORS.iter().any(|e| message.starts_with(&format!("{}: ", e))) {
+        true => ManifestError::InvalidUrl(path, line, column, message),
+        false => ManifestError::Mismatch(path, line, column, message),
+    }
 }
 
 impl Manifest {
@@ -18,21 +107,95 @@ impl Manifest {
         Manifest {
             dependencies: HashSet::new(),
             modules: HashSet::new(),
+            language: None,
+            compiler_versions: BTreeSet::new(),
+            language_override: None,
+            metadata: PackageMetadata::default(),
+            version: None,
+            yanked: BTreeSet::new(),
+            deprecated: BTreeMap::new(),
+            hashes: BTreeMap::new(),
+            flags: vec![],
+            executables: HashSet::new(),
+            tests: HashSet::new(),
+            strays: BTreeSet::new(),
+            features: BTreeMap::new(),
+            hooks: BTreeMap::new(),
+            dirty_policy: DirtyPolicy::default(),
+            mirrors: vec![],
+            vendor_strays: false,
+            vendored: BTreeMap::new(),
+            artifact_mode: None,
+            prebuilt: BTreeMap::new(),
         }
     }
     pub(crate) fn load<P: AsRef<Path>>(path: P) -> Self {
-        if let Ok(data) = read_to_string(&path) {
-            return serde_json::from_str(data.as_str()).unwrap();
+        Manifest::try_load(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Loads the manifest at the given path, explaining where and why it cannot be parsed
+    pub(crate) fn try_load<P: AsRef<Path>>(path: P) -> Result<Self, ManifestError> {
+        let path = path.as_ref();
+        let data = read_to_string(path).map_err(|_| ManifestError::NotFound(path.to_path_buf()))?;
+        let format = Format::from_path(path).unwrap_or(Format::Json);
+        let mut manifest: Manifest = format.deserialize_located(data.as_str()).map_err(|e| classify(path, e))?;
+        manifest.migrate_binary_modules();
+        Ok(manifest)
+    }
+    /// Loads the manifest at the given path like [`Manifest::try_load`], also rejecting it when it contains [`SchemaViolation`]s
+    pub(crate) fn try_load_valid<P: AsRef<Path>>(path: P) -> Result<Self, ManifestError> {
+        let manifest = Manifest::try_load(&path)?;
+        let violations = manifest.validate();
+        match violations.is_empty() {
+            true => Ok(manifest),
+            false => Err(ManifestError::Invalid(path.as_ref().to_path_buf(), violations)),
         }
-        panic!("No manifest found @ {}", path.as_ref().display())
     }
     pub(crate) fn save<P: AsRef<Path>>(&self, path: P) {
-        let contents = serde_json::to_string(self).unwrap();
-        write(path, contents).unwrap()
+        let format = Format::from_path(&path).unwrap_or(Format::Json);
+        let contents = format.serialize(self, Style::default());
+        write_atomic(&path, contents).unwrap();
+        if let Some(root) = path.as_ref().parent() {
+            git_cache::invalidate(root);
+        }
+    }
+
+    /// Returns the entries that parse, but cannot be right
+    pub(crate) fn validate(&self) -> Vec<SchemaViolation> {
+        let mut violations = vec![];
+        let mut locations: Vec<&PathBuf> = self.modules.iter().map(|m| &m.location).collect();
+        locations.sort();
+        for (index, location) in locations.iter().enumerate() {
+            if location.is_absolute() {
+                violations.push(SchemaViolation::AbsoluteModuleLocation(location.to_path_buf()));
+            } else if location.components().any(|c| c == Component::ParentDir) {
+                violations.push(SchemaViolation::ModuleOutsidePackage(location.to_path_buf()));
+            }
+            if index > 0 && locations[index - 1] == *location && locations.get(index + 1) != Some(location) {
+                violations.push(SchemaViolation::DuplicateModuleLocation(location.to_path_buf()));
+            }
+        }
+        for (feature, paths) in &self.features {
+            for path in paths.iter().filter(|p| !locations.contains(p)) {
+                violations.push(SchemaViolation::UnknownFeatureModule(feature.clone(), path.clone()));
+            }
+        }
+        for stray in self.strays.iter().filter(|s| s.is_relative()) {
+            violations.push(SchemaViolation::RelativeStray(stray.clone()));
+        }
+        violations
+    }
+
+    /// Older manifests stored prebuilt executables as binary [`Module`]s, turns them into prebuilt [`Executable`]s
+    fn migrate_binary_modules(&mut self) {
+        let binaries: Vec<Module> = self.modules.iter().filter(|m| m.binary).cloned().collect();
+        for module in binaries {
+            self.modules.remove(&module);
+            self.executables.insert(Executable::prebuilt(&module.location));
+        }
     }
 
     pub(crate) fn add_dependency(&mut self, dependency: Dependency) {
-        self.dependencies.insert(dependency);
+        self.dependencies.replace(dependency);
     }
     pub(crate) fn has_dependency(&self, dependency: &Dependency) -> bool {
         self.dependencies.contains(dependency)
@@ -42,12 +205,52 @@ impl Manifest {
     }
 
     pub(crate) fn add_module(&mut self, module: Module) {
-        self.modules.insert(module);
+        self.modules.replace(module);
     }
     pub(crate) fn get_module_by_location<P: AsRef<Path>>(&self, path: P) -> Option<&Module> {
         self.modules.iter().find(|m|m.location == path.as_ref().to_path_buf())
     }
+    pub(crate) fn get_module_owning<P: AsRef<Path>>(&self, root: &Path, path: P) -> Option<&Module> {
+        self.get_module_by_location(&path)
+            .or_else(|| self.modules.iter().find(|m| m.source_files(root).iter().any(|f| f == path.as_ref())))
+    }
     pub(crate) fn remove_module(&mut self, module: &Module) {
         self.modules.remove(module);
     }
+
+    pub(crate) fn add_executable(&mut self, executable: Executable) {
+        self.executables.retain(|e| e.location != executable.location);
+        self.executables.insert(executable);
+    }
+    pub(crate) fn get_executable_by_location<P: AsRef<Path>>(&self, path: P) -> Option<&Executable> {
+        self.executables.iter().find(|e| e.location == path.as_ref())
+    }
+    pub(crate) fn remove_executable(&mut self, executable: &Executable) {
+        self.executables.remove(executable);
+    }
+
+    pub(crate) fn add_test(&mut self, test: TestModule) {
+        self.tests.retain(|t| t.location != test.location);
+        self.tests.insert(test);
+    }
+    pub(crate) fn remove_test(&mut self, test: &TestModule) {
+        self.tests.remove(test);
+    }
+
+    /// The [`Module`]s that are not optional, followed by the [`Module`]s of the given features
+    pub(crate) fn required_modules(&self, features: &BTreeSet<String>) -> Vec<Module> {
+        let optional: BTreeSet<&PathBuf> = self.features.values().flatten().collect();
+        let enabled: BTreeSet<&PathBuf> = self.features
+            .iter()
+            .filter(|(name, _)| features.contains(*name))
+            .flat_map(|(_, modules)| modules)
+            .collect();
+        let mut modules: Vec<Module> = self.modules
+            .iter()
+            .filter(|m| !optional.contains(&m.location) || enabled.contains(&m.location))
+            .cloned()
+            .collect();
+        modules.sort_by(|a, b| a.location.cmp(&b.location));
+        modules
+    }
 }
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// [`PackageMetadata`] describes a [`Package`] to the people browsing a [`Registry`]
+///
+/// # Examples
+/// ```
+/// # use knapsac_lib::metadata::PackageMetadata;
+///
+/// let mut metadata = PackageMetadata::default();
+/// metadata.set_name(Some("json".to_string()));
+/// metadata.set_keywords(vec!["parser".to_string(), "serialization".to_string()]);
+/// assert_eq!(metadata.name(), Some("json"));
+/// assert!(metadata.description().is_none());
+/// assert_eq!(metadata.keywords().len(), 2);
+/// ```
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry`]: crate::registry::Registry
+pub struct PackageMetadata {
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) authors: Vec<String>,
+    #[serde(default)]
+    pub(crate) license: Option<String>,
+    #[serde(default)]
+    pub(crate) keywords: Vec<String>,
+}
+
+impl PackageMetadata {
+    /// Returns the human readable name of the [`Package`], if set
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the description of the [`Package`], if set
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the authors of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn authors(&self) -> &[String] {
+        &self.authors
+    }
+
+    /// Returns the license the [`Package`] is distributed under, if set
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    /// Returns the keywords the [`Package`] can be found by
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// Sets the human readable name of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    /// Sets the description of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+    }
+
+    /// Sets the authors of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_authors(&mut self, authors: Vec<String>) {
+        self.authors = authors;
+    }
+
+    /// Sets the license the [`Package`] is distributed under, preferably an SPDX identifier
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_license(&mut self, license: Option<String>) {
+        self.license = license;
+    }
+
+    /// Sets the keywords the [`Package`] can be found by
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_keywords(&mut self, keywords: Vec<String>) {
+        self.keywords = keywords;
+    }
+}
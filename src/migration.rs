@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{read_to_string, write};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`RegistryExport`] is a portable copy of a [`Registry`] written by [`Registry::export`]
+///
+/// Paths inside `root`, the deepest directory containing every registered [`Package`], are stored relative to it.
+/// The manifests of the [`Package`]s are included, keyed by their [`Path`] relative to `root`
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::export`]: crate::registry::Registry::export
+pub struct RegistryExport {
+    pub(crate) root: PathBuf,
+    pub(crate) registry: String,
+    pub(crate) manifests: BTreeMap<PathBuf, String>,
+}
+
+impl RegistryExport {
+    /// Loads a [`RegistryExport`] that was written by [`Registry::export`]
+    ///
+    /// # Arguments
+    /// * `path` - A [`Path`] pointing to the file of the [`RegistryExport`]
+    ///
+    /// # Panics
+    /// Panics when the file does not exist or does not contain a [`RegistryExport`]
+    ///
+    /// [`Registry::export`]: crate::registry::Registry::export
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let data = read_to_string(&path)
+            .unwrap_or_else(|_| panic!("No registry export found @ {}", path.as_ref().display()));
+        serde_json::from_str(&data).unwrap()
+    }
+
+    pub(crate) fn save<P: AsRef<Path>>(&self, path: P) {
+        write(path, serde_json::to_string_pretty(self).unwrap()).unwrap();
+    }
+
+    /// Returns the directory relative paths in the [`RegistryExport`] were relative to on the exporting machine
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the contents of every included manifest, keyed by its [`Path`] relative to the root
+    pub fn manifests(&self) -> &BTreeMap<PathBuf, String> {
+        &self.manifests
+    }
+}
+
+/// Finds the deepest directory containing every given [`Path`]
+pub(crate) fn common_root<'a, I: IntoIterator<Item = &'a Path>>(paths: I) -> PathBuf {
+    let mut root: Option<Vec<Component>> = None;
+    for path in paths {
+        let components: Vec<Component> = path.components().collect();
+        root = Some(match root {
+            None => components,
+            Some(root) => root.into_iter().zip(components).take_while(|(a, b)| a == b).map(|(a, _)| a).collect(),
+        });
+    }
+    root.map(|r| r.iter().collect()).unwrap_or_else(|| PathBuf::from("/"))
+}
+
+/// Rewrites the given [`Path`] according to the mapping entry with the longest matching prefix
+pub(crate) fn map_path<P: AsRef<Path>>(path: P, mapping: &[(PathBuf, PathBuf)]) -> PathBuf {
+    let path = path.as_ref();
+    mapping.iter()
+        .filter(|(from, _)| path.starts_with(from))
+        .max_by_key(|(from, _)| from.components().count())
+        .map(|(from, to)| match path.strip_prefix(from).unwrap() {
+            rest if rest.as_os_str().is_empty() => to.clone(),
+            rest => to.join(rest),
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Removes `.` components and resolves `..` components of the given [`Path`] without touching the file system
+pub(crate) fn normalize<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.as_ref().components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(normalized.components().next_back(), Some(Component::Normal(_))) => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Expresses the given absolute [`Path`] relative to the given absolute base directory, using `..` where needed
+/// Paths on another root, like another drive on Windows, are returned unchanged
+pub(crate) fn relative_to<P: AsRef<Path>, B: AsRef<Path>>(path: P, base: B) -> PathBuf {
+    let (path, base) = (normalize(path), normalize(base));
+    let common = common_root([path.as_path(), base.as_path()]);
+    if !common.has_root() || common.components().count() == 0 {
+        return path;
+    }
+    let mut relative: PathBuf = base.strip_prefix(&common).unwrap().components().map(|_| Component::ParentDir).collect();
+    relative.push(path.strip_prefix(&common).unwrap());
+    match relative.as_os_str().is_empty() {
+        true => PathBuf::from("."),
+        false => relative,
+    }
+}
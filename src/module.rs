@@ -1,13 +1,48 @@
+use crate::language::Language;
+use crate::signing::to_hex;
+use crate::utils::list_files;
+
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fs::read;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use url::Url;
 
 #[derive(Deserialize, Serialize)]
-#[derive(Hash)]
-#[derive(Eq, PartialEq)]
 #[derive(Clone)]
+#[derive(Debug)]
 pub struct Module {
     pub identifier: String,
     pub(crate) location: PathBuf,
+    #[serde(default)]
+    pub(crate) language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) language_override: Option<Language>,
+    #[serde(default, skip_serializing)]
+    pub(crate) binary: bool,
+    #[serde(default)]
+    pub(crate) output: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) artifact_tag: Option<String>,
+    #[serde(default)]
+    pub(crate) profile_outputs: BTreeMap<String, PathBuf>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) target_outputs: BTreeMap<String, PathBuf>,
+    #[serde(default)]
+    pub(crate) flags: Vec<String>,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) documentation: Option<Url>,
+    #[serde(default)]
+    pub(crate) sources: BTreeSet<PathBuf>,
+    #[serde(default)]
+    pub(crate) artifacts: BTreeSet<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_hash: Option<String>,
 }
 
 impl Module {
@@ -58,6 +93,405 @@ impl Module {
         Module {
             identifier,
             location: path.as_ref().to_path_buf(),
+            language: None,
+            language_override: None,
+            binary: false,
+            output: None,
+            artifact_tag: None,
+            profile_outputs: BTreeMap::new(),
+            target_outputs: BTreeMap::new(),
+            flags: vec![],
+            description: None,
+            documentation: None,
+            sources: BTreeSet::new(),
+            artifacts: BTreeSet::new(),
+            content_hash: None,
         }
     }
+
+    /// Sets the name of the [`Language`] the [`Module`] is written in
+    /// When no name is set the [`Module`] uses the [`Language`] of its [`Package`]
+    ///
+    /// # Arguments
+    /// * `language` - The name of a [`Language`] registered in a [`Registry`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use knapsac_lib::module::Module;
+    ///
+    /// let mut module = Module::create("a.sac", None);
+    /// assert!(module.language().is_none());
+    /// module.set_language(Some("sac".to_string()));
+    /// assert_eq!(module.language(), Some("sac"));
+    /// ```
+    ///
+    /// [`Language`]: crate::language::Language
+    /// [`Package`]: crate::package::Package
+    /// [`Registry`]: crate::registry::Registry
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    /// Returns the name of the [`Language`] the [`Module`] is written in, if set
+    ///
+    /// [`Language`]: crate::language::Language
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Sets the [`Language`] settings this [`Module`] overrides
+    /// Settings left empty are inherited from the [`Language`] of the [`Module`] or its [`Package`],
+    /// or from the [`Language`] the override extends, see [`Registry::get_module_language`]
+    ///
+    /// # Arguments
+    /// * `language` - The overriding [`Language`] settings, or `None` to remove the override
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Registry::get_module_language`]: crate::registry::Registry::get_module_language
+    pub fn set_language_override(&mut self, language: Option<Language>) {
+        self.language_override = language;
+    }
+
+    /// Returns the [`Language`] settings this [`Module`] overrides, if any
+    pub fn language_override(&self) -> Option<&Language> {
+        self.language_override.as_ref()
+    }
+
+    /// Sets the directory the [`Module`] is compiled to
+    /// A relative [`Path`] is resolved when it is needed, see [`Registry::get_output`]
+    ///
+    /// # Arguments
+    /// * `output` - A [`Path`] pointing to a directory, or `None` to remove it
+    ///
+    /// [`Registry::get_output`]: crate::registry::Registry::get_output
+    pub fn set_output<P: AsRef<Path>>(&mut self, output: Option<P>) {
+        self.output = output.map(|o| o.as_ref().to_path_buf());
+    }
+
+    /// Returns the directory the [`Module`] is compiled to as it is stored, if set
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// Sets the directory the [`Module`] is compiled to when it is built with the given profile, e.g. `debug` or `release`
+    /// Profiles without a directory of their own use the directory set by [`Module::set_output`]
+    ///
+    /// # Arguments
+    /// * `profile` - The name of the profile
+    /// * `output` - A [`Path`] pointing to a directory, or `None` to remove it
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::path::Path;
+    /// # use knapsac_lib::module::Module;
+    ///
+    /// let mut module = Module::create("a.sac", None);
+    /// module.set_output(Some("build"));
+    /// module.set_profile_output("debug", Some("build/debug"));
+    /// assert_eq!(module.output_for(Some("debug")), Some(Path::new("build/debug")));
+    /// assert_eq!(module.output_for(Some("release")), Some(Path::new("build")));
+    /// assert_eq!(module.output_for(None), Some(Path::new("build")));
+    /// ```
+    pub fn set_profile_output<S: Into<String>, P: AsRef<Path>>(&mut self, profile: S, output: Option<P>) {
+        match output {
+            Some(output) => self.profile_outputs.insert(profile.into(), output.as_ref().to_path_buf()),
+            None => self.profile_outputs.remove(&profile.into()),
+        };
+    }
+
+    /// Returns the directories set per profile, see [`Module::set_profile_output`]
+    pub fn profile_outputs(&self) -> &BTreeMap<String, PathBuf> {
+        &self.profile_outputs
+    }
+
+    /// Returns the directory the [`Module`] is compiled to when it is built with the given profile as it is stored, if set
+    /// Falls back to [`Module::output`] when the profile has no directory of its own or no profile is given
+    ///
+    /// # Arguments
+    /// * `profile` - The name of the profile, or `None` for the default output
+    pub fn output_for(&self, profile: Option<&str>) -> Option<&Path> {
+        profile
+            .and_then(|p| self.profile_outputs.get(p))
+            .map(PathBuf::as_path)
+            .or(self.output.as_deref())
+    }
+
+    /// Sets the directory the [`Module`] is compiled to for the given target, e.g. `x86_64-linux` or `wasm32`
+    /// A target directory takes precedence over the directories of profiles, see [`Module::output_for_target`]
+    ///
+    /// # Arguments
+    /// * `target` - The name of the target
+    /// * `output` - A [`Path`] pointing to a directory, or `None` to remove it
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::path::Path;
+    /// # use knapsac_lib::module::Module;
+    ///
+    /// let mut module = Module::create("a.sac", None);
+    /// module.set_output(Some("build"));
+    /// module.set_profile_output("debug", Some("build/debug"));
+    /// module.set_target_output("wasm32", Some("build/wasm32"));
+    /// assert_eq!(module.output_for_target(Some("wasm32"), Some("debug")), Some(Path::new("build/wasm32")));
+    /// assert_eq!(module.output_for_target(Some("x86_64-linux"), Some("debug")), Some(Path::new("build/debug")));
+    /// assert_eq!(module.output_for_target(None, None), Some(Path::new("build")));
+    /// ```
+    pub fn set_target_output<S: Into<String>, P: AsRef<Path>>(&mut self, target: S, output: Option<P>) {
+        match output {
+            Some(output) => self.target_outputs.insert(target.into(), output.as_ref().to_path_buf()),
+            None => self.target_outputs.remove(&target.into()),
+        };
+    }
+
+    /// Returns the directories set per target, see [`Module::set_target_output`]
+    pub fn target_outputs(&self) -> &BTreeMap<String, PathBuf> {
+        &self.target_outputs
+    }
+
+    /// Returns the directory the [`Module`] is compiled to for the given target and profile as it is stored, if set
+    /// Falls back to [`Module::output_for`] when the target has no directory of its own or no target is given
+    ///
+    /// # Arguments
+    /// * `target` - The name of the target, or `None` for the host
+    /// * `profile` - The name of the profile, or `None` for the default output
+    pub fn output_for_target(&self, target: Option<&str>, profile: Option<&str>) -> Option<&Path> {
+        target
+            .and_then(|t| self.target_outputs.get(t))
+            .map(PathBuf::as_path)
+            .or_else(|| self.output_for(profile))
+    }
+
+    /// Returns every output directory of the [`Module`] as it is stored, the default one first
+    pub(crate) fn all_outputs(&self) -> impl Iterator<Item = &PathBuf> {
+        self.output.iter().chain(self.profile_outputs.values()).chain(self.target_outputs.values())
+    }
+
+    /// Sets the extra flags passed to the compiler when building the [`Module`], e.g. an optimization level or defines
+    /// They replace the extra flags of its [`Package`], empty flags are inherited from the [`Package`]
+    ///
+    /// # Arguments
+    /// * `flags` - The extra flags
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_flags(&mut self, flags: Vec<String>) {
+        self.flags = flags;
+    }
+
+    /// Returns the extra flags passed to the compiler when building the [`Module`]
+    pub fn flags(&self) -> &[String] {
+        &self.flags
+    }
+
+    /// Returns the ABI tag of the artifact the [`Module`] was last compiled to, if it was compiled
+    /// See [`Language::abi_tag`]
+    ///
+    /// [`Language::abi_tag`]: crate::language::Language::abi_tag
+    pub fn artifact_tag(&self) -> Option<&str> {
+        self.artifact_tag.as_deref()
+    }
+
+    /// Sets a human-readable description of the [`Module`], shown in search results and listings
+    ///
+    /// # Arguments
+    /// * `description` - The description, or `None` to remove it
+    ///
+    /// # Examples
+    /// ```
+    /// # use knapsac_lib::module::Module;
+    ///
+    /// let mut module = Module::create("a.sac", None);
+    /// module.set_description(Some("Parses JSON".to_string()));
+    /// assert_eq!(module.description(), Some("Parses JSON"));
+    /// ```
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+    }
+
+    /// Returns the description of the [`Module`], if set
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Sets the [`Url`] pointing to the documentation of the [`Module`]
+    ///
+    /// # Arguments
+    /// * `documentation` - The [`Url`], or `None` to remove it
+    pub fn set_documentation(&mut self, documentation: Option<Url>) {
+        self.documentation = documentation;
+    }
+
+    /// Returns the [`Url`] pointing to the documentation of the [`Module`], if set
+    pub fn documentation(&self) -> Option<&Url> {
+        self.documentation.as_ref()
+    }
+
+    /// Adds an auxiliary source file to the [`Module`], passed to the compiler after its entry point
+    /// A directory adds every file in it, as found when the [`Module`] is built
+    /// The [`Module`] remains registered under the [`Path`] of its entry point
+    ///
+    /// # Arguments
+    /// * `path` - A relative [`Path`] that points to a file or directory within the [`Package`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use knapsac_lib::module::Module;
+    ///
+    /// let mut module = Module::create("src/json.sac", None);
+    /// module.add_source("src/json/parse.sac");
+    /// module.add_source("src/json/print.sac");
+    /// assert_eq!(module.sources().len(), 2);
+    /// module.remove_source("src/json/print.sac");
+    /// assert!(module.sources().contains(&PathBuf::from("src/json/parse.sac")));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the received path is absolute
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn add_source<P: AsRef<Path>>(&mut self, path: P) {
+        if path.as_ref().is_absolute() {
+            panic!("Path is absolute")
+        }
+        self.sources.insert(path.as_ref().to_path_buf());
+    }
+
+    /// Removes an auxiliary source file or directory from the [`Module`]
+    pub fn remove_source<P: AsRef<Path>>(&mut self, path: P) {
+        self.sources.remove(path.as_ref());
+    }
+
+    /// Returns the auxiliary source files and directories of the [`Module`], relative to its [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn sources(&self) -> &BTreeSet<PathBuf> {
+        &self.sources
+    }
+
+    /// Returns the absolute [`Path`]s of the files a build of the [`Module`] produced, see [`Registry::record_artifacts`]
+    ///
+    /// [`Registry::record_artifacts`]: crate::registry::Registry::record_artifacts
+    pub fn artifacts(&self) -> &BTreeSet<PathBuf> {
+        &self.artifacts
+    }
+
+    /// Returns the hash of the contents of the source files of the [`Module`] when it was added to its [`Package`],
+    /// see [`Registry::find_duplicates`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Registry::find_duplicates`]: crate::registry::Registry::find_duplicates
+    pub fn content_hash(&self) -> Option<&str> {
+        self.content_hash.as_deref()
+    }
+
+    /// Hashes the contents of the source files of the [`Module`] in the given `local_location`, in order,
+    /// so copies of the [`Module`] at other locations hash the same
+    pub(crate) fn hash_contents(&self, root: &Path) -> String {
+        let mut hasher = Sha256::new();
+        for file in self.source_files(root) {
+            hasher.update(read(root.join(file)).unwrap_or_default());
+        }
+        to_hex(&hasher.finalize())
+    }
+
+    /// Returns every source file of the [`Module`] relative to the given `local_location`, its entry point first
+    /// and auxiliary directories expanded to the files in them
+    pub(crate) fn source_files(&self, root: &Path) -> Vec<PathBuf> {
+        let mut files = vec![self.location.clone()];
+        for source in &self.sources {
+            let expanded = match root.join(source).is_dir() {
+                true => list_files(root.join(source)).into_iter().map(|f| source.join(f)).collect(),
+                false => vec![source.clone()],
+            };
+            for file in expanded {
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+        files
+    }
+}
+
+/// Two [`Module`]s are equal when everything but their recorded content hash is, see [`Module::content_hash`]
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        self.identifier == other.identifier
+            && self.location == other.location
+            && self.language == other.language
+            && self.language_override == other.language_override
+            && self.binary == other.binary
+            && self.output == other.output
+            && self.artifact_tag == other.artifact_tag
+            && self.profile_outputs == other.profile_outputs
+            && self.target_outputs == other.target_outputs
+            && self.flags == other.flags
+            && self.description == other.description
+            && self.documentation == other.documentation
+            && self.sources == other.sources
+            && self.artifacts == other.artifacts
+    }
+}
+
+impl Eq for Module {}
+
+impl Hash for Module {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identifier.hash(state);
+        self.location.hash(state);
+        self.language.hash(state);
+        self.language_override.hash(state);
+        self.binary.hash(state);
+        self.output.hash(state);
+        self.artifact_tag.hash(state);
+        self.profile_outputs.hash(state);
+        self.target_outputs.hash(state);
+        self.flags.hash(state);
+        self.description.hash(state);
+        self.documentation.hash(state);
+        self.sources.hash(state);
+        self.artifacts.hash(state);
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`ModuleError`] explains why the metadata of a [`Module`] could not be changed through a [`Registry`]
+///
+/// [`Registry`]: crate::registry::Registry
+pub enum ModuleError {
+    /// No [`Module`] is registered at the given [`PathBuf`]
+    UnknownSource(PathBuf),
+    /// The given identifier is empty or contains whitespace or path separators
+    InvalidIdentifier(String),
+    /// The given identifier is already used by the [`Module`] at the given [`PathBuf`],
+    /// in the same [`Package`] or in a [`Package`] importing the renamed [`Module`]
+    ///
+    /// [`Package`]: crate::package::Package
+    IdentifierTaken(String, PathBuf),
+    /// The given output directory is an existing file, or contains the [`Package`] at the second [`PathBuf`]
+    /// and would therefore remove its sources when cleaned
+    ///
+    /// [`Package`]: crate::package::Package
+    InvalidOutput(PathBuf, PathBuf),
+    /// The source file or an auxiliary source of a [`Module`] does not exist at the given [`PathBuf`]
+    MissingSource(PathBuf),
+    /// The file at the given [`PathBuf`] could not be changed, for the given reason
+    Io(PathBuf, String),
 }
+
+impl Display for ModuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleError::UnknownSource(path) => write!(f, "No module registered @ {}", path.display()),
+            ModuleError::InvalidIdentifier(identifier) => write!(f, "`{}` is not a valid module identifier", identifier),
+            ModuleError::IdentifierTaken(identifier, path) => write!(f, "identifier `{}` is already used by {}", identifier, path.display()),
+            ModuleError::InvalidOutput(output, package) => write!(f, "{} cannot be the output directory of a module of {}", output.display(), package.display()),
+            ModuleError::MissingSource(path) => write!(f, "{} does not point to an existing file", path.display()),
+            ModuleError::Io(path, reason) => write!(f, "Cannot change {}: {}", path.display(), reason),
+        }
+    }
+}
+
+impl std::error::Error for ModuleError {}
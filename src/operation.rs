@@ -0,0 +1,150 @@
+use crate::registry::Registry;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// The state of a long running operation driven through an [`OperationHandle`]
+pub enum OperationStatus<T, E> {
+    /// The operation has completed the first number of steps out of the second number of steps known so far
+    Running(usize, usize),
+    /// The operation finished with the given result
+    Finished(T),
+    /// The operation failed with the given error, its changes were undone
+    Failed(E),
+    /// The operation was cancelled, its changes were undone
+    Cancelled,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`OperationEvent`] reports what an operation driven through an [`OperationHandle`] did
+pub enum OperationEvent {
+    /// A step completed, described by the given message
+    Step(String),
+    /// A step completed but found something the user should know about, described by the given message
+    Warning(String),
+    /// The operation finished
+    Finished,
+    /// The operation failed, described by the given message
+    Failed(String),
+    /// The operation was cancelled
+    Cancelled,
+}
+
+/// A long running operation that is split up in steps
+pub(crate) trait Operation<T, E> {
+    /// Performs the next step, returning the result once the last step was performed
+    fn step(&mut self, registry: &mut Registry, events: &mut Vec<OperationEvent>) -> Result<Option<T>, E>;
+
+    /// Returns the number of steps that were completed and the number of steps known so far
+    fn progress(&self) -> (usize, usize);
+
+    /// Undoes every step that was performed
+    fn rollback(&mut self, registry: &mut Registry);
+}
+
+/// An [`OperationHandle`] drives a long running operation on a [`Registry`] one step at a time,
+/// so front-ends can show progress and stay responsive without running the operation on another thread
+///
+/// Every call to [`OperationHandle::poll_status`] performs a single step.
+/// An operation that fails or is cancelled undoes the steps it performed
+pub struct OperationHandle<'r, T, E> {
+    registry: &'r mut Registry,
+    operation: Box<dyn Operation<T, E> + 'r>,
+    status: OperationStatus<T, E>,
+    events: Vec<OperationEvent>,
+    cancel_requested: bool,
+}
+
+impl<'r, T: Clone, E: Clone + std::fmt::Display> OperationHandle<'r, T, E> {
+    pub(crate) fn new(registry: &'r mut Registry, operation: Box<dyn Operation<T, E> + 'r>) -> Self {
+        let (completed, total) = operation.progress();
+        OperationHandle {
+            registry,
+            operation,
+            status: OperationStatus::Running(completed, total),
+            events: vec![],
+            cancel_requested: false,
+        }
+    }
+
+    pub(crate) fn failed(registry: &'r mut Registry, operation: Box<dyn Operation<T, E> + 'r>, error: E) -> Self {
+        let mut handle = OperationHandle::new(registry, operation);
+        handle.events.push(OperationEvent::Failed(error.to_string()));
+        handle.status = OperationStatus::Failed(error);
+        handle
+    }
+
+    /// Performs the next step of the operation and returns its [`OperationStatus`]
+    /// Once the operation is no longer running this returns the final [`OperationStatus`] without doing anything
+    pub fn poll_status(&mut self) -> &OperationStatus<T, E> {
+        if !matches!(self.status, OperationStatus::Running(_, _)) {
+            return &self.status;
+        }
+        let reported = self.events.len();
+        self.advance();
+        for event in &self.events[reported..] {
+            self.registry.progress.notify(|h| h.event(event));
+        }
+        &self.status
+    }
+
+    fn advance(&mut self) {
+        if self.cancel_requested {
+            self.operation.rollback(self.registry);
+            self.events.push(OperationEvent::Cancelled);
+            self.status = OperationStatus::Cancelled;
+            return;
+        }
+
+        self.status = match self.operation.step(self.registry, &mut self.events) {
+            Ok(Some(result)) => {
+                self.events.push(OperationEvent::Finished);
+                OperationStatus::Finished(result)
+            }
+            Ok(None) => {
+                let (completed, total) = self.operation.progress();
+                OperationStatus::Running(completed, total)
+            }
+            Err(error) => {
+                self.operation.rollback(self.registry);
+                self.events.push(OperationEvent::Failed(error.to_string()));
+                OperationStatus::Failed(error)
+            }
+        };
+    }
+
+    /// Returns the [`OperationStatus`] without performing a step
+    pub fn status(&self) -> &OperationStatus<T, E> {
+        &self.status
+    }
+
+    /// Requests the operation to stop, the steps it performed are undone on the next call to [`OperationHandle::poll_status`]
+    pub fn cancel(&mut self) {
+        self.cancel_requested = true;
+    }
+
+    /// Returns the [`OperationEvent`]s that happened since the last call
+    pub fn events(&mut self) -> Vec<OperationEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Performs every remaining step and returns the final [`OperationStatus`]
+    pub fn wait(mut self) -> OperationStatus<T, E> {
+        while let OperationStatus::Running(_, _) = self.poll_status() {}
+        self.status
+    }
+
+    /// Performs every remaining step and returns the result of the operation
+    ///
+    /// # Panics
+    /// Panics when the operation was cancelled
+    pub fn result(self) -> Result<T, E> {
+        match self.wait() {
+            OperationStatus::Finished(result) => Ok(result),
+            OperationStatus::Failed(error) => Err(error),
+            _ => panic!("Operation was cancelled"),
+        }
+    }
+}
@@ -1,16 +1,43 @@
 use std::fmt::{Display, Formatter};
 use crate::manifest::Manifest;
-use crate::dependency::Dependency;
-use crate::module::Module;
-use crate::utils::{discover_git_repository, infer_working_directory};
+use crate::artifacts::{ArtifactMode, ARTIFACTS_BRANCH, ARTIFACTS_DIRECTORY};
+use crate::backend::RegistryBackend;
+use crate::dependency::{Dependency, DependencyError, DependencyKind};
+use crate::error::{Error, ManifestError};
+use crate::executable::Executable;
+use crate::fetcher::{Fetcher, GitFetcher};
+use crate::format::{Format, Style};
+use crate::git_cache::{self, GitMetadata};
+use crate::hooks::{self, HookError, HookKind};
+use crate::install::InstallError;
+use crate::integrity::IntegrityIssue;
+use crate::language::Language;
+use crate::metadata::PackageMetadata;
+use crate::module::{Module, ModuleError};
+use crate::package_id::PackageId;
+use crate::packaging::PackagingError;
+use crate::paths::{canonicalize, strip_base};
+use crate::publish::{DirtyPolicy, PublishPlan, PushOptions, ReleaseNotes, UploadPlan};
+use crate::signing::{to_hex, PackageSignature, SignatureError, Signer, TrustStore};
+use crate::testing::TestModule;
+use crate::version::{SemVerIncrement, Version, VersionReq};
+#[cfg(feature = "tokio")]
+use crate::utils::spawn_blocking;
+use crate::utils::{compare_versions, copy_directory, discover_git_repository, hash_directory, infer_working_directory, is_executable, list_files, pack_directory, pack_files, sha256_hex, unpack_archive};
 
-use std::fs::create_dir;
-use git2::Repository;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{create_dir, create_dir_all, read, read_dir, read_to_string, remove_dir_all, remove_file, write};
+use git2::{BranchType, Commit, Index, IndexAddOption, IndexEntry, IndexTime, Oid, Repository, Signature, StatusOptions, TreeWalkMode, TreeWalkResult};
+use git2::build::CheckoutBuilder;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use nanoid::nanoid;
 use url::Url;
 
+/// The directory inside a [`Package`] that stray dependencies are vendored into, see [`Package::set_vendor_strays`]
+pub const VENDORED_DIRECTORY: &str = "vendored";
+
 #[derive(Deserialize, Serialize)]
 #[derive(Clone)]
 #[derive(Debug)]
@@ -51,7 +78,7 @@ impl Package {
     /// let package = Package::create(&path);
     /// ```
     pub fn create<P: AsRef<Path>>(path: P) -> Self {
-        let local_repository_root = infer_working_directory(path);
+        let local_repository_root = canonicalize(infer_working_directory(path));
         let repository = discover_git_repository(&local_repository_root);
         let remotes = repository.remotes().unwrap();
 
@@ -123,255 +150,2409 @@ impl Package {
     /// let package = Package::download(url, path);
     /// ```
     pub fn download<P: AsRef<Path>>(url: Url, path: P) -> Self {
-        if !path.as_ref().is_dir() {
-            panic!("No directory found @ {}", path.as_ref().display());
-        }
-        let mut repository_path = path.as_ref().to_path_buf();
-        repository_path.push(nanoid!());
-        create_dir(&repository_path).unwrap();
-        if Repository::clone(url.as_str(), &repository_path).is_err() {
-            panic!(
-                "Failed to download package from `{}` to `{}`",
-                url,
-                path.as_ref().display()
-            )
-        }
-        Package::create(repository_path)
-    }
-
-    fn load_manifest(&self) -> Manifest {
-        Manifest::load(self.manifest_location())
-    }
-
-    fn manifest_location(&self) -> PathBuf {
-        let mut path: PathBuf = self.local_location.clone();
-        path.push("manifest");
-        path.set_extension("json");
-        path
+        Package::fetch(&GitFetcher, url, path, None)
     }
 
-    /// Strips the [`Package`]'s `local_location` from the given [`Path`]
+    /// Obtains a [`Package`] from the given source using the given [`Fetcher`] and places it in a new directory inside the given [`Path`]
+    /// Contents that do not come with a git repository are turned into one
+    /// A manifest that comes with the contents is kept, so the [`Dependency`]s it declares are not lost
     ///
     /// # Arguments
-    /// * `path` - [`Path`] that needs to be stripped
+    /// * `fetcher` - The [`Fetcher`] used to obtain the [`Package`]
+    /// * `source` - An [`Url`] pointing to the contents of the [`Package`]
+    /// * `path` - An [`Path`] or reference to one that points to where packages need to be downloaded to
+    /// * `checksum` - The expected checksum of the contents, see [`Package::checksum`]
     ///
     /// # Examples
     /// ```
-    /// # use std::env;
-    /// # use std::path::{Path, PathBuf};
-    /// # use git2::Repository;
+    /// # use std::{env, fs};
+    /// # use url::Url;
+    /// # use knapsac_lib::fetcher::LocalFetcher;
     /// # use knapsac_lib::package::Package;
     ///
-    /// let package_path = env::temp_dir().join("mock_package_known");
-    /// Repository::init(&package_path);
-    /// assert!(package_path.exists());
-    /// let package = Package::create(&package_path);
-    /// assert_eq!(package.strip_prefix(package_path.join("example.txt")), PathBuf::from("example.txt"))
+    /// let share = env::temp_dir().join("mock_share_fetch");
+    /// fs::create_dir_all(&share).unwrap();
+    /// fs::write(share.join("a.sac"), "module a;").unwrap();
+    /// let source = Url::from_directory_path(&share).unwrap();
+    /// let checksum = Package::checksum(&share);
+    /// let package = Package::fetch(&LocalFetcher, source, env::temp_dir(), Some(&checksum));
     /// ```
     ///
     /// # Panics
-    /// Panics when the given [`Path`] is not a prefix of the [`Package`]'s `local_location`
+    /// Panics when no directory exists at given [`Path`], when the [`Fetcher`] fails,
+    /// when the [`Fetcher`] requires a checksum but none is given or when the checksum does not match
     /// ```rust, should_panic
-    /// # use std::env;
-    /// # use git2::Repository;
+    /// # use std::{env, fs};
+    /// # use url::Url;
+    /// # use knapsac_lib::fetcher::LocalFetcher;
     /// # use knapsac_lib::package::Package;
     ///
-    /// let package_path = env::temp_dir().join("mock_package_known");
-    /// Repository::init(&package_path);
-    /// assert!(package_path.exists());
-    /// let other_path = env::temp_dir().join("mock_package_registered").join("example.txt");
-    /// Package::create(&package_path).strip_prefix(&other_path);
+    /// let share = env::temp_dir().join("mock_share_fetch_mismatch");
+    /// fs::create_dir_all(&share).unwrap();
+    /// fs::write(share.join("a.sac"), "module a;").unwrap();
+    /// let source = Url::from_directory_path(&share).unwrap();
+    /// Package::fetch(&LocalFetcher, source, env::temp_dir(), Some("0000"));
     /// ```
-    pub fn strip_prefix<P: AsRef<Path>>(&self, path: P) -> PathBuf {
-        path.as_ref().strip_prefix(self.local_location.clone()).unwrap().to_path_buf()
+    pub fn fetch<F: Fetcher, P: AsRef<Path>>(fetcher: &F, source: Url, path: P, checksum: Option<&str>) -> Self {
+        Package::try_fetch(fetcher, source, path, checksum).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    /// Adds a [`Dependency`] to a [`Package`]
+    /// Obtains a [`Package`] like [`Package::fetch`], returning an [`InstallError`] instead of panicking
+    /// The new directory is removed again when fetching fails
     ///
     /// # Arguments
-    /// * `dependency` - A [`Dependency`] that needs to be added
+    /// * `fetcher` - The [`Fetcher`] used to obtain the [`Package`]
+    /// * `source` - An [`Url`] pointing to the contents of the [`Package`]
+    /// * `path` - An [`Path`] or reference to one that points to where packages need to be downloaded to
+    /// * `checksum` - The expected checksum of the contents, see [`Package::checksum`]
     ///
     /// # Examples
     /// ```
-    /// # use std::env;
-    /// # use git2::Repository;
+    /// # use std::{env, fs};
     /// # use url::Url;
+    /// # use knapsac_lib::fetcher::LocalFetcher;
+    /// # use knapsac_lib::install::InstallError;
     /// # use knapsac_lib::package::Package;
-    /// # use knapsac_lib::dependency::Dependency;
     ///
-    /// let url = Url::parse("https://github.com/jcuppen/JSON");
-    /// let path = env::temp_dir().join("mock_package_known");
-    /// Repository::init(&path);
-    /// # assert!(url.is_ok());
-    /// assert!(path.exists());
-    /// let package = Package::create(path);
-    /// let dependency = Dependency::create(url.unwrap());
-    /// package.add_dependency(dependency.clone());
-    /// assert!(package.has_dependency(&dependency));
+    /// let share = env::temp_dir().join("mock_share_try_fetch");
+    /// fs::create_dir_all(&share).unwrap();
+    /// fs::write(share.join("a.sac"), "module a;").unwrap();
+    /// let source = Url::from_directory_path(&share).unwrap();
+    /// let destination = env::temp_dir().join("mock_try_fetch_destination");
+    /// # let _ = fs::remove_dir_all(&destination);
+    /// fs::create_dir_all(&destination).unwrap();
+    ///
+    /// let error = Package::try_fetch(&LocalFetcher, source.clone(), &destination, Some("0000")).unwrap_err();
+    /// assert!(matches!(error, InstallError::ChecksumMismatch(..)));
+    /// assert_eq!(fs::read_dir(&destination).unwrap().count(), 0);
+    ///
+    /// let missing = Url::from_directory_path(env::temp_dir().join("mock_share_try_fetch_missing")).unwrap();
+    /// let error = Package::try_fetch(&LocalFetcher, missing, &destination, None).unwrap_err();
+    /// assert!(matches!(error, InstallError::Fetch(..)));
+    /// assert_eq!(fs::read_dir(&destination).unwrap().count(), 0);
     /// ```
-    pub fn add_dependency(&self, dependency: Dependency) {
-        let mut manifest = self.load_manifest();
-        manifest.add_dependency(dependency);
-        manifest.save(self.manifest_location());
+    ///
+    /// # Errors
+    /// Returns [`InstallError::Fetch`] when no directory exists at the given [`Path`] or the [`Fetcher`] fails,
+    /// [`InstallError::ChecksumRequired`] when the [`Fetcher`] requires a checksum but none is given
+    /// and [`InstallError::ChecksumMismatch`] when the checksum does not match
+    pub fn try_fetch<F: Fetcher, P: AsRef<Path>>(fetcher: &F, source: Url, path: P, checksum: Option<&str>) -> Result<Self, InstallError> {
+        Package::fetch_with_progress(fetcher, source, path, checksum, &mut |_, _| {})
     }
 
-    /// Checks the [`Package`] if it depends on the given [`Dependency`]
-    ///
-    /// # Arguments
-    /// * `dependency` - A reference to a [`Dependency`] that needs to be checked
-    pub fn has_dependency(&self, dependency: &Dependency) -> bool {
-        let manifest = self.load_manifest();
-        manifest.has_dependency(dependency)
+    /// Like [`Package::try_fetch`], reporting transfer progress through the given callback, see [`Fetcher::fetch_with_progress`]
+    pub(crate) fn fetch_with_progress<F: Fetcher, P: AsRef<Path>>(fetcher: &F, source: Url, path: P, checksum: Option<&str>, progress: &mut dyn FnMut(usize, usize)) -> Result<Self, InstallError> {
+        if !path.as_ref().is_dir() {
+            return Err(InstallError::Fetch(format!("No directory found @ {}", path.as_ref().display())));
+        }
+        if fetcher.requires_checksum() && checksum.is_none() {
+            return Err(InstallError::ChecksumRequired(source.to_string(), fetcher.name().to_string()));
+        }
+        let mut repository_path = path.as_ref().to_path_buf();
+        repository_path.push(nanoid!());
+        create_dir(&repository_path).map_err(|e| InstallError::Fetch(format!("Cannot create {}: {}", repository_path.display(), e)))?;
+        if let Err(error) = fetcher.fetch_with_progress(&source, &repository_path, progress) {
+            let _ = remove_dir_all(&repository_path);
+            return Err(InstallError::Fetch(format!("Failed to download package from `{}`: {}", source, error)));
+        }
+        if let Some(expected) = checksum {
+            let actual = Package::checksum(&repository_path);
+            if actual != expected {
+                let _ = remove_dir_all(&repository_path);
+                return Err(InstallError::ChecksumMismatch(source.to_string(), expected.to_string(), actual));
+            }
+        }
+        Ok(Package::adopt(repository_path))
     }
 
-    /// Removes a [`Dependency`] from a [`Package`]
+    /// Packs the [`Package`] into a deterministic gzipped tarball in the given directory and returns its [`Path`]
+    /// The archive contains every file outside the `.git` directory: sources, the manifest
+    /// and outputs written inside the [`Package`]'s directory
+    /// It is named after the [`Package`]'s identifier and [`Version`], e.g. `json-1.0.0.tar.gz`
+    /// With [`ArtifactMode::Archive`] the prebuilt outputs published with the [`Version`] are packed into [`ARTIFACTS_DIRECTORY`]
     ///
     /// # Arguments
-    /// * `dependency` - A reference to a [`Dependency`] that needs to be removed
+    /// * `destination` - A [`Path`] pointing to the directory the archive is written to
     ///
     /// # Examples
     /// ```
-    /// # use std::env;
+    /// # use std::{env, fs};
     /// # use git2::Repository;
-    /// # use url::Url;
-    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::module::Module;
     /// # use knapsac_lib::package::Package;
     ///
-    /// let url = Url::parse("https://github.com/jcuppen/JSON");
-    /// let path = env::temp_dir().join("mock_package_known");
+    /// let path = env::temp_dir().join("mock_package_pack");
+    /// # let _ = fs::remove_dir_all(&path);
     /// Repository::init(&path);
-    /// # assert!(url.is_ok());
-    /// assert!(path.exists());
-    /// let package = Package::create(path);
-    /// let dependency = Dependency::create(url.unwrap());
-    /// package.add_dependency(dependency.clone());
-    /// assert!(package.has_dependency(&dependency));
-    /// package.remove_dependency(&dependency);
-    /// assert!(!package.has_dependency(&dependency))
+    /// fs::write(path.join("a.sac"), "module a;").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_module(Module::create("a.sac", None));
+    ///
+    /// let archive = package.pack(env::temp_dir());
+    /// assert_eq!(archive, env::temp_dir().join("mock_package_pack.tar.gz"));
+    /// assert_eq!(fs::read(&archive).unwrap(), fs::read(package.pack(env::temp_dir())).unwrap());
+    ///
+    /// let restored = env::temp_dir().join("mock_package_unpack");
+    /// # let _ = fs::remove_dir_all(&restored);
+    /// let unpacked = Package::unpack(&archive, &restored);
+    /// assert!(unpacked.get_module_by_location("a.sac").is_some());
     /// ```
-    pub fn remove_dependency(&self, dependency: &Dependency) {
-        let mut manifest = self.load_manifest();
-
-        manifest.remove_dependency(dependency);
-        manifest.save(self.manifest_location());
+    ///
+    /// # Panics
+    /// Panics when no directory exists at the given [`Path`], see [`Package::try_pack`]
+    pub fn pack<P: AsRef<Path>>(&self, destination: P) -> PathBuf {
+        self.try_pack(destination).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    /// Adds a [`Module`] to a [`Package`]
+    /// Packs the [`Package`] like [`Package::pack`], returning an [`Error`] instead of panicking
     ///
     /// # Arguments
-    /// * `module` - A [`Module`] that needs to be added
+    /// * `destination` - A [`Path`] pointing to the directory the archive is written to
     ///
     /// # Examples
     /// ```
     /// # use std::env;
-    /// # use std::path::PathBuf;
     /// # use git2::Repository;
-    /// # use url::Url;
-    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::Error;
     /// # use knapsac_lib::package::Package;
     ///
-    /// let url = Url::parse("https://github.com/jcuppen/JSON");
-    /// # assert!(url.is_ok());
-    /// let package = Package::download(url.unwrap(), env::temp_dir());
-    /// let module_path: PathBuf = ["src","JSON.sac"].iter().collect();
-    /// let module = Module::create(module_path, None);
-    /// package.add_module(module.clone());
-    /// assert!(package.has_module(&module));
+    /// let path = env::temp_dir().join("mock_package_try_pack");
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    ///
+    /// let missing = env::temp_dir().join("mock_package_try_pack_missing");
+    /// assert!(matches!(package.try_pack(&missing), Err(Error::Io(..))));
     /// ```
     ///
+    /// # Errors
+    /// Returns [`Error::Io`] when no directory exists at the given [`Path`] or the archive cannot be written
+    /// and [`Error::Manifest`] when the manifest of the [`Package`] cannot be loaded
+    pub fn try_pack<P: AsRef<Path>>(&self, destination: P) -> Result<PathBuf, Error> {
+        if !destination.as_ref().is_dir() {
+            return Err(Error::Io(destination.as_ref().to_path_buf(), "no directory found".to_string()));
+        }
+        let manifest = Manifest::try_load(self.manifest_location())?;
+        let name = match manifest.version.clone().or_else(|| self.version()) {
+            Some(version) => format!("{}-{}.tar.gz", self.identifier().replace('/', "-"), version),
+            None => format!("{}.tar.gz", self.identifier().replace('/', "-")),
+        };
+        let archive = destination.as_ref().join(name);
+        let mut files: Vec<(PathBuf, PathBuf)> = list_files(&self.local_location)
+            .into_iter()
+            .map(|f| (self.local_location.join(&f), f))
+            .collect();
+        if self.artifact_mode() == Some(ArtifactMode::Archive) {
+            for (location, output) in self.prebuilt_outputs(&manifest) {
+                let directory = PathBuf::from(ARTIFACTS_DIRECTORY).join(location);
+                files.extend(list_files(&output).into_iter().map(|f| (output.join(&f), directory.join(f))));
+            }
+        }
+        write(&archive, pack_files(files)).map_err(|e| Error::Io(archive.clone(), e.to_string()))?;
+        Ok(archive)
+    }
+
+    /// Restores a [`Package`] packed by [`Package::pack`] into the given directory
+    /// The directory is turned into a git repository if the archive does not contain one
+    /// Use [`Registry::unpack`] to register the restored [`Package`] as well
+    ///
+    /// # Arguments
+    /// * `archive` - A [`Path`] pointing to a gzipped tarball
+    /// * `destination` - A [`Path`] pointing to a directory that does not exist yet or is empty
+    ///
     /// # Panics
-    /// Panics when module_path does not point to an existing file
-    /// ```rust, should_panic
-    /// # use std::env;
-    /// # use std::path::PathBuf;
-    /// # use git2::Repository;
-    /// # use knapsac_lib::module::Module;
+    /// Panics when the destination contains files or when the archive cannot be read or unpacked, see [`Package::try_unpack`]
+    ///
+    /// [`Registry::unpack`]: crate::registry::Registry::unpack
+    pub fn unpack<P: AsRef<Path>, Q: AsRef<Path>>(archive: P, destination: Q) -> Self {
+        Package::try_unpack(archive, destination).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Restores a [`Package`] like [`Package::unpack`], returning an [`Error`] instead of panicking
+    ///
+    /// # Arguments
+    /// * `archive` - A [`Path`] pointing to a gzipped tarball
+    /// * `destination` - A [`Path`] pointing to a directory that does not exist yet or is empty
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use knapsac_lib::Error;
     /// # use knapsac_lib::package::Package;
     ///
-    /// let package_path = env::temp_dir().join("mock_package_known");
-    /// Repository::init(&package_path);
-    /// assert!(package_path.exists());
-    /// let package = Package::create(&package_path);
-    /// let module_path: PathBuf = ["nonexistent.c"].iter().collect();
-    /// assert!(!package_path.join(&module_path).exists());
-    /// let module = Module::create(&module_path, None);
-    /// package.add_module(module);
+    /// let archive = env::temp_dir().join("mock_package_try_unpack_missing.tar.gz");
+    /// # let _ = fs::remove_file(&archive);
+    /// let destination = env::temp_dir().join("mock_package_try_unpack");
+    /// # let _ = fs::remove_dir_all(&destination);
+    /// assert!(matches!(Package::try_unpack(&archive, &destination), Err(Error::Io(..))));
     /// ```
-    pub fn add_module(&self, module: Module) {
-        let mut manifest = self.load_manifest();
+    ///
+    /// # Errors
+    /// Returns [`PackagingError::DestinationExists`] when the destination contains files
+    /// and [`Error::Io`] when the archive cannot be read or unpacked
+    ///
+    /// [`PackagingError::DestinationExists`]: crate::packaging::PackagingError::DestinationExists
+    pub fn try_unpack<P: AsRef<Path>, Q: AsRef<Path>>(archive: P, destination: Q) -> Result<Self, Error> {
+        let archive = archive.as_ref();
+        let destination = destination.as_ref();
+        if read_dir(destination).is_ok_and(|mut entries| entries.next().is_some()) {
+            return Err(PackagingError::DestinationExists(destination.to_path_buf()).into());
+        }
+        create_dir_all(destination).map_err(|e| Error::Io(destination.to_path_buf(), e.to_string()))?;
+        let data = read(archive).map_err(|e| Error::Io(archive.to_path_buf(), e.to_string()))?;
+        unpack_archive(&data, destination).map_err(|e| Error::Io(archive.to_path_buf(), e))?;
+        Ok(Package::adopt(destination))
+    }
 
-        let full_module_path = self.local_location.join(&module.location);
-        if full_module_path.exists() && full_module_path.is_file() {
-            manifest.add_module(module);
-            manifest.save(self.manifest_location());
-        } else {
-            panic!("Module does not point to existing file");
+    /// Turns a directory holding the contents of a [`Package`] into a [`Package`], keeping the manifest it contains
+    fn adopt<P: AsRef<Path>>(path: P) -> Self {
+        if Repository::open(&path).is_err() {
+            Repository::init(&path).unwrap();
         }
+
+        let contained_manifest = Package {
+            local_location: path.as_ref().to_path_buf(),
+            remote_location: None,
+        }
+        .manifest_location();
+        let declared = read(&contained_manifest).ok();
+        let package = Package::create(&path);
+        if let Some(contents) = declared {
+            write(contained_manifest, contents).unwrap();
+        }
+        package
     }
 
-    /// Searches the [`Package`] for a [`Module`] that is located at the given [`Path`]
+    /// Reads the remote location of the git repository at the [`Package`]'s local location, like [`Package::create`] does,
+    /// without touching its manifest
+    pub(crate) fn current_remote_location(&self) -> Option<Url> {
+        let repository = Repository::open(&self.local_location).ok()?;
+        let remotes = repository.remotes().ok()?;
+        let remote = repository.find_remote(remotes.get(0)?).ok()?;
+        Url::parse(remote.url()?).ok()
+    }
+
+    /// Computes the checksum of the contents of the directory at the given [`Path`]
+    /// The checksum is a SHA-256 hash over the relative paths and contents of all files outside the `.git` directory
     ///
     /// # Arguments
-    /// * `location` - [`Path`] pointing to a location where a [`Module`] should be located.
+    /// * `path` - A [`Path`] pointing to a directory
+    pub fn checksum<P: AsRef<Path>>(path: P) -> String {
+        hash_directory(path)
+    }
+
+    /// Returns the identifier of the [`Package`] as a [`PackageId`], see [`Package::identifier`]
+    /// Returns `None` when the identifier is not a valid [`PackageId`], e.g. when it was derived from a directory name with spaces
     ///
     /// # Examples
-    /// //TODO
-    pub fn get_module_by_location<P: AsRef<Path>>(&self, location: P) -> Option<Module> {
-        self.load_manifest().get_module_by_location(location).cloned()
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_id");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// assert_eq!(package.package_id().unwrap().as_str(), "mock_package_id");
+    ///
+    /// let mut metadata = package.metadata();
+    /// metadata.set_name(Some("author/json".to_string()));
+    /// package.set_metadata(metadata);
+    /// assert_eq!(package.package_id().unwrap().scope(), Some("author"));
+    /// ```
+    pub fn package_id(&self) -> Option<PackageId> {
+        self.identifier().parse().ok()
     }
 
-    /// Checks the [`Package`] if it provides a given [`Module`]
-    ///
-    /// # Arguments
-    /// * `module` - A reference to a [`Module`] that needs to be checked
+    /// Computes the identifier of the [`Package`], see [`Package::id`]
+    pub(crate) fn identifier(&self) -> String {
+        if let Some(name) = self.metadata().name {
+            return name;
+        }
+        let segment = self.remote_location.as_ref()
+            .and_then(|url| url.path_segments())
+            .and_then(|segments| segments.rev().find(|s| !s.is_empty()))
+            .map(|s| s.trim_end_matches(".git").to_string());
+        segment.unwrap_or_else(|| self.local_location.file_name().unwrap().to_string_lossy().to_string())
+    }
+
+    /// Returns the identifier of the [`Package`]: the name in its [`PackageMetadata`],
+    /// or else the last segment of its remote location or local location
+    /// Unlike [`Package::package_id`] any identifier is returned, also one that is not a valid [`PackageId`]
     ///
     /// # Examples
-    /// //TODO
-    pub fn has_module(&self, module: &Module) -> bool {
-        self.load_manifest().modules.contains(module)
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock package id");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// assert_eq!(package.id(), "mock package id");
+    /// assert!(package.package_id().is_none());
+    /// ```
+    pub fn id(&self) -> String {
+        self.identifier()
     }
 
-    /// Checks the [`Package`] if it has any [`Module`] with a given `identifier`
+    /// Returns the absolute [`Path`] of the [`Package`]'s directory
+    pub fn location(&self) -> &Path {
+        &self.local_location
+    }
+
+    /// Returns the [`Url`] of the remote the [`Package`] was created or downloaded from, if any
+    pub fn remote(&self) -> Option<&Url> {
+        self.remote_location.as_ref()
+    }
+
+    /// Returns a [`PackageInfo`] summarizing the [`Package`], e.g. to display or serialize it
     ///
-    /// # Arguments
-    /// * `identifier` - The identifier to check for
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
     ///
-    pub(crate) fn has_modules_with_identifiers(&self, identifiers: &[String]) -> bool {
-        self.load_manifest().modules.iter().any(|m|identifiers.contains(&m.identifier))
+    /// let path = env::temp_dir().join("mock_package_info");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// fs::write(path.join("json.sac"), "").unwrap();
+    /// package.add_module(Module::create("json.sac", Some("JSON".to_string())));
+    /// package.set_language(Some("sac".to_string()));
+    ///
+    /// let info = package.info();
+    /// assert_eq!(info.id(), "mock_package_info");
+    /// assert_eq!(info.local_location(), package.location());
+    /// assert!(info.remote_location().is_none());
+    /// assert!(info.version().is_none());
+    /// assert_eq!(info.language(), Some("sac"));
+    /// assert_eq!(info.modules(), ["JSON".to_string()]);
+    /// ```
+    pub fn info(&self) -> PackageInfo {
+        PackageInfo {
+            id: self.identifier(),
+            local_location: self.local_location.clone(),
+            remote_location: self.remote_location.clone(),
+            version: self.version(),
+            language: self.language(),
+            description: self.metadata().description,
+            modules: self.modules().map(|m| m.identifier).collect(),
+        }
     }
 
-    /// Removes a [`Module`] from a [`Package`]
+    pub(crate) fn load_manifest(&self) -> Manifest {
+        Manifest::load(self.manifest_location())
+    }
+
+    pub(crate) fn manifest_location(&self) -> PathBuf {
+        let path = self.local_location.join("manifest");
+        #[cfg(feature = "toml")]
+        if path.with_extension(Format::Toml.extension()).is_file() {
+            return path.with_extension(Format::Toml.extension());
+        }
+        path.with_extension(Format::Json.extension())
+    }
+
+    /// Returns the [`Format`] the [`Package`]'s manifest is stored in
+    pub fn manifest_format(&self) -> Format {
+        Format::from_path(self.manifest_location()).unwrap()
+    }
+
+    /// Converts the [`Package`]'s manifest to the given [`Format`]
+    /// The manifest in the previous [`Format`] is removed
     ///
     /// # Arguments
-    /// * `module` - A reference to a [`Module`] that needs to be removed
+    /// * `format` - The [`Format`] the manifest needs to be stored in
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::format::Format;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_manifest_format");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// package.set_manifest_format(Format::Json);
+    /// assert_eq!(package.manifest_format(), Format::Json);
+    /// ```
+    pub fn set_manifest_format(&self, format: Format) {
+        let manifest = self.load_manifest();
+        let old_location = self.manifest_location();
+        let new_location = old_location.with_extension(format.extension());
+        manifest.save(&new_location);
+        if old_location != new_location {
+            remove_file(old_location).unwrap();
+        }
+    }
+
+    /// Returns the [`GitMetadata`] of the [`Package`]'s git repository
+    /// The [`GitMetadata`] is cached, see [`git_cache`] for invalidating it
     ///
     /// # Examples
     /// ```
     /// # use std::env;
-    /// # use std::path::PathBuf;
     /// # use git2::Repository;
-    /// # use url::Url;
-    /// # use knapsac_lib::module::Module;
     /// # use knapsac_lib::package::Package;
     ///
-    /// let url = Url::parse("https://github.com/jcuppen/JSON");
-    /// # assert!(url.is_ok());
-    /// let package = Package::download(url.unwrap(), env::temp_dir());
-    /// let module_path: PathBuf = ["src", "JSON.sac"].iter().collect();
-    /// let module = Module::create(&module_path, None);
-    /// package.add_module(module.clone());
-    /// assert!(package.has_module(&module));
-    /// package.remove_module(&module);
-    /// assert!(!package.has_module(&module))
+    /// let path = env::temp_dir().join("mock_package_git_metadata");
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// assert!(package.git_metadata().tags().is_empty());
     /// ```
-    pub fn remove_module(&self, module: &Module) {
-        let mut manifest = self.load_manifest();
-        manifest.remove_module(module);
-        manifest.save(self.manifest_location());
+    pub fn git_metadata(&self) -> GitMetadata {
+        git_cache::metadata(&self.local_location)
     }
-}
 
-impl Display for Package {
+    /// Returns the files in the [`Package`]'s directory with uncommitted changes, relative to it and sorted
+    /// Untracked files are included, ignored files are not
+    /// Unlike [`Package::git_metadata`] the git repository is read every time
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use std::path::PathBuf;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_uncommitted");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// assert_eq!(package.uncommitted_changes(), vec![PathBuf::from("manifest.json")]);
+    /// ```
+    pub fn uncommitted_changes(&self) -> Vec<PathBuf> {
+        let repository = discover_git_repository(&self.local_location);
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true).include_ignored(false);
+        let workdir = repository.workdir().map(Path::to_path_buf).unwrap_or_default();
+        let mut files: Vec<PathBuf> = repository.statuses(Some(&mut options)).unwrap()
+            .iter()
+            .filter_map(|s| s.path().map(|p| workdir.join(p)))
+            .filter(|p| p.starts_with(&self.local_location))
+            .map(|p| self.strip_prefix(p))
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Returns the [`Version`] of the [`Package`]: the highest of its git tags that is a [`Version`]
+    /// and points to the checked out commit or one of its ancestors
+    pub fn version(&self) -> Option<Version> {
+        let repository = discover_git_repository(&self.local_location);
+        let head = repository.head().ok()?.peel_to_commit().ok()?.id();
+        self.tagged_versions(&repository)
+            .into_iter()
+            .filter(|(_, target)| *target == head || repository.graph_descendant_of(head, *target).unwrap_or(false))
+            .map(|(version, _)| version)
+            .max()
+    }
+
+    /// Checks out the highest published [`Version`] of the [`Package`] that satisfies the given [`VersionReq`]
+    /// Yanked [`Version`]s are skipped, see [`Package::yank`]
+    /// Returns the [`Version`] that was checked out, if any satisfies the [`VersionReq`]
+    ///
+    /// # Arguments
+    /// * `requirement` - The [`VersionReq`] the checked out [`Version`] needs to satisfy
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_checkout_version");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// package.publish(SemVerIncrement::Major, vec![]);
+    /// package.publish(SemVerIncrement::Major, vec![]);
+    /// assert_eq!(package.version(), Some("2.0.0".parse().unwrap()));
+    ///
+    /// let version = package.checkout_version(&"^1.0".parse().unwrap());
+    /// assert_eq!(version, Some("1.0.0".parse().unwrap()));
+    /// assert_eq!(package.version(), version);
+    /// ```
+    pub fn checkout_version(&self, requirement: &VersionReq) -> Option<Version> {
+        let repository = discover_git_repository(&self.local_location);
+        let (version, target) = self.tagged_versions(&repository)
+            .into_iter()
+            .filter(|(version, _)| requirement.matches(version) && !self.is_yanked(version))
+            .max_by(|a, b| a.0.cmp(&b.0))?;
+        let commit = repository.find_object(target, None).unwrap();
+        repository.checkout_tree(&commit, Some(CheckoutBuilder::new().force())).unwrap();
+        repository.set_head_detached(target).unwrap();
+        git_cache::invalidate(&self.local_location);
+        Some(version)
+    }
+
+    fn tagged_versions(&self, repository: &Repository) -> Vec<(Version, Oid)> {
+        self.git_metadata()
+            .tags
+            .iter()
+            .filter_map(|t| {
+                let version = t.parse().ok()?;
+                let target = repository.revparse_single(&format!("refs/tags/{}", t)).ok()?.peel_to_commit().ok()?.id();
+                Some((version, target))
+            })
+            .collect()
+    }
+
+    /// Publishes the next [`Version`] of the [`Package`] and returns it
+    /// The [`Version`] and the SHA-256 hashes of every [`Module`] source and output file are recorded in the manifest,
+    /// the working directory is committed
+    /// and the commit is tagged with the full [`Version`], including pre-release identifiers and build metadata
+    /// See [`Package::plan_publish`] to find out what would be published without publishing it
+    ///
+    /// # Arguments
+    /// * `increment` - The [`SemVerIncrement`] deriving the next [`Version`] from the current one, `0.0.0` when there is none
+    /// * `build` - The build metadata identifiers to attach, e.g. a build number or commit hash
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_publish");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    ///
+    /// let version = package.publish(SemVerIncrement::PreMinor("alpha".to_string()), vec!["build".to_string(), "7".to_string()]);
+    /// assert_eq!(version.to_string(), "0.1.0-alpha.1+build.7");
+    /// assert!(package.git_metadata().tags().contains(&"0.1.0-alpha.1+build.7".to_string()));
+    ///
+    /// let version = package.publish(SemVerIncrement::Release, vec![]);
+    /// assert_eq!(version.to_string(), "0.1.0");
+    /// assert_eq!(package.version(), Some(version));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the manifest is not valid, see [`Package::validate_manifest`],
+    /// when the [`Version`] was already published, when the [`HookKind::PrePublish`] hook fails,
+    /// when a stray dependency that needs to be vendored does not exist, see [`Package::set_vendor_strays`],
+    /// or when unmanaged files have uncommitted changes and the [`DirtyPolicy`] is [`DirtyPolicy::Fail`]
+    pub fn publish(&self, increment: SemVerIncrement, build: Vec<String>) -> Version {
+        self.release(increment, build, None, false).version
+    }
+
+    /// Publishes the next [`Version`] of the [`Package`] like [`Package::publish`], adding its [`ReleaseNotes`] to `CHANGELOG.md`
+    /// The notes list the summaries of the commits made since the previous [`Version`] was published,
+    /// the newest section is placed at the top of the changelog, below its title
+    ///
+    /// # Arguments
+    /// * `increment` - The [`SemVerIncrement`] deriving the next [`Version`] from the current one, `0.0.0` when there is none
+    /// * `build` - The build metadata identifiers to attach, e.g. a build number or commit hash
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use std::path::Path;
+    /// # use git2::{Repository, Signature};
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_changelog");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// let repository = Repository::init(&path).unwrap();
+    /// let package = Package::create(&path);
+    /// fs::write(path.join("a.sac"), "").unwrap();
+    /// let mut index = repository.index().unwrap();
+    /// index.add_path(Path::new("a.sac")).unwrap();
+    /// let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+    /// let author = Signature::now("author", "author@example.com").unwrap();
+    /// repository.commit(Some("HEAD"), &author, &author, "Add module a", &tree, &[]).unwrap();
+    ///
+    /// let notes = package.publish_with_changelog(SemVerIncrement::Minor, vec![]);
+    /// assert_eq!(notes.version().to_string(), "0.1.0");
+    /// assert_eq!(notes.changes(), &["Add module a".to_string()]);
+    ///
+    /// package.publish_with_changelog(SemVerIncrement::Patch, vec![]);
+    /// let changelog = fs::read_to_string(path.join("CHANGELOG.md")).unwrap();
+    /// assert_eq!(changelog, "# Changelog\n\n## 0.1.1\n\n## 0.1.0\n\n- Add module a\n\n");
+    /// ```
+    ///
+    /// # Panics
+    /// Panics like [`Package::publish`]
+    pub fn publish_with_changelog(&self, increment: SemVerIncrement, build: Vec<String>) -> ReleaseNotes {
+        self.release(increment, build, None, true)
+    }
+
+    /// Publishes the next [`Version`] of the [`Package`] like [`Package::publish`], signing the manifest before it is committed
+    /// The [`PackageSignature`] is stored next to the manifest, see [`Package::sign`]
+    ///
+    /// # Arguments
+    /// * `increment` - The [`SemVerIncrement`] deriving the next [`Version`] from the current one, `0.0.0` when there is none
+    /// * `build` - The build metadata identifiers to attach, e.g. a build number or commit hash
+    /// * `signer` - The [`Signer`] signing the manifest
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::signing::{Ed25519Signer, TrustStore};
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_publish_signed");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// let signer = Ed25519Signer::create("release", [7; 32]);
+    /// package.publish_signed(SemVerIncrement::Minor, vec![], &signer);
+    ///
+    /// let mut trust_store = TrustStore::default();
+    /// trust_store.trust("release", &signer.public_key());
+    /// assert_eq!(package.verify_signature(&trust_store), Ok(()));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the manifest is not valid, see [`Package::validate_manifest`],
+    /// when the [`Version`] was already published, when the [`HookKind::PrePublish`] hook fails, when the [`Signer`] fails
+    /// or when unmanaged files have uncommitted changes and the [`DirtyPolicy`] is [`DirtyPolicy::Fail`]
+    pub fn publish_signed<S: Signer>(&self, increment: SemVerIncrement, build: Vec<String>, signer: &S) -> Version {
+        self.release(increment, build, Some(signer), false).version
+    }
+
+    fn release(&self, increment: SemVerIncrement, build: Vec<String>, signer: Option<&dyn Signer>, changelog: bool) -> ReleaseNotes {
+        self.validate_manifest().unwrap_or_else(|e| panic!("{}", e));
+        let plan = self.plan_publish(increment, build);
+        let mut manifest = self.load_manifest();
+        if manifest.dirty_policy == DirtyPolicy::Fail && !plan.unmanaged.is_empty() {
+            let files: Vec<String> = plan.unmanaged.iter().map(|f| f.display().to_string()).collect();
+            panic!("{} has uncommitted changes to unmanaged files: {}", self.local_location.display(), files.join(", "));
+        }
+        let outputs: Vec<PathBuf> = manifest.modules.iter()
+            .filter_map(|m| m.output.as_ref().map(|o| self.local_location.join(o)))
+            .collect::<BTreeSet<PathBuf>>()
+            .into_iter()
+            .collect();
+        self.run_hook(HookKind::PrePublish, &outputs).unwrap_or_else(|e| panic!("{}", e));
+        if manifest.vendor_strays {
+            self.vendor_strays(&mut manifest);
+        }
+        manifest.version = Some(plan.version.clone());
+        manifest.prebuilt.clear();
+        let prebuilt = match manifest.artifact_mode {
+            Some(_) => self.prebuilt_outputs(&manifest),
+            None => vec![],
+        };
+        for (location, _) in &prebuilt {
+            let tag = manifest.get_module_by_location(location).and_then(|m| m.artifact_tag.clone()).unwrap();
+            manifest.prebuilt.insert(location.clone(), tag);
+        }
+        manifest.hashes = self.hash_files(&manifest);
+        manifest.save(self.manifest_location());
+        if let Some(signer) = signer {
+            self.sign(signer);
+        }
+
+        let notes = plan.release_notes();
+        if changelog {
+            let location = self.local_location.join("CHANGELOG.md");
+            let existing = read_to_string(&location).unwrap_or_default();
+            let rest = existing.strip_prefix("# Changelog\n\n").unwrap_or(&existing);
+            write(&location, format!("# Changelog\n\n{}{}", notes.to_markdown(), rest)).unwrap();
+        }
+
+        self.commit_and_tag(&format!("Publish version {}", plan.tag), &plan.tag);
+        if let Some(mode) = manifest.artifact_mode {
+            self.store_artifacts(mode, &plan.tag, &prebuilt);
+        }
+        notes
+    }
+
+    /// Returns the summaries of the commits reachable from the checked out commit
+    /// but not from the previous published [`Version`], oldest first
+    fn changes_since_release(&self, repository: &Repository) -> Vec<String> {
+        let Some(head) = repository.head().ok().and_then(|h| h.peel_to_commit().ok()) else { return vec![] };
+        let mut walk = repository.revwalk().unwrap();
+        walk.push(head.id()).unwrap();
+        let previous = self.tagged_versions(repository)
+            .into_iter()
+            .filter(|(_, target)| *target == head.id() || repository.graph_descendant_of(head.id(), *target).unwrap_or(false))
+            .max_by(|a, b| a.0.cmp(&b.0));
+        if let Some((_, target)) = previous {
+            walk.hide(target).unwrap();
+        }
+        let mut changes: Vec<String> = walk
+            .filter_map(|oid| repository.find_commit(oid.ok()?).ok())
+            .filter_map(|commit| commit.summary().map(String::from))
+            .collect();
+        changes.reverse();
+        changes
+    }
+
+    /// Sets what publishing the [`Package`] does when files it does not manage have uncommitted changes, see [`DirtyPolicy`]
+    ///
+    /// # Arguments
+    /// * `policy` - The [`DirtyPolicy`] to use from now on
+    ///
+    /// # Examples
+    /// ```rust, should_panic
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::publish::DirtyPolicy;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_dirty_policy");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// package.set_dirty_policy(DirtyPolicy::Fail);
+    /// fs::write(path.join("notes.txt"), "").unwrap();
+    /// package.publish(SemVerIncrement::Minor, vec![]);
+    /// ```
+    pub fn set_dirty_policy(&self, policy: DirtyPolicy) {
+        let mut manifest = self.load_manifest();
+        manifest.dirty_policy = policy;
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns what publishing the [`Package`] does when files it does not manage have uncommitted changes
+    pub fn dirty_policy(&self) -> DirtyPolicy {
+        self.load_manifest().dirty_policy
+    }
+
+    /// Sets whether publishing the [`Package`] embeds its stray dependencies, see [`Package::add_stray`]
+    /// Each stray output directory is copied to its own directory inside [`VENDORED_DIRECTORY`] and recorded in the manifest,
+    /// so [`Package`]s depending on it can build against the copy when the stray directory does not exist on their machine
+    ///
+    /// # Arguments
+    /// * `vendor` - Whether stray dependencies are vendored from now on
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::{Package, VENDORED_DIRECTORY};
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_vendor_strays");
+    /// let stray = env::temp_dir().join("mock_stray_vendor_strays/out");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// # let _ = fs::remove_dir_all(&stray);
+    /// Repository::init(&path);
+    /// fs::create_dir_all(&stray).unwrap();
+    /// fs::write(stray.join("json.o"), "").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_stray(&stray);
+    /// package.set_vendor_strays(true);
+    /// package.publish(SemVerIncrement::Minor, vec![]);
+    ///
+    /// let vendored = path.join(VENDORED_DIRECTORY).join("out");
+    /// assert!(vendored.join("json.o").is_file());
+    /// assert_eq!(package.vendored().get(&stray), Some(&vendored));
+    ///
+    /// fs::remove_dir_all(&stray).unwrap();
+    /// assert_eq!(package.resolved_strays(), vec![vendored]);
+    /// ```
+    pub fn set_vendor_strays(&self, vendor: bool) {
+        let mut manifest = self.load_manifest();
+        manifest.vendor_strays = vendor;
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns whether publishing the [`Package`] embeds its stray dependencies, see [`Package::set_vendor_strays`]
+    pub fn vendors_strays(&self) -> bool {
+        self.load_manifest().vendor_strays
+    }
+
+    /// Returns the stray dependencies vendored when the [`Package`] was last published,
+    /// mapping each stray output directory to the absolute directory holding its copy
+    pub fn vendored(&self) -> BTreeMap<PathBuf, PathBuf> {
+        self.load_manifest().vendored
+            .into_iter()
+            .map(|(stray, copy)| (stray, self.local_location.join(copy)))
+            .collect()
+    }
+
+    /// Returns the output directories of the stray dependencies of the [`Package`],
+    /// the vendored copy in place of every stray directory that does not exist, see [`Package::set_vendor_strays`]
+    pub fn resolved_strays(&self) -> Vec<PathBuf> {
+        let manifest = self.load_manifest();
+        manifest.strays
+            .into_iter()
+            .map(|stray| match manifest.vendored.get(&stray) {
+                Some(copy) if !stray.is_dir() && self.local_location.join(copy).is_dir() => self.local_location.join(copy),
+                _ => stray,
+            })
+            .collect()
+    }
+
+    /// Sets where publishing the [`Package`] stores the prebuilt outputs of its [`Module`]s, see [`ArtifactMode`]
+    /// Only [`Module`]s with an ABI tag and an existing output directory inside the [`Package`] are published prebuilt,
+    /// see [`Registry::set_artifact_tag`]
+    ///
+    /// # Arguments
+    /// * `mode` - The [`ArtifactMode`] to use from now on, or `None` to publish sources only
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::artifacts::ArtifactMode;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_artifact_mode.json"));
+    /// let path = env::temp_dir().join("mock_package_artifact_mode");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// let repository = Repository::init(&path).unwrap();
+    /// fs::write(path.join("a.sac"), "module a;").unwrap();
+    /// fs::create_dir_all(path.join("out")).unwrap();
+    /// fs::write(path.join("out/a.so"), "binary").unwrap();
+    /// let package = Package::create(&path);
+    /// let mut module = Module::create("a.sac", None);
+    /// module.set_output(Some("out"));
+    /// package.add_module(module);
+    /// registry.add(package.clone());
+    /// registry.set_artifact_tag(path.join("a.sac"), Some("sac2c-1.3.3-x86_64-linux".to_string()));
+    ///
+    /// package.set_artifact_mode(Some(ArtifactMode::Tag));
+    /// package.publish(SemVerIncrement::Minor, vec![]);
+    /// assert_eq!(package.prebuilt().get(std::path::Path::new("a.sac")).map(String::as_str), Some("sac2c-1.3.3-x86_64-linux"));
+    /// assert!(repository.find_reference("refs/tags/artifacts/0.1.0").is_ok());
+    /// ```
+    ///
+    /// [`Registry::set_artifact_tag`]: crate::registry::Registry::set_artifact_tag
+    pub fn set_artifact_mode(&self, mode: Option<ArtifactMode>) {
+        let mut manifest = self.load_manifest();
+        manifest.artifact_mode = mode;
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns where publishing the [`Package`] stores the prebuilt outputs of its [`Module`]s, see [`Package::set_artifact_mode`]
+    pub fn artifact_mode(&self) -> Option<ArtifactMode> {
+        self.load_manifest().artifact_mode
+    }
+
+    /// Returns the [`Module`]s published with prebuilt artifacts, mapping their locations to the ABI tags of the artifacts
+    pub fn prebuilt(&self) -> BTreeMap<PathBuf, String> {
+        self.load_manifest().prebuilt
+    }
+
+    /// The locations of the [`Module`]s that can be published prebuilt, with their absolute output directories
+    fn prebuilt_outputs(&self, manifest: &Manifest) -> Vec<(PathBuf, PathBuf)> {
+        let mut outputs: Vec<(PathBuf, PathBuf)> = manifest.modules.iter()
+            .filter(|m| m.artifact_tag.is_some())
+            .filter_map(|m| Some((m.location.clone(), self.local_location.join(m.output.as_ref()?))))
+            .filter(|(_, output)| output.is_dir())
+            .collect();
+        outputs.sort();
+        outputs
+    }
+
+    /// Commits the given outputs to the git reference of the [`ArtifactMode`], nothing is committed for [`ArtifactMode::Archive`]
+    fn store_artifacts(&self, mode: ArtifactMode, tag: &str, prebuilt: &[(PathBuf, PathBuf)]) {
+        let Some((reference, directory)) = mode.reference(tag) else { return };
+        if prebuilt.is_empty() {
+            return;
+        }
+        let repository = discover_git_repository(&self.local_location);
+        let parent = repository.find_reference(&reference).ok().and_then(|r| r.peel_to_commit().ok());
+        let mut index = Index::new().unwrap();
+        if let Some(parent) = &parent {
+            index.read_tree(&parent.tree().unwrap()).unwrap();
+        }
+        for (location, output) in prebuilt {
+            for file in list_files(output) {
+                let contents = read(output.join(&file)).unwrap();
+                let path = directory.join(location).join(&file);
+                index.add(&IndexEntry {
+                    ctime: IndexTime::new(0, 0),
+                    mtime: IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode: if is_executable(output.join(&file)) { 0o100755 } else { 0o100644 },
+                    uid: 0,
+                    gid: 0,
+                    file_size: contents.len() as u32,
+                    id: repository.blob(&contents).unwrap(),
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path.to_string_lossy().replace('\\', "/").into_bytes(),
+                }).unwrap();
+            }
+        }
+        let tree = repository.find_tree(index.write_tree_to(&repository).unwrap()).unwrap();
+        let signature = repository.signature()
+            .or_else(|_| Signature::now("KnapSaC", "knapsac@localhost"))
+            .unwrap();
+        let parents: Vec<&Commit> = parent.iter().collect();
+        let message = format!("Prebuilt artifacts of version {}", tag);
+        repository.commit(Some(&reference), &signature, &signature, &message, &tree, &parents).unwrap();
+        git_cache::invalidate(&self.local_location);
+    }
+
+    /// Reads the prebuilt artifacts of the [`Module`] at the given location published with the checked out [`Version`],
+    /// relative to its output directory
+    /// When they are stored in git but not fetched yet, they are fetched from `origin` if `fetch` is set
+    pub(crate) fn prebuilt_files(&self, location: &Path, fetch: bool) -> Option<Vec<(PathBuf, Vec<u8>)>> {
+        let manifest = self.load_manifest();
+        let mode = manifest.artifact_mode?;
+        let tag = manifest.version?.to_string();
+        let Some((reference, directory)) = mode.reference(&tag) else {
+            let directory = self.local_location.join(ARTIFACTS_DIRECTORY).join(location);
+            if !directory.is_dir() {
+                return None;
+            }
+            return Some(list_files(&directory).into_iter().map(|f| (f.clone(), read(directory.join(f)).unwrap())).collect());
+        };
+        let repository = Repository::open(&self.local_location).ok()?;
+        let tracking = reference.replace("refs/heads/", "refs/remotes/origin/");
+        let find = || [&reference, &tracking].iter().find_map(|r| repository.find_reference(r).ok()?.peel_to_tree().ok());
+        let tree = match find() {
+            Some(tree) => tree,
+            None if fetch => {
+                let mut remote = repository.find_remote("origin").ok()?;
+                remote.fetch(&[format!("+{}:{}", reference, tracking)], None, None).ok()?;
+                find()?
+            }
+            None => return None,
+        };
+        let subtree = repository.find_tree(tree.get_path(&directory.join(location)).ok()?.id()).ok()?;
+        let mut files = vec![];
+        subtree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            if let Some(blob) = entry.to_object(&repository).ok().and_then(|o| o.into_blob().ok()) {
+                files.push((PathBuf::from(root).join(entry.name().unwrap_or_default()), blob.content().to_vec()));
+            }
+            TreeWalkResult::Ok
+        }).ok()?;
+        Some(files)
+    }
+
+    /// Replaces the vendored copies of the stray dependencies in [`VENDORED_DIRECTORY`] with fresh ones
+    fn vendor_strays(&self, manifest: &mut Manifest) {
+        let directory = self.local_location.join(VENDORED_DIRECTORY);
+        if directory.is_dir() {
+            remove_dir_all(&directory).unwrap();
+        }
+        manifest.vendored.clear();
+        for stray in &manifest.strays {
+            if !stray.is_dir() {
+                panic!("Stray dependency {} does not exist and cannot be vendored", stray.display());
+            }
+            let name = stray.file_name().map_or("stray".into(), |n| n.to_string_lossy().to_string());
+            let mut copy = PathBuf::from(VENDORED_DIRECTORY).join(&name);
+            let mut suffix = 1;
+            while manifest.vendored.values().any(|c| *c == copy) {
+                suffix += 1;
+                copy = PathBuf::from(VENDORED_DIRECTORY).join(format!("{}-{}", name, suffix));
+            }
+            create_dir_all(self.local_location.join(&copy)).unwrap();
+            copy_directory(stray, self.local_location.join(&copy));
+            manifest.vendored.insert(stray.clone(), copy);
+        }
+    }
+
+    /// Computes the changes [`Package::publish`] would make, without touching the manifest or the git repository
+    /// [`Package::publish_signed`] additionally commits the [`PackageSignature`] stored next to the manifest
+    ///
+    /// # Arguments
+    /// * `increment` - The [`SemVerIncrement`] deriving the next [`Version`] from the current one, `0.0.0` when there is none
+    /// * `build` - The build metadata identifiers to attach, e.g. a build number or commit hash
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use std::path::PathBuf;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_plan_publish");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("a.sac"), "module a;").unwrap();
+    /// let package = Package::create(&path);
+    ///
+    /// let plan = package.plan_publish(SemVerIncrement::Minor, vec![]);
+    /// assert_eq!(plan.tag(), "0.1.0");
+    /// assert_eq!(plan.files(), &[PathBuf::from("a.sac"), PathBuf::from("manifest.json")]);
+    /// assert_eq!(plan.unmanaged_changes(), &[PathBuf::from("a.sac")]);
+    /// assert_eq!(package.version(), None);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the [`Version`] was already published
+    pub fn plan_publish(&self, increment: SemVerIncrement, build: Vec<String>) -> PublishPlan {
+        let manifest = self.load_manifest();
+        let current = manifest.version.clone().or_else(|| self.version()).unwrap_or_else(|| Version::new(0, 0, 0));
+        let mut version = current.increment(&increment);
+        version.build = build;
+
+        let tag = version.to_string();
+        if self.git_metadata().tags.contains(&tag) {
+            panic!("Version {} of {} was already published", tag, self.local_location.display());
+        }
+
+        let repository = discover_git_repository(&self.local_location);
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let mut files: Vec<PathBuf> = repository.statuses(Some(&mut options)).unwrap()
+            .iter()
+            .filter_map(|s| s.path().map(PathBuf::from))
+            .collect();
+        let managed = self.is_managed(&manifest);
+        let mut unmanaged: Vec<PathBuf> = files.iter().filter(|f| !managed(f)).cloned().collect();
+        unmanaged.sort();
+        let changes = self.changes_since_release(&repository);
+        files.push(self.strip_prefix(self.manifest_location()));
+        files.sort();
+        files.dedup();
+        PublishPlan { version, tag, files, unmanaged, changes }
+    }
+
+    /// Packs the [`Package`] into a gzipped tarball and publishes it through the given [`RegistryBackend`]
+    /// The archive contains every file outside the `.git` directory and is published under the [`Package`]'s identifier
+    /// and [`Version`], see [`Package::publish`]
+    /// The manifest published next to the archive leaves out [`DependencyKind::Dev`] [`Dependency`]s,
+    /// they are only needed to test the [`Package`]
+    /// Returns the checksum installers need to verify the unpacked archive against, see [`Package::checksum`]
+    ///
+    /// # Arguments
+    /// * `backend` - The [`RegistryBackend`] to publish to
+    pub fn upload<B: RegistryBackend>(&self, backend: &B) -> Result<String, Error> {
+        let plan = self.plan_upload()?;
+        let mut manifest = Manifest::try_load(self.manifest_location()).map_err(Error::from)?;
+        manifest.dependencies.retain(|d| d.kind != DependencyKind::Dev);
+        let format = Format::from_path(self.manifest_location()).unwrap_or(Format::Json);
+        let manifest = format.serialize(&manifest, Style::default());
+        let archive = pack_directory(&self.local_location, plan.files);
+        backend.upload(&plan.identifier, &plan.version, &archive, &manifest).map_err(Error::Backend)?;
+        Ok(plan.checksum)
+    }
+
+    /// Computes what [`Package::upload`] would publish, without packing or uploading anything
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use std::path::PathBuf;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_plan_upload");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// assert!(package.plan_upload().is_err());
+    ///
+    /// package.publish(SemVerIncrement::Major, vec![]);
+    /// let plan = package.plan_upload().unwrap();
+    /// assert_eq!(plan.identifier(), "mock_package_plan_upload");
+    /// assert_eq!(plan.version().to_string(), "1.0.0");
+    /// assert_eq!(plan.files(), &[PathBuf::from("manifest.json")]);
+    /// assert_eq!(plan.checksum(), Package::checksum(&path));
+    /// ```
+    pub fn plan_upload(&self) -> Result<UploadPlan, Error> {
+        let version = Manifest::try_load(self.manifest_location()).map_err(Error::from)?.version.or_else(|| self.version())
+            .ok_or_else(|| Error::Unpublished(self.local_location.clone()))?;
+        Ok(UploadPlan {
+            identifier: self.identifier(),
+            version,
+            files: list_files(&self.local_location),
+            checksum: Package::checksum(&self.local_location),
+        })
+    }
+
+    /// Pushes a branch of the [`Package`], and optionally its tags, to a git remote
+    /// Returns the refspecs that were pushed
+    ///
+    /// # Arguments
+    /// * `options` - The [`PushOptions`] selecting the remote, branch and whether tags are pushed
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use url::Url;
+    /// # use knapsac_lib::publish::PushOptions;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let remote = env::temp_dir().join("mock_remote_push.git");
+    /// # let _ = fs::remove_dir_all(&remote);
+    /// Repository::init_bare(&remote).unwrap();
+    /// let path = env::temp_dir().join("mock_package_push");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// let repository = Repository::init(&path).unwrap();
+    /// repository.remote("origin", remote.to_str().unwrap()).unwrap();
+    /// let package = Package::create(&path);
+    /// package.publish(SemVerIncrement::Major, vec![]);
+    ///
+    /// let mut options = PushOptions::default();
+    /// options.set_branch(Some("release")).set_tags(true);
+    /// # repository.branch("release", &repository.head().unwrap().peel_to_commit().unwrap(), false).unwrap();
+    /// let pushed = package.push(&options).unwrap();
+    /// assert_eq!(pushed, vec!["refs/heads/release:refs/heads/release", "refs/tags/1.0.0:refs/tags/1.0.0"]);
+    /// assert!(Repository::open_bare(&remote).unwrap().find_reference("refs/tags/1.0.0").is_ok());
+    ///
+    /// let mirror = env::temp_dir().join("mock_mirror_push.git");
+    /// # let _ = fs::remove_dir_all(&mirror);
+    /// Repository::init_bare(&mirror).unwrap();
+    /// package.add_mirror(Url::from_directory_path(&mirror).unwrap());
+    /// # package.publish(SemVerIncrement::Minor, vec![]);
+    /// package.push(options.set_branch(None).set_mirrors(true)).unwrap();
+    /// assert!(Repository::open_bare(&mirror).unwrap().find_reference("refs/tags/1.1.0").is_ok());
+    /// ```
+    pub fn push(&self, options: &PushOptions) -> Result<Vec<String>, Error> {
+        let git_error = |reason: String| Error::Git(self.local_location.clone(), reason);
+        let repository = discover_git_repository(&self.local_location);
+        let branch = match &options.branch {
+            Some(branch) => branch.clone(),
+            None => repository.head().ok()
+                .filter(|h| h.is_branch())
+                .and_then(|h| h.shorthand().map(String::from))
+                .ok_or_else(|| git_error("no branch checked out".to_string()))?,
+        };
+        if repository.find_branch(&branch, BranchType::Local).is_err() {
+            return Err(git_error(format!("branch {} not found", branch)));
+        }
+        let mut refspecs = vec![format!("refs/heads/{0}:refs/heads/{0}", branch)];
+        if options.tags {
+            let tags = repository.tag_names(None).map_err(|e| git_error(e.message().to_string()))?;
+            refspecs.extend(tags.iter().flatten().map(|t| format!("refs/tags/{0}:refs/tags/{0}", t)));
+            if repository.find_branch(ARTIFACTS_BRANCH, BranchType::Local).is_ok() {
+                refspecs.push(format!("refs/heads/{0}:refs/heads/{0}", ARTIFACTS_BRANCH));
+            }
+        }
+        let mut remote = repository.find_remote(&options.remote).map_err(|e| git_error(e.message().to_string()))?;
+        remote.push(&refspecs, None).map_err(|e| git_error(e.message().to_string()))?;
+        if options.mirrors {
+            let mut failed = vec![];
+            for mirror in self.mirrors() {
+                let pushed = repository.remote_anonymous(mirror.as_str())
+                    .and_then(|mut r| r.push(&refspecs, None));
+                if let Err(e) = pushed {
+                    failed.push(format!("{}: {}", mirror, e.message()));
+                }
+            }
+            if !failed.is_empty() {
+                return Err(git_error(format!("failed to push to mirrors: {}", failed.join(", "))));
+            }
+        }
+        Ok(refspecs)
+    }
+
+    /// Records a mirror of the [`Package`], an alternative remote location holding the same git repository
+    /// Mirrors are tried in the order they were added, see [`MirrorFetcher`] and [`PushOptions::set_mirrors`]
+    /// Adding a mirror that was added before does nothing
+    ///
+    /// # Arguments
+    /// * `url` - An [`Url`] pointing to the mirror
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_add_mirror");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// let repository = Repository::init(&path).unwrap();
+    /// repository.remote("origin", "https://github.com/jcuppen/JSON").unwrap();
+    /// let package = Package::create(&path);
+    ///
+    /// let mirror = Url::parse("https://gitlab.com/jcuppen/JSON").unwrap();
+    /// package.add_mirror(mirror.clone());
+    /// assert_eq!(package.mirrors(), vec![mirror.clone()]);
+    /// assert_eq!(package.remotes(), vec![Url::parse("https://github.com/jcuppen/JSON").unwrap(), mirror.clone()]);
+    ///
+    /// package.remove_mirror(&mirror);
+    /// assert!(package.mirrors().is_empty());
+    /// ```
+    ///
+    /// [`MirrorFetcher`]: crate::fetcher::MirrorFetcher
+    pub fn add_mirror(&self, url: Url) {
+        let mut manifest = self.load_manifest();
+        if !manifest.mirrors.contains(&url) {
+            manifest.mirrors.push(url);
+            manifest.save(self.manifest_location());
+        }
+    }
+
+    /// Removes a mirror of the [`Package`], see [`Package::add_mirror`]
+    ///
+    /// # Arguments
+    /// * `url` - An [`Url`] pointing to the mirror
+    pub fn remove_mirror(&self, url: &Url) {
+        let mut manifest = self.load_manifest();
+        manifest.mirrors.retain(|m| m != url);
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the mirrors of the [`Package`], in the order they are tried
+    pub fn mirrors(&self) -> Vec<Url> {
+        self.load_manifest().mirrors
+    }
+
+    /// Returns every remote location of the [`Package`], its primary remote location first followed by its mirrors
+    pub fn remotes(&self) -> Vec<Url> {
+        let mut remotes: Vec<Url> = self.remote_location.iter().cloned().collect();
+        remotes.extend(self.mirrors().into_iter().filter(|m| Some(m) != self.remote_location.as_ref()));
+        remotes
+    }
+
+    /// Signs the manifest of the [`Package`] and stores the [`PackageSignature`] next to it, as `<manifest>.sig`
+    /// Changing the manifest afterwards, e.g. by yanking a [`Version`], invalidates the signature until it is signed again
+    ///
+    /// # Arguments
+    /// * `signer` - The [`Signer`] signing the manifest
+    ///
+    /// # Panics
+    /// Panics when the [`Signer`] fails
+    pub fn sign(&self, signer: &dyn Signer) {
+        let manifest = read(self.manifest_location()).unwrap();
+        let signature = signer.sign(&manifest)
+            .unwrap_or_else(|e| panic!("Failed to sign manifest of {}: {}", self.local_location.display(), e));
+        let signature = PackageSignature {
+            key_id: signer.key_id().to_string(),
+            signature: to_hex(&signature),
+        };
+        write(self.signature_location(), serde_json::to_string(&signature).unwrap()).unwrap();
+    }
+
+    /// Returns the [`PackageSignature`] stored next to the manifest, if the [`Package`] is signed
+    pub fn signature(&self) -> Option<PackageSignature> {
+        serde_json::from_str(&read_to_string(self.signature_location()).ok()?).ok()
+    }
+
+    /// Verifies the [`PackageSignature`] over the manifest of the [`Package`] against the given [`TrustStore`]
+    ///
+    /// # Arguments
+    /// * `trust_store` - The [`TrustStore`] holding the keys the [`Package`] may be signed with
+    pub fn verify_signature(&self, trust_store: &TrustStore) -> Result<(), SignatureError> {
+        let manifest = read(self.manifest_location()).map_err(|_| SignatureError::Invalid)?;
+        trust_store.verify(&manifest, self.signature().as_ref())
+    }
+
+    fn signature_location(&self) -> PathBuf {
+        let mut location = self.manifest_location().into_os_string();
+        location.push(".sig");
+        PathBuf::from(location)
+    }
+
+    /// Checks that the [`Package`]'s manifest parses and contains no [`SchemaViolation`]s
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::error::ManifestError;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_validate_manifest");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// assert_eq!(package.validate_manifest(), Ok(()));
+    ///
+    /// let manifest = fs::read_to_string(path.join("manifest.json")).unwrap();
+    /// fs::write(path.join("manifest.json"), manifest.replace("\"version\": null", "\"version\": \"1.x\"")).unwrap();
+    /// let error = package.validate_manifest().unwrap_err();
+    /// assert!(matches!(&error, ManifestError::InvalidVersion(_, _, _, version) if version == "1.x"));
+    /// assert_eq!(error.position().map(|(line, _)| line), Some(14));
+    ///
+    /// fs::write(path.join("manifest.json"), "{\n  \"modules\": []\n}").unwrap();
+    /// let error = package.validate_manifest().unwrap_err();
+    /// assert!(matches!(&error, ManifestError::MissingField(_, 3, 1, field) if field == "dependencies"));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the [`ManifestError`] explaining what is wrong with the manifest
+    ///
+    /// [`SchemaViolation`]: crate::sync::SchemaViolation
+    pub fn validate_manifest(&self) -> Result<(), ManifestError> {
+        Manifest::try_load_valid(self.manifest_location()).map(|_| ())
+    }
+
+    /// Returns the SHA-256 hashes of the [`Module`] source and output files recorded when the [`Package`] was published
+    /// Paths are relative to the [`Package`]'s `local_location`
+    pub fn file_hashes(&self) -> BTreeMap<PathBuf, String> {
+        self.load_manifest().hashes
+    }
+
+    /// Re-hashes the files whose hashes were recorded when the [`Package`] was published, see [`Package::file_hashes`]
+    /// Returns an [`IntegrityIssue`] for every file that was changed or removed since
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::integrity::IntegrityIssue;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_verify_integrity");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("a.sac"), "module a;").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// package.publish(SemVerIncrement::Minor, vec![]);
+    /// assert!(package.verify_integrity().is_empty());
+    ///
+    /// fs::write(path.join("a.sac"), "module b;").unwrap();
+    /// assert_eq!(package.verify_integrity(), vec![IntegrityIssue::Modified(path.join("a.sac"))]);
+    /// ```
+    pub fn verify_integrity(&self) -> Vec<IntegrityIssue> {
+        self.file_hashes()
+            .into_iter()
+            .filter_map(|(file, expected)| {
+                let path = self.local_location.join(file);
+                match read(&path) {
+                    Err(_) => Some(IntegrityIssue::Missing(path)),
+                    Ok(contents) if sha256_hex(&contents) != expected => Some(IntegrityIssue::Modified(path)),
+                    Ok(_) => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Hashes the source file of every [`Module`] and every file in the output directories inside the [`Package`], of every profile
+    /// Relative output directories are resolved against the directory of the [`Module`]'s source file
+    /// Returns a predicate checking whether a file, relative to the [`Package`], is managed by it, see [`DirtyPolicy`]
+    fn is_managed(&self, manifest: &Manifest) -> impl Fn(&Path) -> bool {
+        let mut files: BTreeSet<PathBuf> = BTreeSet::new();
+        let mut directories: Vec<PathBuf> = vec![];
+        files.insert(self.strip_prefix(self.manifest_location()));
+        files.insert(self.strip_prefix(self.signature_location()));
+        for module in &manifest.modules {
+            files.extend(module.source_files(&self.local_location));
+            for output in module.all_outputs() {
+                let directory = match module.location.parent() {
+                    Some(parent) if output.is_relative() => self.local_location.join(parent).join(output),
+                    _ => self.local_location.join(output),
+                };
+                if directory.starts_with(&self.local_location) {
+                    directories.push(self.strip_prefix(directory));
+                }
+            }
+        }
+        for executable in &manifest.executables {
+            files.insert(executable.location.clone());
+            files.insert(executable.binary_path());
+        }
+        for test in &manifest.tests {
+            files.insert(test.location.clone());
+            files.insert(Executable::create(&test.location).binary_path());
+        }
+        move |file: &Path| files.contains(file) || directories.iter().any(|d| file.starts_with(d))
+    }
+
+    fn hash_files(&self, manifest: &Manifest) -> BTreeMap<PathBuf, String> {
+        let mut hashes = BTreeMap::new();
+        for module in &manifest.modules {
+            let mut files = vec![module.location.clone()];
+            for output in module.all_outputs() {
+                let directory = match module.location.parent() {
+                    Some(parent) if output.is_relative() => self.local_location.join(parent).join(output),
+                    _ => self.local_location.join(output),
+                };
+                if directory.starts_with(&self.local_location) && directory.is_dir() {
+                    let relative = directory.strip_prefix(&self.local_location).unwrap().to_path_buf();
+                    files.extend(list_files(&directory).into_iter().map(|f| relative.join(f)));
+                }
+            }
+            for file in files {
+                if let Ok(contents) = read(self.local_location.join(&file)) {
+                    hashes.insert(file, sha256_hex(contents));
+                }
+            }
+        }
+        hashes
+    }
+
+    /// Yanks a published [`Version`] of the [`Package`] so it is no longer installed as a dependency
+    /// The yank is recorded in the manifest, the working directory is committed and tagged with `yanked/<version>`
+    ///
+    /// # Arguments
+    /// * `version` - The published [`Version`] to yank
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_yank");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// let version = package.publish(SemVerIncrement::Minor, vec![]);
+    ///
+    /// package.yank(&version);
+    /// assert!(package.is_yanked(&version));
+    /// assert!(package.git_metadata().tags().contains(&"yanked/0.1.0".to_string()));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the [`Version`] was not published or is already yanked
+    pub fn yank(&self, version: &Version) {
+        self.ensure_published(version);
+        if self.is_yanked(version) {
+            panic!("Version {} of {} was already yanked", version, self.local_location.display());
+        }
+        let mut manifest = self.load_manifest();
+        manifest.yanked.insert(version.clone());
+        manifest.save(self.manifest_location());
+        self.commit_and_tag(&format!("Yank version {}", version), &format!("yanked/{}", version));
+    }
+
+    /// Deprecates a published [`Version`] of the [`Package`], installing it reports the given message as a warning
+    /// The deprecation is recorded in the manifest, the working directory is committed and tagged with `deprecated/<version>`
+    ///
+    /// # Arguments
+    /// * `version` - The published [`Version`] to deprecate
+    /// * `message` - Explains why the [`Version`] is deprecated, e.g. what to use instead
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_deprecate");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// let version = package.publish(SemVerIncrement::Minor, vec![]);
+    ///
+    /// package.deprecate(&version, "use 0.2.0 instead");
+    /// assert_eq!(package.deprecation(&version), Some("use 0.2.0 instead".to_string()));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the [`Version`] was not published or is already deprecated
+    pub fn deprecate<S: Into<String>>(&self, version: &Version, message: S) {
+        self.ensure_published(version);
+        let tag = format!("deprecated/{}", version);
+        if self.git_metadata().tags.contains(&tag) {
+            panic!("Version {} of {} was already deprecated", version, self.local_location.display());
+        }
+        let mut manifest = self.load_manifest();
+        manifest.deprecated.insert(version.clone(), message.into());
+        manifest.save(self.manifest_location());
+        self.commit_and_tag(&format!("Deprecate version {}", version), &tag);
+    }
+
+    /// Checks whether the given [`Version`] of the [`Package`] was yanked, see [`Package::yank`]
+    pub fn is_yanked(&self, version: &Version) -> bool {
+        self.load_manifest().yanked.contains(version)
+            || self.git_metadata().tags.contains(&format!("yanked/{}", version))
+    }
+
+    /// Returns the message the given [`Version`] of the [`Package`] was deprecated with, if it was deprecated
+    /// See [`Package::deprecate`]
+    pub fn deprecation(&self, version: &Version) -> Option<String> {
+        self.load_manifest().deprecated.get(version).cloned()
+    }
+
+    fn ensure_published(&self, version: &Version) {
+        if !self.git_metadata().tags.contains(&version.to_string()) {
+            panic!("Version {} of {} was not published", version, self.local_location.display());
+        }
+    }
+
+    fn commit_and_tag(&self, message: &str, tag: &str) {
+        let repository = discover_git_repository(&self.local_location);
+        let mut index = repository.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = repository.signature()
+            .or_else(|_| Signature::now("KnapSaC", "knapsac@localhost"))
+            .unwrap();
+        let parent = repository.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&Commit> = parent.iter().collect();
+        let commit = repository.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap();
+        repository.tag_lightweight(tag, &repository.find_object(commit, None).unwrap(), false).unwrap();
+        git_cache::invalidate(&self.local_location);
+    }
+
+    /// Strips the [`Package`]'s `local_location` from the given [`Path`]
+    ///
+    /// # Arguments
+    /// * `path` - [`Path`] that needs to be stripped
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use std::path::{Path, PathBuf};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&package_path);
+    /// assert!(package_path.exists());
+    /// let package = Package::create(&package_path);
+    /// assert_eq!(package.strip_prefix(package_path.join("example.txt")), PathBuf::from("example.txt"))
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the given [`Path`] is not a prefix of the [`Package`]'s `local_location`
+    /// ```rust, should_panic
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&package_path);
+    /// assert!(package_path.exists());
+    /// let other_path = env::temp_dir().join("mock_package_registered").join("example.txt");
+    /// Package::create(&package_path).strip_prefix(&other_path);
+    /// ```
+    pub fn strip_prefix<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        strip_base(path, &self.local_location).unwrap()
+    }
+
+    /// Adds a [`Dependency`] to a [`Package`]
+    /// A [`Dependency`] on the same [`Url`] that was added before is replaced
+    ///
+    /// # Arguments
+    /// * `dependency` - A [`Dependency`] that needs to be added
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::dependency::Dependency;
+    ///
+    /// let url = Url::parse("https://github.com/jcuppen/JSON");
+    /// let path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&path);
+    /// # assert!(url.is_ok());
+    /// assert!(path.exists());
+    /// let package = Package::create(path);
+    /// let dependency = Dependency::create(url.unwrap());
+    /// package.add_dependency(dependency.clone());
+    /// assert!(package.has_dependency(&dependency));
+    /// ```
+    pub fn add_dependency(&self, dependency: Dependency) {
+        let mut manifest = self.load_manifest();
+        manifest.add_dependency(dependency);
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the [`Dependency`]s declared in the [`Package`]'s manifest
+    pub fn dependencies(&self) -> Vec<Dependency> {
+        self.load_manifest().dependencies.into_iter().collect()
+    }
+
+    /// Checks the [`Package`] if it depends on the given [`Dependency`]
+    ///
+    /// # Arguments
+    /// * `dependency` - A reference to a [`Dependency`] that needs to be checked
+    pub fn has_dependency(&self, dependency: &Dependency) -> bool {
+        let manifest = self.load_manifest();
+        manifest.has_dependency(dependency)
+    }
+
+    /// Removes a [`Dependency`] from a [`Package`]
+    ///
+    /// # Arguments
+    /// * `dependency` - A reference to a [`Dependency`] that needs to be removed
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let url = Url::parse("https://github.com/jcuppen/JSON");
+    /// let path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&path);
+    /// # assert!(url.is_ok());
+    /// assert!(path.exists());
+    /// let package = Package::create(path);
+    /// let dependency = Dependency::create(url.unwrap());
+    /// package.add_dependency(dependency.clone());
+    /// assert!(package.has_dependency(&dependency));
+    /// package.remove_dependency(&dependency);
+    /// assert!(!package.has_dependency(&dependency))
+    /// ```
+    pub fn remove_dependency(&self, dependency: &Dependency) {
+        let mut manifest = self.load_manifest();
+
+        manifest.remove_dependency(dependency);
+        manifest.save(self.manifest_location());
+    }
+
+    /// Removes a [`Dependency`] from a [`Package`], like [`Package::remove_dependency`]
+    /// Returns [`DependencyError::UnknownDependency`] when the [`Package`] does not declare the [`Dependency`]
+    ///
+    /// # Arguments
+    /// * `dependency` - A reference to a [`Dependency`] that needs to be removed
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::{Dependency, DependencyError};
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_try_remove_dependency");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// let url = Url::parse("https://github.com/jcuppen/JSON").unwrap();
+    /// let dependency = Dependency::create(url.clone());
+    ///
+    /// assert_eq!(package.try_remove_dependency(&dependency), Err(DependencyError::UnknownDependency(url)));
+    /// package.add_dependency(dependency.clone());
+    /// assert_eq!(package.try_remove_dependency(&dependency), Ok(()));
+    /// ```
+    pub fn try_remove_dependency(&self, dependency: &Dependency) -> Result<(), DependencyError> {
+        let mut manifest = self.load_manifest();
+        if !manifest.has_dependency(dependency) {
+            return Err(DependencyError::UnknownDependency(dependency.git_url.clone()));
+        }
+        manifest.remove_dependency(dependency);
+        manifest.save(self.manifest_location());
+        Ok(())
+    }
+
+    /// Adds a stray dependency to a [`Package`]
+    /// A stray dependency is the output directory of a library that is not provided by a registered [`Package`],
+    /// it is put on the runtime search path of the [`Package`]'s [`Executable`]s like the outputs of its [`Dependency`]s
+    ///
+    /// # Arguments
+    /// * `output` - An absolute [`Path`] pointing to an existing directory
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_stray");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    ///
+    /// let output = env::temp_dir().join("mock_stray_output");
+    /// fs::create_dir_all(&output).unwrap();
+    /// package.add_stray(&output);
+    /// assert_eq!(package.strays(), vec![output.clone()]);
+    ///
+    /// package.remove_stray(&output);
+    /// assert!(package.strays().is_empty());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when [`Package::try_add_stray`] returns a [`DependencyError`]
+    pub fn add_stray<P: AsRef<Path>>(&self, output: P) {
+        self.try_add_stray(output).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Adds a stray dependency to a [`Package`], see [`Package::add_stray`]
+    /// Returns [`DependencyError::StrayNotResolvable`] when the given [`Path`] is relative or does not point to an existing directory
+    ///
+    /// # Arguments
+    /// * `output` - An absolute [`Path`] pointing to an existing directory
+    pub fn try_add_stray<P: AsRef<Path>>(&self, output: P) -> Result<(), DependencyError> {
+        let output = output.as_ref();
+        if output.is_relative() || !output.is_dir() {
+            return Err(DependencyError::StrayNotResolvable(output.to_path_buf()));
+        }
+        let mut manifest = self.load_manifest();
+        manifest.strays.insert(output.to_path_buf());
+        manifest.save(self.manifest_location());
+        Ok(())
+    }
+
+    /// Returns the output directories of the stray dependencies of the [`Package`], see [`Package::add_stray`]
+    pub fn strays(&self) -> Vec<PathBuf> {
+        self.load_manifest().strays.into_iter().collect()
+    }
+
+    /// Removes a stray dependency from a [`Package`]
+    ///
+    /// # Arguments
+    /// * `output` - The output directory of the stray dependency
+    pub fn remove_stray<P: AsRef<Path>>(&self, output: P) {
+        let mut manifest = self.load_manifest();
+        manifest.strays.remove(output.as_ref());
+        manifest.save(self.manifest_location());
+    }
+
+    /// Sets the command run at the given moment in the lifecycle of the [`Package`], see [`HookKind`]
+    /// The command is run from the [`Package`]'s directory with the variables in [`hooks`] set
+    ///
+    /// # Arguments
+    /// * `kind` - The [`HookKind`] of the hook
+    /// * `command` - The program followed by its arguments, or `None` to remove the hook
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::hooks::HookKind;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_hooks");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    ///
+    /// package.set_hook(HookKind::PreBuild, Some(vec!["make".to_string(), "generate".to_string()]));
+    /// assert_eq!(package.hooks()[&HookKind::PreBuild], vec!["make", "generate"]);
+    ///
+    /// package.set_hook(HookKind::PreBuild, None);
+    /// assert!(package.hooks().is_empty());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the command is empty
+    ///
+    /// [`hooks`]: crate::hooks
+    pub fn set_hook(&self, kind: HookKind, command: Option<Vec<String>>) {
+        let mut manifest = self.load_manifest();
+        match command {
+            Some(command) if command.is_empty() => panic!("The {} hook has no command", kind),
+            Some(command) => manifest.hooks.insert(kind, command),
+            None => manifest.hooks.remove(&kind),
+        };
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the hooks of the [`Package`] by [`HookKind`]
+    pub fn hooks(&self) -> BTreeMap<HookKind, Vec<String>> {
+        self.load_manifest().hooks
+    }
+
+    /// Runs the hook of the given [`HookKind`], if the [`Package`] declares one
+    pub(crate) fn run_hook(&self, kind: HookKind, outputs: &[PathBuf]) -> Result<(), HookError> {
+        match self.load_manifest().hooks.get(&kind) {
+            Some(command) => hooks::run_hook(kind, command, &self.local_location, self.version().map(|v| v.to_string()), outputs),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds a [`Module`] to a [`Package`]
+    /// The hash of the contents of its source files is recorded, see [`Module::content_hash`]
+    ///
+    /// # Arguments
+    /// * `module` - A [`Module`] that needs to be added
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use std::path::PathBuf;
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let url = Url::parse("https://github.com/jcuppen/JSON");
+    /// # assert!(url.is_ok());
+    /// let package = Package::download(url.unwrap(), env::temp_dir());
+    /// let module_path: PathBuf = ["src","JSON.sac"].iter().collect();
+    /// let module = Module::create(module_path, None);
+    /// package.add_module(module.clone());
+    /// assert!(package.has_module(&module));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when module_path does not point to an existing file or when an auxiliary source does not exist
+    /// ```rust, should_panic
+    /// # use std::env;
+    /// # use std::path::PathBuf;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&package_path);
+    /// assert!(package_path.exists());
+    /// let package = Package::create(&package_path);
+    /// let module_path: PathBuf = ["nonexistent.c"].iter().collect();
+    /// assert!(!package_path.join(&module_path).exists());
+    /// let module = Module::create(&module_path, None);
+    /// package.add_module(module);
+    /// ```
+    pub fn add_module(&self, module: Module) {
+        self.try_add_module(module).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Adds a [`Module`] to the [`Package`] like [`Package::add_module`], returning an [`Error`] instead of panicking
+    ///
+    /// # Arguments
+    /// * `module` - The [`Module`] that needs to be added
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::Error;
+    /// # use knapsac_lib::module::{Module, ModuleError};
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_try_add_module");
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    ///
+    /// let error = package.try_add_module(Module::create("nonexistent.sac", None)).unwrap_err();
+    /// assert_eq!(error, Error::Module(ModuleError::MissingSource(package_path.join("nonexistent.sac"))));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`ModuleError::MissingSource`] when the source file or an auxiliary source of the [`Module`] does not exist
+    /// and [`Error::Manifest`] when the manifest of the [`Package`] cannot be loaded
+    pub fn try_add_module(&self, module: Module) -> Result<(), Error> {
+        let mut manifest = Manifest::try_load(self.manifest_location())?;
+
+        let full_module_path = self.local_location.join(&module.location);
+        if !full_module_path.is_file() {
+            return Err(ModuleError::MissingSource(full_module_path).into());
+        }
+        if let Some(missing) = module.sources.iter().map(|s| self.local_location.join(s)).find(|s| !s.exists()) {
+            return Err(ModuleError::MissingSource(missing).into());
+        }
+        let content_hash = module.hash_contents(&self.local_location);
+        manifest.add_module(Module { content_hash: Some(content_hash), ..module });
+        manifest.save(self.manifest_location());
+        Ok(())
+    }
+
+    /// Replaces a [`Module`] of the [`Package`] with a changed copy, writing the manifest once
+    /// The files of the copy are checked first, so the [`Module`] stays registered when the copy cannot be added
+    pub(crate) fn replace_module(&self, module: &Module, changed: Module) -> Result<(), ModuleError> {
+        let full_module_path = self.local_location.join(&changed.location);
+        if !full_module_path.is_file() {
+            return Err(ModuleError::MissingSource(full_module_path));
+        }
+        if let Some(missing) = changed.sources.iter().map(|s| self.local_location.join(s)).find(|s| !s.exists()) {
+            return Err(ModuleError::MissingSource(missing));
+        }
+        let content_hash = changed.hash_contents(&self.local_location);
+        let mut manifest = self.load_manifest();
+        manifest.remove_module(module);
+        manifest.add_module(Module { content_hash: Some(content_hash), ..changed });
+        manifest.save(self.manifest_location());
+        Ok(())
+    }
+
+    /// Searches the [`Package`] for a [`Module`] that is located at the given [`Path`]
+    ///
+    /// # Arguments
+    /// * `location` - [`Path`] pointing to a location where a [`Module`] should be located.
+    ///
+    /// # Examples
+    /// //TODO
+    pub fn get_module_by_location<P: AsRef<Path>>(&self, location: P) -> Option<Module> {
+        self.load_manifest().get_module_by_location(location).cloned()
+    }
+
+    /// Searches the [`Package`] for the [`Module`] that the file at the given [`Path`] belongs to,
+    /// either as its entry point or as one of its auxiliary sources, see [`Module::add_source`]
+    ///
+    /// # Arguments
+    /// * `location` - [`Path`] relative to the [`Package`] pointing to a source file
+    pub fn get_module_owning<P: AsRef<Path>>(&self, location: P) -> Option<Module> {
+        self.load_manifest().get_module_owning(&self.local_location, location).cloned()
+    }
+
+    /// Returns an iterator over the [`Module`]s of the [`Package`], ordered by location
+    /// The manifest is read once, when the iterator is created
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_modules");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("b.sac"), "").unwrap();
+    /// fs::write(path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_module(Module::create("b.sac", None));
+    /// package.add_module(Module::create("a.sac", None));
+    ///
+    /// let identifiers: Vec<String> = package.modules().map(|m| m.identifier).collect();
+    /// assert_eq!(identifiers, vec!["a", "b"]);
+    /// ```
+    pub fn modules(&self) -> impl Iterator<Item = Module> {
+        let mut modules: Vec<Module> = self.load_manifest().modules.into_iter().collect();
+        modules.sort_by(|a, b| a.location.cmp(&b.location));
+        modules.into_iter()
+    }
+
+    /// Returns an iterator over the [`Module`]s of the [`Package`], ordered by location
+    #[deprecated(note = "use `Package::modules` instead")]
+    pub fn iter_modules(&self) -> impl Iterator<Item = Module> {
+        self.modules()
+    }
+
+    /// Checks the [`Package`] if it provides a given [`Module`]
+    ///
+    /// # Arguments
+    /// * `module` - A reference to a [`Module`] that needs to be checked
+    ///
+    /// # Examples
+    /// //TODO
+    pub fn has_module(&self, module: &Module) -> bool {
+        self.load_manifest().modules.contains(module)
+    }
+
+    /// Checks the [`Package`] if it has any [`Module`] with a given `identifier`
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier to check for
+    ///
+    pub(crate) fn has_modules_with_identifiers(&self, identifiers: &[String]) -> bool {
+        self.load_manifest().modules.iter().any(|m|identifiers.contains(&m.identifier))
+    }
+
+    /// Adds an [`Executable`] to a [`Package`], replacing the [`Executable`] at the same location if there is one
+    ///
+    /// # Arguments
+    /// * `executable` - The [`Executable`] that needs to be added
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::executable::Executable;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_executable");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("main.sac"), "").unwrap();
+    /// let package = Package::create(&path);
+    ///
+    /// let mut executable = Executable::create("main.sac");
+    /// executable.set_link_flags(vec!["-lm".to_string()]);
+    /// package.add_executable(executable.clone());
+    /// assert!(package.has_executable(&executable));
+    /// assert_eq!(package.get_executable_by_location("main.sac"), Some(executable.clone()));
+    /// assert!(package.get_module_by_location("main.sac").is_none());
+    ///
+    /// package.remove_executable(&executable);
+    /// assert!(package.executables().is_empty());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the location of the [`Executable`] does not point to an existing file
+    pub fn add_executable(&self, executable: Executable) {
+        if !self.local_location.join(&executable.location).is_file() {
+            panic!("Executable does not point to existing file");
+        }
+        let mut manifest = self.load_manifest();
+        manifest.add_executable(executable);
+        manifest.save(self.manifest_location());
+    }
+
+    /// Searches the [`Package`] for an [`Executable`] that is located at the given [`Path`]
+    ///
+    /// # Arguments
+    /// * `location` - [`Path`] relative to the [`Package`]'s `local_location`
+    pub fn get_executable_by_location<P: AsRef<Path>>(&self, location: P) -> Option<Executable> {
+        self.load_manifest().get_executable_by_location(location).cloned()
+    }
+
+    /// Checks the [`Package`] if it provides a given [`Executable`]
+    pub fn has_executable(&self, executable: &Executable) -> bool {
+        self.load_manifest().executables.contains(executable)
+    }
+
+    /// Returns every [`Executable`] the [`Package`] provides
+    pub fn executables(&self) -> Vec<Executable> {
+        self.load_manifest().executables.into_iter().collect()
+    }
+
+    /// Removes an [`Executable`] from a [`Package`]
+    pub fn remove_executable(&self, executable: &Executable) {
+        let mut manifest = self.load_manifest();
+        manifest.remove_executable(executable);
+        manifest.save(self.manifest_location());
+    }
+
+    /// Declares a feature of the [`Package`]: a named group of optional [`Module`]s
+    /// Optional [`Module`]s are only built and vendored for dependents that enable one of their features,
+    /// see [`Dependency::set_features`]
+    /// A feature that was declared before under the same name is replaced
+    ///
+    /// # Arguments
+    /// * `name` - The name of the feature
+    /// * `modules` - Relative [`Path`]s pointing to the [`Module`]s in the feature
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_features");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("core.sac"), "").unwrap();
+    /// fs::write(path.join("yaml.sac"), "").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_module(Module::create("core.sac", None));
+    /// package.add_module(Module::create("yaml.sac", None));
+    /// package.add_feature("yaml", &["yaml.sac"]);
+    ///
+    /// let identifiers = |features: &[&str]| -> Vec<String> {
+    ///     let features = features.iter().map(|f| f.to_string()).collect();
+    ///     package.required_modules(&features).into_iter().map(|m| m.identifier).collect()
+    /// };
+    /// assert_eq!(identifiers(&[]), vec!["core"]);
+    /// assert_eq!(identifiers(&["yaml"]), vec!["core", "yaml"]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when one of the given [`Path`]s is not a [`Module`] of the [`Package`]
+    pub fn add_feature<S: Into<String>, P: AsRef<Path>>(&self, name: S, modules: &[P]) {
+        let mut manifest = self.load_manifest();
+        for module in modules {
+            if manifest.get_module_by_location(module).is_none() {
+                panic!("No module registered @ {}", module.as_ref().display());
+            }
+        }
+        manifest.features.insert(name.into(), modules.iter().map(|m| m.as_ref().to_path_buf()).collect());
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the features of the [`Package`], mapping their names to the locations of their [`Module`]s
+    pub fn features(&self) -> BTreeMap<String, BTreeSet<PathBuf>> {
+        self.load_manifest().features
+    }
+
+    /// Removes a feature from the [`Package`], its [`Module`]s are no longer optional unless they belong to another feature
+    pub fn remove_feature(&self, name: &str) {
+        let mut manifest = self.load_manifest();
+        manifest.features.remove(name);
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the [`Module`]s that are needed when the given features are enabled, ordered by location:
+    /// the [`Module`]s that belong to no feature and those of the enabled features
+    ///
+    /// # Arguments
+    /// * `features` - The names of the enabled features, unknown names are ignored
+    pub fn required_modules(&self, features: &BTreeSet<String>) -> Vec<Module> {
+        self.load_manifest().required_modules(features)
+    }
+
+    /// Adds a [`TestModule`] to a [`Package`]
+    /// A [`TestModule`] already located at the same [`Path`] is replaced
+    ///
+    /// # Arguments
+    /// * `test` - The [`TestModule`] that needs to be added
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use std::path::Path;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::testing::TestModule;
+    ///
+    /// let path = env::temp_dir().join("mock_package_test_module");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("a.sac"), "").unwrap();
+    /// fs::write(path.join("a_test.sac"), "").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_module(Module::create("a.sac", None));
+    ///
+    /// let test = TestModule::create("a_test.sac", Some("a.sac"));
+    /// package.add_test(test.clone());
+    /// assert_eq!(package.tests(), vec![test.clone()]);
+    ///
+    /// package.remove_test(&test);
+    /// assert!(package.tests().is_empty());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the location of the [`TestModule`] does not point to an existing file
+    /// or when its target is not a [`Module`] of the [`Package`]
+    pub fn add_test(&self, test: TestModule) {
+        if !self.local_location.join(&test.location).is_file() {
+            panic!("Test module does not point to existing file");
+        }
+        let mut manifest = self.load_manifest();
+        if test.target.as_ref().is_some_and(|t| manifest.get_module_by_location(t).is_none()) {
+            panic!("Test module does not target a module of the package");
+        }
+        manifest.add_test(test);
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns every [`TestModule`] of the [`Package`], ordered by location
+    pub fn tests(&self) -> Vec<TestModule> {
+        let mut tests: Vec<TestModule> = self.load_manifest().tests.into_iter().collect();
+        tests.sort_by(|a, b| a.location.cmp(&b.location));
+        tests
+    }
+
+    /// Removes a [`TestModule`] from a [`Package`]
+    pub fn remove_test(&self, test: &TestModule) {
+        let mut manifest = self.load_manifest();
+        manifest.remove_test(test);
+        manifest.save(self.manifest_location());
+    }
+
+    /// Sets the name of the [`Language`] the [`Package`]'s [`Module`]s are written in
+    ///
+    /// # Arguments
+    /// * `language` - The name of a [`Language`] registered in a [`Registry`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_language");
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// assert!(package.language().is_none());
+    /// package.set_language(Some("sac".to_string()));
+    /// assert_eq!(package.language(), Some("sac".to_string()));
+    /// ```
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    pub fn set_language(&self, language: Option<String>) {
+        let mut manifest = self.load_manifest();
+        manifest.language = language;
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the name of the [`Language`] the [`Package`]'s [`Module`]s are written in, if set
+    pub fn language(&self) -> Option<String> {
+        self.load_manifest().language
+    }
+
+    /// Sets the extra flags passed to the compiler when building the [`Package`]'s [`Module`]s
+    /// A [`Module`] with extra flags of its own uses those instead, see [`Module::set_flags`]
+    ///
+    /// # Arguments
+    /// * `flags` - The extra flags, e.g. an optimization level or defines
+    pub fn set_flags(&self, flags: Vec<String>) {
+        let mut manifest = self.load_manifest();
+        manifest.flags = flags;
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the extra flags passed to the compiler when building the [`Package`]'s [`Module`]s
+    pub fn flags(&self) -> Vec<String> {
+        self.load_manifest().flags
+    }
+
+    /// Returns the [`PackageMetadata`] describing the [`Package`]
+    pub fn metadata(&self) -> PackageMetadata {
+        self.load_manifest().metadata
+    }
+
+    /// Replaces the [`PackageMetadata`] describing the [`Package`]
+    ///
+    /// # Arguments
+    /// * `metadata` - The new [`PackageMetadata`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_metadata");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    ///
+    /// let mut metadata = package.metadata();
+    /// metadata.set_description(Some("A JSON parser".to_string()));
+    /// metadata.set_license(Some("MIT".to_string()));
+    /// package.set_metadata(metadata);
+    /// assert_eq!(package.metadata().description(), Some("A JSON parser"));
+    /// assert_eq!(package.metadata().license(), Some("MIT"));
+    /// ```
+    pub fn set_metadata(&self, metadata: PackageMetadata) {
+        let mut manifest = self.load_manifest();
+        manifest.metadata = metadata;
+        manifest.save(self.manifest_location());
+    }
+
+    /// Sets the [`Language`] settings this [`Package`] overrides
+    /// Settings left empty are inherited from the [`Language`] named by [`Package::language`],
+    /// or from the [`Language`] the override extends
+    ///
+    /// # Arguments
+    /// * `language` - The overriding [`Language`] settings, or `None` to remove the override
+    pub fn set_language_override(&self, language: Option<Language>) {
+        let mut manifest = self.load_manifest();
+        manifest.language_override = language;
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the [`Language`] settings this [`Package`] overrides, if any
+    pub fn language_override(&self) -> Option<Language> {
+        self.load_manifest().language_override
+    }
+
+    /// Records that the [`Package`] was built or tested with the given compiler version
+    /// The lowest and highest recorded versions form the range of compiler versions the [`Package`] is compatible with
+    ///
+    /// # Arguments
+    /// * `version` - A dot separated compiler version, e.g. `1.3.3`
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let path = env::temp_dir().join("mock_package_compiler_versions");
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// package.record_compiler_version("1.10.0");
+    /// package.record_compiler_version("1.3.3");
+    /// assert!(package.is_compatible_with_compiler("1.4"));
+    /// assert!(!package.is_compatible_with_compiler("1.11.0"));
+    /// ```
+    pub fn record_compiler_version<S: Into<String>>(&self, version: S) {
+        let mut manifest = self.load_manifest();
+        manifest.compiler_versions.insert(version.into());
+        manifest.save(self.manifest_location());
+    }
+
+    /// Returns the compiler versions the [`Package`] was built or tested with
+    pub fn compiler_versions(&self) -> Vec<String> {
+        let mut versions: Vec<String> = self.load_manifest().compiler_versions.into_iter().collect();
+        versions.sort_by(|a, b| compare_versions(a, b));
+        versions
+    }
+
+    /// Checks whether the given compiler version lies within the range of recorded compiler versions
+    /// A [`Package`] without recorded compiler versions is compatible with every compiler version
+    ///
+    /// # Arguments
+    /// * `version` - A dot separated compiler version
+    pub fn is_compatible_with_compiler(&self, version: &str) -> bool {
+        let versions = self.compiler_versions();
+        match (versions.first(), versions.last()) {
+            (Some(min), Some(max)) => {
+                compare_versions(version, min) != Ordering::Less && compare_versions(version, max) != Ordering::Greater
+            }
+            _ => true,
+        }
+    }
+
+    /// Removes a [`Module`] from a [`Package`]
+    ///
+    /// # Arguments
+    /// * `module` - A reference to a [`Module`] that needs to be removed
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use std::path::PathBuf;
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    ///
+    /// let url = Url::parse("https://github.com/jcuppen/JSON");
+    /// # assert!(url.is_ok());
+    /// let package = Package::download(url.unwrap(), env::temp_dir());
+    /// let module_path: PathBuf = ["src", "JSON.sac"].iter().collect();
+    /// let module = Module::create(&module_path, None);
+    /// package.add_module(module.clone());
+    /// assert!(package.has_module(&module));
+    /// package.remove_module(&module);
+    /// assert!(!package.has_module(&module))
+    /// ```
+    pub fn remove_module(&self, module: &Module) {
+        let mut manifest = self.load_manifest();
+        manifest.remove_module(module);
+        manifest.save(self.manifest_location());
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Package {
+    /// Like [`Package::publish`], running on the blocking thread pool so the async runtime is not blocked
+    ///
+    /// # Arguments
+    /// * `increment` - The part of the [`Version`] to increment
+    /// * `build` - The build metadata of the new [`Version`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_publish_async");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// let version = runtime.block_on(package.publish_async(SemVerIncrement::Minor, vec![]));
+    /// assert_eq!(version.to_string(), "0.1.0");
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when [`Package::publish`] panics
+    pub async fn publish_async(&self, increment: SemVerIncrement, build: Vec<String>) -> Version {
+        let package = self.clone();
+        spawn_blocking(move || package.publish(increment, build)).await
+    }
+
+    /// Like [`Package::upload`], running on the blocking thread pool so the async runtime is not blocked
+    ///
+    /// # Arguments
+    /// * `backend` - The [`RegistryBackend`] to publish to
+    pub async fn upload_async<B: RegistryBackend + Send + 'static>(&self, backend: B) -> Result<String, Error> {
+        let package = self.clone();
+        spawn_blocking(move || package.upload(&backend)).await
+    }
+
+    /// Like [`Package::push`], running on the blocking thread pool so the async runtime is not blocked
+    ///
+    /// # Arguments
+    /// * `options` - The [`PushOptions`] selecting the remote, branch and whether tags are pushed
+    pub async fn push_async(&self, options: PushOptions) -> Result<Vec<String>, Error> {
+        let package = self.clone();
+        spawn_blocking(move || package.push(&options)).await
+    }
+}
+
+impl Display for Package {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
@@ -381,3 +2562,56 @@ impl Display for Package {
         )
     }
 }
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`PackageInfo`] is a summary of a [`Package`] that can be displayed or serialized, see [`Package::info`]
+/// Unlike the [`Package`] itself it does not read the manifest or git repository again
+pub struct PackageInfo {
+    pub(crate) id: String,
+    pub(crate) local_location: PathBuf,
+    pub(crate) remote_location: Option<Url>,
+    pub(crate) version: Option<Version>,
+    pub(crate) language: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) modules: Vec<String>,
+}
+
+impl PackageInfo {
+    /// Returns the identifier of the [`Package`], see [`Package::id`]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the absolute [`Path`] of the [`Package`]'s directory
+    pub fn local_location(&self) -> &Path {
+        &self.local_location
+    }
+
+    /// Returns the [`Url`] the [`Package`] was obtained from, if any
+    pub fn remote_location(&self) -> Option<&Url> {
+        self.remote_location.as_ref()
+    }
+
+    /// Returns the [`Version`] of the [`Package`], if it has one
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// Returns the name of the [`Language`] of the [`Package`], if set
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Returns the description in the [`PackageMetadata`] of the [`Package`], if set
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the identifiers of the [`Module`]s of the [`Package`], ordered by location
+    pub fn modules(&self) -> &[String] {
+        &self.modules
+    }
+}
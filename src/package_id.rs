@@ -0,0 +1,126 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`ParsePackageIdError`] explains why a string is not a valid [`PackageId`]
+pub struct ParsePackageIdError(pub(crate) String);
+
+impl Display for ParsePackageIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid package identifier: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParsePackageIdError {}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(PartialOrd, Ord)]
+#[derive(Hash)]
+/// A [`PackageId`] identifies a [`Package`], optionally within the scope of its author, e.g. `author/json`
+///
+/// A scope and a name consist of letters, digits, `-`, `_` and `.` and do not start with `.`
+/// Unscoped identifiers, e.g. `json`, remain valid, so identifiers written before scopes existed still parse
+/// Two [`PackageId`]s are only equal when both their scopes and names are, `json` and `author/json` differ
+///
+/// # Examples
+/// ```
+/// # use knapsac_lib::package_id::PackageId;
+///
+/// let scoped: PackageId = "author/json".parse().unwrap();
+/// assert_eq!(scoped.scope(), Some("author"));
+/// assert_eq!(scoped.name(), "json");
+///
+/// let unscoped: PackageId = "json".parse().unwrap();
+/// assert_eq!(unscoped.scope(), None);
+/// assert_ne!(scoped, unscoped);
+///
+/// assert!("author/".parse::<PackageId>().is_err());
+/// assert!("a/b/c".parse::<PackageId>().is_err());
+/// assert!("json parser".parse::<PackageId>().is_err());
+/// ```
+///
+/// [`Package`]: crate::package::Package
+pub struct PackageId(String);
+
+impl PackageId {
+    /// Creates a [`PackageId`] from a scope and a name
+    ///
+    /// # Arguments
+    /// * `scope` - The scope of the [`PackageId`], usually the name of the author, `None` for an unscoped [`PackageId`]
+    /// * `name` - The name of the [`PackageId`]
+    ///
+    /// # Panics
+    /// Panics when the scope or name contains characters that are not allowed
+    pub fn create(scope: Option<&str>, name: &str) -> Self {
+        let identifier = match scope {
+            Some(scope) => format!("{}/{}", scope, name),
+            None => name.to_string(),
+        };
+        identifier.parse().unwrap_or_else(|e: ParsePackageIdError| panic!("{}", e))
+    }
+
+    /// Returns the scope of the [`PackageId`], if it is scoped
+    pub fn scope(&self) -> Option<&str> {
+        self.0.split_once('/').map(|(scope, _)| scope)
+    }
+
+    /// Returns the name of the [`PackageId`], without its scope
+    pub fn name(&self) -> &str {
+        self.0.split_once('/').map_or(self.0.as_str(), |(_, name)| name)
+    }
+
+    /// Returns the [`PackageId`] as it is written, e.g. `author/json`
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn valid_segment(segment: &str) -> bool {
+        !segment.is_empty()
+            && !segment.starts_with('.')
+            && segment.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    }
+}
+
+impl FromStr for PackageId {
+    type Err = ParsePackageIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid = match s.split_once('/') {
+            Some((scope, name)) => PackageId::valid_segment(scope) && PackageId::valid_segment(name),
+            None => PackageId::valid_segment(s),
+        };
+        match valid {
+            true => Ok(PackageId(s.to_string())),
+            false => Err(ParsePackageIdError(s.to_string())),
+        }
+    }
+}
+
+impl Display for PackageId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq<str> for PackageId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl Serialize for PackageId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
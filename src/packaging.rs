@@ -0,0 +1,301 @@
+use crate::dependency::Dependency;
+use crate::executable::Executable;
+use crate::item::RegistryItem;
+use crate::module::Module;
+use crate::operation::{Operation, OperationEvent};
+use crate::package::Package;
+use crate::registry::Registry;
+use crate::utils::{is_executable, list_files};
+
+use std::collections::VecDeque;
+use std::fmt::{Debug, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`ExecutablePolicy`] determines what happens to executables found while sweeping a directory into a [`Package`]
+///
+/// [`Package`]: crate::package::Package
+pub enum ExecutablePolicy {
+    /// Leave executables out of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    Skip,
+    /// Add executables to the [`Package`] as prebuilt [`Executable`]s
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Package`]: crate::package::Package
+    IncludeAsBinary,
+    /// Refuse to sweep a directory that contains executables
+    Error,
+}
+
+#[derive(Clone)]
+#[derive(Default)]
+/// A [`PackageSelection`] determines which files are swept into a [`Package`], see [`Registry::package_selected`]
+/// Files that are not selected are left alone, so they can be packaged separately later
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry::package_selected`]: crate::registry::Registry::package_selected
+pub enum PackageSelection {
+    /// Every file in the directory
+    #[default]
+    All,
+    /// Only the files at the given absolute [`PathBuf`]s
+    Only(Vec<PathBuf>),
+    /// Only the files whose absolute [`Path`] the predicate accepts
+    Matching(Arc<dyn Fn(&Path) -> bool + Send + Sync>),
+}
+
+impl PackageSelection {
+    /// Creates a [`PackageSelection`] of the files whose absolute [`Path`] the given predicate accepts
+    ///
+    /// # Arguments
+    /// * `predicate` - The predicate deciding whether a file is selected
+    pub fn matching<F: Fn(&Path) -> bool + Send + Sync + 'static>(predicate: F) -> Self {
+        PackageSelection::Matching(Arc::new(predicate))
+    }
+
+    /// Checks whether the file at the given absolute [`Path`] is selected
+    pub fn selects<P: AsRef<Path>>(&self, path: P) -> bool {
+        match self {
+            PackageSelection::All => true,
+            PackageSelection::Only(paths) => paths.iter().any(|p| p == path.as_ref()),
+            PackageSelection::Matching(predicate) => predicate(path.as_ref()),
+        }
+    }
+}
+
+impl Debug for PackageSelection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageSelection::All => write!(f, "All"),
+            PackageSelection::Only(paths) => f.debug_tuple("Only").field(paths).finish(),
+            PackageSelection::Matching(_) => write!(f, "Matching(..)"),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`PackagingError`] explains why a directory could not be swept into a [`Package`]
+///
+/// [`Package`]: crate::package::Package
+pub enum PackagingError {
+    /// No registered [`Package`] contains the given [`PathBuf`]
+    ///
+    /// [`Package`]: crate::package::Package
+    UnregisteredPackage(PathBuf),
+    /// The directory contains the executables at the given [`PathBuf`]s
+    ExecutablesFound(Vec<PathBuf>),
+    /// The given [`PathBuf`] lies outside the allowed roots of the [`Registry`]
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    OutsideAllowedRoots(PathBuf),
+    /// The given [`PathBuf`] is not a file, or already belongs to a registered [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    NotStandalone(PathBuf),
+    /// The directory the [`Package`] would be created in, the given [`PathBuf`], already exists
+    ///
+    /// [`Package`]: crate::package::Package
+    DestinationExists(PathBuf),
+    /// The given identifier is not a valid [`PackageId`]
+    ///
+    /// [`PackageId`]: crate::package_id::PackageId
+    InvalidIdentifier(String),
+    /// No [`Package`] is registered under the given identifier
+    ///
+    /// [`Package`]: crate::package::Package
+    UnknownPackage(String),
+}
+
+impl Display for PackagingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackagingError::UnregisteredPackage(path) => write!(f, "no registered package contains {}", path.display()),
+            PackagingError::ExecutablesFound(executables) => {
+                let paths: Vec<String> = executables.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "directory contains executables: {}", paths.join(", "))
+            }
+            PackagingError::OutsideAllowedRoots(path) => write!(f, "{} lies outside the allowed roots of the registry", path.display()),
+            PackagingError::NotStandalone(path) => write!(f, "{} is not a standalone module", path.display()),
+            PackagingError::DestinationExists(path) => write!(f, "{} already exists", path.display()),
+            PackagingError::InvalidIdentifier(identifier) => write!(f, "`{}` is not a valid package identifier", identifier),
+            PackagingError::UnknownPackage(identifier) => write!(f, "no package registered as `{}`", identifier),
+        }
+    }
+}
+
+impl std::error::Error for PackagingError {}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// An [`UnpackageReport`] lists what [`Registry::unpackage`] turned into standalone [`Module`]s and stray dependencies
+///
+/// [`Module`]: crate::module::Module
+/// [`Registry::unpackage`]: crate::registry::Registry::unpackage
+pub struct UnpackageReport {
+    pub(crate) modules: Vec<(PathBuf, Option<PathBuf>)>,
+    pub(crate) dependents: Vec<PathBuf>,
+    pub(crate) missing_outputs: Vec<PathBuf>,
+}
+
+impl UnpackageReport {
+    /// Returns the absolute source file of every [`Module`] of the unpackaged [`Package`], with its output directory if it has one
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    pub fn modules(&self) -> &[(PathBuf, Option<PathBuf>)] {
+        &self.modules
+    }
+
+    /// Returns the `local_location`s of the [`Package`]s whose [`Dependency`] was turned into stray dependencies
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    pub fn dependents(&self) -> &[PathBuf] {
+        &self.dependents
+    }
+
+    /// Returns the output directories that do not exist yet and could therefore not become stray dependencies
+    pub fn missing_outputs(&self) -> &[PathBuf] {
+        &self.missing_outputs
+    }
+}
+
+pub(crate) struct PackageOperation {
+    source: PathBuf,
+    policy: ExecutablePolicy,
+    selection: PackageSelection,
+    package: Option<Package>,
+    pending: VecDeque<(PathBuf, bool)>,
+    added: Vec<RegistryItem>,
+}
+
+impl PackageOperation {
+    pub(crate) fn create<P: AsRef<Path>>(source: P, policy: ExecutablePolicy, selection: PackageSelection) -> Self {
+        PackageOperation {
+            source: source.as_ref().to_path_buf(),
+            policy,
+            selection,
+            package: None,
+            pending: VecDeque::new(),
+            added: vec![],
+        }
+    }
+
+    fn scan(&mut self, registry: &Registry) -> Result<Package, PackagingError> {
+        let package = registry.get_by_source(&self.source)
+            .ok_or_else(|| PackagingError::UnregisteredPackage(self.source.clone()))?;
+        let manifest_location = package.manifest_location();
+        let rules = registry.ignore_rules(package);
+
+        let mut sources = vec![];
+        let mut executables = vec![];
+        for file in list_files(&self.source) {
+            let path = self.source.join(file);
+            let location = package.strip_prefix(&path);
+            if path == manifest_location
+                || rules.is_ignored(&location)
+                || !self.selection.selects(&path)
+                || package.get_module_owning(&location).is_some()
+                || package.get_executable_by_location(&location).is_some() {
+                continue;
+            }
+            match is_executable(&path) {
+                true => executables.push(path),
+                false => sources.push(path),
+            }
+        }
+
+        match self.policy {
+            ExecutablePolicy::Skip => executables.clear(),
+            ExecutablePolicy::IncludeAsBinary => {}
+            ExecutablePolicy::Error if executables.is_empty() => {}
+            ExecutablePolicy::Error => {
+                registry.audit("package", vec![self.source.display().to_string()], "executables found");
+                return Err(PackagingError::ExecutablesFound(executables));
+            }
+        }
+
+        self.pending = sources.into_iter().map(|p| (p, false)).chain(executables.into_iter().map(|p| (p, true))).collect();
+        Ok(package.clone())
+    }
+
+    fn finish(&self, registry: &Registry, package: &Package) -> Vec<RegistryItem> {
+        for dependency in package.dependencies() {
+            if dependency.identifier.is_none() {
+                if let Some(identifier) = registry.resolve_dependency(&dependency).and_then(|p| p.package_id()) {
+                    package.add_dependency(Dependency::declare(dependency.git_url, identifier.as_str(), dependency.requirement));
+                }
+            }
+        }
+        registry.audit("package", vec![self.source.display().to_string()], &format!("{} items", self.added.len()));
+        self.added.clone()
+    }
+}
+
+impl Operation<Vec<RegistryItem>, PackagingError> for PackageOperation {
+    fn step(&mut self, registry: &mut Registry, events: &mut Vec<OperationEvent>) -> Result<Option<Vec<RegistryItem>>, PackagingError> {
+        let package = match &self.package {
+            Some(package) => package.clone(),
+            None => {
+                let package = self.scan(registry)?;
+                events.push(OperationEvent::Step(format!("found {} files in {}", self.pending.len(), self.source.display())));
+                self.package = Some(package.clone());
+                return match self.pending.is_empty() {
+                    true => Ok(Some(self.finish(registry, &package))),
+                    false => Ok(None),
+                };
+            }
+        };
+
+        let (path, binary) = self.pending.pop_front().unwrap();
+        registry.progress.notify(|h| h.module_started(&path));
+        let location = package.strip_prefix(&path);
+        let item = match binary {
+            true => {
+                let executable = Executable::prebuilt(location);
+                package.add_executable(executable.clone());
+                RegistryItem::Executable(executable)
+            }
+            false => {
+                let module = Module::create(location, None);
+                package.add_module(module.clone());
+                RegistryItem::Module(module)
+            }
+        };
+        events.push(OperationEvent::Step(format!("added {}", path.display())));
+        registry.progress.notify(|h| h.module_finished(&path));
+        self.added.push(item);
+
+        match self.pending.is_empty() {
+            true => Ok(Some(self.finish(registry, &package))),
+            false => Ok(None),
+        }
+    }
+
+    fn progress(&self) -> (usize, usize) {
+        match self.package {
+            Some(_) => (self.added.len() + 1, self.added.len() + self.pending.len() + 1),
+            None => (0, 1),
+        }
+    }
+
+    fn rollback(&mut self, _registry: &mut Registry) {
+        if let Some(package) = &self.package {
+            for item in self.added.drain(..) {
+                match item {
+                    RegistryItem::Module(module) => package.remove_module(&module),
+                    RegistryItem::Executable(executable) => package.remove_executable(&executable),
+                }
+            }
+        }
+    }
+}
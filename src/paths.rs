@@ -0,0 +1,252 @@
+use crate::migration::normalize;
+
+use std::ffi::OsString;
+use std::fs::{canonicalize as canonicalize_existing, read_dir};
+use std::path::{Component, Path, PathBuf};
+
+/// Whether the file systems of the current platform usually treat paths differing only in case as the same file
+pub const CASE_INSENSITIVE: bool = cfg!(any(target_os = "windows", target_os = "macos"));
+
+/// The length, in UTF-16 code units, from which Windows only accepts a path with the verbatim `\\?\` prefix
+pub const MAX_PATH: usize = 260;
+
+/// Returns the canonical form of the given absolute [`Path`], so every way of referring to a file gives the same [`PathBuf`]
+/// Symbolic links are resolved and, on platforms that ignore case, every component takes the case it has on disk
+/// The part of the [`Path`] that does not exist yet is only cleaned up, see [`same_path`]
+/// The [`Path`] is returned as is when it is relative or already canonical
+///
+/// # Arguments
+/// * `path` - The [`Path`] to canonicalize
+///
+/// # Examples
+/// ```
+/// # use std::{env, fs};
+/// # use knapsac_lib::paths::canonicalize;
+///
+/// let directory = env::temp_dir().join("mock_canonicalize");
+/// fs::create_dir_all(directory.join("src")).unwrap();
+/// let canonical = canonicalize(&directory);
+///
+/// assert_eq!(canonicalize(directory.join("src/../missing/./a.sac")), canonical.join("missing/a.sac"));
+/// # #[cfg(unix)]
+/// # {
+/// let link = env::temp_dir().join("mock_canonicalize_link");
+/// # let _ = fs::remove_file(&link);
+/// std::os::unix::fs::symlink(&directory, &link).unwrap();
+/// assert_eq!(canonicalize(link.join("src")), canonical.join("src"));
+/// # }
+/// ```
+pub fn canonicalize<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    if !path.has_root() {
+        return path.to_path_buf();
+    }
+    let normalized = normalize(path);
+    let mut missing: Vec<OsString> = vec![];
+    let mut existing = normalized.as_path();
+    let canonical = loop {
+        if let Ok(canonical) = canonicalize_existing(existing) {
+            break disk_case(strip_verbatim(canonical));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => break existing.to_path_buf(),
+        }
+    };
+    let canonical: PathBuf = canonical.iter().chain(missing.iter().rev().map(|n| n.as_os_str())).collect();
+    match canonical == path {
+        true => path.to_path_buf(),
+        false => canonical,
+    }
+}
+
+/// Checks whether two absolute [`Path`]s refer to the same file, see [`canonicalize`]
+/// On platforms that ignore case, the parts that do not exist yet are compared ignoring case as well
+///
+/// # Arguments
+/// * `a` - The first [`Path`]
+/// * `b` - The second [`Path`]
+pub fn same_path<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> bool {
+    let (a, b) = (canonicalize(a), canonicalize(b));
+    match CASE_INSENSITIVE {
+        true => fold_case(&a) == fold_case(&b),
+        false => a == b,
+    }
+}
+
+/// Strips the given base directory from the given [`Path`], trying their canonical forms when the [`Path`]s do not match as they are
+/// Returns `None` when the [`Path`] does not lie inside the base directory
+pub(crate) fn strip_base<P: AsRef<Path>, B: AsRef<Path>>(path: P, base: B) -> Option<PathBuf> {
+    let (path, base) = (path.as_ref(), base.as_ref());
+    if let Ok(stripped) = path.strip_prefix(base) {
+        return Some(stripped.to_path_buf());
+    }
+    let (path, base) = (canonicalize(path), canonicalize(base));
+    if let Ok(stripped) = path.strip_prefix(&base) {
+        return Some(stripped.to_path_buf());
+    }
+    match CASE_INSENSITIVE {
+        true => {
+            let count = base.components().count();
+            let matches = path.components().count() >= count && fold_case(&path.iter().take(count).collect::<PathBuf>()) == fold_case(&base);
+            matches.then(|| path.iter().skip(count).collect())
+        }
+        false => None,
+    }
+}
+
+/// Checks whether the given [`Path`] lies inside the given base directory, see [`strip_base`]
+pub(crate) fn starts_with<P: AsRef<Path>, B: AsRef<Path>>(path: P, base: B) -> bool {
+    strip_base(path, base).is_some()
+}
+
+/// Writes the given [`Path`] so it can be read on every platform, see [`from_portable`]
+/// A relative [`Path`] is written with `/` separators, an absolute one as it is, as it only exists on the platform that wrote it
+///
+/// # Arguments
+/// * `path` - The [`Path`] to write
+///
+/// # Examples
+/// ```
+/// # use std::path::PathBuf;
+/// # use knapsac_lib::paths::to_portable;
+///
+/// let path: PathBuf = ["..", "lib", "a.sac"].iter().collect();
+/// assert_eq!(to_portable(&path), "../lib/a.sac");
+/// ```
+pub fn to_portable<P: AsRef<Path>>(path: P) -> String {
+    let path = path.as_ref();
+    match path.has_root() || path.components().any(|c| matches!(c, Component::Prefix(_))) {
+        true => path.to_string_lossy().to_string(),
+        false => path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"),
+    }
+}
+
+/// Reads a [`Path`] written by [`to_portable`], or by hand on another platform
+/// A relative [`Path`] may use `/` and `\` as separators, both become the separator of the current platform
+/// An absolute one, starting with `/`, `\` or a drive letter, is taken as it is
+///
+/// # Arguments
+/// * `path` - The written [`Path`]
+///
+/// # Examples
+/// ```
+/// # use std::path::PathBuf;
+/// # use knapsac_lib::paths::from_portable;
+///
+/// let expected: PathBuf = ["..", "lib", "a.sac"].iter().collect();
+/// assert_eq!(from_portable("../lib/a.sac"), expected);
+/// assert_eq!(from_portable("..\\lib\\a.sac"), expected);
+/// assert_eq!(from_portable("/home/user/lib"), PathBuf::from("/home/user/lib"));
+/// ```
+pub fn from_portable<S: AsRef<str>>(path: S) -> PathBuf {
+    let path = path.as_ref();
+    match path.starts_with(['/', '\\']) || has_drive(path) {
+        true => PathBuf::from(path),
+        false => path.split(['/', '\\']).filter(|c| !c.is_empty() && *c != ".").collect(),
+    }
+}
+
+/// Removes the verbatim prefix Windows adds to the paths returned by e.g. [`std::fs::canonicalize`],
+/// `\\?\C:\a` becomes `C:\a` and `\\?\UNC\server\share\a` becomes `\\server\share\a`
+/// A [`Path`] that needs the prefix, because it is at least [`MAX_PATH`] long, keeps it, as do other [`Path`]s
+///
+/// # Arguments
+/// * `path` - The [`Path`] to remove the prefix from
+///
+/// # Examples
+/// ```
+/// # use std::path::PathBuf;
+/// # use knapsac_lib::paths::strip_verbatim;
+///
+/// assert_eq!(strip_verbatim(r"\\?\C:\lib\a.sac"), PathBuf::from(r"C:\lib\a.sac"));
+/// assert_eq!(strip_verbatim(r"\\?\UNC\server\share\lib"), PathBuf::from(r"\\server\share\lib"));
+/// assert_eq!(strip_verbatim("/home/user/lib"), PathBuf::from("/home/user/lib"));
+/// ```
+pub fn strip_verbatim<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    let stripped = match (text.strip_prefix(r"\\?\UNC\"), text.strip_prefix(r"\\?\")) {
+        (Some(share), _) => format!(r"\\{}", share),
+        (None, Some(rest)) if has_drive(rest) => rest.to_string(),
+        _ => return path.to_path_buf(),
+    };
+    match stripped.encode_utf16().count() < MAX_PATH {
+        true => PathBuf::from(stripped),
+        false => path.to_path_buf(),
+    }
+}
+
+/// Adds the verbatim prefix to an absolute Windows [`Path`] that is at least [`MAX_PATH`] long, so Windows accepts it,
+/// the inverse of [`strip_verbatim`]
+/// `/` separators are replaced, as Windows does not accept them after the prefix, other [`Path`]s are returned as they are
+///
+/// # Arguments
+/// * `path` - The [`Path`] to add the prefix to
+///
+/// # Examples
+/// ```
+/// # use std::path::PathBuf;
+/// # use knapsac_lib::paths::{extended, MAX_PATH};
+///
+/// let long = format!(r"C:\{}", "a".repeat(MAX_PATH));
+/// assert_eq!(extended(&long), PathBuf::from(format!(r"\\?\{}", long)));
+/// let share = format!(r"\\server\share\{}", "a".repeat(MAX_PATH));
+/// assert_eq!(extended(&share), PathBuf::from(format!(r"\\?\UNC\{}", &share[2..])));
+/// assert_eq!(extended(r"C:\lib"), PathBuf::from(r"C:\lib"));
+/// ```
+pub fn extended<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    if text.starts_with(r"\\?\") || text.encode_utf16().count() < MAX_PATH {
+        return path.to_path_buf();
+    }
+    match (text.strip_prefix(r"\\"), has_drive(text)) {
+        (Some(share), _) => PathBuf::from(format!(r"\\?\UNC\{}", share.replace('/', r"\"))),
+        (None, true) => PathBuf::from(format!(r"\\?\{}", text.replace('/', r"\"))),
+        (None, false) => path.to_path_buf(),
+    }
+}
+
+/// Checks whether the given text starts with a drive letter followed by a separator, e.g. `C:\`
+fn has_drive(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'/' | b'\\')
+}
+
+/// Lowercases every component of the given [`Path`]
+fn fold_case(path: &Path) -> PathBuf {
+    path.components()
+        .map(|c| match c {
+            Component::Normal(name) => OsString::from(name.to_string_lossy().to_lowercase()),
+            component => component.as_os_str().to_os_string(),
+        })
+        .collect()
+}
+
+/// Replaces every component of the given existing [`Path`] by the name it has on disk, on platforms that ignore case
+fn disk_case(path: PathBuf) -> PathBuf {
+    if !CASE_INSENSITIVE {
+        return path;
+    }
+    let mut cased = PathBuf::new();
+    for component in path.components() {
+        let name = match component {
+            Component::Normal(name) => read_dir(&cased).ok()
+                .and_then(|entries| entries.flatten()
+                    .map(|e| e.file_name())
+                    .find(|n| n.to_string_lossy().to_lowercase() == name.to_string_lossy().to_lowercase()))
+                .unwrap_or_else(|| name.to_os_string()),
+            component => component.as_os_str().to_os_string(),
+        };
+        cased.push(name);
+    }
+    cased
+}
@@ -0,0 +1,108 @@
+use crate::operation::OperationEvent;
+
+use std::fmt::{Debug, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+use url::Url;
+
+/// A [`ProgressHandler`] is notified while a [`Registry`] performs long running work,
+/// so front-ends can render progress bars and logs as it happens
+/// Every callback does nothing by default, implement the ones of interest
+///
+/// # Examples
+/// ```
+/// # use std::{env, fs};
+/// # use std::path::Path;
+/// # use std::sync::{Arc, Mutex};
+/// # use git2::Repository;
+/// # use knapsac_lib::package::Package;
+/// # use knapsac_lib::packaging::ExecutablePolicy;
+/// # use knapsac_lib::progress::ProgressHandler;
+/// # use knapsac_lib::registry::Registry;
+///
+/// struct Log(Arc<Mutex<Vec<String>>>);
+///
+/// impl ProgressHandler for Log {
+///     fn module_finished(&self, module: &Path) {
+///         self.0.lock().unwrap().push(module.display().to_string());
+///     }
+/// }
+///
+/// let package_path = env::temp_dir().join("mock_package_progress");
+/// # let _ = fs::remove_dir_all(&package_path);
+/// Repository::init(&package_path);
+/// fs::write(package_path.join("a.sac"), "").unwrap();
+/// let mut registry = Registry::initialize(env::temp_dir().join("registry_progress.json"));
+/// registry.add(Package::create(&package_path));
+///
+/// let log = Arc::new(Mutex::new(vec![]));
+/// registry.set_progress_handler(Some(Box::new(Log(log.clone()))));
+/// registry.package(&package_path, ExecutablePolicy::Skip).unwrap();
+/// assert_eq!(*log.lock().unwrap(), vec![package_path.join("a.sac").display().to_string()]);
+/// ```
+///
+/// [`Registry`]: crate::registry::Registry
+pub trait ProgressHandler {
+    /// Called when a [`Fetcher`] starts obtaining a [`Package`] from the given source
+    ///
+    /// [`Fetcher`]: crate::fetcher::Fetcher
+    /// [`Package`]: crate::package::Package
+    fn fetch_started(&self, _source: &Url) {}
+
+    /// Called while a [`Fetcher`] transfers the given source, with the number of objects received
+    /// and the total number of objects, for [`Fetcher`]s that report it
+    ///
+    /// [`Fetcher`]: crate::fetcher::Fetcher
+    fn fetch_progress(&self, _source: &Url, _received: usize, _total: usize) {}
+
+    /// Called when a [`Fetcher`] has obtained the given source
+    ///
+    /// [`Fetcher`]: crate::fetcher::Fetcher
+    fn fetch_finished(&self, _source: &Url) {}
+
+    /// Called when an operation starts working on the [`Module`] with the given source file
+    ///
+    /// [`Module`]: crate::module::Module
+    fn module_started(&self, _module: &Path) {}
+
+    /// Called when an operation is done working on the [`Module`] with the given source file
+    ///
+    /// [`Module`]: crate::module::Module
+    fn module_finished(&self, _module: &Path) {}
+
+    /// Called for every [`OperationEvent`] of an operation driven through an [`OperationHandle`]
+    ///
+    /// [`OperationHandle`]: crate::operation::OperationHandle
+    fn event(&self, _event: &OperationEvent) {}
+}
+
+#[derive(Clone)]
+#[derive(Default)]
+/// Holds the [`ProgressHandler`] registered on a [`Registry`], which takes no part in comparing or printing it
+/// Clones of the [`Registry`] share it
+///
+/// [`Registry`]: crate::registry::Registry
+pub(crate) struct Progress(pub(crate) Option<Arc<dyn ProgressHandler + Send + Sync>>);
+
+impl Progress {
+    pub(crate) fn notify<C: FnOnce(&dyn ProgressHandler)>(&self, callback: C) {
+        if let Some(handler) = &self.0 {
+            callback(handler.as_ref());
+        }
+    }
+}
+
+impl Debug for Progress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "Progress(Some(..))"),
+            None => write!(f, "Progress(None)"),
+        }
+    }
+}
+
+impl PartialEq for Progress {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`PruneReport`] lists the entries that were (or would be) removed by [`Registry::prune`]
+///
+/// [`Registry::prune`]: crate::registry::Registry::prune
+pub struct PruneReport {
+    pub(crate) packages: Vec<PathBuf>,
+    pub(crate) modules: Vec<PathBuf>,
+    pub(crate) dependencies: Vec<(PathBuf, Url)>,
+}
+
+impl PruneReport {
+    /// Returns the `local_location`s of [`Package`]s whose directory no longer exists
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn packages(&self) -> &[PathBuf] {
+        &self.packages
+    }
+
+    /// Returns the source files of [`Module`]s that no longer exist
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn modules(&self) -> &[PathBuf] {
+        &self.modules
+    }
+
+    /// Returns the [`Dependency`]s on pruned [`Package`]s, paired with the `local_location` of the dependent [`Package`]
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    pub fn dependencies(&self) -> &[(PathBuf, Url)] {
+        &self.dependencies
+    }
+
+    /// Checks whether nothing was pruned
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty() && self.modules.is_empty() && self.dependencies.is_empty()
+    }
+}
@@ -0,0 +1,249 @@
+use crate::version::Version;
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`DirtyPolicy`] decides what publishing a [`Package`] does when its directory has uncommitted changes
+/// to files it does not manage, e.g. unrelated notes or scratch files, see [`PublishPlan::unmanaged_changes`]
+/// Managed files are the manifest and its signature, the sources of [`Module`]s, [`Executable`]s and [`TestModule`]s,
+/// and the files in the output directories of [`Module`]s
+///
+/// [`Executable`]: crate::executable::Executable
+/// [`Module`]: crate::module::Module
+/// [`Package`]: crate::package::Package
+/// [`TestModule`]: crate::testing::TestModule
+pub enum DirtyPolicy {
+    /// Publish, committing the unmanaged changes as well
+    Allow,
+    /// Publish, committing the unmanaged changes as well, after listing them in the [`PublishPlan`] so front-ends can warn about them
+    #[default]
+    Warn,
+    /// Refuse to publish
+    Fail,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`PublishPlan`] lists the changes publishing a [`Package`] makes, see [`Package::plan_publish`]
+///
+/// [`Package`]: crate::package::Package
+/// [`Package::plan_publish`]: crate::package::Package::plan_publish
+pub struct PublishPlan {
+    pub(crate) version: Version,
+    pub(crate) tag: String,
+    pub(crate) files: Vec<PathBuf>,
+    pub(crate) unmanaged: Vec<PathBuf>,
+    pub(crate) changes: Vec<String>,
+}
+
+impl PublishPlan {
+    /// Returns the [`Version`] that is published
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Returns the git tag that is created
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Returns the files that are committed, relative to the `local_location` of the [`Package`], sorted
+    /// The manifest is always among them, as the [`Version`] and file hashes are recorded in it
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Returns the files among [`PublishPlan::files`] with uncommitted changes that the [`Package`] does not manage, see [`DirtyPolicy`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn unmanaged_changes(&self) -> &[PathBuf] {
+        &self.unmanaged
+    }
+
+    /// Returns the summaries of the commits made since the previous [`Version`] was published, oldest first
+    pub fn changes(&self) -> &[String] {
+        &self.changes
+    }
+
+    /// Returns the [`ReleaseNotes`] publishing the [`Version`] produces
+    pub fn release_notes(&self) -> ReleaseNotes {
+        ReleaseNotes {
+            version: self.version.clone(),
+            changes: self.changes.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// [`ReleaseNotes`] list the changes made in a published [`Version`], see [`Package::publish_with_changelog`]
+///
+/// [`Package::publish_with_changelog`]: crate::package::Package::publish_with_changelog
+pub struct ReleaseNotes {
+    pub(crate) version: Version,
+    pub(crate) changes: Vec<String>,
+}
+
+impl ReleaseNotes {
+    /// Returns the published [`Version`]
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Returns the summaries of the commits made since the previous [`Version`], oldest first
+    pub fn changes(&self) -> &[String] {
+        &self.changes
+    }
+
+    /// Returns the [`ReleaseNotes`] as a section of a markdown changelog
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::version::SemVerIncrement;
+    ///
+    /// let path = env::temp_dir().join("mock_package_release_notes");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    ///
+    /// let notes = package.plan_publish(SemVerIncrement::Minor, vec![]).release_notes();
+    /// assert_eq!(notes.to_markdown(), "## 0.1.0\n\n");
+    /// ```
+    pub fn to_markdown(&self) -> String {
+        let mut section = format!("## {}\n\n", self.version);
+        for change in &self.changes {
+            section.push_str(&format!("- {}\n", change));
+        }
+        if !self.changes.is_empty() {
+            section.push('\n');
+        }
+        section
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`UploadPlan`] describes the archive uploading a [`Package`] publishes, see [`Package::plan_upload`]
+///
+/// [`Package`]: crate::package::Package
+/// [`Package::plan_upload`]: crate::package::Package::plan_upload
+pub struct UploadPlan {
+    pub(crate) identifier: String,
+    pub(crate) version: Version,
+    pub(crate) files: Vec<PathBuf>,
+    pub(crate) checksum: String,
+}
+
+impl UploadPlan {
+    /// Returns the identifier the archive is published under
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Returns the [`Version`] the archive is published under
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Returns the files packed into the archive, relative to the `local_location` of the [`Package`], sorted
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Returns the checksum installers need to verify the unpacked archive against, see [`Package::checksum`]
+    ///
+    /// [`Package::checksum`]: crate::package::Package::checksum
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// [`PushOptions`] select what [`Package::push`] pushes and where to
+///
+/// [`Package::push`]: crate::package::Package::push
+pub struct PushOptions {
+    pub(crate) remote: String,
+    pub(crate) branch: Option<String>,
+    pub(crate) tags: bool,
+    pub(crate) mirrors: bool,
+}
+
+impl Default for PushOptions {
+    fn default() -> Self {
+        PushOptions {
+            remote: "origin".to_string(),
+            branch: None,
+            tags: false,
+            mirrors: false,
+        }
+    }
+}
+
+impl PushOptions {
+    /// Sets the name of the git remote to push to, `origin` by default
+    pub fn set_remote(&mut self, remote: &str) -> &mut Self {
+        self.remote = remote.to_string();
+        self
+    }
+
+    /// Sets the branch to push, by default the branch currently checked out
+    pub fn set_branch(&mut self, branch: Option<&str>) -> &mut Self {
+        self.branch = branch.map(String::from);
+        self
+    }
+
+    /// Sets whether every tag, and so every published [`Version`], is pushed as well
+    pub fn set_tags(&mut self, tags: bool) -> &mut Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets whether the mirrors of the [`Package`] are pushed to as well, see [`Package::add_mirror`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Package::add_mirror`]: crate::package::Package::add_mirror
+    pub fn set_mirrors(&mut self, mirrors: bool) -> &mut Self {
+        self.mirrors = mirrors;
+        self
+    }
+
+    /// Returns the name of the git remote to push to
+    pub fn remote(&self) -> &str {
+        &self.remote
+    }
+
+    /// Returns the branch to push, [`None`] meaning the branch currently checked out
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    /// Checks whether tags are pushed
+    pub fn tags(&self) -> bool {
+        self.tags
+    }
+
+    /// Checks whether the mirrors of the [`Package`] are pushed to
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn mirrors(&self) -> bool {
+        self.mirrors
+    }
+}
@@ -1,21 +1,189 @@
+use crate::artifacts::PrebuiltReport;
+use crate::audit::AuditLog;
+use crate::build::{BuildPlan, BuildPlanFormat, BuildStep};
+use crate::bundle::{BundledPackage, FailureBundle};
+use crate::clean::{CleanReport, CleanTarget};
+use crate::cache::PackageCache;
+use crate::dependency::{Dependency, DependencyError, DependencyKind};
+use crate::depfile::{DepfileFormat, DepfileImport};
+use crate::diagnostics::{Diagnostic, DiagnosticsReport};
+use crate::diff::DiffReport;
+use crate::docs::{DocExtractor, Extractor};
+use crate::duplicates::DuplicateGroup;
+use crate::environment::{host, metadata_path, recorded_variables, ArtifactExplanation, BuildEnvironment};
+use crate::error::{Error, RegistryError};
+use crate::executable::Executable;
+use crate::fetcher::{Fetcher, Provenance};
+use crate::format::{Format, Style};
+use crate::git_cache;
+use crate::hooks::{HookError, HookKind, HookPolicy};
+use crate::ignore::{IgnoreRules, IGNORE_FILE};
+use crate::index::{IndexEntry, IndexError, RemoteIndex};
+use crate::graph::{EdgeKind, Graph, GraphFormat, NodeKind};
+use crate::install::{InstallError, InstallOperation};
+use crate::integrity::IntegrityIssue;
+use crate::item::{ImportKind, ImportTarget, RegistryItem};
+use crate::journal::Journal;
+use crate::language::Language;
+use crate::manifest::Manifest;
+use crate::migration::{common_root, map_path, normalize, relative_to, RegistryExport};
+use crate::module::{Module, ModuleError};
+use crate::operation::OperationHandle;
 use crate::package::Package;
-use crate::utils::infer_working_directory;
+use crate::package_id::PackageId;
+use crate::paths::{canonicalize, from_portable, same_path, starts_with, to_portable};
+use crate::packaging::{ExecutablePolicy, PackageOperation, PackageSelection, PackagingError, UnpackageReport};
+use crate::progress::{Progress, ProgressHandler};
+use crate::prune::PruneReport;
+use crate::removal::{RemovalError, RemovalPlan, RemovalPolicy, UninstallOptions, UninstallReport};
+use crate::run::{RunError, RunOutput, SEARCH_PATH_VARIABLE};
+use crate::search::{find, rank, MatchField, SearchMatch, SearchOptions, SearchResult};
+use crate::shared::FileLock;
+use crate::signing::TrustStore;
+use crate::snapshot::{DependencyView, ItemKind, ItemView, PackageView, RegistrySnapshot};
+use crate::stats::RegistryStats;
+use crate::sync::{Discrepancy, SyncError, SyncReport};
+use crate::testing::{TestModule, TestOutcome, TestReport, TestResult};
+use crate::utils::{copy_directory, discover_git_repository, glob_matches, infer_working_directory, list_files, sha256_hex, sorted_map, sorted_set, write_atomic};
+use crate::vendor::{LockedPackage, Lockfile};
+#[cfg(feature = "watch")]
+use crate::watch::{RegistryWatcher, WatchCallback, WatchError};
+use crate::version::{SemVerIncrement, Version, VersionReq};
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use git2::Repository;
+use std::env;
 use serde::{Deserialize, Serialize};
-use std::fs::{read_to_string, write};
+use std::fs::{copy, create_dir_all, read_to_string, remove_dir_all, remove_file, rename, write};
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// Determines when a [`Registry`] writes its changes to disk
+pub enum AutoSave {
+    /// Every change is written to disk immediately
+    #[default]
+    Immediate,
+    /// Changes made within the given [`Duration`] after the last write are coalesced into a single write
+    /// No timer runs in the background: pending changes are only written by the first change made after the
+    /// [`Duration`] has passed, by [`Registry::flush`] or when the [`Registry`] is dropped
+    /// Call [`Registry::flush`] once a batch of changes is complete, so they do not stay in memory indefinitely
+    Debounced(Duration),
+}
+
+/// The directory holding a project-local registry file, see [`Registry::discover`]
+pub const PROJECT_DIRECTORY: &str = ".knapsac";
+/// Overrides where the global registry file is located, see [`Registry::discover`]
+pub const REGISTRY_VARIABLE: &str = "KNAPSAC_REGISTRY";
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// Describes how the file of a [`Registry`] was chosen, see [`Registry::origin`]
+pub enum RegistryOrigin {
+    /// The [`Registry`] was loaded or initialized from a given [`Path`]
+    #[default]
+    Explicit,
+    /// [`Registry::discover`] found a project-local registry in the given project directory,
+    /// the directory containing [`PROJECT_DIRECTORY`]
+    Project(PathBuf),
+    /// [`Registry::discover`] found no project-local registry and fell back to the global one
+    Global,
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone, Copy)]
+#[derive(Debug, Default)]
+#[derive(PartialEq, Eq)]
+/// Determines what relative output directories of [`Module`]s are resolved against
+pub enum OutputPolicy {
+    /// Relative output directories are resolved against the [`Registry`]'s artifacts root,
+    /// or against the directory of the [`Module`]'s source file when no artifacts root is set
+    #[default]
+    ArtifactsRoot,
+    /// Relative output directories are resolved against the directory of the [`Module`]'s source file
+    SourceDirectory,
+}
 
 #[derive(Deserialize, Serialize)]
 #[derive(Debug)]
-#[derive(PartialEq)]
 /// A [`Registry`] represents all [`Package`]s managed by KnapSaC
+/// Two [`Registry`]s are equal when they hold the same state, regardless of where they are saved
+/// Clones share the [`ProgressHandler`], [`DocExtractor`] and [`AuditLog`], see [`Registry::clone_in_memory`]
+/// A clone starts without pending changes, so dropping it never writes the state it was cloned with
+///
+/// [`AuditLog`]: crate::audit::AuditLog
 pub struct Registry {
     #[serde(skip)]
     pub(crate) location: PathBuf,
+    #[serde(serialize_with = "sorted_set")]
     pub(crate) packages: HashSet<Package>,
+    #[serde(default, serialize_with = "sorted_map")]
+    pub(crate) languages: HashMap<String, Language>,
+    #[serde(default, serialize_with = "sorted_map")]
+    pub(crate) provenance: HashMap<PathBuf, Provenance>,
+    #[serde(default)]
+    pub(crate) artifacts_root: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) output_policy: OutputPolicy,
+    #[serde(default)]
+    pub(crate) allowed_roots: Vec<PathBuf>,
+    #[serde(default)]
+    pub(crate) remote_index: Option<RemoteIndex>,
+    #[serde(default)]
+    pub(crate) trust_store: Option<TrustStore>,
+    #[serde(default)]
+    pub(crate) cache_directory: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) offline: bool,
+    #[serde(default)]
+    pub(crate) journal: Option<Journal>,
+    #[serde(default)]
+    pub(crate) hook_policy: HookPolicy,
+    #[serde(default)]
+    pub(crate) style: Style,
+    #[serde(default)]
+    pub(crate) relative_paths: bool,
+    #[serde(default)]
+    pub(crate) target: Option<String>,
+    #[serde(default)]
+    pub(crate) ignore_patterns: Vec<String>,
+    #[serde(skip)]
+    pub(crate) audit_log: Option<AuditLog>,
+    #[serde(skip)]
+    pub(crate) progress: Progress,
+    #[serde(skip)]
+    pub(crate) doc_extractor: Extractor,
+    #[serde(skip)]
+    pub(crate) autosave: AutoSave,
+    #[serde(skip)]
+    pub(crate) last_saved: Option<Instant>,
+    #[serde(skip)]
+    pub(crate) dirty: bool,
+    #[serde(skip)]
+    pub(crate) recovered: bool,
+    #[serde(skip)]
+    pub(crate) journal_baseline: Option<String>,
+    #[serde(skip)]
+    pub(crate) in_memory: bool,
+    #[serde(skip)]
+    pub(crate) origin: RegistryOrigin,
 }
 
+/// The suffix appended to the file of a [`Registry`] to name the file holding its SHA-256 checksum,
+/// so a partially written file can be told apart from one that was edited by hand
+const CHECKSUM_SUFFIX: &str = ".sha256";
+
+/// Registries saved by earlier versions start with a line holding this prefix followed by the SHA-256 checksum of the rest of the file
+const LEGACY_CHECKSUM_PREFIX: &str = "#sha256=";
+
 impl Registry {
     /// Creates a new empty [`Registry`] and writes it to the given [`Path`]
     ///
@@ -28,14 +196,43 @@ impl Registry {
     /// # assert!(Registry::initialize(path).is_empty())
     /// ```
     pub fn initialize<P: AsRef<Path>>(path: P) -> Self {
-        let registry = Registry {
-            location: path.as_ref().to_path_buf(),
-            packages: HashSet::new()
-        };
+        let registry = Registry::new(path);
         registry.save().unwrap();
         registry
     }
 
+    pub(crate) fn new<P: AsRef<Path>>(path: P) -> Self {
+        Registry {
+            location: path.as_ref().to_path_buf(),
+            packages: HashSet::new(),
+            languages: HashMap::new(),
+            provenance: HashMap::new(),
+            artifacts_root: None,
+            output_policy: OutputPolicy::default(),
+            allowed_roots: vec![],
+            remote_index: None,
+            trust_store: None,
+            cache_directory: None,
+            offline: false,
+            journal: None,
+            hook_policy: HookPolicy::default(),
+            style: Style::default(),
+            relative_paths: false,
+            target: None,
+            ignore_patterns: vec![],
+            audit_log: None,
+            progress: Progress::default(),
+            doc_extractor: Extractor::default(),
+            autosave: AutoSave::default(),
+            last_saved: None,
+            dirty: false,
+            recovered: false,
+            journal_baseline: None,
+            in_memory: false,
+            origin: RegistryOrigin::default(),
+        }
+    }
+
     /// Loads and returns a [`Registry`] based on the given [`Path`]
     ///
     /// # Examples
@@ -98,12 +295,361 @@ impl Registry {
     /// let registry = Registry::load(path);
     /// ```
     pub fn load<P: AsRef<Path>>(path: P) -> Self {
-        if let Ok(data) = read_to_string(&path) {
-            let mut registry: Registry = serde_json::from_str(data.as_str()).unwrap();
-            registry.location = path.as_ref().to_path_buf();
-            return registry
+        Registry::try_load(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Loads and returns a [`Registry`] based on the given [`Path`]
+    ///
+    /// When the file was only partially written, or is otherwise invalid, the backup made by the last successful save is loaded instead.
+    /// [`Registry::recovered_from_backup`] tells whether that happened.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::error::RegistryError;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let path = env::temp_dir().join("registry_partial.json");
+    /// # let _ = fs::remove_file(path.with_extension("json.bak"));
+    /// let mut registry = Registry::initialize(&path);
+    /// let package_path = env::temp_dir().join("mock_package_partial");
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// registry.add(package.clone());
+    ///
+    /// let contents = fs::read_to_string(&path).unwrap();
+    /// fs::write(&path, &contents[..contents.len() - 10]).unwrap();
+    ///
+    /// let recovered = Registry::try_load(&path).unwrap();
+    /// assert!(recovered.recovered_from_backup());
+    /// assert!(recovered.is_empty());
+    ///
+    /// fs::remove_file(path.with_extension("json.bak")).unwrap();
+    /// assert_eq!(Registry::try_load(&path), Err(RegistryError::PartialWriteDetected(path)));
+    /// ```
+    pub fn try_load<P: AsRef<Path>>(path: P) -> Result<Self, RegistryError> {
+        let path = path.as_ref();
+        let format = Format::from_path(path).unwrap_or(Format::Json);
+        match Registry::read(path, format) {
+            Err(error @ (RegistryError::PartialWriteDetected(_) | RegistryError::Malformed(_, _))) => {
+                match Registry::read(Registry::backup_location(path), format) {
+                    Ok(mut registry) => {
+                        registry.location = path.to_path_buf();
+                        registry.recovered = true;
+                        Ok(registry)
+                    }
+                    Err(_) => Err(error),
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Finds the [`Registry`] to use for the given directory, the way git finds a repository
+    /// The directory and its ancestors are searched for a project-local registry file,
+    /// `registry.json` (or `registry.toml`) in a [`PROJECT_DIRECTORY`], and the nearest one is loaded
+    /// Without one the global registry is used, see [`Registry::global`]
+    /// See [`Registry::origin`] for which one was chosen
+    ///
+    /// # Arguments
+    /// * `start` - A [`Path`] pointing to the directory to start searching from, relative paths are resolved against
+    ///   the current working directory
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use knapsac_lib::registry::{Registry, RegistryOrigin, REGISTRY_VARIABLE};
+    ///
+    /// let project = env::temp_dir().join("mock_project_discover");
+    /// # let _ = fs::remove_dir_all(&project);
+    /// fs::create_dir_all(project.join(".knapsac")).unwrap();
+    /// fs::create_dir_all(project.join("src/nested")).unwrap();
+    /// Registry::initialize(project.join(".knapsac/registry.json"));
+    ///
+    /// let registry = Registry::discover(project.join("src/nested")).unwrap();
+    /// assert_eq!(registry.origin(), &RegistryOrigin::Project(project.clone()));
+    /// assert_eq!(registry.location(), project.join(".knapsac/registry.json"));
+    ///
+    /// env::set_current_dir(project.join("src")).unwrap();
+    /// let registry = Registry::discover("nested").unwrap();
+    /// assert_eq!(registry.location(), project.join(".knapsac/registry.json"));
+    ///
+    /// let global = env::temp_dir().join("registry_discover_global.json");
+    /// # let _ = fs::remove_file(&global);
+    /// env::set_var(REGISTRY_VARIABLE, &global);
+    /// let registry = Registry::discover("/").unwrap();
+    /// assert_eq!(registry.origin(), &RegistryOrigin::Global);
+    /// assert_eq!(registry.location(), global);
+    /// ```
+    pub fn discover<P: AsRef<Path>>(start: P) -> Result<Self, RegistryError> {
+        let start = match start.as_ref().is_absolute() {
+            true => start.as_ref().to_path_buf(),
+            false => env::current_dir().map_err(|_| RegistryError::NotFound(start.as_ref().to_path_buf()))?.join(start),
+        };
+        for directory in normalize(start).ancestors() {
+            let candidates = [Format::Json, #[cfg(feature = "toml")] Format::Toml]
+                .map(|f| directory.join(PROJECT_DIRECTORY).join("registry").with_extension(f.extension()));
+            if let Some(path) = candidates.into_iter().find(|p| p.is_file()) {
+                let mut registry = Registry::try_load(path)?;
+                registry.origin = RegistryOrigin::Project(directory.to_path_buf());
+                return Ok(registry);
+            }
+        }
+        Registry::global()
+    }
+
+    /// Loads the global [`Registry`], the one [`Registry::discover`] falls back to
+    /// It is the file named by [`REGISTRY_VARIABLE`] when it is set, otherwise `.knapsac/registry.json` in the home directory,
+    /// and it is initialized when it does not exist yet
+    pub fn global() -> Result<Self, RegistryError> {
+        let global = match env::var_os(REGISTRY_VARIABLE) {
+            Some(path) => PathBuf::from(path),
+            None => env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(PROJECT_DIRECTORY).join("registry.json"))
+                .ok_or_else(|| RegistryError::NotFound(PathBuf::from(PROJECT_DIRECTORY).join("registry.json")))?,
+        };
+        let mut registry = match global.is_file() {
+            true => Registry::try_load(&global)?,
+            false => {
+                if let Some(directory) = global.parent() {
+                    create_dir_all(directory).map_err(|_| RegistryError::NotFound(global.clone()))?;
+                }
+                Registry::initialize(&global)
+            }
+        };
+        registry.origin = RegistryOrigin::Global;
+        Ok(registry)
+    }
+
+    /// Returns how the file of the [`Registry`] was chosen, see [`Registry::discover`]
+    pub fn origin(&self) -> &RegistryOrigin {
+        &self.origin
+    }
+
+    /// Returns the [`Path`] of the file the [`Registry`] is saved to
+    pub fn location(&self) -> &Path {
+        &self.location
+    }
+
+    fn read<P: AsRef<Path>>(path: P, format: Format) -> Result<Self, RegistryError> {
+        let location = path.as_ref().to_path_buf();
+        let data = read_to_string(&path).map_err(|_| RegistryError::NotFound(location.clone()))?;
+        let (contents, checksum) = match data.strip_prefix(LEGACY_CHECKSUM_PREFIX).and_then(|d| d.split_once('\n')) {
+            Some((checksum, contents)) => (contents, Some(checksum.to_string())),
+            None => (data.as_str(), read_to_string(Registry::checksum_location(&location)).ok().map(|c| c.trim().to_string())),
+        };
+        let mut registry: Registry = match format.deserialize(contents) {
+            Ok(registry) => registry,
+            Err(_) if checksum.is_some_and(|c| c != sha256_hex(contents)) => return Err(RegistryError::PartialWriteDetected(location)),
+            Err(error) => return Err(RegistryError::Malformed(location, error)),
+        };
+        if registry.relative_paths {
+            let base = location.parent().map(Path::to_path_buf).unwrap_or_default();
+            registry.map_paths(|p| normalize(base.join(from_portable(p.to_string_lossy()))));
+        }
+        registry.canonicalize_paths();
+        registry.location = location;
+        if registry.journal.is_some() {
+            registry.journal_baseline = Some(serde_json::to_string(&registry).unwrap());
+        }
+        Ok(registry)
+    }
+
+    fn backup_location<P: AsRef<Path>>(path: P) -> PathBuf {
+        let mut backup = path.as_ref().as_os_str().to_os_string();
+        backup.push(".bak");
+        PathBuf::from(backup)
+    }
+
+    fn checksum_location<P: AsRef<Path>>(path: P) -> PathBuf {
+        let mut checksum = path.as_ref().as_os_str().to_os_string();
+        checksum.push(CHECKSUM_SUFFIX);
+        PathBuf::from(checksum)
+    }
+
+    /// Writes a portable [`RegistryExport`] of the [`Registry`] and the manifests of its [`Package`]s to the given [`Path`]
+    /// Paths inside the deepest directory containing every [`Package`] are stored relative to it, see [`Registry::import`]
+    ///
+    /// # Arguments
+    /// * `path` - A [`Path`] pointing to where the [`RegistryExport`] needs to be written to
+    pub fn export<P: AsRef<Path>>(&self, path: P) -> RegistryExport {
+        let root = common_root(self.packages.iter().map(|p| p.local_location.as_path()));
+        let mut copy: Registry = serde_json::from_str(&serde_json::to_string(self).unwrap()).unwrap();
+        copy.map_paths(|p| p.strip_prefix(&root).map(|r| PathBuf::from(to_portable(r))).unwrap_or_else(|_| p.to_path_buf()));
+
+        let manifests = self.packages.iter()
+            .filter_map(|p| {
+                let location = p.manifest_location();
+                let contents = read_to_string(&location).ok()?;
+                Some((PathBuf::from(to_portable(location.strip_prefix(&root).unwrap())), contents))
+            })
+            .collect::<BTreeMap<PathBuf, String>>();
+        let export = RegistryExport {
+            registry: serde_json::to_string(&copy).unwrap(),
+            root,
+            manifests,
+        };
+        export.save(path);
+        export
+    }
+
+    /// Creates a [`Registry`] from a [`RegistryExport`] written by [`Registry::export`] and writes it to the given location
+    ///
+    /// Relative paths are resolved against the root of the [`RegistryExport`], after which every path is rewritten
+    /// according to the mapping entry with the longest matching prefix, paths without a matching entry are kept.
+    /// Manifests are written to [`Package`]s whose directory exists but has no manifest yet
+    /// [`Package`]s without a [`Provenance`] get one pointing at the export, so their hooks only run with [`HookPolicy::All`]
+    ///
+    /// # Arguments
+    /// * `export` - A [`Path`] pointing to the file of the [`RegistryExport`]
+    /// * `location` - A [`Path`] pointing to where the [`Registry`] needs to be written to
+    /// * `mapping` - Pairs of directories on the exporting machine and the directories they moved to
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let old_home = env::temp_dir().join("mock_import_old");
+    /// let new_home = env::temp_dir().join("mock_import_new");
+    /// # let _ = fs::remove_dir_all(&old_home);
+    /// # let _ = fs::remove_dir_all(&new_home);
+    /// Repository::init(old_home.join("json"));
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_export.json"));
+    /// registry.add(Package::create(old_home.join("json")));
+    /// let export_path = env::temp_dir().join("registry_export.knapsac");
+    /// registry.export(&export_path);
+    ///
+    /// Repository::init(new_home.join("json"));
+    /// let mapping = vec![(old_home.join("json"), new_home.join("json"))];
+    /// let imported = Registry::import(&export_path, env::temp_dir().join("registry_import.json"), &mapping);
+    /// assert!(imported.get_by_local_location(new_home.join("json")).is_some());
+    /// assert!(new_home.join("json").join("manifest.json").is_file());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the file does not exist or does not contain a [`RegistryExport`]
+    pub fn import<P: AsRef<Path>, Q: AsRef<Path>>(export: P, location: Q, mapping: &[(PathBuf, PathBuf)]) -> Self {
+        let export_location = std::fs::canonicalize(&export).unwrap_or_else(|_| export.as_ref().to_path_buf());
+        let export = RegistryExport::load(export);
+        let root = export.root.clone();
+        let resolve = |p: &Path| map_path(root.join(from_portable(p.to_string_lossy())), mapping);
+
+        let mut registry: Registry = serde_json::from_str(&export.registry).unwrap();
+        registry.location = location.as_ref().to_path_buf();
+        registry.map_paths(resolve);
+        if let Ok(source) = Url::from_file_path(&export_location) {
+            for package in &registry.packages {
+                registry.provenance.entry(package.local_location.clone()).or_insert_with(|| Provenance {
+                    source: source.clone(),
+                    fetcher: "import".to_string(),
+                    checksum: None,
+                    reference: None,
+                    revision: None,
+                });
+            }
+        }
+        for (manifest, contents) in &export.manifests {
+            let manifest = resolve(manifest);
+            if manifest.parent().is_some_and(Path::is_dir) && !manifest.exists() {
+                write(manifest, contents).unwrap();
+            }
+        }
+        registry.save().unwrap();
+        registry
+    }
+
+    /// Rewrites every path stored in the [`Registry`]
+    fn map_paths<F: Fn(&Path) -> PathBuf>(&mut self, f: F) {
+        self.packages = std::mem::take(&mut self.packages)
+            .into_iter()
+            .map(|mut p| {
+                p.local_location = f(&p.local_location);
+                p
+            })
+            .collect();
+        self.provenance = std::mem::take(&mut self.provenance)
+            .into_iter()
+            .map(|(path, provenance)| (f(&path), provenance))
+            .collect();
+        self.artifacts_root = self.artifacts_root.as_deref().map(&f);
+        self.allowed_roots = self.allowed_roots.iter().map(|p| f(p)).collect();
+        self.cache_directory = self.cache_directory.as_deref().map(&f);
+        if let Some(index) = &mut self.remote_index {
+            index.checkout = f(&index.checkout);
+        }
+        if let Some(journal) = &mut self.journal {
+            journal.location = f(&journal.location);
+        }
+    }
+
+    /// Rewrites every path stored in the [`Registry`] to its canonical form, see [`canonicalize`],
+    /// and merges [`Package`]s registered more than once, e.g. through a symbolic link or with different case
+    /// Registries written before paths were canonicalized are migrated like this whenever they are loaded,
+    /// this also saves the result
+    /// Returns the locations of the [`Package`]s that changed, each with its canonical location, sorted
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::paths::canonicalize;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_normalize_paths");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// let path = env::temp_dir().join("registry_normalize_paths.json");
+    /// let detour = package_path.join("src").join("..").display().to_string();
+    /// fs::write(&path, format!("{{\"packages\": [{{\"local_location\": {:?}, \"remote_location\": null}}]}}", detour)).unwrap();
+    ///
+    /// let mut registry = Registry::load(&path);
+    /// assert_eq!(registry.count_packages(), 1);
+    /// assert!(registry.contains(&package));
+    /// assert!(registry.normalize_paths().is_empty());
+    /// assert_eq!(registry.get_by_source(canonicalize(&package_path).join("a.sac")), Some(&package));
+    /// ```
+    pub fn normalize_paths(&mut self) -> Vec<(PathBuf, PathBuf)> {
+        let rewritten = self.canonicalize_paths();
+        if !rewritten.is_empty() {
+            self.audit("normalize_paths", vec![rewritten.len().to_string()], "ok");
+            self.persist();
+        }
+        rewritten
+    }
+
+    /// Rewrites every stored path to its canonical form and merges [`Package`]s that end up at the same location,
+    /// keeping the one whose remote location matches its repository
+    fn canonicalize_paths(&mut self) -> Vec<(PathBuf, PathBuf)> {
+        let mut rewritten: Vec<(PathBuf, PathBuf)> = self.packages.iter()
+            .map(|p| (p.local_location.clone(), canonicalize(&p.local_location)))
+            .filter(|(from, to)| from != to)
+            .collect();
+        rewritten.sort();
+        self.map_paths(|p: &Path| canonicalize(p));
+
+        let mut merged: HashMap<PathBuf, Package> = HashMap::new();
+        for package in std::mem::take(&mut self.packages) {
+            match merged.get(&package.local_location) {
+                Some(kept) if kept.remote_location == kept.current_remote_location() => {}
+                _ => {
+                    merged.insert(package.local_location.clone(), package);
+                }
+            }
         }
-        panic!("No registry found @ {}", path.as_ref().display())
+        self.packages = merged.into_values().collect();
+        rewritten
+    }
+
+    /// Checks whether the [`Registry`] was loaded from a backup because its file was only partially written
+    pub fn recovered_from_backup(&self) -> bool {
+        self.recovered
     }
 
     /// Retrieves the [`Package`] that is registered at the given [`Path`]
@@ -157,237 +703,7507 @@ impl Registry {
     pub fn get_by_local_location<P: AsRef<Path>>(&self, local_location: P) -> Option<&Package> {
         let inferred_working_directory = infer_working_directory(local_location);
         self.packages.iter().find(|p|p.local_location == inferred_working_directory)
+            .or_else(|| self.packages.iter().find(|p| same_path(&p.local_location, &inferred_working_directory)))
     }
 
     pub fn search_by_module_identifiers(&self, module_identifiers: &[String]) -> Vec<&Package> {
         self.packages.iter().filter(|p|p.has_modules_with_identifiers(module_identifiers)).collect()
     }
 
-    /// Checks if the [`Registry`] contains a certain [`Package`]
-    ///
-    /// # Arguments
-    /// * `package` - A reference to a [`Package`] that needs to be checked
-    pub fn contains(&self, package: &Package) -> bool {
-        self.packages.contains(package)
-    }
-
-    /// Returns how many [`Package`]s are in the [`Registry`]
-    pub fn count_packages(&self) -> usize {
-        self.packages.len()
-    }
-
-    /// Check whether the number of packages in the [`Registry`] is 0
-    pub fn is_empty(&self) -> bool {
-        self.packages.is_empty()
-    }
-
-    /// Adds a [`Package`] to the [`Registry`] and saves the [`Registry`]
+    /// Searches all registered [`Module`]s for identifiers matching `query`
+    /// Results are ranked: exact matches first, then prefix matches, then fuzzy matches.
+    /// Results with equal scores are ordered by identifier and [`Package`] location
     ///
     /// # Arguments
-    /// * `package` - A [`Package`] that needs to be added
+    /// * `query` - The text to search for
+    /// * `offset` - The number of ranked results to skip
+    /// * `limit` - The maximum number of results to return
     ///
     /// # Examples
     /// ```
-    /// # use std::env;
+    /// # use std::{env, fs};
     /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
     /// # use knapsac_lib::package::Package;
     /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::search::MatchKind;
     ///
-    /// let mut registry = Registry::initialize(env::temp_dir().join("registry.json"));
-    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_search_ranked.json"));
+    /// let package_path = env::temp_dir().join("mock_package_search_ranked");
     /// Repository::init(&package_path);
     /// let package = Package::create(&package_path);
-    /// assert!(registry.is_empty());
-    /// registry.add(package.clone());
-    /// assert!(registry.contains(&package));
+    /// for name in ["json", "json_parser", "my_json", "xml"] {
+    ///     fs::write(package_path.join(format!("{}.sac", name)), "").unwrap();
+    ///     package.add_module(Module::create(format!("{}.sac", name), None));
+    /// }
+    /// registry.add(package);
+    ///
+    /// let results = registry.search_modules("json", 0, 10);
+    /// let identifiers: Vec<&str> = results.iter().map(|r| r.module().identifier.as_str()).collect();
+    /// assert_eq!(identifiers, vec!["json", "json_parser", "my_json"]);
+    /// assert_eq!(results[2].kind(), MatchKind::Fuzzy);
+    ///
+    /// let page = registry.search_modules("json", 1, 1);
+    /// assert_eq!(page[0].module().identifier, "json_parser");
+    /// assert!(page[0].metadata().description().is_none());
     /// ```
-    /// Adding the same [`Package`] twice does not create duplicate entries
+    pub fn search_modules(&self, query: &str, offset: usize, limit: usize) -> Vec<SearchResult<'_>> {
+        let mut results: Vec<SearchResult> = self.packages
+            .iter()
+            .flat_map(|package| {
+                let manifest = package.load_manifest();
+                let metadata = manifest.metadata;
+                manifest.modules.into_iter().filter_map(move |module| {
+                    rank(query, &module.identifier).map(|(kind, score)| SearchResult {
+                        package,
+                        module,
+                        metadata: metadata.clone(),
+                        kind,
+                        score,
+                    })
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.module.identifier.cmp(&b.module.identifier))
+                .then_with(|| a.package.local_location.cmp(&b.package.local_location))
+        });
+        results.into_iter().skip(offset).take(limit).collect()
+    }
+
+    /// Searches the registered [`Package`]s for `query` as configured by the [`SearchOptions`]
+    /// [`Module`] identifiers, [`Package`] identifiers and [`PackageMetadata`] are matched exactly, by prefix, by substring or fuzzily
+    /// Results are ranked by score, then by the [`MatchField`] they were found in, then by text and [`Package`] location
+    ///
+    /// # Arguments
+    /// * `query` - The text to search for
+    /// * `options` - The [`SearchOptions`] to apply
+    ///
+    /// # Examples
     /// ```
-    /// # use std::env;
+    /// # use std::{env, fs};
     /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
     /// # use knapsac_lib::package::Package;
     /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::search::{MatchField, MatchKind, SearchOptions};
     ///
-    /// let mut registry = Registry::initialize(env::temp_dir().join("registry.json"));
-    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_search_options.json"));
+    /// let package_path = env::temp_dir().join("mock_package_search_options");
+    /// # let _ = fs::remove_dir_all(&package_path);
     /// Repository::init(&package_path);
-    /// # assert!(package_path.is_dir());
-    /// let package = Package::create(package_path);
-    ///
-    /// assert!(registry.is_empty());
-    ///
-    /// registry.add(package.clone());
-    ///
-    /// assert!(registry.contains(&package));
-    /// assert_eq!(registry.count_packages(), 1);
+    /// let package = Package::create(&package_path);
+    /// for name in ["Json", "my_json", "jxsxoxn"] {
+    ///     fs::write(package_path.join(format!("{}.sac", name)), "").unwrap();
+    ///     package.add_module(Module::create(format!("{}.sac", name), None));
+    /// }
+    /// let mut metadata = package.metadata();
+    /// metadata.set_description(Some("Reads and writes JSON".to_string()));
+    /// package.set_metadata(metadata);
+    /// registry.add(package);
     ///
-    /// registry.add(package.clone());
+    /// let results = registry.search("json", &SearchOptions::default());
+    /// let texts: Vec<&str> = results.iter().map(|r| r.text()).collect();
+    /// assert_eq!(texts, vec!["Json", "my_json", "Reads and writes JSON", "jxsxoxn"]);
+    /// assert_eq!(results[1].kind(), MatchKind::Substring);
+    /// assert_eq!(results[1].positions(), &[3, 4, 5, 6]);
+    /// assert_eq!(results[2].field(), MatchField::Description);
     ///
-    /// assert!(registry.contains(&package));
-    /// assert_eq!(registry.count_packages(), 1);
+    /// let mut options = SearchOptions::default();
+    /// options.set_case_sensitive(true);
+    /// options.set_minimum_kind(MatchKind::Substring);
+    /// let results = registry.search("json", &options);
+    /// assert_eq!(results.len(), 1);
+    /// assert_eq!(results[0].module().unwrap().identifier, "my_json");
     /// ```
-    pub fn add(&mut self, package: Package) {
-        self.packages.insert(package);
-        self.save().unwrap();
+    ///
+    /// [`PackageMetadata`]: crate::metadata::PackageMetadata
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Vec<SearchMatch> {
+        let mut results: Vec<SearchMatch> = vec![];
+        for package in &self.packages {
+            let manifest = package.load_manifest();
+            let mut candidates: Vec<(MatchField, String, Option<Module>)> = vec![];
+            if options.modules {
+                candidates.extend(manifest.modules.iter().map(|m| (MatchField::ModuleIdentifier, m.identifier.clone(), Some(m.clone()))));
+                candidates.extend(manifest.modules.iter().filter_map(|m| Some((MatchField::ModuleDescription, m.description.clone()?, Some(m.clone())))));
+            }
+            if options.packages {
+                candidates.push((MatchField::PackageIdentifier, package.identifier(), None));
+            }
+            if options.metadata {
+                let metadata = &manifest.metadata;
+                candidates.extend(metadata.name.iter().map(|n| (MatchField::Name, n.clone(), None)));
+                candidates.extend(metadata.keywords.iter().map(|k| (MatchField::Keyword, k.clone(), None)));
+                candidates.extend(metadata.authors.iter().map(|a| (MatchField::Author, a.clone(), None)));
+                candidates.extend(metadata.description.iter().map(|d| (MatchField::Description, d.clone(), None)));
+            }
+            for (field, text, module) in candidates {
+                if let Some((kind, score, positions)) = find(query, &text, options.case_sensitive) {
+                    if kind >= options.minimum_kind {
+                        results.push(SearchMatch {
+                            package: package.local_location.clone(),
+                            module,
+                            field,
+                            text,
+                            positions,
+                            kind,
+                            score,
+                        });
+                    }
+                }
+            }
+        }
+        results.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.field.cmp(&b.field))
+                .then_with(|| a.text.cmp(&b.text))
+                .then_with(|| a.package.cmp(&b.package))
+        });
+        results.into_iter().skip(options.offset).take(options.limit).collect()
     }
 
-    /// Removes a [`Package`] from the [`Registry`] and saves the [`Registry`]
+    /// Retrieves the registered [`Package`] whose `local_location` contains the given [`Path`]
+    /// Unlike [`Registry::get_by_local_location`] this does not require the [`Path`] to exist
+    /// A [`Path`] reaching the [`Package`] through a symbolic link or with different case is found as well, see [`canonicalize`]
     ///
     /// # Arguments
-    /// * `package` - A reference to a [`Package`] that needs to removed
+    /// * `source` - An absolute [`Path`] pointing to a file inside a [`Package`]
+    pub fn get_by_source<P: AsRef<Path>>(&self, source: P) -> Option<&Package> {
+        self.packages.iter().find(|p|source.as_ref().starts_with(&p.local_location))
+            .or_else(|| self.packages.iter().find(|p| starts_with(&source, &p.local_location)))
+    }
+
+    /// Returns an iterator over the registered [`Package`]s, ordered by `local_location`
+    pub fn iter_packages(&self) -> impl Iterator<Item = &Package> {
+        let mut packages: Vec<&Package> = self.packages.iter().collect();
+        packages.sort_by(|a, b| a.local_location.cmp(&b.local_location));
+        packages.into_iter()
+    }
+
+    /// Returns an iterator over every registered [`Module`] and [`Executable`] as a [`RegistryItem`],
+    /// paired with the [`Package`] providing it
+    /// Items are ordered by [`Package`] like [`Registry::iter_packages`], then by location, [`Module`]s before [`Executable`]s
+    /// The manifest of a [`Package`] is read when the iterator reaches it
     ///
     /// # Examples
     /// ```
     /// # use std::{env, fs};
     /// # use git2::Repository;
+    /// # use knapsac_lib::executable::Executable;
+    /// # use knapsac_lib::module::Module;
     /// # use knapsac_lib::package::Package;
     /// # use knapsac_lib::registry::Registry;
     ///
-    /// let mut registry = Registry::initialize(env::temp_dir().join("registry.json"));
-    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_iter_items.json"));
+    /// let package_path = env::temp_dir().join("mock_package_iter_items");
+    /// # let _ = fs::remove_dir_all(&package_path);
     /// Repository::init(&package_path);
+    /// fs::write(package_path.join("b.sac"), "").unwrap();
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// fs::write(package_path.join("main.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("b.sac", None));
+    /// package.add_module(Module::create("a.sac", None));
+    /// package.add_executable(Executable::create("main.sac"));
+    /// registry.add(package);
     ///
-    /// assert!(package_path.exists());
-    ///
-    /// let package = Package::create(package_path);
-    /// registry.add(package.clone());
-    ///
-    /// assert!(registry.contains(&package));
+    /// let locations: Vec<_> = registry.iter_items().map(|(_, i)| i.location().to_path_buf()).collect();
+    /// assert_eq!(locations, vec!["a.sac", "b.sac", "main.sac"].iter().map(Into::into).collect::<Vec<std::path::PathBuf>>());
+    /// assert_eq!(registry.iter_modules().count(), 2);
+    /// assert_eq!(registry.iter_executables().next().unwrap().1.binary_name(), "main");
+    /// ```
+    pub fn iter_items(&self) -> impl Iterator<Item = (&Package, RegistryItem)> {
+        self.iter_packages().flat_map(|package| {
+            let manifest = package.load_manifest();
+            let mut modules: Vec<Module> = manifest.modules.into_iter().collect();
+            modules.sort_by(|a, b| a.location.cmp(&b.location));
+            let mut executables: Vec<Executable> = manifest.executables.into_iter().collect();
+            executables.sort_by(|a, b| a.location.cmp(&b.location));
+            modules.into_iter()
+                .map(RegistryItem::Module)
+                .chain(executables.into_iter().map(RegistryItem::Executable))
+                .map(move |item| (package, item))
+        })
+    }
+
+    /// Returns an iterator over every registered [`Module`], paired with the [`Package`] providing it
+    /// [`Module`]s are ordered like [`Registry::iter_items`]
+    pub fn iter_modules(&self) -> impl Iterator<Item = (&Package, Module)> {
+        self.iter_packages().flat_map(|package| package.modules().map(move |module| (package, module)))
+    }
+
+    /// Returns an iterator over every registered [`Executable`], paired with the [`Package`] providing it
+    /// [`Executable`]s are ordered like [`Registry::iter_items`]
+    pub fn iter_executables(&self) -> impl Iterator<Item = (&Package, Executable)> {
+        self.iter_items().filter_map(|(package, item)| match item {
+            RegistryItem::Executable(executable) => Some((package, executable)),
+            RegistryItem::Module(_) => None,
+        })
+    }
+
+    /// Retrieves the [`Module`] or [`Executable`] registered at the given source [`Path`]
     ///
-    /// registry.remove(&package);
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the file of a [`RegistryItem`]
     ///
-    /// assert!(registry.is_empty());
-    /// ```
-    /// If the [`Registry`] does not contain the [`Package`] referenced, it does nothing.
+    /// # Examples
     /// ```
-    /// # use std::env;
+    /// # use std::{env, fs};
     /// # use git2::Repository;
+    /// # use knapsac_lib::executable::Executable;
+    /// # use knapsac_lib::item::RegistryItem;
+    /// # use knapsac_lib::module::Module;
     /// # use knapsac_lib::package::Package;
     /// # use knapsac_lib::registry::Registry;
     ///
-    /// let mut registry = Registry::initialize(env::temp_dir().join("registry.json"));
-    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_get_item.json"));
+    /// let package_path = env::temp_dir().join("mock_package_get_item");
+    /// # let _ = fs::remove_dir_all(&package_path);
     /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// fs::write(package_path.join("main.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// package.add_executable(Executable::create("main.sac"));
+    /// registry.add(package);
     ///
-    /// assert!(package_path.is_dir());
-    /// assert!(registry.is_empty());
-    ///
-    /// let package = Package::create(package_path);
-    /// registry.remove(&package);
-    ///
-    /// assert!(registry.is_empty());
+    /// assert!(matches!(registry.get_item(package_path.join("a.sac")), Some(RegistryItem::Module(_))));
+    /// assert!(matches!(registry.get_item(package_path.join("main.sac")), Some(RegistryItem::Executable(_))));
+    /// registry.remove_item(package_path.join("main.sac"));
+    /// assert!(registry.get_item(package_path.join("main.sac")).is_none());
     /// ```
-    pub fn remove(&mut self, package: &Package) {
-        self.packages.remove(package);
-        self.save().unwrap();
-    }
-
-    /// Serializes the [`Registry`] to a JSON file located at the [`Registry`]'s `location`
-    /// This overwrites the file located at that location
-    pub(crate) fn save(&self) -> Result<(), &str> {
-        let path = self.location.to_path_buf();
-
-        if path.is_relative() {
-            return Err("Path is relative")
-        }
-
-        if let Some(ext) = path.extension() {
-            if ext != "json" {
-                return Err("Path does not point to a JSON file")
-            }
-        } else {
-            return Err("Path does not point to a file")
+    pub fn get_item<P: AsRef<Path>>(&self, source: P) -> Option<RegistryItem> {
+        let package = self.get_by_source(&source)?;
+        let location = package.strip_prefix(&source);
+        let manifest = package.load_manifest();
+        if let Some(executable) = manifest.get_executable_by_location(&location) {
+            return Some(RegistryItem::Executable(executable.clone()));
         }
-
-        let contents = serde_json::to_string(self).unwrap();
-
-        write(path, contents).unwrap();
-        Ok(())
+        manifest.get_module_owning(&package.local_location, &location).cloned().map(RegistryItem::Module)
     }
-}
 
+    /// Resolves an identifier imported by a source file to the [`Module`] it refers to, e.g. for jump-to-definition in an editor
+    /// The [`Module`]s of the [`Package`] containing the source file are searched first,
+    /// then those of the [`Package`]s its [`Dependency`]s resolve to and finally the output directories of its stray dependencies,
+    /// where a file named after the identifier, optionally prefixed with `lib`, is looked for
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the importing source file
+    /// * `identifier` - The identifier of the imported [`Module`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::item::ImportKind;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_resolve_import.json"));
+    /// let path = env::temp_dir().join("mock_package_resolve_import");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("a.sac"), "module a;").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let target = registry.resolve_import(path.join("main.sac"), "a").unwrap();
+    /// assert_eq!(target.kind(), ImportKind::Local);
+    /// assert_eq!(target.source(), Some(path.join("a.sac").as_path()));
+    /// assert_eq!(target.package(), Some(path.as_path()));
+    /// assert!(registry.resolve_import(path.join("main.sac"), "b").is_none());
+    /// ```
+    pub fn resolve_import<P: AsRef<Path>>(&self, source: P, identifier: &str) -> Option<ImportTarget> {
+        let package = self.get_by_source(&source)?;
+        let mut dependencies = package.dependencies();
+        dependencies.sort_by(|a, b| a.git_url.cmp(&b.git_url));
+        let providers = std::iter::once((ImportKind::Local, package))
+            .chain(dependencies.iter().filter_map(|d| self.resolve_dependency(d)).map(|p| (ImportKind::Dependency, p)));
+        for (kind, provider) in providers {
+            if let Some(module) = provider.load_manifest().modules.into_iter().find(|m| m.identifier == identifier) {
+                let defining = provider.local_location.join(&module.location);
+                return Some(ImportTarget {
+                    kind,
+                    output: self.get_output(&defining),
+                    source: Some(defining),
+                    package: Some(provider.local_location.clone()),
+                });
+            }
+        }
+        let library = format!("lib{}", identifier);
+        package.strays().into_iter().find(|stray| {
+            list_files(stray).iter()
+                .filter_map(|f| f.file_stem())
+                .any(|stem| stem == identifier || stem == library.as_str())
+        }).map(|stray| ImportTarget {
+            kind: ImportKind::Stray,
+            source: None,
+            output: Some(stray),
+            package: None,
+        })
+    }
+
+    /// Removes the [`Module`] or [`Executable`] registered at the given source [`Path`] from its [`Package`]
+    /// and returns it, if there was one
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the file of a [`RegistryItem`]
+    pub fn remove_item<P: AsRef<Path>>(&self, source: P) -> Option<RegistryItem> {
+        let package = self.get_by_source(&source)?;
+        let item = self.get_item(&source)?;
+        let manifests = self.manifest_contents(&[package]);
+        match &item {
+            RegistryItem::Module(module) => package.remove_module(module),
+            RegistryItem::Executable(executable) => package.remove_executable(executable),
+        }
+        self.journal_manifests(manifests);
+        self.audit("remove_item", vec![source.as_ref().display().to_string()], "ok");
+        Some(item)
+    }
+
+    /// Takes a [`RegistrySnapshot`]: an owned, serializable description of every registered [`Package`],
+    /// the files it registers and the [`Dependency`]s it declares
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::executable::Executable;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::snapshot::ItemKind;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_snapshot.json"));
+    /// let package_path = env::temp_dir().join("mock_package_snapshot");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// fs::write(package_path.join("main.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// package.add_executable(Executable::create("main.sac"));
+    /// registry.add(package);
+    ///
+    /// let snapshot = registry.snapshot();
+    /// let view = snapshot.get_package(&package_path).unwrap();
+    /// assert_eq!(view.identifier(), "mock_package_snapshot");
+    /// assert_eq!(view.items().len(), 2);
+    /// assert_eq!(snapshot.get_item(package_path.join("a.sac")).unwrap().kind(), ItemKind::Module);
+    /// assert_eq!(snapshot.get_item(package_path.join("main.sac")).unwrap().name(), "main");
+    /// ```
+    pub fn snapshot(&self) -> RegistrySnapshot {
+        let mut packages: Vec<PackageView> = self.packages.iter().map(|p| self.package_view(p)).collect();
+        packages.sort_by(|a, b| a.local_location.cmp(&b.local_location));
+        RegistrySnapshot { packages }
+    }
+
+    /// Compares this [`Registry`] with another one and reports what the other one adds, removes or changes, see [`DiffReport`]
+    /// Both [`Registry`]s are described as they are now, including the manifests of their [`Package`]s,
+    /// to review an operation on the same [`Package`]s take a [`Registry::snapshot`] before it and use [`DiffReport::compare`]
+    ///
+    /// # Arguments
+    /// * `other` - The [`Registry`] holding the new state
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let before = Registry::initialize(env::temp_dir().join("registry_diff_before.json"));
+    /// let mut after = Registry::initialize(env::temp_dir().join("registry_diff_after.json"));
+    /// let package_path = env::temp_dir().join("mock_package_diff");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// after.add(Package::create(&package_path));
+    ///
+    /// let report = before.diff(&after);
+    /// assert_eq!(report.added_packages(), &[package_path]);
+    /// assert!(after.diff(&after).is_empty());
+    /// ```
+    pub fn diff(&self, other: &Registry) -> DiffReport {
+        DiffReport::compare(&self.snapshot(), &other.snapshot())
+    }
+
+    /// Returns a copy of the [`Registry`] that never touches disk, to speculatively change it and compare the result
+    /// Saving, flushing and dropping the copy do not write the registry file, and it records nothing in the [`Journal`]
+    /// or the [`AuditLog`]; the manifests of its [`Package`]s are still shared with the original
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let path = env::temp_dir().join("registry_clone_in_memory.json");
+    /// let registry = Registry::initialize(&path);
+    /// let package_path = env::temp_dir().join("mock_package_clone_in_memory");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    ///
+    /// let mut copy = registry.clone_in_memory();
+    /// assert_eq!(copy, registry);
+    /// copy.add(Package::create(&package_path));
+    /// assert_ne!(copy, registry);
+    /// assert_eq!(registry.diff(&copy).added_packages(), &[package_path]);
+    ///
+    /// drop(copy);
+    /// assert!(Registry::load(&path).is_empty());
+    /// ```
+    ///
+    /// [`AuditLog`]: crate::audit::AuditLog
+    pub fn clone_in_memory(&self) -> Registry {
+        let mut copy = self.clone();
+        copy.in_memory = true;
+        copy.audit_log = None;
+        copy.journal = None;
+        copy.journal_baseline = None;
+        copy
+    }
+
+    /// Checks whether the [`Registry`] is a copy that never touches disk, see [`Registry::clone_in_memory`]
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory
+    }
+
+    fn package_view(&self, package: &Package) -> PackageView {
+        let manifest = package.load_manifest();
+        let root = &package.local_location;
+        let mut items: Vec<ItemView> = vec![];
+        items.extend(manifest.modules.iter().map(|m| ItemView {
+            kind: ItemKind::Module,
+            source: root.join(&m.location),
+            name: m.identifier.clone(),
+            language: m.language.clone(),
+            output: self.get_output(root.join(&m.location)),
+            description: m.description.clone(),
+        }));
+        items.extend(manifest.executables.iter().map(|e| ItemView {
+            kind: ItemKind::Executable,
+            source: root.join(&e.location),
+            name: e.binary_name(),
+            language: e.language.clone(),
+            output: Some(root.join(e.binary_path())),
+            description: None,
+        }));
+        items.extend(manifest.tests.iter().map(|t| {
+            let executable = Executable::create(&t.location);
+            ItemView {
+                kind: ItemKind::Test,
+                source: root.join(&t.location),
+                name: executable.binary_name(),
+                language: None,
+                output: Some(root.join(executable.binary_path())),
+                description: None,
+            }
+        }));
+        items.sort_by(|a, b| a.source.cmp(&b.source));
+        let mut dependencies: Vec<DependencyView> = manifest.dependencies.iter().map(|d| DependencyView {
+            git_url: d.git_url.clone(),
+            identifier: d.identifier.clone(),
+            requirement: d.requirement.clone(),
+            alias: d.alias.clone(),
+            kind: d.kind,
+            resolved: self.resolve_dependency(d).map(|p| p.local_location.clone()),
+        }).collect();
+        dependencies.sort_by(|a, b| a.git_url.cmp(&b.git_url));
+        PackageView {
+            identifier: package.identifier(),
+            local_location: root.clone(),
+            remote_location: package.remote_location.clone(),
+            version: package.version(),
+            language: manifest.language.clone(),
+            metadata: manifest.metadata.clone(),
+            items,
+            dependencies,
+            strays: manifest.strays.iter().cloned().collect(),
+        }
+    }
+
+    /// Retrieves the registered [`Package`] a [`Dependency`] refers to
+    /// When several versions of the [`Package`] are registered the highest [`Version`] satisfying
+    /// the [`Dependency`]'s requirement is picked
+    ///
+    /// # Arguments
+    /// * `dependency` - A reference to the [`Dependency`] that needs to be resolved
+    pub fn resolve_dependency(&self, dependency: &Dependency) -> Option<&Package> {
+        self.packages
+            .iter()
+            .filter(|p|p.remote_location.as_ref() == Some(&dependency.git_url))
+            .map(|p|(p.version(), p))
+            .filter(|(v, _)|match (&dependency.requirement, v) {
+                (None, _) => true,
+                (Some(requirement), Some(version)) => requirement.matches(version),
+                (Some(_), None) => false,
+            })
+            .max_by(|a, b|a.0.cmp(&b.0))
+            .map(|(_, p)|p)
+    }
+
+    /// Declares a [`Dependency`] of the [`Package`] containing the item at the given source [`Path`],
+    /// referred to locally by the given alias instead of its identifier, see [`Dependency::set_alias`]
+    /// Declaring a [`Dependency`] that is already declared replaces it
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the file of a [`RegistryItem`]
+    /// * `dependency` - The [`Dependency`] to declare
+    /// * `alias` - The local name of the [`Dependency`], or `None` to refer to it by its identifier
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::{Dependency, DependencyError};
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_alias.json"));
+    /// let path = env::temp_dir().join("mock_package_alias");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// registry.add(Package::create(&path));
+    ///
+    /// let json = Dependency::declare(Url::parse("https://github.com/jcuppen/JSON").unwrap(), "json", None);
+    /// let other = Dependency::declare(Url::parse("https://example.com/other/json").unwrap(), "json", None);
+    /// registry.add_dependency_to_item(path.join("main.sac"), json, None).unwrap();
+    /// assert_eq!(
+    ///     registry.add_dependency_to_item(path.join("main.sac"), other.clone(), None),
+    ///     Err(DependencyError::AliasConflict("json".to_string())),
+    /// );
+    /// registry.add_dependency_to_item(path.join("main.sac"), other, Some("json2")).unwrap();
+    ///
+    /// let package = registry.get_by_source(path.join("main.sac")).unwrap();
+    /// let aliased = package.dependencies().into_iter().find(|d| d.alias() == Some("json2")).unwrap();
+    /// assert_eq!(aliased.identifier(), Some("json"));
+    /// assert_eq!(aliased.local_name(), Some("json2"));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`DependencyError::UnknownSource`] when no registered [`Package`] contains `source`,
+    /// [`DependencyError::AliasConflict`] when another [`Dependency`] already goes by the same local name,
+    /// or [`DependencyError::UnsatisfiedRequirement`] when the [`Package`] depended on is registered,
+    /// but none of its registered versions satisfies the requirement of the [`Dependency`]
+    pub fn add_dependency_to_item<P: AsRef<Path>>(&self, source: P, mut dependency: Dependency, alias: Option<&str>) -> Result<(), DependencyError> {
+        let package = self.get_by_source(&source)
+            .ok_or_else(|| DependencyError::UnknownSource(source.as_ref().to_path_buf()))?;
+        dependency.set_alias(alias);
+        let others: Vec<Dependency> = package.dependencies().into_iter().filter(|d| d != &dependency).collect();
+        self.check_dependency(&dependency, &others)?;
+        let manifests = self.manifest_contents(&[package]);
+        package.add_dependency(dependency);
+        self.journal_manifests(manifests);
+        Ok(())
+    }
+
+    /// Retrieves the [`Dependency`] the item at the given source [`Path`] refers to by the given local name,
+    /// its alias or else its declared identifier, together with the registered [`Package`] it resolves to
+    /// The [`Dependency`] reports the alias, the [`Package`] its canonical identifier
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the file of a [`RegistryItem`]
+    /// * `name` - The local name of the [`Dependency`]
+    pub fn resolve_local_name<P: AsRef<Path>>(&self, source: P, name: &str) -> Option<(Dependency, &Package)> {
+        let dependency = self.get_by_source(&source)?
+            .dependencies()
+            .into_iter()
+            .find(|d| d.local_name() == Some(name))?;
+        let package = self.resolve_dependency(&dependency)?;
+        Some((dependency, package))
+    }
+
+    /// Replaces the [`Dependency`]s of the [`Package`] containing the item at the given source [`Path`],
+    /// e.g. to take over the dependencies declared in an external build file
+    /// Every [`Dependency`] is referred to locally by the name it is paired with, see [`Dependency::set_alias`]
+    /// Every [`Dependency`] is checked like in [`Registry::add_dependency_to_item`] before anything changes,
+    /// and the manifest is written once, so either all [`Dependency`]s are replaced or none are
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the file of a [`RegistryItem`]
+    /// * `dependencies` - The new [`Dependency`]s, each paired with its local name
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::{Dependency, DependencyError};
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_set_dependencies.json"));
+    /// let from = env::temp_dir().join("mock_package_set_dependencies_from");
+    /// let to = env::temp_dir().join("mock_package_set_dependencies_to");
+    /// # let _ = fs::remove_dir_all(&from);
+    /// # let _ = fs::remove_dir_all(&to);
+    /// Repository::init(&from);
+    /// Repository::init(&to);
+    /// registry.add(Package::create(&from));
+    /// registry.add(Package::create(&to));
+    ///
+    /// let json = Dependency::create(Url::parse("https://github.com/jcuppen/JSON").unwrap());
+    /// let other = Dependency::create(Url::parse("https://example.com/json").unwrap());
+    /// assert_eq!(
+    ///     registry.set_dependencies(from.join("main.sac"), vec![("json".to_string(), json.clone()), ("json".to_string(), other.clone())]),
+    ///     Err(DependencyError::AliasConflict("json".to_string())),
+    /// );
+    /// assert!(registry.get_by_source(from.join("main.sac")).unwrap().dependencies().is_empty());
+    ///
+    /// registry.set_dependencies(from.join("main.sac"), vec![("json".to_string(), json), ("other".to_string(), other)]).unwrap();
+    /// registry.copy_dependencies(from.join("main.sac"), to.join("main.sac")).unwrap();
+    /// assert_eq!(registry.get_by_source(to.join("main.sac")).unwrap().dependencies().len(), 2);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the [`DependencyError`] of the first [`Dependency`] that does not pass the checks,
+    /// see [`Registry::add_dependency_to_item`]
+    pub fn set_dependencies<P: AsRef<Path>>(&self, source: P, dependencies: Vec<(String, Dependency)>) -> Result<(), DependencyError> {
+        let package = self.get_by_source(&source)
+            .ok_or_else(|| DependencyError::UnknownSource(source.as_ref().to_path_buf()))?;
+        let mut checked: Vec<Dependency> = vec![];
+        for (name, mut dependency) in dependencies {
+            dependency.set_alias(Some(name).filter(|n| Some(n.as_str()) != dependency.identifier()));
+            checked.retain(|d| d != &dependency);
+            self.check_dependency(&dependency, &checked)?;
+            checked.push(dependency);
+        }
+        let mut manifest = package.load_manifest();
+        manifest.dependencies = checked.into_iter().collect();
+        manifest.save(package.manifest_location());
+        Ok(())
+    }
+
+    /// Copies the [`Dependency`]s of the [`Package`] containing the item at `from`
+    /// to the [`Package`] containing the item at `to`, replacing [`Dependency`]s on the same [`Package`]
+    /// Every [`Dependency`] is checked like in [`Registry::add_dependency_to_item`] before anything changes,
+    /// and the manifest is written once, so either all [`Dependency`]s are copied or none are
+    ///
+    /// # Arguments
+    /// * `from` - An absolute [`Path`] pointing to the file of the [`RegistryItem`] to copy from
+    /// * `to` - An absolute [`Path`] pointing to the file of the [`RegistryItem`] to copy to
+    ///
+    /// # Errors
+    /// Returns the [`DependencyError`] of the first [`Dependency`] that does not pass the checks,
+    /// see [`Registry::add_dependency_to_item`]
+    pub fn copy_dependencies<P: AsRef<Path>, Q: AsRef<Path>>(&self, from: P, to: Q) -> Result<(), DependencyError> {
+        let copied = self.get_by_source(&from)
+            .ok_or_else(|| DependencyError::UnknownSource(from.as_ref().to_path_buf()))?
+            .dependencies();
+        let package = self.get_by_source(&to)
+            .ok_or_else(|| DependencyError::UnknownSource(to.as_ref().to_path_buf()))?;
+        let mut manifest = package.load_manifest();
+        for dependency in copied {
+            manifest.dependencies.remove(&dependency);
+            let others: Vec<Dependency> = manifest.dependencies.iter().cloned().collect();
+            self.check_dependency(&dependency, &others)?;
+            manifest.dependencies.insert(dependency);
+        }
+        manifest.save(package.manifest_location());
+        Ok(())
+    }
+
+    /// Registers the dependencies listed in a dependency file emitted by a compiler while building the item at the given source [`Path`]
+    /// Every listed path belonging to another registered [`Package`] becomes a [`Dependency`] on that [`Package`],
+    /// any other existing path becomes a stray dependency on its directory, see [`Package::add_stray`]
+    /// Relative paths are taken relative to the directory of the dependency file
+    /// The manifest is written once, after every listed path was handled
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the file of a [`RegistryItem`]
+    /// * `path` - A [`Path`] pointing to the dependency file
+    /// * `format` - The [`DepfileFormat`] the dependency file is written in
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::depfile::DepfileFormat;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_depfile.json"));
+    /// let path = env::temp_dir().join("mock_package_depfile");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// registry.add(Package::create(&path));
+    /// let library = env::temp_dir().join("mock_library_depfile");
+    /// fs::create_dir_all(&library).unwrap();
+    /// fs::write(library.join("lib.h"), "").unwrap();
+    ///
+    /// let depfile = path.join("main.d");
+    /// fs::write(&depfile, format!("main.o: main.sac \\\n  {}\n", library.join("lib.h").display())).unwrap();
+    /// let import = registry.import_depfile(path.join("main.sac"), &depfile, DepfileFormat::Make).unwrap();
+    /// assert_eq!(import.strays(), &[library.clone()]);
+    /// assert_eq!(import.skipped(), &[path.join("main.sac")]);
+    /// assert_eq!(registry.get_by_source(path.join("main.sac")).unwrap().strays(), vec![library]);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`DependencyError::UnknownSource`] when no registered [`Package`] contains `source`,
+    /// [`DependencyError::Depfile`] when the dependency file cannot be read or parsed,
+    /// or the [`DependencyError`] of a new [`Dependency`] that does not pass the checks of [`Registry::add_dependency_to_item`]
+    pub fn import_depfile<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, path: Q, format: DepfileFormat) -> Result<DepfileImport, DependencyError> {
+        let package = self.get_by_source(&source)
+            .ok_or_else(|| DependencyError::UnknownSource(source.as_ref().to_path_buf()))?;
+        let path = path.as_ref();
+        let listed = read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| format.parse(&c))
+            .map_err(|e| DependencyError::Depfile(path.to_path_buf(), e))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+
+        let mut manifest = package.load_manifest();
+        let mut import = DepfileImport::default();
+        for listed in listed {
+            let listed = normalize(base.join(listed));
+            if listed.starts_with(&package.local_location) {
+                import.skip(&listed);
+                continue;
+            }
+            match self.get_by_source(&listed) {
+                Some(owner) => match &owner.remote_location {
+                    Some(url) if !import.dependencies.contains(url) => {
+                        let dependency = match owner.package_id() {
+                            Some(identifier) => Dependency::declare(url.clone(), identifier.as_str(), None),
+                            None => Dependency::create(url.clone()),
+                        };
+                        if !manifest.has_dependency(&dependency) {
+                            let others: Vec<Dependency> = manifest.dependencies.iter().cloned().collect();
+                            self.check_dependency(&dependency, &others)?;
+                            manifest.add_dependency(dependency);
+                        }
+                        import.dependencies.push(url.clone());
+                    }
+                    Some(_) => {}
+                    None => import.skip(&listed),
+                },
+                None => {
+                    let directory = match listed.is_dir() {
+                        true => Some(listed.as_path()),
+                        false => listed.parent().filter(|_| listed.exists()),
+                    };
+                    match directory {
+                        Some(directory) if !import.strays.iter().any(|s| s == directory) => {
+                            manifest.strays.insert(directory.to_path_buf());
+                            import.strays.push(directory.to_path_buf());
+                        }
+                        Some(_) => {}
+                        None => import.skip(&listed),
+                    }
+                }
+            }
+        }
+        manifest.save(package.manifest_location());
+        Ok(import)
+    }
+
+    /// Checks that a [`Dependency`] can be declared next to the given other [`Dependency`]s,
+    /// see [`Registry::add_dependency_to_item`]
+    fn check_dependency(&self, dependency: &Dependency, others: &[Dependency]) -> Result<(), DependencyError> {
+        if let Some(name) = dependency.local_name() {
+            if others.iter().any(|d| d.local_name() == Some(name)) {
+                return Err(DependencyError::AliasConflict(name.to_string()));
+            }
+        }
+        if let Some(requirement) = &dependency.requirement {
+            let registered = self.packages.iter().any(|p| p.remote_location.as_ref() == Some(&dependency.git_url));
+            if registered && self.resolve_dependency(dependency).is_none() {
+                return Err(DependencyError::UnsatisfiedRequirement(dependency.git_url.clone(), requirement.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Retrieves the registered [`Package`] with the given identifier and [`Version`]
+    /// Several versions of a [`Package`] can be registered side by side, each in their own directory
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier of the [`Package`], its name or the last segment of its remote location
+    /// * `version` - The [`Version`] to retrieve, or `None` to retrieve the highest registered [`Version`]
+    pub fn get_by_identifier(&self, identifier: &str, version: Option<&Version>) -> Option<&Package> {
+        self.packages
+            .iter()
+            .filter(|p|p.identifier() == identifier)
+            .map(|p|(p.version(), p))
+            .filter(|(v, _)|version.is_none() || v.as_ref() == version)
+            .max_by(|a, b|a.0.cmp(&b.0))
+            .map(|(_, p)|p)
+    }
+
+    /// Re-hashes the files of every registered [`Package`] with the given identifier
+    /// and compares them to the hashes recorded when it was published, see [`Package::verify_integrity`]
+    /// Returns an [`IntegrityIssue`] for every file that was tampered with, corrupted or removed
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier of the [`Package`]
+    ///
+    /// # Panics
+    /// Panics when no [`Package`] with the given identifier is registered
+    pub fn verify(&self, identifier: &str) -> Vec<IntegrityIssue> {
+        let packages: Vec<&Package> = self.packages.iter().filter(|p|p.identifier() == identifier).collect();
+        if packages.is_empty() {
+            panic!("No package registered with identifier {}", identifier);
+        }
+        packages.iter().flat_map(|p|p.verify_integrity()).collect()
+    }
+
+    /// Retrieves the registered [`Package`]s that depend on the given [`Package`]
+    ///
+    /// # Arguments
+    /// * `package` - A reference to the [`Package`] whose dependents are needed
+    /// * `transitive` - Whether [`Package`]s that depend on it indirectly are included as well
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_dependents.json"));
+    /// let mut packages = vec![];
+    /// for name in ["json", "parser", "app"] {
+    ///     let path = env::temp_dir().join(format!("mock_package_dependents_{}", name));
+    ///     # let _ = fs::remove_dir_all(&path);
+    ///     let url = format!("https://example.com/{}", name);
+    ///     Repository::init(&path).unwrap().remote("origin", &url).unwrap();
+    ///     let package = Package::create(&path);
+    ///     registry.add(package.clone());
+    ///     packages.push((package, Url::parse(&url).unwrap()));
+    /// }
+    /// let (json, json_url) = &packages[0];
+    /// let (parser, parser_url) = &packages[1];
+    /// let (app, _) = &packages[2];
+    /// parser.add_dependency(Dependency::create(json_url.clone()));
+    /// app.add_dependency(Dependency::create(parser_url.clone()));
+    ///
+    /// assert_eq!(registry.dependents_of_package(json, false), vec![parser]);
+    /// assert_eq!(registry.dependents_of_package(json, true).len(), 2);
+    /// ```
+    pub fn dependents_of_package(&self, package: &Package, transitive: bool) -> Vec<&Package> {
+        let mut dependents: Vec<&Package> = vec![];
+        let mut pending = vec![package];
+        while let Some(current) = pending.pop() {
+            let direct = self.packages.iter().filter(|p| {
+                p.load_manifest().dependencies.iter().any(|d|self.resolve_dependency(d) == Some(current))
+            });
+            for dependent in direct {
+                if dependent != package && !dependents.contains(&dependent) {
+                    dependents.push(dependent);
+                    if transitive {
+                        pending.push(dependent);
+                    }
+                }
+            }
+        }
+        dependents
+    }
+
+    /// Retrieves the registered [`Package`]s that depend on a [`Package`] providing a [`Module`] with the given `identifier`
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier of the [`Module`] whose dependents are needed
+    /// * `transitive` - Whether [`Package`]s that depend on it indirectly are included as well
+    pub fn dependents_of(&self, identifier: &str, transitive: bool) -> Vec<&Package> {
+        let mut dependents: Vec<&Package> = vec![];
+        for provider in self.search_by_module_identifiers(&[identifier.to_string()]) {
+            for dependent in self.dependents_of_package(provider, transitive) {
+                if !dependents.contains(&dependent) {
+                    dependents.push(dependent);
+                }
+            }
+        }
+        dependents
+    }
+
+    /// Exports the dependency graph of the [`Registry`] as text in the given [`GraphFormat`]
+    /// [`Package`]s, the [`Module`]s they provide and [`Dependency`]s that do not resolve are styled differently
+    ///
+    /// # Arguments
+    /// * `format` - The [`GraphFormat`] to export to
+    /// * `root` - When given, only this [`Package`] and the [`Package`]s it depends on are exported
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::graph::GraphFormat;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_graph.json"));
+    /// let package_path = env::temp_dir().join("mock_package_graph");
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// package.add_dependency(Dependency::create(Url::parse("https://example.com/json").unwrap()));
+    /// registry.add(package.clone());
+    ///
+    /// let dot = registry.export_graph(GraphFormat::Dot, Some(&package));
+    /// assert!(dot.starts_with("digraph"));
+    /// assert!(dot.contains("p0 -> u0;"));
+    /// let mermaid = registry.export_graph(GraphFormat::Mermaid, None);
+    /// assert!(mermaid.contains("p0_m0([\"a\"])"));
+    /// ```
+    pub fn export_graph(&self, format: GraphFormat, root: Option<&Package>) -> String {
+        let mut packages: Vec<&Package> = match root {
+            Some(root) => {
+                let mut included = vec![root];
+                let mut index = 0;
+                while index < included.len() {
+                    for dependency in included[index].load_manifest().dependencies {
+                        if let Some(resolved) = self.resolve_dependency(&dependency) {
+                            if !included.contains(&resolved) {
+                                included.push(resolved);
+                            }
+                        }
+                    }
+                    index += 1;
+                }
+                included
+            }
+            None => self.packages.iter().collect(),
+        };
+        packages.sort_by(|a, b|a.local_location.cmp(&b.local_location));
+
+        let mut graph = Graph::default();
+        let mut unresolved: Vec<Url> = vec![];
+        for (index, package) in packages.iter().enumerate() {
+            graph.node(format!("p{}", index), package.local_location.display().to_string(), NodeKind::Package);
+
+            let manifest = package.load_manifest();
+            let mut modules: Vec<&Module> = manifest.modules.iter().collect();
+            modules.sort_by(|a, b|a.location.cmp(&b.location));
+            for (module_index, module) in modules.iter().enumerate() {
+                let id = format!("p{}_m{}", index, module_index);
+                graph.node(id.clone(), module.identifier.clone(), NodeKind::Module);
+                graph.edge(format!("p{}", index), id, EdgeKind::Provides);
+            }
+
+            let mut dependencies: Vec<&Dependency> = manifest.dependencies.iter().collect();
+            dependencies.sort_by(|a, b|a.git_url.cmp(&b.git_url));
+            for dependency in dependencies {
+                let target = match self.resolve_dependency(dependency).and_then(|r|packages.iter().position(|p|*p == r)) {
+                    Some(target) => format!("p{}", target),
+                    None => {
+                        let position = unresolved.iter().position(|u|*u == dependency.git_url).unwrap_or_else(|| {
+                            unresolved.push(dependency.git_url.clone());
+                            graph.node(format!("u{}", unresolved.len() - 1), dependency.git_url.to_string(), NodeKind::Unresolved);
+                            unresolved.len() - 1
+                        });
+                        format!("u{}", position)
+                    }
+                };
+                graph.edge(format!("p{}", index), target, EdgeKind::DependsOn);
+            }
+        }
+        graph.render(format)
+    }
+
+    /// Checks whether the [`Module`] at the given [`Path`] can be built
+    ///
+    /// Errors are reported when the [`Module`] or its [`Package`] is not registered,
+    /// when a [`Dependency`] does not resolve to a registered [`Package`]
+    /// or when [`Package`]s depend on each other in a cycle.
+    /// [`DependencyKind::Dev`] [`Dependency`]s are only checked for the [`Package`] of the checked [`Module`],
+    /// and an unresolved [`DependencyKind::Optional`] [`Dependency`] is only a warning.
+    /// Errors are also reported when a [`Module`] of a [`Package`] depended on was compiled to an artifact
+    /// with an ABI tag other than the one the checked [`Module`] needs, see [`Registry::set_artifact_tag`],
+    /// or when its output directory is set but does not exist.
+    /// Warnings are reported for [`Module`] files that no longer exist, for an output directory of the checked [`Module`]
+    /// that does not exist yet, for unregistered [`Language`]s
+    /// and for [`Package`]s that were not built or tested with the locally installed compiler version.
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::diagnostics::Diagnostic;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_check.json"));
+    /// let package_path = env::temp_dir().join("mock_package_check");
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package.clone());
+    /// assert!(registry.check(package_path.join("a.sac")).is_ok());
+    ///
+    /// let url = Url::parse("https://example.com/unknown").unwrap();
+    /// package.add_dependency(Dependency::create(url.clone()));
+    /// let report = registry.check(package_path.join("a.sac"));
+    /// assert_eq!(report.errors(), &[Diagnostic::UnresolvedDependency(url)]);
+    /// ```
+    ///
+    /// Output directories that are set but missing are reported, for the checked [`Module`] and its dependencies
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::diagnostics::Diagnostic;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::{OutputPolicy, Registry};
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_check_outputs.json"));
+    /// registry.set_output_policy(OutputPolicy::SourceDirectory);
+    /// let app_path = env::temp_dir().join("mock_package_check_outputs_app");
+    /// let library_path = env::temp_dir().join("mock_package_check_outputs_library");
+    /// # let _ = fs::remove_dir_all(&app_path);
+    /// # let _ = fs::remove_dir_all(&library_path);
+    /// Repository::init(&app_path);
+    /// let library_url = Url::parse("https://example.com/check_outputs_library").unwrap();
+    /// Repository::init(&library_path).unwrap().remote("origin", library_url.as_str()).unwrap();
+    /// fs::write(app_path.join("main.sac"), "").unwrap();
+    /// fs::write(library_path.join("lib.sac"), "").unwrap();
+    /// let app = Package::create(&app_path);
+    /// app.add_module(Module::create("main.sac", None));
+    /// app.add_dependency(Dependency::create(library_url));
+    /// let library = Package::create(&library_path);
+    /// library.add_module(Module::create("lib.sac", None));
+    /// registry.add(app);
+    /// registry.add(library);
+    ///
+    /// registry.set_output(app_path.join("main.sac"), "out");
+    /// registry.set_output(library_path.join("lib.sac"), "out");
+    /// let report = registry.check(app_path.join("main.sac"));
+    /// assert_eq!(report.warnings(), &[Diagnostic::MissingOutputDirectory(app_path.join("out"))]);
+    /// assert_eq!(report.errors(), &[Diagnostic::MissingOutputDirectory(library_path.join("out"))]);
+    ///
+    /// fs::create_dir_all(app_path.join("out")).unwrap();
+    /// fs::create_dir_all(library_path.join("out")).unwrap();
+    /// assert!(registry.check(app_path.join("main.sac")).is_ok());
+    /// ```
+    pub fn check<P: AsRef<Path>>(&self, source: P) -> DiagnosticsReport {
+        let mut report = DiagnosticsReport::default();
+        let source = source.as_ref();
+
+        let package = match self.get_by_source(source) {
+            Some(package) => package,
+            None => {
+                report.error(Diagnostic::UnregisteredPackage(source.to_path_buf()));
+                return report;
+            }
+        };
+
+        let mut compiler_version = None;
+        let mut abi_tag = None;
+        match package.get_module_by_location(package.strip_prefix(source)) {
+            Some(module) => {
+                if !source.is_file() {
+                    report.warning(Diagnostic::MissingModuleFile(source.to_path_buf()));
+                }
+                if let Some(output) = self.missing_output(source, &module) {
+                    report.warning(Diagnostic::MissingOutputDirectory(output));
+                }
+                if let Some(name) = module.language.clone().or_else(|| package.language()) {
+                    match self.get_module_language(package, &module) {
+                        Some(language) => {
+                            compiler_version = language.detect_version();
+                            abi_tag = compiler_version.as_deref().map(|v| language.abi_tag(v));
+                        }
+                        None => report.warning(Diagnostic::UnknownLanguage(name)),
+                    }
+                }
+            }
+            None => report.error(Diagnostic::UnregisteredModule(source.to_path_buf())),
+        }
+
+        self.check_package(package, compiler_version.as_deref(), abi_tag.as_deref(), &mut vec![], &mut report);
+        report
+    }
+
+    fn check_package<'a>(&'a self, package: &'a Package, compiler_version: Option<&str>, abi_tag: Option<&str>, stack: &mut Vec<&'a Package>, report: &mut DiagnosticsReport) {
+        if let Some(index) = stack.iter().position(|p|*p == package) {
+            let mut cycle: Vec<PathBuf> = stack[index..].iter().map(|p|p.local_location.clone()).collect();
+            cycle.push(package.local_location.clone());
+            report.error(Diagnostic::DependencyCycle(cycle));
+            return;
+        }
+
+        if let Some(version) = compiler_version {
+            if !package.is_compatible_with_compiler(version) {
+                report.warning(Diagnostic::IncompatibleCompiler(package.local_location.clone(), version.to_string()));
+            }
+        }
+
+        if !stack.is_empty() {
+            for module in package.load_manifest().modules {
+                let source = package.local_location.join(&module.location);
+                if let Some(output) = self.missing_output(&source, &module) {
+                    report.error(Diagnostic::MissingOutputDirectory(output));
+                }
+                if let Some((expected, found)) = abi_tag.zip(module.artifact_tag).filter(|(e, f)| f != e) {
+                    report.error(Diagnostic::IncompatibleArtifact(source, found, expected.to_string()));
+                }
+            }
+        }
+
+        let nested = !stack.is_empty();
+        stack.push(package);
+        for dependency in package.load_manifest().dependencies {
+            if nested && dependency.kind == DependencyKind::Dev {
+                continue;
+            }
+            match (self.resolve_dependency(&dependency), dependency.kind) {
+                (Some(resolved), _) => self.check_package(resolved, compiler_version, abi_tag, stack, report),
+                (None, DependencyKind::Optional) => report.warning(Diagnostic::UnresolvedDependency(dependency.git_url)),
+                (None, _) => report.error(Diagnostic::UnresolvedDependency(dependency.git_url)),
+            }
+        }
+        stack.pop();
+    }
+
+    /// Returns the resolved output directory of the given [`Module`] when it is set but does not exist
+    fn missing_output(&self, source: &Path, module: &Module) -> Option<PathBuf> {
+        let output = module.output_for_target(self.target.as_deref(), None)?;
+        self.resolve_output(source, output).filter(|o| !o.is_dir())
+    }
+
+    /// Collects the given [`Package`] and every registered [`Package`] it depends on, directly or indirectly
+    /// Every [`Package`] appears once, the given [`Package`] first
+    pub(crate) fn dependency_closure<'a>(&'a self, package: &'a Package) -> Vec<&'a Package> {
+        self.dependency_closure_of(package, &DependencyKind::ALL)
+    }
+
+    /// Collects the given [`Package`] and every registered [`Package`] it depends on, directly or indirectly,
+    /// following only [`Dependency`]s of the given [`DependencyKind`]s
+    /// [`DependencyKind::Dev`] [`Dependency`]s are only followed from the given [`Package`], never from the [`Package`]s it depends on
+    /// Every [`Package`] appears once, the given [`Package`] first
+    ///
+    /// # Arguments
+    /// * `package` - The [`Package`] to start from
+    /// * `kinds` - The [`DependencyKind`]s of the [`Dependency`]s to follow
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::{Dependency, DependencyKind};
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_closure_kinds.json"));
+    /// let tested_path = env::temp_dir().join("mock_package_closure_tested");
+    /// let tester_path = env::temp_dir().join("mock_package_closure_tester");
+    /// # let _ = fs::remove_dir_all(&tested_path);
+    /// # let _ = fs::remove_dir_all(&tester_path);
+    /// Repository::init(&tested_path);
+    /// let tester_url = Url::parse("https://example.com/tester").unwrap();
+    /// Repository::init(&tester_path).unwrap().remote("origin", tester_url.as_str()).unwrap();
+    /// let tested = Package::create(&tested_path);
+    /// let mut dependency = Dependency::create(tester_url);
+    /// dependency.set_kind(DependencyKind::Dev);
+    /// tested.add_dependency(dependency);
+    /// registry.add(tested.clone());
+    /// registry.add(Package::create(&tester_path));
+    ///
+    /// assert_eq!(registry.dependency_closure_of(&tested, &DependencyKind::ALL).len(), 2);
+    /// assert_eq!(registry.dependency_closure_of(&tested, &[DependencyKind::Normal]).len(), 1);
+    /// ```
+    pub fn dependency_closure_of<'a>(&'a self, package: &'a Package, kinds: &[DependencyKind]) -> Vec<&'a Package> {
+        let mut closure = vec![package];
+        let mut index = 0;
+        while index < closure.len() {
+            for dependency in closure[index].dependencies() {
+                if !kinds.contains(&dependency.kind) || (index > 0 && dependency.kind == DependencyKind::Dev) {
+                    continue;
+                }
+                if let Some(resolved) = self.resolve_dependency(&dependency) {
+                    if !closure.contains(&resolved) {
+                        closure.push(resolved);
+                    }
+                }
+            }
+            index += 1;
+        }
+        closure
+    }
+
+    /// Collects the features every [`Package`] in the [`Registry::dependency_closure`] of the given [`Package`] is needed with,
+    /// the union of the features enabled by the [`Dependency`]s on it, keyed by `local_location`
+    pub(crate) fn enabled_features(&self, package: &Package) -> HashMap<PathBuf, BTreeSet<String>> {
+        let mut features: HashMap<PathBuf, BTreeSet<String>> = HashMap::new();
+        for dependent in self.dependency_closure(package) {
+            for dependency in dependent.dependencies() {
+                if let Some(resolved) = self.resolve_dependency(&dependency) {
+                    features.entry(resolved.local_location.clone()).or_default().extend(dependency.features);
+                }
+            }
+        }
+        features
+    }
+
+    /// Returns the command line building the [`Module`] at the given source [`Path`], the compiler first
+    /// It consists of the compiler and flags of the [`Module`]'s [`Language`], the extra flags of the [`Module`]
+    /// or else those of its [`Package`], a flag pointing at every [`Package`] it depends on and the source file itself
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_invocation.json"));
+    /// registry.register_language("sac", Language::create("sac2c", vec!["-v1".to_string()]));
+    /// let package_path = env::temp_dir().join("mock_package_invocation");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// fs::write(package_path.join("b.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.set_language(Some("sac".to_string()));
+    /// package.set_flags(vec!["-O3".to_string()]);
+    /// package.add_module(Module::create("a.sac", None));
+    /// let mut b = Module::create("b.sac", None);
+    /// b.set_flags(vec!["-g".to_string()]);
+    /// package.add_module(b);
+    /// registry.add(package);
+    ///
+    /// let a = package_path.join("a.sac");
+    /// assert_eq!(registry.invocation(&a), vec!["sac2c", "-v1", "-O3", &a.display().to_string()]);
+    /// let b = package_path.join("b.sac");
+    /// assert_eq!(registry.invocation(&b), vec!["sac2c", "-v1", "-g", &b.display().to_string()]);
+    /// ```
+    /// Auxiliary sources of a [`Module`] follow its entry point
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_invocation_sources.json"));
+    /// registry.register_language("sac", Language::create("sac2c", vec![]));
+    /// let package_path = env::temp_dir().join("mock_package_invocation_sources");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::create_dir_all(package_path.join("json")).unwrap();
+    /// fs::write(package_path.join("json.sac"), "").unwrap();
+    /// fs::write(package_path.join("json/parse.sac"), "").unwrap();
+    /// fs::write(package_path.join("json/print.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.set_language(Some("sac".to_string()));
+    /// let mut module = Module::create("json.sac", None);
+    /// module.add_source("json");
+    /// package.add_module(module);
+    /// registry.add(package);
+    ///
+    /// let invocation = registry.invocation(package_path.join("json.sac"));
+    /// assert_eq!(&invocation[1..], &[
+    ///     package_path.join("json.sac").display().to_string(),
+    ///     package_path.join("json/parse.sac").display().to_string(),
+    ///     package_path.join("json/print.sac").display().to_string(),
+    /// ]);
+    /// assert!(registry.get_item(package_path.join("json/print.sac")).is_some());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at the given [`Path`] or when its [`Language`] is unknown
+    pub fn invocation<P: AsRef<Path>>(&self, source: P) -> Vec<String> {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .unwrap_or_else(|| panic!("No package registered for {}", source.display()));
+        let module = package.get_module_by_location(package.strip_prefix(source))
+            .unwrap_or_else(|| panic!("No module registered @ {}", source.display()));
+        let language = self.get_module_language(package, &module)
+            .unwrap_or_else(|| panic!("No language known for {}", source.display()));
+        self.command_line(package, Some(&module), &language, source)
+    }
+
+    fn command_line(&self, package: &Package, module: Option<&Module>, language: &Language, source: &Path) -> Vec<String> {
+        let mut invocation = vec![language.compiler.clone()];
+        invocation.extend(language.flags().iter().cloned());
+        match module.filter(|m| !m.flags.is_empty()) {
+            Some(module) => invocation.extend(module.flags.iter().cloned()),
+            None => invocation.extend(package.flags()),
+        }
+        if let Some(template) = &language.include_template {
+            for dependency in &self.dependency_closure(package)[1..] {
+                invocation.push(template.replace("{path}", &dependency.local_location.display().to_string()));
+            }
+        }
+        invocation.push(source.display().to_string());
+        if let Some(module) = module {
+            let files = module.source_files(&package.local_location);
+            invocation.extend(files[1..].iter().map(|f| package.local_location.join(f).display().to_string()));
+        }
+        invocation
+    }
+
+    /// Sets the extra flags passed to the compiler when building the [`Module`] at the given source [`Path`]
+    /// and returns the changed [`Module`], see [`Module::set_flags`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `flags` - The extra flags, empty flags are inherited from the [`Package`]
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at the given [`Path`], see [`Registry::try_set_flags`]
+    pub fn set_flags<P: AsRef<Path>>(&self, source: P, flags: Vec<String>) -> Module {
+        self.try_set_flags(source, flags).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Sets the extra compiler flags of a [`Module`] like [`Registry::set_flags`], returning an [`Error`] instead of panicking
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `flags` - The extra flags, empty flags are inherited from the [`Package`]
+    ///
+    /// # Errors
+    /// Returns [`ModuleError::UnknownSource`] when no [`Module`] is registered at the given [`Path`]
+    /// and [`ModuleError::MissingSource`] when a source file of the [`Module`] no longer exists
+    pub fn try_set_flags<P: AsRef<Path>>(&self, source: P, flags: Vec<String>) -> Result<Module, Error> {
+        let (package, module) = self.registered_module(source.as_ref())?;
+
+        let mut changed = module.clone();
+        changed.set_flags(flags.clone());
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, changed.clone())?;
+        self.journal_manifests(manifests);
+        let mut arguments = vec![source.as_ref().display().to_string()];
+        arguments.extend(flags);
+        self.audit("set_flags", arguments, "ok");
+        Ok(changed)
+    }
+
+    /// Writes a [`FailureBundle`] for the [`Module`] at the given source [`Path`] to the given [`Path`] and returns it
+    /// The [`FailureBundle`] contains the compiler invocation, the detected compiler version, the [`Diagnostic`]s
+    /// found by [`Registry::check`] and the slice of the [`Registry`] describing every [`Package`] involved
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `output` - The output the failed build produced, if it was captured
+    /// * `path` - A [`Path`] pointing to where the [`FailureBundle`] needs to be written to
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::bundle::FailureBundle;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_failure_bundle.json"));
+    /// registry.register_language("sac", Language::create("sac2c", vec!["-O3".to_string()]));
+    /// let package_path = env::temp_dir().join("mock_package_failure_bundle");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.set_language(Some("sac".to_string()));
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let bundle_path = env::temp_dir().join("failure_bundle.json");
+    /// registry.failure_bundle(package_path.join("a.sac"), Some("error: oops"), &bundle_path);
+    ///
+    /// let bundle = FailureBundle::load(&bundle_path);
+    /// assert_eq!(bundle.invocation()[0], "sac2c");
+    /// assert_eq!(bundle.output(), Some("error: oops"));
+    /// assert_eq!(bundle.packages().len(), 1);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] contains the given source [`Path`]
+    pub fn failure_bundle<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, output: Option<&str>, path: Q) -> FailureBundle {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .unwrap_or_else(|| panic!("No package registered for {}", source.display()));
+        let closure = self.dependency_closure(package);
+
+        let module = package.get_module_by_location(package.strip_prefix(source));
+        let language = match &module {
+            Some(module) => self.get_module_language(package, module),
+            None => self.get_package_language(package),
+        };
+        let invocation = match &language {
+            Some(language) => self.command_line(package, module.as_ref(), language, source),
+            None => vec![source.display().to_string()],
+        };
+
+        let report = self.check(source);
+        let bundle = FailureBundle {
+            source: source.to_path_buf(),
+            created: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            knapsac_version: env!("CARGO_PKG_VERSION").to_string(),
+            compiler_version: language.as_ref().and_then(|l| l.detect_version()),
+            language,
+            invocation,
+            output: output.map(String::from),
+            diagnostics: report.errors().iter().chain(report.warnings()).map(|d| d.to_string()).collect(),
+            packages: closure.iter().map(|p| {
+                let manifest = p.load_manifest();
+                BundledPackage {
+                    local_location: p.local_location.clone(),
+                    remote_location: p.remote_location.clone(),
+                    dependencies: manifest.dependencies.into_iter().map(|d| d.git_url).collect(),
+                    modules: manifest.modules.into_iter().collect(),
+                    language: manifest.language,
+                    provenance: self.provenance.get(&p.local_location).cloned(),
+                }
+            }).collect(),
+        };
+        bundle.save(path);
+        bundle
+    }
+
+    /// Copies every [`Package`] the [`Module`] at the given source [`Path`] depends on, directly or indirectly,
+    /// into `<target>/vendor` and writes a [`Lockfile`] pointing at the copies to `<target>/knapsac.lock`
+    /// An existing copy or [`Lockfile`] is replaced, so the result does not depend on earlier runs
+    ///
+    /// Every copy contains the manifest of the [`Package`] and the output directory of each of its [`Module`]s
+    /// as `output/<module identifier>`, see [`Registry::get_output`]
+    /// Optional [`Module`]s are only copied when a dependent enables one of their features, see [`Package::add_feature`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `target` - A [`Path`] pointing to the directory of the project
+    /// * `include_sources` - Whether the source files of the [`Module`]s are copied as well
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] contains the given source [`Path`]
+    pub fn vendor<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, target: Q, include_sources: bool) -> Lockfile {
+        self.vendor_kinds(source, target, include_sources, &DependencyKind::ALL)
+    }
+
+    /// Like [`Registry::vendor`], copying only the [`Package`]s reached through [`Dependency`]s of the given [`DependencyKind`]s,
+    /// see [`Registry::dependency_closure_of`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `target` - A [`Path`] pointing to the directory of the project
+    /// * `include_sources` - Whether the source files of the [`Module`]s are copied as well
+    /// * `kinds` - The [`DependencyKind`]s of the [`Dependency`]s to follow
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] contains the given source [`Path`]
+    pub fn vendor_kinds<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, target: Q, include_sources: bool, kinds: &[DependencyKind]) -> Lockfile {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .unwrap_or_else(|| panic!("No package registered for {}", source.display()));
+
+        let features = self.enabled_features(package);
+        let mut lockfile = Lockfile::default();
+        for dependency in &self.dependency_closure_of(package, kinds)[1..] {
+            let version = dependency.version();
+            let name = match &version {
+                Some(version) => format!("{}-{}", dependency.identifier(), version),
+                None => dependency.identifier(),
+            };
+            let relative = Path::new("vendor").join(name);
+            let destination = target.as_ref().join(&relative);
+            let _ = remove_dir_all(&destination);
+            create_dir_all(&destination).unwrap();
+
+            let manifest_location = dependency.manifest_location();
+            copy(&manifest_location, destination.join(manifest_location.file_name().unwrap())).unwrap();
+            let enabled = features.get(&dependency.local_location).cloned().unwrap_or_default();
+            for module in dependency.required_modules(&enabled) {
+                let module_source = dependency.local_location.join(&module.location);
+                if include_sources {
+                    for file in module.source_files(&dependency.local_location) {
+                        if dependency.local_location.join(&file).is_file() {
+                            create_dir_all(destination.join(&file).parent().unwrap()).unwrap();
+                            copy(dependency.local_location.join(&file), destination.join(&file)).unwrap();
+                        }
+                    }
+                }
+                if let Some(output) = self.get_output(&module_source).filter(|o| o.is_dir()) {
+                    copy_directory(output, destination.join("output").join(&module.identifier));
+                }
+            }
+
+            lockfile.packages.push(LockedPackage {
+                identifier: dependency.identifier(),
+                version,
+                source: dependency.remote_location.clone(),
+                checksum: Package::checksum(&dependency.local_location),
+                path: relative,
+            });
+        }
+        lockfile.save(target.as_ref().join(Lockfile::FILE_NAME));
+        lockfile
+    }
+
+    /// Checks if the [`Registry`] contains a certain [`Package`]
+    ///
+    /// # Arguments
+    /// * `package` - A reference to a [`Package`] that needs to be checked
+    pub fn contains(&self, package: &Package) -> bool {
+        self.packages.contains(package)
+    }
+
+    /// Returns how many [`Package`]s are in the [`Registry`]
+    pub fn count_packages(&self) -> usize {
+        self.packages.len()
+    }
+
+    /// Check whether the number of packages in the [`Registry`] is 0
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// Adds a [`Package`] to the [`Registry`] and saves the [`Registry`]
+    /// The [`Package`] is registered at the canonical form of its location, see [`canonicalize`],
+    /// so adding it again through a symbolic link or with different case does not register it twice
+    ///
+    /// # Arguments
+    /// * `package` - A [`Package`] that needs to be added
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry.json"));
+    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// assert!(registry.is_empty());
+    /// registry.add(package.clone());
+    /// assert!(registry.contains(&package));
+    /// ```
+    /// Adding the same [`Package`] twice does not create duplicate entries
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry.json"));
+    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&package_path);
+    /// # assert!(package_path.is_dir());
+    /// let package = Package::create(package_path);
+    ///
+    /// assert!(registry.is_empty());
+    ///
+    /// registry.add(package.clone());
+    ///
+    /// assert!(registry.contains(&package));
+    /// assert_eq!(registry.count_packages(), 1);
+    ///
+    /// registry.add(package.clone());
+    ///
+    /// assert!(registry.contains(&package));
+    /// assert_eq!(registry.count_packages(), 1);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when the [`Package`] lies outside the [`Registry`]'s allowed roots, see [`Registry::set_allowed_roots`]
+    /// See [`Registry::try_add`] for a non-panicking variant
+    pub fn add(&mut self, package: Package) {
+        self.try_add(package).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Adds a [`Package`] to the [`Registry`] like [`Registry::add`], explaining why it cannot be added
+    ///
+    /// # Arguments
+    /// * `package` - The [`Package`] that needs to be added
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::Error;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_try_add.json"));
+    /// registry.set_allowed_roots(vec![env::temp_dir().join("allowed_try_add")]);
+    /// let package_path = env::temp_dir().join("mock_package_try_add");
+    /// Repository::init(&package_path);
+    ///
+    /// let error = registry.try_add(Package::create(&package_path)).unwrap_err();
+    /// assert!(matches!(error, Error::OutsideAllowedRoots(_)));
+    /// assert!(registry.is_empty());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`Error::OutsideAllowedRoots`] when the [`Package`] lies outside the [`Registry`]'s allowed roots
+    /// and [`Error::Manifest`] when its manifest cannot be loaded
+    pub fn try_add(&mut self, mut package: Package) -> Result<(), Error> {
+        package.local_location = canonicalize(&package.local_location);
+        self.check_allowed(&package.local_location)?;
+        if self.doc_extractor.0.is_some() {
+            let mut manifest = Manifest::try_load(package.manifest_location())?;
+            let mut modules: Vec<Module> = manifest.modules.drain().collect();
+            let mut changed = false;
+            for module in &mut modules {
+                changed |= self.describe(&package, module);
+            }
+            manifest.modules.extend(modules);
+            if changed {
+                manifest.save(package.manifest_location());
+            }
+        }
+        self.audit("add", vec![package.local_location.display().to_string()], "ok");
+        self.packages.insert(package);
+        self.persist();
+        Ok(())
+    }
+
+    /// Restores a [`Package`] packed by [`Package::pack`] into the given directory and adds it to the [`Registry`]
+    /// See [`Package::unpack`]
+    /// The archive is recorded as the [`Provenance`] of the [`Package`], so its hooks only run with [`HookPolicy::All`]
+    ///
+    /// # Arguments
+    /// * `archive` - A [`Path`] pointing to a gzipped tarball
+    /// * `destination` - A [`Path`] pointing to a directory that does not exist yet or is empty
+    ///
+    /// # Panics
+    /// Panics when the destination lies outside the [`Registry`]'s allowed roots, contains files
+    /// or when the archive cannot be unpacked, see [`Registry::try_unpack`]
+    pub fn unpack<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, archive: P, destination: Q) -> Package {
+        self.try_unpack(archive, destination).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Restores and registers a [`Package`] like [`Registry::unpack`], returning an [`Error`] instead of panicking
+    ///
+    /// # Arguments
+    /// * `archive` - A [`Path`] pointing to a gzipped tarball
+    /// * `destination` - A [`Path`] pointing to a directory that does not exist yet or is empty
+    ///
+    /// # Errors
+    /// Returns [`Error::OutsideAllowedRoots`] when the destination lies outside the [`Registry`]'s allowed roots
+    /// and the errors of [`Package::try_unpack`] and [`Registry::try_add`]
+    pub fn try_unpack<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, archive: P, destination: Q) -> Result<Package, Error> {
+        self.check_allowed(destination.as_ref())?;
+        let package = Package::try_unpack(&archive, destination)?;
+        let archive = std::fs::canonicalize(&archive).unwrap_or_else(|_| archive.as_ref().to_path_buf());
+        let source = Url::from_file_path(&archive).map_err(|_| Error::RelativePath(archive.clone()))?;
+        self.provenance.insert(canonicalize(&package.local_location), Provenance {
+            source,
+            fetcher: "archive".to_string(),
+            checksum: None,
+            reference: None,
+            revision: None,
+        });
+        self.try_add(package.clone())?;
+        Ok(package)
+    }
+
+    /// Removes a [`Package`] from the [`Registry`] and saves the [`Registry`]
+    ///
+    /// # Arguments
+    /// * `package` - A reference to a [`Package`] that needs to removed
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry.json"));
+    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&package_path);
+    ///
+    /// assert!(package_path.exists());
+    ///
+    /// let package = Package::create(package_path);
+    /// registry.add(package.clone());
+    ///
+    /// assert!(registry.contains(&package));
+    ///
+    /// registry.remove(&package);
+    ///
+    /// assert!(registry.is_empty());
+    /// ```
+    /// If the [`Registry`] does not contain the [`Package`] referenced, it does nothing.
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry.json"));
+    /// let package_path = env::temp_dir().join("mock_package_known");
+    /// Repository::init(&package_path);
+    ///
+    /// assert!(package_path.is_dir());
+    /// assert!(registry.is_empty());
+    ///
+    /// let package = Package::create(package_path);
+    /// registry.remove(&package);
+    ///
+    /// assert!(registry.is_empty());
+    /// ```
+    pub fn remove(&mut self, package: &Package) {
+        self.audit("remove", vec![package.local_location.display().to_string()], "ok");
+        self.packages.remove(package);
+        self.provenance.remove(&package.local_location);
+        self.persist();
+    }
+
+    /// Removes a [`Package`] from the [`Registry`], handles its dependents according to the given [`RemovalPolicy`]
+    /// and saves the [`Registry`]
+    /// Returns every [`Package`] that was removed
+    /// See [`Registry::plan_removal`] to find out what would be removed without removing it
+    ///
+    /// # Arguments
+    /// * `package` - A reference to a [`Package`] that needs to removed
+    /// * `policy` - The [`RemovalPolicy`] that determines what happens to the [`Package`]'s dependents
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::removal::RemovalPolicy;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_remove_policy.json"));
+    /// let url = Url::parse("https://example.com/json").unwrap();
+    /// let json_path = env::temp_dir().join("mock_package_remove_policy_json");
+    /// # let _ = fs::remove_dir_all(&json_path);
+    /// Repository::init(&json_path).unwrap().remote("origin", url.as_str()).unwrap();
+    /// let json = Package::create(&json_path);
+    /// registry.add(json.clone());
+    ///
+    /// let app_path = env::temp_dir().join("mock_package_remove_policy_app");
+    /// Repository::init(&app_path);
+    /// let app = Package::create(&app_path);
+    /// app.add_dependency(Dependency::create(url.clone()));
+    /// registry.add(app.clone());
+    ///
+    /// assert!(registry.remove_with_policy(&json, RemovalPolicy::Forbid).is_err());
+    /// assert!(registry.contains(&json));
+    ///
+    /// registry.remove_with_policy(&json, RemovalPolicy::Detach).unwrap();
+    /// assert!(!registry.contains(&json));
+    /// assert!(!app.has_dependency(&Dependency::create(url)));
+    /// ```
+    pub fn remove_with_policy(&mut self, package: &Package, policy: RemovalPolicy) -> Result<Vec<Package>, RemovalError> {
+        let plan = match self.plan_removal(package, policy) {
+            Ok(plan) => plan,
+            Err(error) => {
+                self.audit("remove_with_policy", vec![package.local_location.display().to_string(), format!("{:?}", policy)], &error.to_string());
+                return Err(error);
+            }
+        };
+
+        for (dependent, url) in &plan.detached {
+            if let Some(dependent) = self.get_by_local_location(dependent) {
+                dependent.remove_dependency(&Dependency::create(url.clone()));
+            }
+        }
+        for package in &plan.packages {
+            self.packages.remove(package);
+            self.provenance.remove(&package.local_location);
+        }
+        self.persist();
+        self.audit("remove_with_policy", vec![package.local_location.display().to_string(), format!("{:?}", policy)], &format!("removed {} package(s)", plan.packages.len()));
+        Ok(plan.packages)
+    }
+
+    /// Uninstalls the [`Package`] with the given identifier: removes it from the [`Registry`] like [`Registry::remove_with_policy`]
+    /// and, when the [`UninstallOptions`] ask for it, deletes the directories of every removed [`Package`]
+    /// Directories with uncommitted changes, see [`Package::uncommitted_changes`], are only deleted when forced
+    /// Nothing is changed when an error is returned
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier of the [`Package`], the highest registered [`Version`] is uninstalled
+    /// * `options` - The [`UninstallOptions`] to use
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::removal::{RemovalError, UninstallOptions};
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_uninstall.json"));
+    /// let package_path = env::temp_dir().join("mock_package_uninstall");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// registry.add(Package::create(&package_path));
+    ///
+    /// let mut options = UninstallOptions::default();
+    /// options.set_delete_files(true);
+    /// let error = registry.uninstall_package("mock_package_uninstall", options).unwrap_err();
+    /// assert!(matches!(error, RemovalError::UncommittedChanges(..)));
+    ///
+    /// options.set_force(true);
+    /// let report = registry.uninstall_package("mock_package_uninstall", options).unwrap();
+    /// assert_eq!(report.deleted().len(), 1);
+    /// assert!(!package_path.exists());
+    /// assert!(registry.is_empty());
+    /// ```
+    pub fn uninstall_package(&mut self, identifier: &str, options: UninstallOptions) -> Result<UninstallReport, RemovalError> {
+        let arguments = vec![identifier.to_string(), format!("{:?}", options)];
+        let package = match self.get_by_identifier(identifier, None) {
+            Some(package) => package.clone(),
+            None => {
+                let error = RemovalError::UnknownPackage(identifier.to_string());
+                self.audit("uninstall_package", arguments, &error.to_string());
+                return Err(error);
+            }
+        };
+        let plan = self.plan_removal(&package, options.policy);
+        let plan = plan.and_then(|plan| {
+            if options.delete_files && !options.force {
+                for removed in plan.packages.iter().filter(|p| p.local_location.is_dir()) {
+                    let changes = removed.uncommitted_changes();
+                    if !changes.is_empty() {
+                        return Err(RemovalError::UncommittedChanges(removed.local_location.clone(), changes));
+                    }
+                }
+            }
+            Ok(plan)
+        });
+        let plan = match plan {
+            Ok(plan) => plan,
+            Err(error) => {
+                self.audit("uninstall_package", arguments, &error.to_string());
+                return Err(error);
+            }
+        };
+
+        let removed = self.remove_with_policy(&package, options.policy)?;
+        let mut report = UninstallReport {
+            packages: removed.iter().map(|p| p.local_location.clone()).collect(),
+            detached: plan.detached,
+            deleted: vec![],
+        };
+        if options.delete_files {
+            for removed in removed.iter().filter(|p| p.local_location.is_dir()) {
+                remove_dir_all(&removed.local_location).unwrap();
+                git_cache::invalidate(&removed.local_location);
+                report.deleted.push(removed.local_location.clone());
+            }
+        }
+        self.audit("uninstall_package", arguments, &format!("deleted {} directory(s)", report.deleted.len()));
+        Ok(report)
+    }
+
+    /// Computes the changes [`Registry::remove_with_policy`] would make, without making them
+    ///
+    /// # Arguments
+    /// * `package` - A reference to a [`Package`] that would be removed
+    /// * `policy` - The [`RemovalPolicy`] that determines what happens to the [`Package`]'s dependents
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::removal::RemovalPolicy;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_plan_removal.json"));
+    /// let url = Url::parse("https://example.com/plan_removal/json").unwrap();
+    /// let json_path = env::temp_dir().join("mock_package_plan_removal_json");
+    /// # let _ = fs::remove_dir_all(&json_path);
+    /// Repository::init(&json_path).unwrap().remote("origin", url.as_str()).unwrap();
+    /// let json = Package::create(&json_path);
+    /// registry.add(json.clone());
+    ///
+    /// let app_path = env::temp_dir().join("mock_package_plan_removal_app");
+    /// # let _ = fs::remove_dir_all(&app_path);
+    /// Repository::init(&app_path);
+    /// let app = Package::create(&app_path);
+    /// app.add_dependency(Dependency::create(url.clone()));
+    /// registry.add(app.clone());
+    ///
+    /// let plan = registry.plan_removal(&json, RemovalPolicy::Detach).unwrap();
+    /// assert_eq!(plan.packages(), &[json.clone()]);
+    /// assert_eq!(plan.detached(), &[(app_path.clone(), url.clone())]);
+    /// assert!(registry.contains(&json));
+    /// assert!(app.has_dependency(&Dependency::create(url)));
+    /// ```
+    pub fn plan_removal(&self, package: &Package, policy: RemovalPolicy) -> Result<RemovalPlan, RemovalError> {
+        let mut plan = RemovalPlan {
+            packages: vec![package.clone()],
+            detached: vec![],
+        };
+        match policy {
+            RemovalPolicy::Forbid => {
+                let dependents = self.dependents_of_package(package, false);
+                if !dependents.is_empty() {
+                    return Err(RemovalError::HasDependents(dependents.iter().map(|p| p.local_location.clone()).collect()));
+                }
+            }
+            RemovalPolicy::Cascade => plan.packages.extend(self.dependents_of_package(package, true).into_iter().cloned()),
+            RemovalPolicy::Detach => {
+                if let Some(url) = &package.remote_location {
+                    for dependent in self.dependents_of_package(package, false) {
+                        plan.detached.push((dependent.local_location.clone(), url.clone()));
+                    }
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Obtains a [`Package`] using the given [`Fetcher`], adds it to the [`Registry`] and records its [`Provenance`]
+    /// The [`Dependency`]s declared in its manifest that cannot be resolved are installed the same way
+    /// See [`Package::fetch`] and [`Registry::try_install`]
+    ///
+    /// # Arguments
+    /// * `fetcher` - The [`Fetcher`] used to obtain the [`Package`]
+    /// * `source` - An [`Url`] pointing to the contents of the [`Package`]
+    /// * `path` - A [`Path`] pointing to where packages need to be downloaded to
+    /// * `checksum` - The expected checksum of the contents, see [`Package::checksum`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use url::Url;
+    /// # use knapsac_lib::fetcher::LocalFetcher;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_install.json"));
+    /// let share = env::temp_dir().join("mock_share_install");
+    /// fs::create_dir_all(&share).unwrap();
+    /// fs::write(share.join("a.sac"), "module a;").unwrap();
+    /// let source = Url::from_directory_path(&share).unwrap();
+    /// let checksum = Package::checksum(&share);
+    ///
+    /// let package = registry.install(&LocalFetcher, source.clone(), env::temp_dir(), Some(&checksum));
+    /// let provenance = registry.get_provenance(&package).unwrap();
+    /// assert_eq!(provenance.source(), &source);
+    /// assert_eq!(provenance.fetcher(), "local");
+    /// assert_eq!(provenance.checksum(), Some(checksum.as_str()));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when [`Registry::try_install`] returns an [`InstallError`]
+    ///
+    /// Panics when the [`Fetcher`] requires a checksum and the [`Package`] declares a [`Dependency`] that is not registered,
+    /// as declared [`Dependency`]s carry no checksum
+    pub fn install<F: Fetcher, P: AsRef<Path>>(&mut self, fetcher: &F, source: Url, path: P, checksum: Option<&str>) -> Package {
+        self.try_install(fetcher, source, path, checksum).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Obtains a [`Package`] and every [`Package`] it depends on, directly or indirectly, and adds them to the [`Registry`]
+    ///
+    /// A declared [`Dependency`] is resolved to the registered [`Package`] with the same remote location or identifier.
+    /// When it cannot be resolved the [`Package`] is fetched from the [`Dependency`]'s [`Url`] using the same [`Fetcher`].
+    /// The version of the resolved or fetched [`Package`] needs to satisfy the requirement of every [`Dependency`] on it.
+    /// [`DependencyKind::Dev`] [`Dependency`]s are only needed to test a [`Package`] and are not installed.
+    /// When it does not, or when a [`Dependency`] refers to a registered identifier at a different [`Url`],
+    /// every [`Package`] fetched so far is removed again and an [`InstallError`] is returned
+    ///
+    /// # Arguments
+    /// * `fetcher` - The [`Fetcher`] used to obtain the [`Package`]s
+    /// * `source` - An [`Url`] pointing to the contents of the [`Package`]
+    /// * `path` - A [`Path`] pointing to where packages need to be downloaded to
+    /// * `checksum` - The expected checksum of the contents of the [`Package`], see [`Package::checksum`]
+    pub fn try_install<F: Fetcher, P: AsRef<Path>>(&mut self, fetcher: &F, source: Url, path: P, checksum: Option<&str>) -> Result<Package, InstallError> {
+        self.begin_install(fetcher, source, path, checksum).result()
+    }
+
+    /// Starts installing a [`Package`] and every [`Package`] it depends on, see [`Registry::try_install`]
+    /// Every call to [`OperationHandle::poll_status`] installs a single [`Package`]
+    ///
+    /// # Arguments
+    /// * `fetcher` - The [`Fetcher`] used to obtain the [`Package`]s
+    /// * `source` - An [`Url`] pointing to the contents of the [`Package`]
+    /// * `path` - A [`Path`] pointing to where packages need to be downloaded to
+    /// * `checksum` - The expected checksum of the contents of the [`Package`], see [`Package::checksum`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use url::Url;
+    /// # use knapsac_lib::fetcher::LocalFetcher;
+    /// # use knapsac_lib::operation::{OperationEvent, OperationStatus};
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_begin_install.json"));
+    /// let share = env::temp_dir().join("mock_share_begin_install");
+    /// fs::create_dir_all(&share).unwrap();
+    /// fs::write(share.join("a.sac"), "module a;").unwrap();
+    /// let source = Url::from_directory_path(&share).unwrap();
+    /// let checksum = Package::checksum(&share);
+    ///
+    /// let mut handle = registry.begin_install(&LocalFetcher, source.clone(), env::temp_dir(), Some(&checksum));
+    /// assert_eq!(handle.status(), &OperationStatus::Running(0, 1));
+    /// handle.poll_status();
+    /// assert!(matches!(handle.status(), OperationStatus::Finished(_)));
+    /// assert_eq!(handle.events().last(), Some(&OperationEvent::Finished));
+    /// drop(handle);
+    ///
+    /// let mut handle = registry.begin_install(&LocalFetcher, source, env::temp_dir(), Some(&checksum));
+    /// handle.cancel();
+    /// assert_eq!(handle.poll_status(), &OperationStatus::Cancelled);
+    /// ```
+    pub fn begin_install<'r, F: Fetcher, P: AsRef<Path>>(&'r mut self, fetcher: &'r F, source: Url, path: P, checksum: Option<&str>) -> OperationHandle<'r, Package, InstallError> {
+        let allowed = self.is_allowed(&path);
+        let operation = Box::new(InstallOperation::create(fetcher, source, &path, checksum));
+        match allowed {
+            true => OperationHandle::new(self, operation),
+            false => OperationHandle::failed(self, operation, InstallError::OutsideAllowedRoots(path.as_ref().to_path_buf())),
+        }
+    }
+
+    /// Installs the latest [`Version`] of the [`Package`] published under the given identifier in the [`RemoteIndex`]
+    /// that satisfies the given [`VersionReq`], together with every [`Package`] it depends on
+    /// A registered [`Package`] satisfying the [`VersionReq`] is returned instead of installing it again
+    ///
+    /// # Arguments
+    /// * `fetcher` - The [`Fetcher`] used to obtain the [`Package`]s
+    /// * `identifier` - The identifier the [`Package`] is published under, see [`Registry::search_remote`]
+    /// * `requirement` - The [`VersionReq`] to satisfy, or `None` to install the latest [`Version`]
+    /// * `path` - A [`Path`] pointing to where packages need to be downloaded to
+    ///
+    /// [`VersionReq`]: crate::version::VersionReq
+    pub fn install_by_name<F: Fetcher, P: AsRef<Path>>(&mut self, fetcher: &F, identifier: &str, requirement: Option<VersionReq>, path: P) -> Result<Package, InstallError> {
+        let index = self.remote_index.as_ref().ok_or(InstallError::Index(IndexError::NoIndex))?;
+        let entry = index.get(identifier).ok_or_else(|| InstallError::UnknownPackage(identifier.to_string()))?;
+        if !self.is_allowed(&path) {
+            return Err(InstallError::OutsideAllowedRoots(path.as_ref().to_path_buf()));
+        }
+        let dependency = Dependency::declare(entry.git_url, identifier, requirement);
+        let operation = Box::new(InstallOperation::declared(fetcher, dependency, &path));
+        OperationHandle::new(self, operation).result()
+    }
+
+    /// Obtains a [`Package`] from the [`PackageCache`] if it holds a copy, or else through the given [`Fetcher`], and registers it
+    /// Fetched [`Package`]s are stored in the [`PackageCache`]
+    pub(crate) fn fetch_and_register<F: Fetcher>(&mut self, fetcher: &F, source: Url, path: &Path, checksum: Option<&str>, requirement: Option<&VersionReq>) -> Result<Package, InstallError> {
+        let cache = self.cache();
+        let reference = fetcher.reference();
+        let cached = cache.as_ref().filter(|_| reference.is_none()).and_then(|c| c.lookup(&source, requirement, checksum));
+        let (package, fetcher_name) = match (&cache, cached) {
+            (Some(cache), Some(entry)) => (cache.restore(&entry, path), "cache"),
+            _ if self.offline && source.scheme() != "file" => return Err(InstallError::Offline(source.to_string())),
+            _ => {
+                self.progress.notify(|h| h.fetch_started(&source));
+                let package = Package::fetch_with_progress(fetcher, source.clone(), path, checksum, &mut |received, total| {
+                    self.progress.notify(|h| h.fetch_progress(&source, received, total));
+                });
+                self.progress.notify(|h| h.fetch_finished(&source));
+                let package = package?;
+                if let Err(error) = package.validate_manifest() {
+                    let _ = remove_dir_all(&package.local_location);
+                    return Err(InstallError::Manifest(error));
+                }
+                if let Some(cache) = &cache {
+                    cache.store(&package, &source);
+                }
+                (package, fetcher.name())
+            }
+        };
+        self.provenance.insert(package.local_location.clone(), Provenance {
+            source,
+            fetcher: fetcher_name.to_string(),
+            checksum: checksum.map(String::from),
+            reference,
+            revision: Repository::open(&package.local_location).ok()
+                .and_then(|r| Some(r.head().ok()?.peel_to_commit().ok()?.id().to_string())),
+        });
+        self.packages.insert(package.clone());
+        self.persist();
+        self.audit("install", vec![fetcher_name.to_string(), package.local_location.display().to_string()], "ok");
+        Ok(package)
+    }
+
+    pub(crate) fn resolve_declared(&self, dependency: &Dependency) -> Result<Option<&Package>, InstallError> {
+        if let Some(resolved) = self.resolve_dependency(dependency) {
+            return Ok(Some(resolved));
+        }
+        let identifier = match dependency.identifier() {
+            Some(identifier) => identifier,
+            None => return Ok(None),
+        };
+        match self.packages.iter().find(|p| p.identifier() == identifier && p.remote_location.as_ref() != Some(&dependency.git_url)) {
+            Some(registered) => Err(InstallError::ConflictingSources(
+                identifier.to_string(),
+                dependency.git_url.to_string(),
+                registered.remote_location.as_ref().map(Url::to_string),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn check_requirement(dependency: &Dependency, package: &Package) -> Result<(), InstallError> {
+        let requirement = match &dependency.requirement {
+            Some(requirement) => requirement,
+            None => return Ok(()),
+        };
+        let version = package.version();
+        match &version {
+            Some(version) if requirement.matches(version) => Ok(()),
+            _ => Err(InstallError::ConflictingRequirement(
+                dependency.identifier().map(String::from).unwrap_or_else(|| package.identifier()),
+                requirement.to_string(),
+                version.map(|v| v.to_string()),
+            )),
+        }
+    }
+
+    /// Retrieves the [`Provenance`] of a [`Package`] that was added using [`Registry::install`]
+    ///
+    /// # Arguments
+    /// * `package` - A reference to a [`Package`]
+    pub fn get_provenance(&self, package: &Package) -> Option<&Provenance> {
+        self.provenance.get(&package.local_location)
+    }
+
+    /// Moves the [`Package`] containing the given [`Path`] from this [`Registry`] to `other` and saves both
+    /// Returns the [`Package`]s that were moved, as registered in `other`
+    /// Locations are rewritten the way `other` stores its own paths: relative locations are resolved against this
+    /// [`Registry`]'s directory and canonicalized, and are written relative to `other`'s directory when it uses
+    /// relative paths, see [`Registry::set_relative_paths`]
+    /// The [`Provenance`] of a moved [`Package`] is moved along
+    ///
+    /// # Arguments
+    /// * `other` - The [`Registry`] the [`Package`] is moved to
+    /// * `source` - An absolute [`Path`] pointing to a file inside the [`Package`]
+    /// * `with_exclusive_dependencies` - Whether [`Package`]s only the moved [`Package`]s depend on are moved as well
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut workspace = Registry::initialize(env::temp_dir().join("registry_workspace.json"));
+    /// let mut global = Registry::initialize(env::temp_dir().join("registry_global.json"));
+    /// let package_path = env::temp_dir().join("mock_package_transfer");
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// workspace.add(package.clone());
+    ///
+    /// workspace.transfer_package(&mut global, package_path.join("a.sac"), false);
+    /// assert!(!workspace.contains(&package));
+    /// assert!(global.contains(&package));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] contains the given [`Path`]
+    /// See [`Registry::try_transfer_package`] for a non-panicking variant
+    pub fn transfer_package<P: AsRef<Path>>(&mut self, other: &mut Registry, source: P, with_exclusive_dependencies: bool) -> Vec<Package> {
+        self.try_transfer_package(other, source, with_exclusive_dependencies).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Moves the [`Package`] containing the given [`Path`] to `other` like [`Registry::transfer_package`],
+    /// explaining why it cannot be moved
+    ///
+    /// # Arguments
+    /// * `other` - The [`Registry`] the [`Package`] is moved to
+    /// * `source` - An absolute [`Path`] pointing to a file inside the [`Package`]
+    /// * `with_exclusive_dependencies` - Whether [`Package`]s only the moved [`Package`]s depend on are moved as well
+    ///
+    /// # Errors
+    /// Returns [`Error::UnknownPackage`] when no registered [`Package`] contains the given [`Path`]
+    /// and [`Error::OutsideAllowedRoots`] when a moved [`Package`] lies outside `other`'s allowed roots,
+    /// nothing is moved in either case
+    pub fn try_transfer_package<P: AsRef<Path>>(&mut self, other: &mut Registry, source: P, with_exclusive_dependencies: bool) -> Result<Vec<Package>, Error> {
+        let package = match self.get_by_source(&source) {
+            Some(package) => package.clone(),
+            None => return Err(Error::UnknownPackage(source.as_ref().display().to_string())),
+        };
+
+        let mut moved = vec![package];
+        if with_exclusive_dependencies {
+            while let Some(exclusive) = self.find_exclusive_dependency(&moved) {
+                moved.push(exclusive);
+            }
+        }
+
+        let base = self.location.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut transferred = vec![];
+        for package in &moved {
+            let mut carried = package.clone();
+            carried.local_location = canonicalize(normalize(base.join(&package.local_location)));
+            other.check_allowed(&carried.local_location)?;
+            transferred.push(carried);
+        }
+
+        for (package, carried) in moved.iter().zip(&transferred) {
+            self.packages.remove(package);
+            if let Some(provenance) = self.provenance.remove(&package.local_location) {
+                other.provenance.insert(carried.local_location.clone(), provenance);
+            }
+            other.packages.insert(carried.clone());
+        }
+        self.persist();
+        other.persist();
+        let arguments = vec![source.as_ref().display().to_string(), other.location.display().to_string()];
+        let result = format!("moved {} package(s)", transferred.len());
+        self.audit("transfer_package", arguments.clone(), &result);
+        other.audit("transfer_package", arguments, &result);
+        Ok(transferred)
+    }
+
+    fn find_exclusive_dependency(&self, moved: &[Package]) -> Option<Package> {
+        moved
+            .iter()
+            .flat_map(|p|p.load_manifest().dependencies)
+            .filter_map(|d|self.resolve_dependency(&d))
+            .find(|candidate| {
+                !moved.contains(candidate) && self.packages.iter().all(|p| {
+                    moved.contains(p) || !p.load_manifest().dependencies.iter().any(|d|self.resolve_dependency(d) == Some(candidate))
+                })
+            })
+            .cloned()
+    }
+
+    /// Moves the registration of the [`Module`] at `old_source` to `new_source` and returns the moved [`Module`]
+    /// The [`Module`] keeps its identifier, so [`Package`]s looking it up by identifier are unaffected.
+    /// When `new_source` lies in another [`Package`] the [`Module`] is moved to that [`Package`]
+    ///
+    /// # Arguments
+    /// * `old_source` - An absolute [`Path`] pointing to where the [`Module`]'s file used to be
+    /// * `new_source` - An absolute [`Path`] pointing to where the [`Module`]'s file is now
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_move.json"));
+    /// let package_path = env::temp_dir().join("mock_package_move");
+    /// Repository::init(&package_path);
+    /// fs::create_dir_all(package_path.join("src")).unwrap();
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package.clone());
+    ///
+    /// fs::rename(package_path.join("a.sac"), package_path.join("src").join("a.sac")).unwrap();
+    /// let moved = registry.move_module(package_path.join("a.sac"), package_path.join("src").join("a.sac"));
+    /// assert_eq!(moved.identifier, "a");
+    /// assert!(package.get_module_by_location("a.sac").is_none());
+    /// assert!(package.has_module(&moved));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at `old_source`, when no registered [`Package`] contains `new_source`
+    /// or when `new_source` does not point to an existing file
+    ///
+    /// Panics when `new_source` lies outside the [`Registry`]'s allowed roots, see [`Registry::set_allowed_roots`]
+    ///
+    /// See [`Registry::try_move_module`] for a non-panicking variant
+    pub fn move_module<P: AsRef<Path>, Q: AsRef<Path>>(&self, old_source: P, new_source: Q) -> Module {
+        self.try_move_module(old_source, new_source).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Moves the registration of the [`Module`] at `old_source` to `new_source` like [`Registry::move_module`],
+    /// explaining why it cannot be moved
+    ///
+    /// # Arguments
+    /// * `old_source` - An absolute [`Path`] pointing to where the [`Module`]'s file used to be
+    /// * `new_source` - An absolute [`Path`] pointing to where the [`Module`]'s file is now
+    ///
+    /// # Errors
+    /// Returns [`Error::OutsideAllowedRoots`] when `new_source` lies outside the [`Registry`]'s allowed roots,
+    /// [`Error::UnknownPackage`] when no registered [`Package`] contains `new_source`,
+    /// [`ModuleError::UnknownSource`] when no [`Module`] is registered at `old_source`
+    /// and [`ModuleError::MissingSource`] when `new_source` or an auxiliary source of the [`Module`] does not exist,
+    /// the registration is left untouched in every case
+    pub fn try_move_module<P: AsRef<Path>, Q: AsRef<Path>>(&self, old_source: P, new_source: Q) -> Result<Module, Error> {
+        self.check_allowed(&new_source)?;
+        let (old_package, module) = self.registered_module(old_source.as_ref())?;
+        let new_package = self.get_by_source(&new_source)
+            .ok_or_else(|| Error::UnknownPackage(new_source.as_ref().display().to_string()))?;
+
+        if !new_source.as_ref().is_file() {
+            return Err(ModuleError::MissingSource(new_source.as_ref().to_path_buf()).into());
+        }
+        if let Some(missing) = module.sources.iter().map(|s| new_package.local_location.join(s)).find(|s| !s.exists()) {
+            return Err(ModuleError::MissingSource(missing).into());
+        }
+
+        let mut moved = module.clone();
+        moved.location = new_package.strip_prefix(&new_source);
+        let manifests = self.manifest_contents(&[old_package, new_package]);
+        old_package.remove_module(&module);
+        new_package.add_module(moved.clone());
+        self.journal_manifests(manifests);
+        self.audit("move_module", vec![old_source.as_ref().display().to_string(), new_source.as_ref().display().to_string()], "ok");
+        Ok(moved)
+    }
+
+    /// Changes the remote [`Url`] of the [`Package`] located at `old_url` to `new_url` and saves the [`Registry`]
+    /// The remote [`Url`] identifies a [`Package`], so every [`Dependency`] on it is rewritten
+    /// and the git remote of the [`Package`]'s repository is updated
+    ///
+    /// # Arguments
+    /// * `old_url` - The current remote [`Url`] of the [`Package`]
+    /// * `new_url` - The new remote [`Url`] of the [`Package`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let old_url = Url::parse("https://example.com/json").unwrap();
+    /// let new_url = Url::parse("https://example.com/sac-json").unwrap();
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_rename_package.json"));
+    ///
+    /// let json_path = env::temp_dir().join("mock_package_rename_json");
+    /// # let _ = fs::remove_dir_all(&json_path);
+    /// Repository::init(&json_path).unwrap().remote("origin", old_url.as_str()).unwrap();
+    /// registry.add(Package::create(&json_path));
+    ///
+    /// let app_path = env::temp_dir().join("mock_package_rename_app");
+    /// Repository::init(&app_path);
+    /// let app = Package::create(&app_path);
+    /// app.add_dependency(Dependency::create(old_url.clone()));
+    /// registry.add(app.clone());
+    ///
+    /// registry.rename_package(&old_url, new_url.clone());
+    /// assert!(app.has_dependency(&Dependency::create(new_url.clone())));
+    /// assert!(registry.resolve_dependency(&Dependency::create(new_url)).is_some());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] is located at `old_url`
+    /// See [`Registry::try_rename_package`] for a non-panicking variant
+    pub fn rename_package(&mut self, old_url: &Url, new_url: Url) {
+        self.try_rename_package(old_url, new_url).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Changes the remote [`Url`] of the [`Package`] located at `old_url` like [`Registry::rename_package`],
+    /// explaining why it cannot be changed
+    ///
+    /// # Arguments
+    /// * `old_url` - The current remote [`Url`] of the [`Package`]
+    /// * `new_url` - The new remote [`Url`] of the [`Package`]
+    ///
+    /// # Errors
+    /// Returns [`Error::UnknownPackage`] when no registered [`Package`] is located at `old_url`
+    /// and [`Error::Git`] when the git remote of the [`Package`]'s repository cannot be updated
+    pub fn try_rename_package(&mut self, old_url: &Url, new_url: Url) -> Result<(), Error> {
+        let old_dependency = Dependency::create(old_url.clone());
+        let new_dependency = Dependency::create(new_url.clone());
+        let package = self.resolve_dependency(&old_dependency)
+            .ok_or_else(|| Error::UnknownPackage(old_url.to_string()))?
+            .clone();
+
+        let git_error = |e: git2::Error| Error::Git(package.local_location.clone(), e.message().to_string());
+        let repository = discover_git_repository(&package.local_location);
+        let remotes = repository.remotes().map_err(git_error)?;
+        for name in remotes.iter().flatten() {
+            let remote = repository.find_remote(name).map_err(git_error)?;
+            if remote.url().and_then(|u|Url::parse(u).ok()).as_ref() == Some(old_url) {
+                repository.remote_set_url(name, new_url.as_str()).map_err(git_error)?;
+            }
+        }
+        git_cache::invalidate(&package.local_location);
+
+        for dependent in self.packages.iter().filter(|p|p.has_dependency(&old_dependency)) {
+            dependent.remove_dependency(&old_dependency);
+            dependent.add_dependency(new_dependency.clone());
+        }
+
+        let mut renamed = package.clone();
+        renamed.remote_location = Some(new_url);
+        self.packages.remove(&package);
+        self.packages.insert(renamed);
+        self.persist();
+        self.audit("rename_package", vec![old_url.to_string(), new_dependency.git_url.to_string()], "ok");
+        Ok(())
+    }
+
+    /// Changes the identifier of the [`Module`] at the given [`Path`] and returns the renamed [`Module`]
+    /// See [`Registry::set_identifier`], which explains why the identifier cannot be changed instead of panicking
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `identifier` - The new identifier of the [`Module`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_rename_module.json"));
+    /// let package_path = env::temp_dir().join("mock_package_rename_module");
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package.clone());
+    ///
+    /// registry.rename_module(package_path.join("a.sac"), "b");
+    /// assert_eq!(package.get_module_by_location("a.sac").unwrap().identifier, "b");
+    /// assert_eq!(registry.search_by_module_identifiers(&["b".to_string()]), vec![&package]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at the given [`Path`], when the identifier is not valid
+    /// or when it is already used by another [`Module`] it would be confused with, see [`Registry::set_identifier`]
+    pub fn rename_module<P: AsRef<Path>, S: Into<String>>(&self, source: P, identifier: S) -> Module {
+        self.set_identifier(source, identifier).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Sets the directory the [`Module`] at the given source [`Path`] is compiled to and returns the changed [`Module`]
+    /// A relative output directory is stored as is, so the [`Registry`] stays valid when it is moved to another machine
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `output` - A [`Path`] pointing to a directory, relative paths are resolved according to the [`OutputPolicy`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::{OutputPolicy, Registry};
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_output.json"));
+    /// let package_path = env::temp_dir().join("mock_package_output");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::create_dir_all(package_path.join("src")).unwrap();
+    /// fs::write(package_path.join("src").join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("src/a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let source = package_path.join("src").join("a.sac");
+    /// registry.set_output(&source, "build");
+    /// assert_eq!(registry.get_output(&source), Some(package_path.join("src").join("build")));
+    ///
+    /// let artifacts = env::temp_dir().join("artifacts");
+    /// registry.set_artifacts_root(Some(&artifacts));
+    /// assert_eq!(registry.get_output(&source), Some(artifacts.join("build")));
+    ///
+    /// registry.set_output_policy(OutputPolicy::SourceDirectory);
+    /// assert_eq!(registry.get_output(&source), Some(package_path.join("src").join("build")));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at the given [`Path`], see [`Registry::try_set_output`]
+    pub fn set_output<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, output: Q) -> Module {
+        self.try_set_output(source, output).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Sets the output directory of a [`Module`] like [`Registry::set_output`], returning an [`Error`] instead of panicking
+    /// Unlike [`Registry::set_output_path`] the output directory is not checked
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `output` - A [`Path`] pointing to the output directory, relative ones are resolved per [`OutputPolicy`]
+    ///
+    /// # Errors
+    /// Returns [`ModuleError::UnknownSource`] when no [`Module`] is registered at the given [`Path`]
+    /// and [`ModuleError::MissingSource`] when a source file of the [`Module`] no longer exists
+    pub fn try_set_output<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, output: Q) -> Result<Module, Error> {
+        let (package, module) = self.registered_module(source.as_ref())?;
+
+        let mut changed = module.clone();
+        changed.set_output(Some(output.as_ref()));
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, changed.clone())?;
+        self.journal_manifests(manifests);
+        self.audit("set_output", vec![source.as_ref().display().to_string(), output.as_ref().display().to_string()], "ok");
+        Ok(changed)
+    }
+
+    /// Retrieves the [`Module`] registered at the given source [`Path`], e.g. to read its metadata
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    pub fn get_module<P: AsRef<Path>>(&self, source: P) -> Option<Module> {
+        let package = self.get_by_source(&source)?;
+        package.get_module_by_location(package.strip_prefix(&source))
+    }
+
+    /// Sets or removes the directory the [`Module`] at the given source [`Path`] is compiled to and returns the changed [`Module`]
+    /// Unlike [`Registry::set_output`] the output directory is checked first, as [`Registry::clean`] removes it
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `output` - A [`Path`] pointing to a directory, resolved like [`Registry::set_output`], or `None` to remove it
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::{Module, ModuleError};
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::{OutputPolicy, Registry};
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_output_path.json"));
+    /// registry.set_output_policy(OutputPolicy::SourceDirectory);
+    /// let package_path = env::temp_dir().join("mock_package_output_path");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let source = package_path.join("a.sac");
+    /// registry.set_output_path(&source, Some("build")).unwrap();
+    /// assert_eq!(registry.get_output(&source), Some(package_path.join("build")));
+    ///
+    /// let error = registry.set_output_path(&source, Some(".")).unwrap_err();
+    /// assert!(matches!(error, ModuleError::InvalidOutput(..)));
+    /// assert_eq!(registry.get_output(&source), Some(package_path.join("build")));
+    ///
+    /// registry.set_output_path(&source, None::<&str>).unwrap();
+    /// assert!(registry.get_output(&source).is_none());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`ModuleError::UnknownSource`] when no [`Module`] is registered at the given [`Path`]
+    /// and [`ModuleError::InvalidOutput`] when the resolved output directory is an existing file
+    /// or the directory of the [`Package`] or one containing it,
+    /// and [`ModuleError::MissingSource`] when a source file of the [`Module`] no longer exists
+    pub fn set_output_path<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, output: Option<Q>) -> Result<Module, ModuleError> {
+        let source = source.as_ref();
+        let (package, module) = self.registered_module(source)?;
+        if let Some(resolved) = output.as_ref().and_then(|o| self.resolve_output(source, o.as_ref())) {
+            let resolved = normalize(resolved);
+            if resolved.is_file() || starts_with(&package.local_location, &resolved) {
+                return Err(ModuleError::InvalidOutput(resolved, package.local_location.clone()));
+            }
+        }
+
+        let mut changed = module.clone();
+        changed.set_output(output.as_ref());
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, changed.clone())?;
+        self.journal_manifests(manifests);
+        let output = output.map_or("none".to_string(), |o| o.as_ref().display().to_string());
+        self.audit("set_output_path", vec![source.display().to_string(), output], "ok");
+        Ok(changed)
+    }
+
+    /// Changes the identifier of the [`Module`] at the given source [`Path`] and returns the renamed [`Module`]
+    /// The identifier is checked first, so imports keep resolving to a single [`Module`], see [`Registry::resolve_import`]
+    /// Compiled files named after the identifier through the output template of its [`Language`] are renamed along,
+    /// so [`Package`]s depending on the [`Module`] find them under the new name without rebuilding it
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `identifier` - The new identifier of the [`Module`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::{Module, ModuleError};
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_set_identifier.json"));
+    /// let package_path = env::temp_dir().join("mock_package_set_identifier");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// fs::write(package_path.join("b.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// package.add_module(Module::create("b.sac", None));
+    /// registry.add(package);
+    ///
+    /// let source = package_path.join("a.sac");
+    /// assert_eq!(
+    ///     registry.set_identifier(&source, "b"),
+    ///     Err(ModuleError::IdentifierTaken("b".to_string(), package_path.join("b.sac"))),
+    /// );
+    /// assert_eq!(registry.set_identifier(&source, "my json"), Err(ModuleError::InvalidIdentifier("my json".to_string())));
+    ///
+    /// registry.set_identifier(&source, "json").unwrap();
+    /// assert_eq!(registry.get_module(&source).unwrap().identifier, "json");
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`ModuleError::UnknownSource`] when no [`Module`] is registered at the given [`Path`],
+    /// [`ModuleError::InvalidIdentifier`] when the identifier is empty or contains whitespace or path separators
+    /// [`ModuleError::IdentifierTaken`] when another [`Module`] of the same [`Package`],
+    /// or of a [`Package`] depending on it directly, already has the identifier,
+    /// [`ModuleError::MissingSource`] when a source file of the [`Module`] no longer exists
+    /// and [`ModuleError::Io`] when a compiled file cannot be renamed, the identifier and the files renamed so far
+    /// are then restored
+    pub fn set_identifier<P: AsRef<Path>, S: Into<String>>(&self, source: P, identifier: S) -> Result<Module, ModuleError> {
+        let source = source.as_ref();
+        let identifier = identifier.into();
+        let (package, module) = self.registered_module(source)?;
+        if identifier.is_empty() || identifier.contains(|c: char| c.is_whitespace() || c == '/' || c == '\\') {
+            return Err(ModuleError::InvalidIdentifier(identifier));
+        }
+        let importers = std::iter::once(package).chain(self.dependents_of_package(package, false));
+        for importer in importers {
+            if let Some(taken) = importer.modules().find(|m| m.identifier == identifier && !(importer == package && m.location == module.location)) {
+                return Err(ModuleError::IdentifierTaken(identifier, importer.local_location.join(&taken.location)));
+            }
+        }
+
+        let mut renamed = module.clone();
+        renamed.identifier = identifier;
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, renamed.clone())?;
+
+        if let Some(template) = self.get_module_language(package, &module).and_then(|l| l.output_template) {
+            let (old, new) = (template.replace("{identifier}", &module.identifier), template.replace("{identifier}", &renamed.identifier));
+            let mut moved: Vec<(PathBuf, PathBuf)> = vec![];
+            for output in module.all_outputs().filter_map(|o| self.resolve_output(source, o)) {
+                let (from, to) = (output.join(&old), output.join(&new));
+                if !from.exists() {
+                    continue;
+                }
+                if let Err(error) = rename(&from, &to) {
+                    for (from, to) in moved.iter().rev() {
+                        let _ = rename(to, from);
+                        let _ = rename(metadata_path(to), metadata_path(from));
+                    }
+                    package.replace_module(&renamed, module.clone())?;
+                    return Err(ModuleError::Io(from, error.to_string()));
+                }
+                let _ = rename(metadata_path(&from), metadata_path(&to));
+                moved.push((from, to));
+            }
+        }
+
+        self.journal_manifests(manifests);
+        self.audit("set_identifier", vec![source.display().to_string(), renamed.identifier.clone()], "ok");
+        Ok(renamed)
+    }
+
+    /// Sets or removes the description of the [`Module`] at the given source [`Path`] and returns the changed [`Module`],
+    /// see [`Module::set_description`]
+    /// Surrounding whitespace is trimmed and an empty description is removed, so listings never show a blank description
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `description` - The description, or `None` to remove it
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_set_description.json"));
+    /// let package_path = env::temp_dir().join("mock_package_set_description");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let source = package_path.join("a.sac");
+    /// registry.set_description(&source, Some(" Parses JSON\n".to_string())).unwrap();
+    /// assert_eq!(registry.get_module(&source).unwrap().description(), Some("Parses JSON"));
+    /// registry.set_description(&source, Some("  ".to_string())).unwrap();
+    /// assert!(registry.get_module(&source).unwrap().description().is_none());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`ModuleError::UnknownSource`] when no [`Module`] is registered at the given [`Path`]
+    /// and [`ModuleError::MissingSource`] when a source file of the [`Module`] no longer exists
+    pub fn set_description<P: AsRef<Path>>(&self, source: P, description: Option<String>) -> Result<Module, ModuleError> {
+        let source = source.as_ref();
+        let (package, module) = self.registered_module(source)?;
+        let description = description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+
+        let mut changed = module.clone();
+        changed.set_description(description.clone());
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, changed.clone())?;
+        self.journal_manifests(manifests);
+        self.audit("set_description", vec![source.display().to_string(), description.unwrap_or_else(|| "none".to_string())], "ok");
+        Ok(changed)
+    }
+
+    /// Retrieves the [`Package`] containing the given source [`Path`] and the [`Module`] registered at it
+    fn registered_module(&self, source: &Path) -> Result<(&Package, Module), ModuleError> {
+        self.get_by_source(source)
+            .and_then(|package| package.get_module_by_location(package.strip_prefix(source)).map(|module| (package, module)))
+            .ok_or_else(|| ModuleError::UnknownSource(source.to_path_buf()))
+    }
+
+    /// Registers every file below the given directory that matches a glob pattern as a [`Module`] of the [`Package`] containing it
+    /// and returns the added [`Module`]s, the manifest is written once
+    /// Files that already are a [`Module`] or an [`Executable`] and files ignored by [`Registry::ignore_rules`] are left alone
+    ///
+    /// # Arguments
+    /// * `root` - An absolute [`Path`] pointing to a directory within a registered [`Package`]
+    /// * `pattern` - A glob pattern relative to `root`, e.g. `src/**/*.sac`,
+    ///   where `*` and `?` match within a directory and `**` matches any number of directories
+    /// * `output_root` - A directory the output directories of the [`Module`]s are placed in, mirroring their location below `root`,
+    ///   a relative [`Path`] is resolved against `root`, `None` leaves the output directories unset
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_add_matching.json"));
+    /// let package_path = env::temp_dir().join("mock_package_add_matching");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::create_dir_all(package_path.join("src/json")).unwrap();
+    /// fs::write(package_path.join("src/a.sac"), "").unwrap();
+    /// fs::write(package_path.join("src/json/parse.sac"), "").unwrap();
+    /// fs::write(package_path.join("src/json/README.md"), "").unwrap();
+    /// registry.add(Package::create(&package_path));
+    ///
+    /// let added = registry.add_items_matching(&package_path, "src/**/*.sac", Some("build"));
+    /// assert_eq!(added.len(), 2);
+    /// assert_eq!(registry.get_output(package_path.join("src/json/parse.sac")), Some(package_path.join("build/src/json")));
+    /// assert!(registry.add_items_matching(&package_path, "src/**/*.sac", None::<&str>).is_empty());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] contains `root` or when `root` is not a directory
+    pub fn add_items_matching<P: AsRef<Path>, Q: AsRef<Path>>(&self, root: P, pattern: &str, output_root: Option<Q>) -> Vec<Module> {
+        let root = root.as_ref();
+        let package = self.get_by_source(root)
+            .unwrap_or_else(|| panic!("No package registered for {}", root.display()));
+        if !root.is_dir() {
+            panic!("{} is not a directory", root.display());
+        }
+        let output_root = output_root.map(|o| root.join(o));
+
+        let rules = self.ignore_rules(package);
+        let mut manifest = package.load_manifest();
+        let mut added = vec![];
+        for file in list_files(root).into_iter().filter(|f| glob_matches(pattern, f)) {
+            let location = package.strip_prefix(root.join(&file));
+            if rules.is_ignored(&location)
+                || manifest.get_module_owning(&package.local_location, &location).is_some()
+                || manifest.get_executable_by_location(&location).is_some() {
+                continue;
+            }
+            let mut module = Module::create(&location, None);
+            if let Some(output_root) = &output_root {
+                module.set_output(Some(output_root.join(file.parent().unwrap_or(Path::new("")))));
+            }
+            self.describe(package, &mut module);
+            manifest.add_module(module.clone());
+            added.push(module);
+        }
+        if !added.is_empty() {
+            let manifests = self.manifest_contents(&[package]);
+            manifest.save(package.manifest_location());
+            self.journal_manifests(manifests);
+        }
+        self.audit("add_items_matching", vec![root.display().to_string(), pattern.to_string(), added.len().to_string()], "ok");
+        added
+    }
+
+    #[cfg(feature = "watch")]
+    /// Starts watching the directories of the registered [`Package`]s for changes, only available with the `watch` feature enabled
+    /// See [`RegistryWatcher`], [`Package`]s registered afterwards are not watched
+    ///
+    /// # Arguments
+    /// * `callback` - Called for every [`WatchEvent`] as it happens, e.g. to trigger a rebuild
+    ///
+    /// [`WatchEvent`]: crate::watch::WatchEvent
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use std::time::Duration;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_watch.json"));
+    /// let package_path = env::temp_dir().join("mock_package_watch");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let watcher = registry.watch(None).unwrap();
+    /// fs::write(package_path.join("a.sac"), "changed").unwrap();
+    /// let event = watcher.next_event(Duration::from_secs(5)).unwrap();
+    /// assert_eq!(event.path(), package_path.join("a.sac"));
+    /// assert!(event.is_registered());
+    /// ```
+    pub fn watch(&self, callback: Option<WatchCallback>) -> Result<RegistryWatcher, WatchError> {
+        let packages: Vec<PathBuf> = self.iter_packages()
+            .map(|p| p.local_location.clone())
+            .filter(|p| p.is_dir())
+            .collect();
+        let mut sources: HashSet<PathBuf> = self.iter_items()
+            .flat_map(|(p, i)| match i {
+                RegistryItem::Module(m) => m.source_files(&p.local_location).into_iter().map(|f| p.local_location.join(f)).collect(),
+                RegistryItem::Executable(e) => vec![p.local_location.join(e.location())],
+            })
+            .collect();
+        for package in self.packages.iter().filter(|p| p.local_location.is_dir()) {
+            sources.extend(package.tests().iter().map(|t| package.local_location.join(&t.location)));
+        }
+        RegistryWatcher::start(packages, sources, callback)
+    }
+
+    /// Retrieves the absolute directory the [`Module`] at the given source [`Path`] is compiled to, if set
+    /// A relative output directory is resolved according to the [`Registry`]'s [`OutputPolicy`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    pub fn get_output<P: AsRef<Path>>(&self, source: P) -> Option<PathBuf> {
+        self.get_profile_output(source, None)
+    }
+
+    /// Sets the directory the [`Module`] at the given source [`Path`] is compiled to when it is built with the given profile
+    /// and returns the changed [`Module`], see [`Module::set_profile_output`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `profile` - The name of the profile, e.g. `debug` or `release`
+    /// * `output` - A [`Path`] pointing to a directory, resolved like [`Registry::set_output`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::{OutputPolicy, Registry};
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_profile_output.json"));
+    /// registry.set_output_policy(OutputPolicy::SourceDirectory);
+    /// let package_path = env::temp_dir().join("mock_package_profile_output");
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let source = package_path.join("a.sac");
+    /// registry.set_output(&source, "build");
+    /// registry.set_profile_output(&source, "release", "build/release");
+    /// assert_eq!(registry.get_profile_output(&source, Some("release")), Some(package_path.join("build/release")));
+    /// assert_eq!(registry.get_profile_output(&source, Some("debug")), Some(package_path.join("build")));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at the given [`Path`]
+    pub fn set_profile_output<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, profile: &str, output: Q) -> Module {
+        let package = self.get_by_source(&source)
+            .unwrap_or_else(|| panic!("No package registered for {}", source.as_ref().display()));
+        let module = package.get_module_by_location(package.strip_prefix(&source))
+            .unwrap_or_else(|| panic!("No module registered @ {}", source.as_ref().display()));
+
+        let mut changed = module.clone();
+        changed.set_profile_output(profile, Some(output.as_ref()));
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, changed.clone()).unwrap_or_else(|e| panic!("{}", e));
+        self.journal_manifests(manifests);
+        self.audit("set_profile_output", vec![source.as_ref().display().to_string(), profile.to_string(), output.as_ref().display().to_string()], "ok");
+        changed
+    }
+
+    /// Sets the directory the [`Module`] at the given source [`Path`] is compiled to for the given target
+    /// and returns the changed [`Module`], see [`Module::set_target_output`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `target` - The name of the target, e.g. `x86_64-linux` or `wasm32`
+    /// * `output` - A [`Path`] pointing to a directory, resolved like [`Registry::set_output`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::{OutputPolicy, Registry};
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_target_output.json"));
+    /// registry.set_output_policy(OutputPolicy::SourceDirectory);
+    /// let package_path = env::temp_dir().join("mock_package_target_output");
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let source = package_path.join("a.sac");
+    /// registry.set_output(&source, "build");
+    /// registry.set_target_output(&source, "wasm32", "build/wasm32");
+    /// assert_eq!(registry.get_output(&source), Some(package_path.join("build")));
+    /// registry.set_target(Some("wasm32"));
+    /// assert_eq!(registry.get_output(&source), Some(package_path.join("build/wasm32")));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at the given [`Path`]
+    pub fn set_target_output<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, target: &str, output: Q) -> Module {
+        let package = self.get_by_source(&source)
+            .unwrap_or_else(|| panic!("No package registered for {}", source.as_ref().display()));
+        let module = package.get_module_by_location(package.strip_prefix(&source))
+            .unwrap_or_else(|| panic!("No module registered @ {}", source.as_ref().display()));
+
+        let mut changed = module.clone();
+        changed.set_target_output(target, Some(output.as_ref()));
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, changed.clone()).unwrap_or_else(|e| panic!("{}", e));
+        self.journal_manifests(manifests);
+        self.audit("set_target_output", vec![source.as_ref().display().to_string(), target.to_string(), output.as_ref().display().to_string()], "ok");
+        changed
+    }
+
+    /// Retrieves the absolute directory the [`Module`] at the given source [`Path`] is compiled to
+    /// when it is built with the given profile for the requested target, if set, see [`Module::output_for_target`]
+    /// and [`Registry::set_target`]
+    /// A relative output directory is resolved according to the [`Registry`]'s [`OutputPolicy`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `profile` - The name of the profile, or `None` for the default output
+    pub fn get_profile_output<P: AsRef<Path>>(&self, source: P, profile: Option<&str>) -> Option<PathBuf> {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)?;
+        let module = package.get_module_by_location(package.strip_prefix(source))?;
+        let output = module.output_for_target(self.target.as_deref(), profile)?;
+        self.resolve_output(source, output)
+    }
+
+    /// Resolves the output directory of the [`Module`] at the given source [`Path`] according to the [`OutputPolicy`]
+    fn resolve_output(&self, source: &Path, output: &Path) -> Option<PathBuf> {
+        if output.is_absolute() {
+            return Some(output.to_path_buf());
+        }
+        let base = match (self.output_policy, &self.artifacts_root) {
+            (OutputPolicy::ArtifactsRoot, Some(root)) => root.clone(),
+            _ => source.parent()?.to_path_buf(),
+        };
+        Some(base.join(output))
+    }
+
+    /// Retrieves the absolute output directories of the [`Module`]s of every [`Package`] the [`Module`] at the given source [`Path`]
+    /// depends on, directly or indirectly, for the given profile, see [`Registry::try_dependency_outputs`]
+    /// Stray dependencies that are not an existing absolute directory are left out instead of being reported
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `profile` - The name of the profile, or `None` for the default outputs
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] contains the given [`Path`]
+    pub fn dependency_outputs<P: AsRef<Path>>(&self, source: P, profile: Option<&str>) -> Vec<PathBuf> {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .unwrap_or_else(|| panic!("{}", DependencyError::UnknownSource(source.to_path_buf())));
+        self.outputs_of(package, profile, true, false).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Retrieves the absolute output directories of the [`Module`]s of every [`Package`] the [`Module`] at the given source [`Path`]
+    /// depends on, directly or indirectly, for the given profile, see [`Registry::get_profile_output`]
+    /// [`Module`]s without an output directory and optional [`Module`]s of features no dependent enables are left out
+    /// The stray dependencies of those [`Package`]s and of the [`Package`] itself follow, see [`Package::add_stray`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `profile` - The name of the profile, or `None` for the default outputs
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::dependency::DependencyError;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_try_dependency_outputs.json"));
+    /// let path = env::temp_dir().join("mock_package_try_dependency_outputs");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// let stray = env::temp_dir().join("mock_stray_try_dependency_outputs");
+    /// fs::create_dir_all(&stray).unwrap();
+    /// package.add_stray(&stray);
+    /// registry.add(package);
+    ///
+    /// let source = path.join("main.sac");
+    /// assert_eq!(registry.try_dependency_outputs(&source, None), Ok(vec![stray.clone()]));
+    ///
+    /// fs::remove_dir_all(&stray).unwrap();
+    /// assert_eq!(registry.try_dependency_outputs(&source, None), Err(DependencyError::StrayNotResolvable(stray)));
+    /// assert!(registry.dependency_outputs(&source, None).is_empty());
+    /// let unknown = env::temp_dir().join("mock_package_unregistered/main.sac");
+    /// assert_eq!(registry.try_dependency_outputs(&unknown, None), Err(DependencyError::UnknownSource(unknown)));
+    /// ```
+    pub fn try_dependency_outputs<P: AsRef<Path>>(&self, source: P, profile: Option<&str>) -> Result<Vec<PathBuf>, DependencyError> {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .ok_or_else(|| DependencyError::UnknownSource(source.to_path_buf()))?;
+        self.outputs_of(package, profile, true, true)
+    }
+
+    /// Returns the arguments pointing a compiler at the output directory of every [`Package`] the [`Module`]
+    /// at the given source [`Path`] depends on, so a build driver does not need to resolve the [`Dependency`]s itself
+    /// Every output directory, see [`Registry::try_dependency_outputs`], is put into the include template of the [`Language`],
+    /// no arguments are returned when the [`Language`] has no include template
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `language` - The [`Language`] of the compiler the arguments are meant for
+    /// * `transitive` - Whether indirect [`Dependency`]s are included, or only the [`Package`]s depended on directly
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_compile_args.json"));
+    /// let path = env::temp_dir().join("mock_package_compile_args");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// let stray = env::temp_dir().join("mock_stray_compile_args");
+    /// fs::create_dir_all(&stray).unwrap();
+    /// package.add_stray(&stray);
+    /// registry.add(package);
+    ///
+    /// let mut language = Language::create("sac2c", vec![]);
+    /// language.set_include_template(Some("-I{path}".to_string()));
+    /// let args = registry.compile_args(path.join("main.sac"), &language, false).unwrap();
+    /// assert_eq!(args, vec![format!("-I{}", stray.display())]);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the [`DependencyError`] of [`Registry::try_dependency_outputs`]
+    pub fn compile_args<P: AsRef<Path>>(&self, source: P, language: &Language, transitive: bool) -> Result<Vec<String>, DependencyError> {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .ok_or_else(|| DependencyError::UnknownSource(source.to_path_buf()))?;
+        let Some(template) = &language.include_template else {
+            return Ok(vec![]);
+        };
+        Ok(self.outputs_of(package, None, transitive, true)?
+            .into_iter()
+            .map(|o| template.replace("{path}", &o.display().to_string()))
+            .collect())
+    }
+
+    /// Collects the output directories of the [`Package`]s the given [`Package`] depends on, directly or, when `transitive`, indirectly,
+    /// followed by the stray dependencies of the given [`Package`] and, when `transitive`, of those [`Package`]s
+    /// A stray dependency that is not an existing absolute directory is reported when `strict` is set and left out otherwise
+    fn outputs_of(&self, package: &Package, profile: Option<&str>, transitive: bool, strict: bool) -> Result<Vec<PathBuf>, DependencyError> {
+        let features = self.enabled_features(package);
+        let closure = match transitive {
+            true => self.dependency_closure(package),
+            false => {
+                let mut direct = vec![package];
+                for resolved in package.dependencies().iter().filter_map(|d| self.resolve_dependency(d)) {
+                    if !direct.contains(&resolved) {
+                        direct.push(resolved);
+                    }
+                }
+                direct
+            }
+        };
+        let mut outputs = vec![];
+        for dependency in &closure[1..] {
+            let enabled = features.get(&dependency.local_location).cloned().unwrap_or_default();
+            for module in dependency.required_modules(&enabled) {
+                if let Some(output) = self.get_profile_output(dependency.local_location.join(&module.location), profile) {
+                    if !outputs.contains(&output) {
+                        outputs.push(output);
+                    }
+                }
+            }
+        }
+        let strays = match transitive {
+            true => closure.iter().flat_map(|p| p.resolved_strays()).collect(),
+            false => package.resolved_strays(),
+        };
+        for stray in strays {
+            if stray.is_relative() || !stray.is_dir() {
+                match strict {
+                    true => return Err(DependencyError::StrayNotResolvable(stray)),
+                    false => continue,
+                }
+            }
+            if !outputs.contains(&stray) {
+                outputs.push(stray);
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// Runs the [`Executable`] at the given source [`Path`] with the given arguments and waits for it to exit
+    /// An [`Executable`] that is not prebuilt is built first when its binary is missing or older than its source file, see [`Registry::link`]
+    /// The output directories of every [`Package`] it depends on, see [`Registry::dependency_outputs`],
+    /// are prepended to the runtime search path of the process
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the file of an [`Executable`]
+    /// * `args` - The arguments passed to the process
+    pub fn run<P: AsRef<Path>, S: AsRef<str>>(&self, source: P, args: &[S]) -> Result<RunOutput, RunError> {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .ok_or_else(|| RunError::UnknownExecutable(source.to_path_buf()))?;
+        let executable = package.get_executable_by_location(package.strip_prefix(source))
+            .ok_or_else(|| RunError::UnknownExecutable(source.to_path_buf()))?;
+        self.build_and_run(package, &executable, args)
+    }
+
+    /// Builds the binary of the [`Executable`] at the given source [`Path`] and returns its absolute [`Path`]
+    /// The [`Language`]'s linker, or else its compiler, is invoked with the source, the [`Language`]'s link template applied
+    /// to every output directory the [`Executable`] depends on, see [`Registry::dependency_outputs`],
+    /// and the [`Executable`]'s link flags
+    /// The [`HookKind::PreBuild`] and [`HookKind::PostBuild`] hooks of its [`Package`] run around the invocation
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the file of an [`Executable`] that is not prebuilt,
+    ///   [`RunError::UnknownExecutable`] is returned otherwise
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::executable::Executable;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_link.json"));
+    /// let mut language = Language::create("missing-compiler", vec![]);
+    /// language.set_linker(Some(String::from("missing-linker")));
+    /// registry.register_language("mock", language);
+    /// let package_path = env::temp_dir().join("mock_package_link");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("main.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.set_language(Some(String::from("mock")));
+    /// package.add_executable(Executable::create("main.sac"));
+    /// registry.add(package);
+    ///
+    /// let error = registry.link(package_path.join("main.sac")).unwrap_err();
+    /// assert!(error.to_string().starts_with("could not start `missing-linker"));
+    /// ```
+    ///
+    /// [`HookKind::PreBuild`]: crate::hooks::HookKind::PreBuild
+    /// [`HookKind::PostBuild`]: crate::hooks::HookKind::PostBuild
+    pub fn link<P: AsRef<Path>>(&self, source: P) -> Result<PathBuf, RunError> {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .ok_or_else(|| RunError::UnknownExecutable(source.to_path_buf()))?;
+        let executable = package.get_executable_by_location(package.strip_prefix(source))
+            .filter(|e| !e.is_prebuilt())
+            .ok_or_else(|| RunError::UnknownExecutable(source.to_path_buf()))?;
+        self.link_executable(package, &executable)
+    }
+
+    fn link_executable(&self, package: &Package, executable: &Executable) -> Result<PathBuf, RunError> {
+        let source = &package.local_location.join(&executable.location);
+        let (invocation, binary) = self.link_command(package, executable)?;
+        if let Some(directory) = binary.parent() {
+            create_dir_all(directory).unwrap();
+        }
+        self.run_hook(package, HookKind::PreBuild).map_err(RunError::Hook)?;
+        let output = Command::new(&invocation[0]).args(&invocation[1..]).output()
+            .map_err(|e| RunError::Spawn(invocation.join(" "), e.to_string()))?;
+        if !output.status.success() {
+            return Err(RunError::BuildFailed(source.to_path_buf(), String::from_utf8_lossy(&output.stderr).to_string()));
+        }
+        self.run_hook(package, HookKind::PostBuild).map_err(RunError::Hook)?;
+        self.build_environment(source, &binary).save();
+        Ok(binary)
+    }
+
+    /// Returns the command line linking the given [`Executable`] and the binary it produces
+    fn link_command(&self, package: &Package, executable: &Executable) -> Result<(Vec<String>, PathBuf), RunError> {
+        let source = &package.local_location.join(&executable.location);
+        let binary = package.local_location.join(executable.binary_path());
+        let language = match &executable.language {
+            Some(name) => self.resolve_language(name),
+            None => self.get_package_language(package),
+        }.ok_or_else(|| RunError::UnknownLanguage(source.to_path_buf()))?;
+        let mut invocation = self.command_line(package, None, &language, source);
+        if let Some(linker) = &language.linker {
+            invocation[0] = linker.clone();
+        }
+        if let Some(template) = &language.link_template {
+            for output in self.try_dependency_outputs(source, None).map_err(RunError::Dependency)? {
+                invocation.push(template.replace("{path}", &output.display().to_string()));
+            }
+        }
+        invocation.extend(executable.link_flags.iter().cloned());
+        invocation.push("-o".to_string());
+        invocation.push(binary.display().to_string());
+        Ok((invocation, binary))
+    }
+
+    /// Computes the [`BuildPlan`] building the [`Module`] or [`Executable`] at the given source [`Path`]
+    /// It compiles every required [`Module`] of every [`Package`] depended on, directly or indirectly, see [`Registry::invocation`],
+    /// the [`Package`]s depended on before their dependents, and then compiles the [`Module`] or links the [`Executable`], see [`Registry::link`]
+    /// A prebuilt [`Executable`] needs no step of its own
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`] or [`Executable`]
+    ///
+    /// # Errors
+    /// Returns [`RunError::UnknownTarget`] when no [`Module`] or [`Executable`] is registered at `source`,
+    /// [`RunError::UnknownLanguage`] when the [`Language`] of a [`Module`] or the [`Executable`] is unknown,
+    /// or [`RunError::Dependency`] when the output directories linked against cannot be resolved
+    pub fn build_plan<P: AsRef<Path>>(&self, source: P) -> Result<BuildPlan, RunError> {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .ok_or_else(|| RunError::UnknownTarget(source.to_path_buf()))?;
+        let item = self.get_item(source)
+            .ok_or_else(|| RunError::UnknownTarget(source.to_path_buf()))?;
+
+        let mut ordered = vec![];
+        self.dependencies_first(package, &mut vec![], &mut ordered);
+        let features = self.enabled_features(package);
+        let mut provided: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        let mut plan = BuildPlan::default();
+        for dependency in ordered.iter().filter(|p| **p != package) {
+            let after = self.steps_provided_to(dependency, &provided);
+            let enabled = features.get(&dependency.local_location).cloned().unwrap_or_default();
+            for module in dependency.required_modules(&enabled) {
+                let step = self.module_step(dependency, &module, after.clone())?;
+                provided.entry(dependency.local_location.clone()).or_default().push(step.id.clone());
+                plan.steps.push(step);
+            }
+        }
+
+        let after = self.steps_provided_to(package, &provided);
+        match item {
+            RegistryItem::Module(module) => plan.steps.push(self.module_step(package, &module, after)?),
+            RegistryItem::Executable(executable) if !executable.is_prebuilt() => {
+                let (command, binary) = self.link_command(package, &executable)?;
+                plan.steps.push(BuildStep {
+                    id: source.to_path_buf(),
+                    command,
+                    inputs: vec![source.to_path_buf()],
+                    outputs: vec![binary],
+                    dependencies: plan.steps.iter().map(|s| s.id.clone()).collect(),
+                });
+            }
+            RegistryItem::Executable(_) => {}
+        }
+        Ok(plan)
+    }
+
+    /// Emits the [`BuildPlan`] building the [`Module`] or [`Executable`] at the given source [`Path`], see [`Registry::build_plan`],
+    /// as JSON or as a ninja build file, so an existing build tool can run it
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`] or [`Executable`]
+    /// * `format` - The [`BuildPlanFormat`] to emit
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::build::{BuildPlan, BuildPlanFormat};
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_build_plan.json"));
+    /// registry.register_language("sac", Language::create("sac2c", vec![]));
+    /// let path = env::temp_dir().join("mock_package_build_plan");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("a.sac"), "module a;").unwrap();
+    /// let package = Package::create(&path);
+    /// package.set_language(Some("sac".to_string()));
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package);
+    ///
+    /// let source = path.join("a.sac");
+    /// let plan = registry.build_plan(&source).unwrap();
+    /// assert_eq!(plan.steps().len(), 1);
+    /// assert_eq!(plan.steps()[0].command(), registry.invocation(&source));
+    ///
+    /// let json = registry.emit_build_plan(&source, BuildPlanFormat::Json).unwrap();
+    /// assert_eq!(serde_json::from_str::<BuildPlan>(&json).unwrap(), plan);
+    /// let ninja = registry.emit_build_plan(&source, BuildPlanFormat::Ninja).unwrap();
+    /// assert!(ninja.contains("rule knapsac"));
+    /// assert!(ninja.contains("command = sac2c"));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the [`RunError`] of [`Registry::build_plan`]
+    pub fn emit_build_plan<P: AsRef<Path>>(&self, source: P, format: BuildPlanFormat) -> Result<String, RunError> {
+        Ok(self.build_plan(source)?.render(format))
+    }
+
+    /// Collects the given [`Package`] and the [`Package`]s it depends on, every [`Package`] after the ones it depends on
+    fn dependencies_first<'a>(&'a self, package: &'a Package, visiting: &mut Vec<&'a Package>, ordered: &mut Vec<&'a Package>) {
+        if visiting.contains(&package) || ordered.contains(&package) {
+            return;
+        }
+        visiting.push(package);
+        for dependency in package.dependencies() {
+            if let Some(resolved) = self.resolve_dependency(&dependency) {
+                self.dependencies_first(resolved, visiting, ordered);
+            }
+        }
+        visiting.pop();
+        ordered.push(package);
+    }
+
+    /// Returns the ids of the [`BuildStep`]s of the [`Package`]s the given [`Package`] depends on directly
+    fn steps_provided_to(&self, package: &Package, provided: &HashMap<PathBuf, Vec<PathBuf>>) -> Vec<PathBuf> {
+        let mut steps: Vec<PathBuf> = vec![];
+        for dependency in package.dependencies() {
+            for id in self.resolve_dependency(&dependency).and_then(|r| provided.get(&r.local_location)).into_iter().flatten() {
+                if !steps.contains(id) {
+                    steps.push(id.clone());
+                }
+            }
+        }
+        steps
+    }
+
+    /// Returns the [`BuildStep`] compiling the given [`Module`] of the given [`Package`]
+    fn module_step(&self, package: &Package, module: &Module, dependencies: Vec<PathBuf>) -> Result<BuildStep, RunError> {
+        let source = package.local_location.join(&module.location);
+        let language = self.get_module_language(package, module)
+            .ok_or_else(|| RunError::UnknownLanguage(source.clone()))?;
+        let outputs = match (self.get_output(&source), &language.output_template) {
+            (Some(output), Some(template)) => vec![output.join(template.replace("{identifier}", &module.identifier))],
+            (Some(output), None) => vec![output],
+            (None, _) => vec![],
+        };
+        Ok(BuildStep {
+            command: self.command_line(package, Some(module), &language, &source),
+            inputs: module.source_files(&package.local_location).iter().map(|f| package.local_location.join(f)).collect(),
+            outputs,
+            dependencies,
+            id: source,
+        })
+    }
+
+    fn build_and_run<S: AsRef<str>>(&self, package: &Package, executable: &Executable, args: &[S]) -> Result<RunOutput, RunError> {
+        let source = &package.local_location.join(&executable.location);
+        let binary = package.local_location.join(executable.binary_path());
+
+        let modified = |path: &Path| path.metadata().and_then(|m| m.modified()).ok();
+        let stale = !executable.is_prebuilt()
+            && modified(&binary).is_none_or(|built| modified(source).is_some_and(|changed| changed > built));
+        if stale {
+            self.link_executable(package, executable)?;
+        }
+
+        let search_path = self.try_dependency_outputs(source, None).map_err(RunError::Dependency)?;
+        let mut directories = search_path.clone();
+        if let Some(existing) = env::var_os(SEARCH_PATH_VARIABLE) {
+            directories.extend(env::split_paths(&existing));
+        }
+        let output = Command::new(&binary)
+            .args(args.iter().map(|a| a.as_ref()))
+            .env(SEARCH_PATH_VARIABLE, env::join_paths(directories).unwrap())
+            .output()
+            .map_err(|e| RunError::Spawn(binary.display().to_string(), e.to_string()))?;
+        Ok(RunOutput {
+            status: output.status,
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            built: stale,
+            search_path,
+        })
+    }
+
+    /// Attaches the [`TestModule`] at the given source [`Path`] to the [`Module`] it tests and returns it
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of the [`TestModule`]
+    /// * `for_module` - An absolute [`Path`] pointing to the source file of the [`Module`] being tested,
+    ///   `None` to test the [`Package`] as a whole
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] contains the given source [`Path`],
+    /// or when the [`Module`] being tested is not a [`Module`] of that same [`Package`]
+    pub fn add_test<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, for_module: Option<Q>) -> TestModule {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .unwrap_or_else(|| panic!("No package registered for {}", source.display()));
+        let target = for_module.as_ref().map(|m| {
+            m.as_ref().strip_prefix(&package.local_location)
+                .unwrap_or_else(|_| panic!("{} is not part of the package of {}", m.as_ref().display(), source.display()))
+        });
+        let test = TestModule::create(package.strip_prefix(source), target);
+        let manifests = self.manifest_contents(&[package]);
+        package.add_test(test.clone());
+        self.journal_manifests(manifests);
+        let mut arguments = vec![source.display().to_string()];
+        arguments.extend(for_module.map(|m| m.as_ref().display().to_string()));
+        self.audit("add_test", arguments, "ok");
+        test
+    }
+
+    /// Builds and runs the [`TestModule`]s of the given target and collects their results in a [`TestReport`]
+    /// Every [`TestModule`] of a [`Package`] is run when the target is its `local_location`, otherwise only the
+    /// [`TestModule`]s attached to the [`Module`] at the target are run
+    /// [`TestModule`]s are built like [`Executable`]s of their [`Package`], see [`Registry::run`]
+    ///
+    /// # Arguments
+    /// * `target` - An absolute [`Path`] pointing to the directory of a [`Package`] or to the source file of a [`Module`]
+    ///
+    /// # Panics
+    /// Panics when no registered [`Package`] contains the given target [`Path`]
+    pub fn test<P: AsRef<Path>>(&self, target: P) -> TestReport {
+        let target = target.as_ref();
+        let package = self.get_by_source(target)
+            .unwrap_or_else(|| panic!("No package registered for {}", target.display()));
+        let location = package.strip_prefix(target);
+        let mut report = TestReport::default();
+        for test in package.tests() {
+            if target != package.local_location && test.target.as_deref() != Some(location.as_path()) {
+                continue;
+            }
+            let source = package.local_location.join(&test.location);
+            let outcome = match self.build_and_run(package, &Executable::create(&test.location), &[] as &[&str]) {
+                Ok(output) if output.success() => TestOutcome::Passed(output),
+                Ok(output) => TestOutcome::Failed(output),
+                Err(error) => TestOutcome::Error(error),
+            };
+            report.results.push(TestResult { source, outcome });
+        }
+        report
+    }
+
+    /// Returns the ABI tag that artifacts of the [`Module`] at the given source [`Path`] are produced with
+    /// Returns `None` when the [`Language`] of the [`Module`] is unknown or its compiler version cannot be detected
+    /// See [`Language::abi_tag`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    pub fn abi_tag<P: AsRef<Path>>(&self, source: P) -> Option<String> {
+        let package = self.get_by_source(&source)?;
+        let module = package.get_module_by_location(package.strip_prefix(&source))?;
+        let language = self.get_module_language(package, &module)?;
+        language.detect_version().map(|v| language.abi_tag(&v))
+    }
+
+    /// Records the ABI tag of the artifact the [`Module`] at the given source [`Path`] was compiled to
+    /// Should be called after every successful compilation, usually with [`Registry::abi_tag`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `tag` - The ABI tag of the artifact, or `None` when the artifact was removed
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_artifact_tag.json"));
+    /// let package_path = env::temp_dir().join("mock_package_artifact_tag");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package.clone());
+    ///
+    /// let module = registry.set_artifact_tag(package_path.join("a.sac"), Some("sac2c-1.3.3-x86_64-linux".to_string()));
+    /// assert_eq!(module.artifact_tag(), Some("sac2c-1.3.3-x86_64-linux"));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at the given [`Path`]
+    pub fn set_artifact_tag<P: AsRef<Path>>(&self, source: P, tag: Option<String>) -> Module {
+        let package = self.get_by_source(&source)
+            .unwrap_or_else(|| panic!("No package registered for {}", source.as_ref().display()));
+        let module = package.get_module_by_location(package.strip_prefix(&source))
+            .unwrap_or_else(|| panic!("No module registered @ {}", source.as_ref().display()));
+
+        let mut changed = module.clone();
+        changed.artifact_tag = tag.clone();
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, changed.clone()).unwrap_or_else(|e| panic!("{}", e));
+        self.journal_manifests(manifests);
+        self.audit("set_artifact_tag", vec![source.as_ref().display().to_string(), tag.unwrap_or_default()], "ok");
+        changed
+    }
+
+    /// Restores the prebuilt outputs the given [`Package`] was published with, see [`Package::set_artifact_mode`]
+    /// The outputs of a [`Module`] are restored to its output directory when their ABI tag matches the one
+    /// the host produces, see [`Registry::abi_tag`], otherwise the [`Module`] needs to be built from source
+    /// Artifacts stored in git are fetched from the `origin` remote unless the [`Registry`] is offline
+    /// Called for every [`Package`] that is installed
+    ///
+    /// # Arguments
+    /// * `package` - The registered [`Package`] to restore prebuilt outputs for
+    pub fn restore_prebuilt(&self, package: &Package) -> PrebuiltReport {
+        let mut report = PrebuiltReport::default();
+        for (location, tag) in package.prebuilt() {
+            let source = package.local_location.join(&location);
+            let restored = match (self.abi_tag(&source), self.get_output(&source)) {
+                (Some(host), Some(output)) if host == tag => match package.prebuilt_files(&location, !self.offline) {
+                    Some(files) => {
+                        for (file, contents) in files {
+                            create_dir_all(output.join(&file).parent().unwrap()).unwrap();
+                            write(output.join(&file), contents).unwrap();
+                        }
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            };
+            report.record(&source, restored);
+        }
+        report
+    }
+
+    /// Records the files a build of the [`Module`] at the given source [`Path`] produced, so [`Registry::clean`] can remove them,
+    /// and returns the changed [`Module`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`]
+    /// * `files` - The files reported by the compiler, relative ones are resolved against the [`Module`]'s output directory,
+    ///   `None` records every file currently in the [`Module`]'s output directories, including those of its profiles
+    ///
+    /// # Panics
+    /// Panics when no [`Module`] is registered at the given [`Path`]
+    pub fn record_artifacts<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, files: Option<Vec<Q>>) -> Module {
+        let source = source.as_ref();
+        let package = self.get_by_source(source)
+            .unwrap_or_else(|| panic!("No package registered for {}", source.display()));
+        let module = package.get_module_by_location(package.strip_prefix(source))
+            .unwrap_or_else(|| panic!("No module registered @ {}", source.display()));
+
+        let output = self.get_output(source).unwrap_or_else(|| package.local_location.clone());
+        let artifacts: BTreeSet<PathBuf> = match files {
+            Some(files) => files.iter().map(|f| output.join(f)).collect(),
+            None => {
+                let mut outputs: Vec<PathBuf> = self.get_output(source).into_iter().collect();
+                outputs.extend(module.profile_outputs.keys().filter_map(|p| self.get_profile_output(source, Some(p))));
+                outputs.iter()
+                    .filter(|o| o.is_dir())
+                    .flat_map(|o| list_files(o).into_iter().map(move |f| o.join(f)))
+                    .collect()
+            }
+        };
+        let mut changed = module.clone();
+        changed.artifacts = artifacts;
+        let manifests = self.manifest_contents(&[package]);
+        package.replace_module(&module, changed.clone()).unwrap_or_else(|e| panic!("{}", e));
+        self.journal_manifests(manifests);
+        self.audit("record_artifacts", vec![source.display().to_string(), changed.artifacts.len().to_string()], "ok");
+        changed
+    }
+
+    /// Records the [`BuildEnvironment`] the given output of the [`Module`] or [`Executable`] at the given source [`Path`]
+    /// is produced in next to the output, see [`Registry::explain_artifact`], and returns it
+    /// [`Registry::link`] records the binaries it produces, external build drivers call it after every successful compilation
+    /// The file is removed by [`Registry::clean`] together with the output
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to the source file of a [`Module`] or [`Executable`]
+    /// * `output` - A [`Path`] pointing to the file or directory that was produced
+    pub fn record_build_environment<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, output: Q) -> BuildEnvironment {
+        let environment = self.build_environment(source.as_ref(), output.as_ref());
+        environment.save();
+        self.audit("record_build_environment", vec![source.as_ref().display().to_string(), output.as_ref().display().to_string()], "ok");
+        environment
+    }
+
+    /// Reports how the given artifact was produced: the [`BuildEnvironment`] recorded for it, or for the output directory containing it,
+    /// and how building it now would differ, e.g. because the compiler was upgraded or an environment variable changed
+    /// Returns `None` when no [`BuildEnvironment`] was recorded for the artifact
+    ///
+    /// # Arguments
+    /// * `path` - A [`Path`] pointing to a file or directory produced by a build
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::environment::EnvironmentChange;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_explain_artifact.json"));
+    /// registry.register_language("sac", Language::create("missing-compiler", vec![]));
+    /// let package_path = env::temp_dir().join("mock_package_explain_artifact");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.set_language(Some("sac".to_string()));
+    /// let mut module = Module::create("a.sac", None);
+    /// module.set_output(Some(package_path.join("build")));
+    /// package.add_module(module);
+    /// registry.add(package);
+    ///
+    /// fs::create_dir_all(package_path.join("build")).unwrap();
+    /// fs::write(package_path.join("build/liba.so"), "").unwrap();
+    /// registry.record_build_environment(package_path.join("a.sac"), package_path.join("build"));
+    ///
+    /// let explanation = registry.explain_artifact(package_path.join("build/liba.so")).unwrap();
+    /// assert_eq!(explanation.recorded().source(), package_path.join("a.sac"));
+    /// assert_eq!(explanation.recorded().language().unwrap().compiler(), "missing-compiler");
+    /// assert!(!explanation.is_stale());
+    ///
+    /// registry.register_language("sac", Language::create("missing-compiler", vec!["-O3".to_string()]));
+    /// let explanation = registry.explain_artifact(package_path.join("build/liba.so")).unwrap();
+    /// assert!(explanation.changes().contains(&EnvironmentChange::Language));
+    /// assert!(registry.explain_artifact(package_path.join("a.sac")).is_none());
+    /// ```
+    pub fn explain_artifact<P: AsRef<Path>>(&self, path: P) -> Option<ArtifactExplanation> {
+        let artifact = path.as_ref();
+        let recorded = artifact.ancestors().find_map(BuildEnvironment::load)?;
+        let current = self.build_environment(&recorded.source, &recorded.output);
+        Some(ArtifactExplanation {
+            artifact: artifact.to_path_buf(),
+            changes: recorded.changes(&current),
+            recorded,
+        })
+    }
+
+    /// Captures the [`BuildEnvironment`] the given output of the [`Module`] or [`Executable`] at the given source [`Path`] is produced in
+    fn build_environment(&self, source: &Path, output: &Path) -> BuildEnvironment {
+        let (language, invocation) = match (self.get_by_source(source), self.get_item(source)) {
+            (Some(package), Some(RegistryItem::Module(module))) => {
+                let language = self.get_module_language(package, &module);
+                let invocation = language.as_ref()
+                    .map(|l| self.command_line(package, Some(&module), l, source))
+                    .unwrap_or_default();
+                (language, invocation)
+            }
+            (Some(package), Some(RegistryItem::Executable(executable))) => {
+                let language = match &executable.language {
+                    Some(name) => self.resolve_language(name),
+                    None => self.get_package_language(package),
+                };
+                let invocation = self.link_command(package, &executable).map(|(i, _)| i).unwrap_or_default();
+                (language, invocation)
+            }
+            _ => (None, vec![]),
+        };
+        BuildEnvironment {
+            source: source.to_path_buf(),
+            output: output.to_path_buf(),
+            created: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            knapsac_version: env!("CARGO_PKG_VERSION").to_string(),
+            host: host(),
+            target: self.target.clone(),
+            toolchain: language.as_ref().and_then(|l| l.version_output()),
+            language,
+            invocation,
+            variables: recorded_variables(),
+        }
+    }
+
+    /// Removes the build artifacts of the given [`CleanTarget`]: the files recorded for [`Module`]s by [`Registry::record_artifacts`]
+    /// and the binaries of [`Executable`]s and [`TestModule`]s that are not prebuilt
+    /// Returns a [`CleanReport`] listing the removed files
+    ///
+    /// # Arguments
+    /// * `target` - The [`CleanTarget`] selecting the items to clean
+    /// * `dry_run` - When `true` nothing is removed and the [`CleanReport`] lists what would be removed
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::clean::CleanTarget;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_clean.json"));
+    /// let package_path = env::temp_dir().join("mock_package_clean");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// let mut module = Module::create("a.sac", None);
+    /// module.set_output(Some(package_path.join("build")));
+    /// package.add_module(module);
+    /// registry.add(package);
+    ///
+    /// fs::create_dir_all(package_path.join("build")).unwrap();
+    /// fs::write(package_path.join("build/liba.so"), "0123456789").unwrap();
+    /// registry.record_artifacts(package_path.join("a.sac"), None::<Vec<&str>>);
+    ///
+    /// let report = registry.clean(CleanTarget::Package(package_path.clone()), true);
+    /// assert_eq!(report.removed(), &[package_path.join("build/liba.so")]);
+    /// assert_eq!(report.bytes(), 10);
+    /// assert!(package_path.join("build/liba.so").exists());
+    ///
+    /// registry.clean(CleanTarget::All, false);
+    /// assert!(!package_path.join("build/liba.so").exists());
+    /// ```
+    ///
+    /// [`TestModule`]: crate::testing::TestModule
+    pub fn clean(&self, target: CleanTarget, dry_run: bool) -> CleanReport {
+        let mut report = CleanReport::default();
+        let (packages, item): (Vec<&Package>, Option<&Path>) = match &target {
+            CleanTarget::Item(source) => (self.get_by_source(source).into_iter().collect(), Some(source.as_path())),
+            CleanTarget::Package(location) => (self.packages.iter().filter(|p| same_path(&p.local_location, location)).collect(), None),
+            CleanTarget::All => (self.iter_packages().collect(), None),
+        };
+        for package in packages {
+            let root = &package.local_location;
+            let selected = |location: &Path| item.is_none_or(|i| root.join(location) == i);
+            let mut manifest = package.load_manifest();
+            let modules: Vec<Module> = manifest.modules.iter().filter(|m| selected(&m.location) && !m.artifacts.is_empty()).cloned().collect();
+            for module in &modules {
+                module.artifacts.iter().for_each(|a| report.add(a));
+                module.artifacts.iter().for_each(|a| report.add(&metadata_path(a)));
+                if let Some(output) = self.get_output(root.join(&module.location)) {
+                    report.add(&metadata_path(output));
+                }
+            }
+            for executable in manifest.executables.iter().filter(|e| selected(&e.location) && !e.is_prebuilt()) {
+                report.add(&root.join(executable.binary_path()));
+                report.add(&metadata_path(root.join(executable.binary_path())));
+            }
+            for test in manifest.tests.iter().filter(|t| selected(&t.location)) {
+                report.add(&root.join(Executable::create(&test.location).binary_path()));
+            }
+            if !dry_run && !modules.is_empty() {
+                for module in modules {
+                    manifest.remove_module(&module);
+                    manifest.add_module(Module { artifacts: BTreeSet::new(), ..module });
+                }
+                manifest.save(package.manifest_location());
+            }
+        }
+        report.removed.sort();
+        if !dry_run {
+            for file in &report.removed {
+                let _ = remove_file(file);
+            }
+            self.audit("clean", vec![format!("{:?}", target), report.removed.len().to_string()], "ok");
+        }
+        report
+    }
+
+    /// Sets the [`RemoteIndex`] used to find [`Package`]s by identifier, see [`Registry::update_index`]
+    ///
+    /// # Arguments
+    /// * `index` - The [`RemoteIndex`] to use from now on, or `None` to remove it
+    pub fn set_remote_index(&mut self, index: Option<RemoteIndex>) {
+        let argument = index.as_ref().map(|i| i.remote.to_string()).unwrap_or_default();
+        self.audit("set_remote_index", vec![argument], "ok");
+        self.remote_index = index;
+        self.persist();
+    }
+
+    /// Returns the [`RemoteIndex`] used to find [`Package`]s by identifier, if set
+    pub fn remote_index(&self) -> Option<&RemoteIndex> {
+        self.remote_index.as_ref()
+    }
+
+    /// Sets the directory of the [`PackageCache`] downloaded [`Package`]s are stored in
+    /// While a [`PackageCache`] is set, installing a [`Package`] restores a cached copy instead of downloading it again
+    ///
+    /// # Arguments
+    /// * `directory` - An absolute [`Path`] pointing to a directory, or `None` to stop caching
+    ///
+    /// # Panics
+    /// Panics when the given [`Path`] is relative
+    pub fn set_cache_directory<P: AsRef<Path>>(&mut self, directory: Option<P>) {
+        if directory.as_ref().is_some_and(|d| d.as_ref().is_relative()) {
+            panic!("Path is relative")
+        }
+        self.cache_directory = directory.map(|d| d.as_ref().to_path_buf());
+        let argument = self.cache_directory.as_ref().map(|d| d.display().to_string()).unwrap_or_default();
+        self.audit("set_cache_directory", vec![argument], "ok");
+        self.persist();
+    }
+
+    /// Returns the [`PackageCache`] downloaded [`Package`]s are stored in, if set
+    pub fn cache(&self) -> Option<PackageCache> {
+        self.cache_directory.as_ref().map(PackageCache::open)
+    }
+
+    /// Turns offline mode on or off
+    /// While offline, [`Package`]s that are not in the [`PackageCache`] can only be installed from `file://` sources
+    /// and the [`RemoteIndex`] cannot be updated
+    ///
+    /// # Arguments
+    /// * `offline` - Whether network access is forbidden
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use url::Url;
+    /// # use knapsac_lib::fetcher::GitFetcher;
+    /// # use knapsac_lib::install::InstallError;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_offline.json"));
+    /// registry.offline(true);
+    /// let source = Url::parse("https://example.com/json.git").unwrap();
+    /// let result = registry.try_install(&GitFetcher, source, env::temp_dir(), None);
+    /// assert_eq!(result.err(), Some(InstallError::Offline("https://example.com/json.git".to_string())));
+    /// ```
+    pub fn offline(&mut self, offline: bool) {
+        self.audit("offline", vec![offline.to_string()], "ok");
+        self.offline = offline;
+        self.persist();
+    }
+
+    /// Checks whether the [`Registry`] is in offline mode, see [`Registry::offline`]
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Sets the [`TrustStore`] installed [`Package`]s are verified against
+    /// While a [`TrustStore`] is set, installing a [`Package`] that is not signed with one of its keys fails
+    ///
+    /// # Arguments
+    /// * `trust_store` - The [`TrustStore`] to use from now on, or `None` to install unsigned [`Package`]s
+    pub fn set_trust_store(&mut self, trust_store: Option<TrustStore>) {
+        let keys = trust_store.as_ref().map(|t| t.keys.keys().cloned().collect()).unwrap_or_default();
+        self.audit("set_trust_store", keys, "ok");
+        self.trust_store = trust_store;
+        self.persist();
+    }
+
+    /// Returns the [`TrustStore`] installed [`Package`]s are verified against, if set
+    pub fn trust_store(&self) -> Option<&TrustStore> {
+        self.trust_store.as_ref()
+    }
+
+    /// Clones or fetches the [`RemoteIndex`] so its latest [`IndexEntry`]s can be searched and installed
+    /// Returns the number of [`IndexEntry`]s in the updated [`RemoteIndex`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::{Repository, Signature};
+    /// # use url::Url;
+    /// # use knapsac_lib::index::RemoteIndex;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let index_path = env::temp_dir().join("mock_index_update");
+    /// # let _ = fs::remove_dir_all(&index_path);
+    /// let repository = Repository::init(&index_path).unwrap();
+    /// fs::write(index_path.join("json.json"), r#"{"identifier": "json", "git_url": "https://example.com/json.git", "versions": ["1.0.0"]}"#).unwrap();
+    /// let mut index = repository.index().unwrap();
+    /// index.add_path("json.json".as_ref()).unwrap();
+    /// let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+    /// let signature = Signature::now("knapsac", "knapsac@example.com").unwrap();
+    /// repository.commit(Some("HEAD"), &signature, &signature, "Add json", &tree, &[]).unwrap();
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_update_index.json"));
+    /// let checkout = env::temp_dir().join("mock_index_update_checkout");
+    /// # let _ = fs::remove_dir_all(&checkout);
+    /// registry.set_remote_index(Some(RemoteIndex::create(Url::from_directory_path(&index_path).unwrap(), &checkout)));
+    /// assert_eq!(registry.update_index(), Ok(1));
+    /// assert_eq!(registry.search_remote("jsn")[0].identifier(), "json");
+    /// ```
+    pub fn update_index(&mut self) -> Result<usize, IndexError> {
+        let index = self.remote_index.as_ref().ok_or(IndexError::NoIndex)?;
+        if self.offline && index.remote.scheme() != "file" {
+            return Err(IndexError::Offline);
+        }
+        let result = index.update().map(|_| index.entries().len());
+        let outcome = match &result {
+            Ok(count) => format!("{} entries", count),
+            Err(error) => error.to_string(),
+        };
+        self.audit("update_index", vec![index.remote.to_string()], &outcome);
+        result
+    }
+
+    /// Searches the local checkout of the [`RemoteIndex`] for [`IndexEntry`]s whose identifier matches the query
+    /// Exact matches come first, then prefix matches and fuzzy matches, see [`MatchKind`]
+    /// Returns no [`IndexEntry`]s when no [`RemoteIndex`] is set
+    ///
+    /// # Arguments
+    /// * `query` - The (partial) identifier to search for
+    ///
+    /// [`MatchKind`]: crate::search::MatchKind
+    pub fn search_remote(&self, query: &str) -> Vec<IndexEntry> {
+        let index = match &self.remote_index {
+            Some(index) => index,
+            None => return vec![],
+        };
+        let mut results: Vec<(u32, IndexEntry)> = index
+            .entries()
+            .into_iter()
+            .filter_map(|e| rank(query, e.identifier.as_str()).map(|(_, score)| (score, e)))
+            .collect();
+        results.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.identifier.cmp(&b.1.identifier)));
+        results.into_iter().map(|(_, e)| e).collect()
+    }
+
+    /// Sets the directory relative output directories are resolved against under [`OutputPolicy::ArtifactsRoot`]
+    ///
+    /// # Arguments
+    /// * `root` - An absolute [`Path`] pointing to a directory, or `None` to remove the artifacts root
+    ///
+    /// # Panics
+    /// Panics when the given [`Path`] is relative, see [`Registry::try_set_artifacts_root`]
+    pub fn set_artifacts_root<P: AsRef<Path>>(&mut self, root: Option<P>) {
+        self.try_set_artifacts_root(root).unwrap_or_else(|e| panic!("Artifacts root {}", e))
+    }
+
+    /// Sets the artifacts root like [`Registry::set_artifacts_root`], returning an [`Error`] instead of panicking
+    ///
+    /// # Arguments
+    /// * `root` - An absolute [`Path`] pointing to a directory, or `None` to remove the artifacts root
+    ///
+    /// # Errors
+    /// Returns [`Error::RelativePath`] when the given [`Path`] is relative, the artifacts root is left unchanged
+    pub fn try_set_artifacts_root<P: AsRef<Path>>(&mut self, root: Option<P>) -> Result<(), Error> {
+        let root = root.map(|r| r.as_ref().to_path_buf());
+        if let Some(root) = root.as_ref().filter(|r| r.is_relative()) {
+            return Err(Error::RelativePath(root.clone()));
+        }
+        self.audit("set_artifacts_root", root.iter().map(|r| r.display().to_string()).collect(), "ok");
+        self.artifacts_root = root;
+        self.persist();
+        Ok(())
+    }
+
+    /// Returns the directory relative output directories are resolved against, if set
+    pub fn artifacts_root(&self) -> Option<&Path> {
+        self.artifacts_root.as_deref()
+    }
+
+    /// Restricts the [`Registry`] to [`Package`]s and [`Module`]s located inside the given directories
+    /// This protects a shared [`Registry`] from absorbing temporary files, build outputs or other users' files
+    /// An empty list allows every location
+    ///
+    /// # Arguments
+    /// * `roots` - Absolute [`Path`]s pointing to directories
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::packaging::{ExecutablePolicy, PackagingError};
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_allowed_roots.json"));
+    /// let package_path = env::temp_dir().join("mock_package_allowed_roots");
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// registry.add(package);
+    ///
+    /// registry.set_allowed_roots(vec![env::temp_dir().join("projects")]);
+    /// assert!(!registry.is_allowed(&package_path));
+    /// assert_eq!(
+    ///     registry.package(&package_path, ExecutablePolicy::Skip),
+    ///     Err(PackagingError::OutsideAllowedRoots(package_path))
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when one of the given [`Path`]s is relative, see [`Registry::try_set_allowed_roots`]
+    pub fn set_allowed_roots(&mut self, roots: Vec<PathBuf>) {
+        self.try_set_allowed_roots(roots).unwrap_or_else(|e| panic!("Allowed root {}", e))
+    }
+
+    /// Restricts the [`Registry`] like [`Registry::set_allowed_roots`], returning an [`Error`] instead of panicking
+    ///
+    /// # Arguments
+    /// * `roots` - Absolute [`Path`]s pointing to directories
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use std::path::PathBuf;
+    /// # use knapsac_lib::Error;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_try_allowed_roots.json"));
+    /// let error = registry.try_set_allowed_roots(vec![PathBuf::from("projects")]).unwrap_err();
+    /// assert_eq!(error, Error::RelativePath(PathBuf::from("projects")));
+    /// assert!(registry.allowed_roots().is_empty());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`Error::RelativePath`] when one of the given [`Path`]s is relative, the allowed roots are left unchanged
+    pub fn try_set_allowed_roots(&mut self, roots: Vec<PathBuf>) -> Result<(), Error> {
+        if let Some(root) = roots.iter().find(|r| r.is_relative()) {
+            return Err(Error::RelativePath(root.clone()));
+        }
+        let roots: Vec<PathBuf> = roots.iter().map(canonicalize).collect();
+        self.audit("set_allowed_roots", roots.iter().map(|r| r.display().to_string()).collect(), "ok");
+        self.allowed_roots = roots;
+        self.persist();
+        Ok(())
+    }
+
+    /// Returns the directories [`Package`]s and [`Module`]s need to be located in, empty when every location is allowed
+    pub fn allowed_roots(&self) -> &[PathBuf] {
+        &self.allowed_roots
+    }
+
+    /// Checks whether the given [`Path`] lies inside one of the [`Registry`]'s allowed roots
+    /// Both are compared in their canonical form, see [`canonicalize`], so `..` components and symbolic links
+    /// cannot be used to step out of a root, and a root reached through a symbolic link still matches
+    ///
+    /// # Arguments
+    /// * `path` - An absolute [`Path`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_is_allowed.json"));
+    /// let root = env::temp_dir().join("mock_allowed_root");
+    /// registry.set_allowed_roots(vec![root.clone()]);
+    /// assert!(registry.is_allowed(root.join("json")));
+    /// assert!(!registry.is_allowed(root.join("..").join("elsewhere")));
+    /// ```
+    pub fn is_allowed<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = canonicalize(path);
+        self.allowed_roots.is_empty() || self.allowed_roots.iter().any(|r| starts_with(&path, canonicalize(r)))
+    }
+
+    fn check_allowed<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        match self.is_allowed(&path) {
+            true => Ok(()),
+            false => Err(Error::OutsideAllowedRoots(path.as_ref().to_path_buf())),
+        }
+    }
+
+    /// Sets which hooks declared by [`Package`]s the [`Registry`] runs, see [`HookPolicy`]
+    /// By default only hooks of local [`Package`]s run, so installing a [`Package`] never executes the commands it ships
+    ///
+    /// # Arguments
+    /// * `policy` - The [`HookPolicy`] to use from now on
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use knapsac_lib::hooks::HookPolicy;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_hook_policy.json"));
+    /// assert_eq!(registry.hook_policy(), HookPolicy::Local);
+    /// registry.set_hook_policy(HookPolicy::All);
+    /// assert_eq!(registry.hook_policy(), HookPolicy::All);
+    /// ```
+    pub fn set_hook_policy(&mut self, policy: HookPolicy) {
+        self.audit("set_hook_policy", vec![format!("{:?}", policy)], "ok");
+        self.hook_policy = policy;
+        self.persist();
+    }
+
+    /// Returns which hooks declared by [`Package`]s the [`Registry`] runs
+    pub fn hook_policy(&self) -> HookPolicy {
+        self.hook_policy
+    }
+
+    /// Sets whether the [`Registry`] stores paths relative to the directory of its file, `false` by default
+    /// Paths are resolved against that directory again when the [`Registry`] is loaded, so a project-local registry,
+    /// e.g. a checked-in `.knapsac/registry.json`, keeps working when the project is moved or checked out elsewhere
+    /// Relative paths are written with `/` separators and read with either separator, see [`to_portable`] and [`from_portable`],
+    /// so the registry can be shared between Linux, macOS and Windows
+    ///
+    /// # Arguments
+    /// * `relative` - Whether paths are stored relative to the directory of the registry file
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let project = env::temp_dir().join("mock_project_relative");
+    /// let moved = env::temp_dir().join("mock_project_relative_moved");
+    /// # let _ = fs::remove_dir_all(&project);
+    /// # let _ = fs::remove_dir_all(&moved);
+    /// fs::create_dir_all(project.join(".knapsac")).unwrap();
+    /// Repository::init(project.join("lib"));
+    /// let mut registry = Registry::initialize(project.join(".knapsac/registry.json"));
+    /// registry.add(Package::create(project.join("lib")));
+    ///
+    /// registry.set_relative_paths(true);
+    /// drop(registry);
+    /// assert!(fs::read_to_string(project.join(".knapsac/registry.json")).unwrap().contains("\"../lib\""));
+    ///
+    /// fs::rename(&project, &moved).unwrap();
+    /// let registry = Registry::load(moved.join(".knapsac/registry.json"));
+    /// assert!(registry.get_by_local_location(moved.join("lib")).is_some());
+    /// ```
+    pub fn set_relative_paths(&mut self, relative: bool) {
+        self.audit("set_relative_paths", vec![relative.to_string()], "ok");
+        self.relative_paths = relative;
+        self.persist();
+    }
+
+    /// Checks whether the [`Registry`] stores paths relative to the directory of its file
+    pub fn relative_paths(&self) -> bool {
+        self.relative_paths
+    }
+
+    /// Sets the [`Style`] the [`Registry`] is written to disk in, [`Style::Pretty`] by default
+    ///
+    /// # Arguments
+    /// * `style` - The [`Style`] to write the registry file in from now on
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use knapsac_lib::format::Style;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let path = env::temp_dir().join("registry_style.json");
+    /// let mut registry = Registry::initialize(&path);
+    /// assert!(fs::read_to_string(&path).unwrap().lines().count() > 2);
+    ///
+    /// registry.set_style(Style::Compact);
+    /// assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+    /// assert_eq!(Registry::load(&path).style(), Style::Compact);
+    /// ```
+    pub fn set_style(&mut self, style: Style) {
+        self.audit("set_style", vec![format!("{:?}", style)], "ok");
+        self.style = style;
+        self.persist();
+    }
+
+    /// Returns the [`Style`] the [`Registry`] is written to disk in
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Runs the hook of the given [`HookKind`] of a [`Package`], if it declares one and the [`HookPolicy`] allows it
+    pub(crate) fn run_hook(&self, package: &Package, kind: HookKind) -> Result<(), HookError> {
+        let allowed = match self.hook_policy {
+            HookPolicy::All => true,
+            HookPolicy::Local => !self.provenance.contains_key(&package.local_location),
+            HookPolicy::None => false,
+        };
+        if !allowed {
+            return Ok(());
+        }
+        let outputs: BTreeSet<PathBuf> = package.load_manifest().modules.iter()
+            .filter_map(|m| self.get_output(package.local_location.join(&m.location)))
+            .collect();
+        let outputs: Vec<PathBuf> = outputs.into_iter().collect();
+        package.run_hook(kind, &outputs)
+    }
+
+    /// Sets the target outputs are requested for, e.g. `x86_64-linux` or `wasm32`
+    /// Resolving outputs, building and vendoring then select the directories [`Module`]s have for that target,
+    /// see [`Registry::set_target_output`], and the default ones for [`Module`]s without them
+    ///
+    /// # Arguments
+    /// * `target` - The name of the target, or `None` to request outputs for the host
+    pub fn set_target<S: Into<String>>(&mut self, target: Option<S>) {
+        let target = target.map(Into::into);
+        self.audit("set_target", vec![target.clone().unwrap_or_default()], "ok");
+        self.target = target;
+        self.persist();
+    }
+
+    /// Returns the target outputs are requested for, `None` for the host, see [`Registry::set_target`]
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Sets the patterns every sweep of a [`Package`] leaves out, see [`IgnoreRules`]
+    /// They apply to [`Registry::package`] and [`Registry::add_items_matching`] in every [`Package`],
+    /// the [`IGNORE_FILE`] in the root of a [`Package`] takes precedence over them
+    ///
+    /// # Arguments
+    /// * `patterns` - The rules, each a single line of an [`IGNORE_FILE`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_ignore_patterns.json"));
+    /// registry.set_ignore_patterns(vec!["*~".to_string(), "/vendor".to_string()]);
+    /// let package_path = env::temp_dir().join("mock_package_ignore_patterns");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::create_dir_all(package_path.join("vendor")).unwrap();
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// fs::write(package_path.join("a.sac~"), "").unwrap();
+    /// fs::write(package_path.join("vendor/json.sac"), "").unwrap();
+    /// fs::write(package_path.join("b.sac"), "").unwrap();
+    /// fs::write(package_path.join(".knapsacignore"), "b.sac\n").unwrap();
+    /// registry.add(Package::create(&package_path));
+    ///
+    /// let added = registry.add_items_matching(&package_path, "**", None::<&str>);
+    /// assert_eq!(added.len(), 1);
+    /// assert_eq!(added[0].identifier, "a");
+    /// ```
+    pub fn set_ignore_patterns(&mut self, patterns: Vec<String>) {
+        self.audit("set_ignore_patterns", patterns.clone(), "ok");
+        self.ignore_patterns = patterns;
+        self.persist();
+    }
+
+    /// Returns the patterns every sweep of a [`Package`] leaves out, see [`Registry::set_ignore_patterns`]
+    pub fn ignore_patterns(&self) -> &[String] {
+        &self.ignore_patterns
+    }
+
+    /// Returns the [`IgnoreRules`] sweeping the given [`Package`] applies: the [`IGNORE_FILE`] itself,
+    /// then the patterns of the [`Registry`], see [`Registry::set_ignore_patterns`], and finally the rules in its [`IGNORE_FILE`]
+    ///
+    /// # Arguments
+    /// * `package` - The [`Package`] being swept
+    pub fn ignore_rules(&self, package: &Package) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        rules.add(IGNORE_FILE);
+        self.ignore_patterns.iter().for_each(|p| rules.add(p));
+        rules.extend(IgnoreRules::load(&package.local_location));
+        rules
+    }
+
+    /// Sets what relative output directories of [`Module`]s are resolved against
+    ///
+    /// # Arguments
+    /// * `policy` - The [`OutputPolicy`] to use from now on
+    pub fn set_output_policy(&mut self, policy: OutputPolicy) {
+        self.audit("set_output_policy", vec![format!("{:?}", policy)], "ok");
+        self.output_policy = policy;
+        self.persist();
+    }
+
+    /// Sweeps every file in the given directory into the registered [`Package`] containing it
+    /// Files that already are a [`Module`] or an [`Executable`], the [`Package`]'s manifest
+    /// and files ignored by the [`Package`]'s [`IgnoreRules`], see [`Registry::ignore_rules`], are left alone
+    /// [`Dependency`]s of the [`Package`] on registered [`Package`]s are declared with the identifier of that [`Package`]
+    /// Returns the [`RegistryItem`]s that were added
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to a directory within a registered [`Package`]
+    /// * `policy` - The [`ExecutablePolicy`] deciding what happens to executables in the directory
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::packaging::ExecutablePolicy;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_package_sweep.json"));
+    /// let package_path = env::temp_dir().join("mock_package_sweep");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::create_dir_all(package_path.join("src")).unwrap();
+    /// fs::write(package_path.join("src").join("a.sac"), "").unwrap();
+    /// fs::write(package_path.join("src").join("b.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// registry.add(package.clone());
+    ///
+    /// let added = registry.package(package_path.join("src"), ExecutablePolicy::Error).unwrap();
+    /// assert_eq!(added.len(), 2);
+    /// assert!(package.get_module_by_location("src/a.sac").is_some());
+    /// ```
+    pub fn package<P: AsRef<Path>>(&mut self, source: P, policy: ExecutablePolicy) -> Result<Vec<RegistryItem>, PackagingError> {
+        self.begin_package(source, policy).result()
+    }
+
+    /// Sweeps the selected files in the given directory into the registered [`Package`] containing it, like [`Registry::package`]
+    /// Files that are not selected are left alone, e.g. to carve a [`Package`] out of a larger source tree
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to a directory within a registered [`Package`]
+    /// * `policy` - The [`ExecutablePolicy`] deciding what happens to executables in the directory
+    /// * `selection` - The [`PackageSelection`] deciding which files are swept
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::packaging::{ExecutablePolicy, PackageSelection};
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_package_selected.json"));
+    /// let package_path = env::temp_dir().join("mock_package_selected");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// for file in ["a.sac", "b.sac", "c.sac"] {
+    ///     fs::write(package_path.join(file), "").unwrap();
+    /// }
+    /// let package = Package::create(&package_path);
+    /// registry.add(package.clone());
+    ///
+    /// let selection = PackageSelection::Only(vec![package_path.join("a.sac")]);
+    /// assert_eq!(registry.package_selected(&package_path, ExecutablePolicy::Error, selection).unwrap().len(), 1);
+    /// let selection = PackageSelection::matching(|p| p.file_stem().is_some_and(|s| s != "c"));
+    /// assert_eq!(registry.package_selected(&package_path, ExecutablePolicy::Error, selection).unwrap().len(), 1);
+    /// assert!(package.get_module_by_location("b.sac").is_some());
+    /// assert!(package.get_module_by_location("c.sac").is_none());
+    /// ```
+    pub fn package_selected<P: AsRef<Path>>(&mut self, source: P, policy: ExecutablePolicy, selection: PackageSelection) -> Result<Vec<RegistryItem>, PackagingError> {
+        self.begin_package_selected(source, policy, selection).result()
+    }
+
+    /// Starts sweeping every file in the given directory into the registered [`Package`] containing it, see [`Registry::package`]
+    /// The first call to [`OperationHandle::poll_status`] scans the directory, every following call adds a single [`Module`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to a directory within a registered [`Package`]
+    /// * `policy` - The [`ExecutablePolicy`] deciding what happens to executables in the directory
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::operation::OperationStatus;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::packaging::ExecutablePolicy;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_begin_package.json"));
+    /// let package_path = env::temp_dir().join("mock_package_begin_package");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// fs::write(package_path.join("b.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// registry.add(package.clone());
+    ///
+    /// let mut handle = registry.begin_package(&package_path, ExecutablePolicy::Error);
+    /// assert_eq!(handle.poll_status(), &OperationStatus::Running(1, 3));
+    /// assert_eq!(handle.poll_status(), &OperationStatus::Running(2, 3));
+    /// handle.cancel();
+    /// assert_eq!(handle.poll_status(), &OperationStatus::Cancelled);
+    /// assert!(package.get_module_by_location("a.sac").is_none());
+    /// ```
+    pub fn begin_package<P: AsRef<Path>>(&mut self, source: P, policy: ExecutablePolicy) -> OperationHandle<'_, Vec<RegistryItem>, PackagingError> {
+        self.begin_package_selected(source, policy, PackageSelection::All)
+    }
+
+    /// Starts sweeping the selected files in the given directory into the registered [`Package`] containing it,
+    /// see [`Registry::package_selected`] and [`Registry::begin_package`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to a directory within a registered [`Package`]
+    /// * `policy` - The [`ExecutablePolicy`] deciding what happens to executables in the directory
+    /// * `selection` - The [`PackageSelection`] deciding which files are swept
+    pub fn begin_package_selected<P: AsRef<Path>>(&mut self, source: P, policy: ExecutablePolicy, selection: PackageSelection) -> OperationHandle<'_, Vec<RegistryItem>, PackagingError> {
+        let source = source.as_ref();
+        let operation = Box::new(PackageOperation::create(source, policy, selection));
+        match self.is_allowed(source) {
+            true => OperationHandle::new(self, operation),
+            false => OperationHandle::failed(self, operation, PackagingError::OutsideAllowedRoots(source.to_path_buf())),
+        }
+    }
+
+    /// Turns a standalone source file into a published [`Package`] holding it as its only [`Module`]
+    /// The [`Package`] is created in a new git repository named after the identifier without its scope, next to the source file,
+    /// the source file is copied into it and the [`Package`] is published as `0.1.0`, see [`Package::publish`]
+    /// The repository is its own `origin`, so registered [`Package`]s that used the directory of the source file
+    /// as a stray dependency, see [`Package::add_stray`], now declare a [`Dependency`] on the new [`Package`] instead
+    /// Building the [`Module`] is left to the build tool, see [`Registry::build_plan`]
+    ///
+    /// # Arguments
+    /// * `source` - An absolute [`Path`] pointing to a source file that does not belong to a registered [`Package`]
+    /// * `identifier` - The identifier of the new [`Package`] and its [`Module`], see [`PackageId`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_single_module.json"));
+    /// let directory = env::temp_dir().join("mock_single_module");
+    /// # let _ = fs::remove_dir_all(&directory);
+    /// fs::create_dir_all(&directory).unwrap();
+    /// fs::write(directory.join("json.sac"), "module json;").unwrap();
+    ///
+    /// let package = registry.package_single_module(directory.join("json.sac"), "json").unwrap();
+    /// assert_eq!(package.package_id().unwrap().as_str(), "json");
+    /// assert_eq!(package.version().unwrap().to_string(), "0.1.0");
+    /// assert!(package.get_module_by_location("json.sac").is_some());
+    /// assert!(registry.contains(&package));
+    /// assert!(registry.package_single_module(directory.join("json.sac"), "json").is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`PackagingError::InvalidIdentifier`] when the identifier is not a valid [`PackageId`],
+    /// [`PackagingError::NotStandalone`] when `source` is not a file or already belongs to a registered [`Package`],
+    /// [`PackagingError::OutsideAllowedRoots`] when the new [`Package`] would lie outside the allowed roots
+    /// or [`PackagingError::DestinationExists`] when the directory of the new [`Package`] already exists
+    ///
+    /// [`PackageId`]: crate::package_id::PackageId
+    pub fn package_single_module<P: AsRef<Path>>(&mut self, source: P, identifier: &str) -> Result<Package, PackagingError> {
+        let source = source.as_ref();
+        let package_id: PackageId = identifier.parse()
+            .map_err(|_| PackagingError::InvalidIdentifier(identifier.to_string()))?;
+        let (Some(directory), Some(file_name)) = (source.parent(), source.file_name()) else {
+            return Err(PackagingError::NotStandalone(source.to_path_buf()));
+        };
+        if !source.is_file() || self.get_by_source(source).is_some() {
+            return Err(PackagingError::NotStandalone(source.to_path_buf()));
+        }
+        let root = directory.join(package_id.name());
+        if !self.is_allowed(&root) {
+            return Err(PackagingError::OutsideAllowedRoots(root));
+        }
+        if root.exists() {
+            return Err(PackagingError::DestinationExists(root));
+        }
+
+        let url = Url::from_directory_path(&root).unwrap();
+        Repository::init(&root).unwrap().remote("origin", url.as_str()).unwrap();
+        copy(source, root.join(file_name)).unwrap();
+        let package = Package::create(&root);
+        let mut metadata = package.metadata();
+        metadata.set_name(Some(package_id.as_str().to_string()));
+        package.set_metadata(metadata);
+        package.add_module(Module::create(file_name, Some(package_id.name().to_string())));
+        package.publish(SemVerIncrement::Minor, vec![]);
+
+        for dependent in self.packages.iter().filter(|p| p.strays().iter().any(|s| s == directory)) {
+            dependent.remove_stray(directory);
+            dependent.add_dependency(Dependency::declare(url.clone(), package_id.as_str(), None));
+        }
+        self.audit("package_single_module", vec![source.display().to_string(), identifier.to_string()], "ok");
+        self.packages.insert(package.clone());
+        self.persist();
+        Ok(package)
+    }
+
+    /// Dissolves the registered [`Package`] with the given identifier into standalone [`Module`]s, the inverse of [`Registry::package`]
+    /// Every [`Dependency`] on the [`Package`] is replaced by stray dependencies on the output directories of its [`Module`]s,
+    /// see [`Package::add_stray`], and the [`Package`] is removed from the [`Registry`]
+    /// The files of the [`Package`], its manifest included, are left on disk
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier of the [`Package`], the highest registered [`Version`] is unpackaged
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_unpackage.json"));
+    /// let path = env::temp_dir().join("mock_package_unpackage");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("a.sac"), "module a;").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package.clone());
+    ///
+    /// let report = registry.unpackage("mock_package_unpackage").unwrap();
+    /// assert_eq!(report.modules(), &[(path.join("a.sac"), None)]);
+    /// assert!(!registry.contains(&package));
+    /// assert!(registry.unpackage("mock_package_unpackage").is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`PackagingError::UnknownPackage`] when no [`Package`] is registered under the identifier
+    pub fn unpackage(&mut self, identifier: &str) -> Result<UnpackageReport, PackagingError> {
+        let package = self.get_by_identifier(identifier, None).cloned()
+            .ok_or_else(|| PackagingError::UnknownPackage(identifier.to_string()))?;
+
+        let mut report = UnpackageReport::default();
+        let mut modules: Vec<Module> = package.load_manifest().modules.into_iter().collect();
+        modules.sort_by(|a, b| a.location.cmp(&b.location));
+        for module in modules {
+            let source = package.local_location.join(&module.location);
+            let output = self.get_output(&source);
+            report.modules.push((source, output));
+        }
+        let mut outputs: Vec<PathBuf> = vec![];
+        for output in report.modules.iter().filter_map(|(_, o)| o.clone()) {
+            match output.is_dir() {
+                true if !outputs.contains(&output) => outputs.push(output),
+                true => {}
+                false => report.missing_outputs.push(output),
+            }
+        }
+
+        let mut dependents: Vec<&Package> = self.packages.iter().filter(|p| **p != package).collect();
+        dependents.sort_by(|a, b| a.local_location.cmp(&b.local_location));
+        for dependent in dependents {
+            let dependencies: Vec<Dependency> = dependent.dependencies().into_iter()
+                .filter(|d| self.resolve_dependency(d) == Some(&package))
+                .collect();
+            if dependencies.is_empty() {
+                continue;
+            }
+            let mut manifest = dependent.load_manifest();
+            for dependency in &dependencies {
+                manifest.remove_dependency(dependency);
+            }
+            manifest.strays.extend(outputs.iter().cloned());
+            manifest.save(dependent.manifest_location());
+            report.dependents.push(dependent.local_location.clone());
+        }
+
+        self.audit("unpackage", vec![identifier.to_string()], &format!("{} dependents", report.dependents.len()));
+        self.packages.remove(&package);
+        self.provenance.remove(&package.local_location);
+        self.persist();
+        Ok(report)
+    }
+
+    /// Re-reads the manifest of the registered [`Package`] at the given [`Path`] after it was edited by hand,
+    /// and reconciles the [`Registry`] with it
+    /// The remote location of the [`Package`] is taken over from its git repository
+    /// and the content hashes of changed [`Module`]s are updated, see [`Module::content_hash`]
+    /// Everything else that disagrees is reported as a [`Discrepancy`], but left as is
+    ///
+    /// The [`Package`] is looked up by its location instead of its identifier, as the identifier is read from the manifest
+    ///
+    /// # Arguments
+    /// * `location` - A [`Path`] inside the [`Package`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::error::ManifestError;
+    /// # use knapsac_lib::sync::{Discrepancy, SyncError};
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_sync_package.json"));
+    /// let path = env::temp_dir().join("mock_package_sync_package");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// fs::write(path.join("a.sac"), "module a;").unwrap();
+    /// let package = Package::create(&path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package.clone());
+    /// assert!(registry.sync_package(&path).unwrap().is_clean());
+    ///
+    /// fs::write(path.join("a.sac"), "module a; int one() { return 1; }").unwrap();
+    /// let report = registry.sync_package(&path).unwrap();
+    /// assert_eq!(report.discrepancies(), &[Discrepancy::ChangedModule(path.join("a.sac"))]);
+    /// assert!(registry.sync_package(&path).unwrap().is_clean());
+    ///
+    /// fs::write(path.join("manifest.json"), "{ \"modules\": [").unwrap();
+    /// assert!(matches!(registry.sync_package(&path), Err(SyncError::Manifest(ManifestError::Syntax(..)))));
+    /// ```
+    ///
+    /// # Errors
+    /// * [`SyncError::UnknownPackage`] when no [`Package`] is registered at the given [`Path`]
+    /// * [`SyncError::Manifest`] when the manifest cannot be parsed or contains [`SchemaViolation`]s
+    ///
+    /// [`Module::content_hash`]: crate::module::Module::content_hash
+    /// [`SchemaViolation`]: crate::sync::SchemaViolation
+    pub fn sync_package<P: AsRef<Path>>(&mut self, location: P) -> Result<SyncReport, SyncError> {
+        let package = self.packages.iter()
+            .filter(|p| starts_with(&location, &p.local_location))
+            .max_by_key(|p| p.local_location.components().count())
+            .cloned()
+            .ok_or_else(|| SyncError::UnknownPackage(location.as_ref().to_path_buf()))?;
+        let path = package.manifest_location();
+        let mut manifest = Manifest::try_load_valid(&path).map_err(SyncError::Manifest)?;
+
+        let mut report = SyncReport::default();
+        let actual = Package {
+            local_location: package.local_location.clone(),
+            remote_location: package.current_remote_location(),
+        };
+        if actual.remote_location != package.remote_location {
+            report.discrepancies.push(Discrepancy::RemoteLocation(package.remote_location.clone(), actual.remote_location.clone()));
+        }
+
+        let mut modules: Vec<Module> = manifest.modules.iter().cloned().collect();
+        modules.sort_by(|a, b| a.location.cmp(&b.location));
+        let mut rehashed = false;
+        for mut module in modules {
+            let source = package.local_location.join(&module.location);
+            if !source.is_file() {
+                report.discrepancies.push(Discrepancy::MissingModuleFile(source));
+                continue;
+            }
+            let current = module.hash_contents(&package.local_location);
+            if module.content_hash.as_ref().is_some_and(|h| *h != current) {
+                report.discrepancies.push(Discrepancy::ChangedModule(source));
+                module.content_hash = Some(current);
+                manifest.modules.replace(module);
+                rehashed = true;
+            }
+        }
+
+        let mut dependencies: Vec<&Dependency> = manifest.dependencies.iter().collect();
+        dependencies.sort_by(|a, b| a.git_url.cmp(&b.git_url));
+        for dependency in dependencies.into_iter().filter(|d| self.resolve_dependency(d).is_none()) {
+            report.discrepancies.push(Discrepancy::UnresolvedDependency(dependency.git_url.clone()));
+        }
+
+        let identifier = package.identifier();
+        let mut conflicts: Vec<&Package> = self.packages.iter()
+            .filter(|p| p.local_location != package.local_location)
+            .filter(|p| p.identifier() == identifier && p.version() == manifest.version)
+            .collect();
+        conflicts.sort_by(|a, b| a.local_location.cmp(&b.local_location));
+        for conflict in conflicts {
+            report.discrepancies.push(Discrepancy::IdentifierConflict(identifier.clone(), conflict.local_location.clone()));
+        }
+
+        if rehashed {
+            manifest.save(&path);
+        }
+        if actual != package {
+            self.packages.remove(&package);
+            self.packages.insert(actual);
+        }
+        self.audit("sync_package", vec![package.local_location.display().to_string()], &format!("{} discrepancies", report.discrepancies.len()));
+        self.persist();
+        Ok(report)
+    }
+
+    /// Finds registered [`Module`]s that are copies of each other, e.g. because a source file was copied between projects
+    /// [`Module`]s are grouped by the content hash recorded when they were added, see [`Module::content_hash`],
+    /// so copies that diverged since are still found and reported as such, see [`DuplicateGroup::diverged`]
+    /// [`Module`]s registered without a content hash are grouped by the hash of their current contents
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_duplicates.json"));
+    /// let first = env::temp_dir().join("mock_package_duplicates_first");
+    /// let second = env::temp_dir().join("mock_package_duplicates_second");
+    /// for (path, file) in [(&first, "json.sac"), (&second, "parse.sac")] {
+    ///     # let _ = fs::remove_dir_all(path);
+    ///     Repository::init(path);
+    ///     fs::write(path.join(file), "module json;").unwrap();
+    ///     let package = Package::create(path);
+    ///     package.add_module(Module::create(file, None));
+    ///     registry.add(package);
+    /// }
+    ///
+    /// let duplicates = registry.find_duplicates();
+    /// assert_eq!(duplicates.len(), 1);
+    /// assert_eq!(duplicates[0].modules().len(), 2);
+    /// assert!(duplicates[0].diverged().is_empty());
+    ///
+    /// fs::write(second.join("parse.sac"), "module parse;").unwrap();
+    /// let duplicates = registry.find_duplicates();
+    /// assert_eq!(duplicates[0].diverged(), &[second.join("parse.sac")]);
+    /// assert_eq!(duplicates[0].suggested(), first.join("json.sac"));
+    /// ```
+    pub fn find_duplicates(&self) -> Vec<DuplicateGroup> {
+        let mut groups: BTreeMap<String, DuplicateGroup> = BTreeMap::new();
+        for package in &self.packages {
+            for module in package.load_manifest().modules {
+                let current = module.hash_contents(&package.local_location);
+                let recorded = module.content_hash.clone().unwrap_or_else(|| current.clone());
+                let source = package.local_location.join(&module.location);
+                let group = groups.entry(recorded.clone()).or_insert_with(|| DuplicateGroup {
+                    content_hash: recorded.clone(),
+                    modules: vec![],
+                    diverged: vec![],
+                });
+                if current != recorded {
+                    group.diverged.push(source.clone());
+                }
+                group.modules.push((source, module.identifier));
+            }
+        }
+        groups.into_values()
+            .filter(|g| g.modules.len() > 1)
+            .map(|mut g| {
+                g.modules.sort();
+                g.diverged.sort();
+                g
+            })
+            .collect()
+    }
+
+    /// Computes [`RegistryStats`] summarizing the contents of the [`Registry`] and the references in it that are dangling
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use url::Url;
+    /// # use knapsac_lib::dependency::Dependency;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_stats.json"));
+    /// let package_path = env::temp_dir().join("mock_package_stats");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// fs::create_dir_all(package_path.join("build")).unwrap();
+    /// fs::write(package_path.join("build/liba.so"), "0123456789").unwrap();
+    /// let package = Package::create(&package_path);
+    /// let mut module = Module::create("a.sac", None);
+    /// module.set_output(Some(package_path.join("build")));
+    /// package.add_module(module);
+    /// package.add_dependency(Dependency::create(Url::parse("https://example.com/missing.git").unwrap()));
+    /// registry.add(package);
+    ///
+    /// let stats = registry.stats();
+    /// assert_eq!(stats.packages(), 1);
+    /// assert_eq!(stats.items(), 1);
+    /// assert_eq!(stats.dependency_edges(), 1);
+    /// assert_eq!(stats.dangling_dependencies(), 1);
+    /// assert_eq!(stats.output_bytes(), 10);
+    /// assert!(!stats.is_healthy());
+    /// ```
+    pub fn stats(&self) -> RegistryStats {
+        let mut stats = RegistryStats {
+            packages: self.packages.len(),
+            ..RegistryStats::default()
+        };
+        let mut outputs: HashSet<PathBuf> = HashSet::new();
+        for package in &self.packages {
+            if !package.local_location.is_dir() {
+                stats.missing_files += 1;
+                continue;
+            }
+            let manifest = package.load_manifest();
+            stats.modules += manifest.modules.len();
+            stats.executables += manifest.executables.len();
+            stats.dependency_edges += manifest.dependencies.len();
+            stats.dangling_dependencies += manifest.dependencies.iter().filter(|d| self.resolve_dependency(d).is_none()).count();
+            stats.missing_strays += manifest.strays.iter().filter(|s| !s.is_dir()).count();
+            let locations = manifest.modules.iter().map(|m| &m.location).chain(manifest.executables.iter().map(|e| &e.location));
+            stats.missing_files += locations.filter(|l| !package.local_location.join(l).is_file()).count();
+            for module in &manifest.modules {
+                let source = package.local_location.join(&module.location);
+                outputs.extend(module.profile_outputs.keys().filter_map(|p| self.get_profile_output(&source, Some(p))));
+                outputs.extend(self.get_output(&source));
+            }
+        }
+        stats.output_bytes = outputs
+            .iter()
+            .filter(|o| o.is_dir())
+            .flat_map(|o| list_files(o).into_iter().map(move |f| o.join(f)))
+            .filter_map(|f| f.metadata().ok())
+            .map(|m| m.len())
+            .sum();
+        stats
+    }
+
+    /// Lists the stray dependencies of registered [`Package`]s whose output directory no longer exists,
+    /// paired with the `local_location` of the [`Package`] declaring them, see [`Package::add_stray`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_audit_strays.json"));
+    /// let package_path = env::temp_dir().join("mock_package_audit_strays");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// let output = env::temp_dir().join("mock_stray_audit_output");
+    /// fs::create_dir_all(&output).unwrap();
+    /// package.add_stray(&output);
+    /// registry.add(package);
+    /// assert!(registry.audit_strays().is_empty());
+    ///
+    /// fs::remove_dir_all(&output).unwrap();
+    /// assert_eq!(registry.audit_strays(), vec![(package_path, output)]);
+    /// ```
+    pub fn audit_strays(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.packages
+            .iter()
+            .filter(|p| p.local_location.is_dir())
+            .flat_map(|p| p.strays().into_iter().filter(|s| !s.is_dir()).map(|s| (p.local_location.clone(), s)))
+            .collect()
+    }
+
+    /// Removes [`Package`]s whose directory no longer exists, [`Module`]s whose file no longer exists
+    /// and [`Dependency`]s on removed [`Package`]s, then saves the [`Registry`]
+    /// Returns a [`PruneReport`] listing everything that was removed
+    ///
+    /// # Arguments
+    /// * `report_only` - When `true` nothing is removed and the [`PruneReport`] lists what would be removed
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_prune.json"));
+    /// let package_path = env::temp_dir().join("mock_package_prune");
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// let module = Module::create("a.sac", None);
+    /// package.add_module(module.clone());
+    /// registry.add(package.clone());
+    ///
+    /// fs::remove_file(package_path.join("a.sac")).unwrap();
+    /// assert_eq!(registry.prune(true).modules().len(), 1);
+    /// assert!(package.has_module(&module));
+    ///
+    /// registry.prune(false);
+    /// assert!(!package.has_module(&module));
+    /// ```
+    pub fn prune(&mut self, report_only: bool) -> PruneReport {
+        let mut report = PruneReport::default();
+
+        let (vanished, remaining): (Vec<Package>, Vec<Package>) = self.packages
+            .iter()
+            .cloned()
+            .partition(|p|!p.local_location.is_dir());
+        let vanished_urls: Vec<Url> = vanished.iter().filter_map(|p|p.remote_location.clone()).collect();
+        report.packages = vanished.iter().map(|p|p.local_location.clone()).collect();
+
+        for package in &remaining {
+            let mut manifest = package.load_manifest();
+            let missing_modules: Vec<Module> = manifest.modules
+                .iter()
+                .filter(|m|!package.local_location.join(&m.location).is_file())
+                .cloned()
+                .collect();
+            let missing_executables: Vec<Executable> = manifest.executables
+                .iter()
+                .filter(|e|!package.local_location.join(&e.location).is_file())
+                .cloned()
+                .collect();
+            let dangling_dependencies: Vec<Dependency> = manifest.dependencies
+                .iter()
+                .filter(|d|vanished_urls.contains(&d.git_url))
+                .cloned()
+                .collect();
+
+            report.modules.extend(missing_modules.iter().map(|m|package.local_location.join(&m.location)));
+            report.modules.extend(missing_executables.iter().map(|e|package.local_location.join(&e.location)));
+            report.dependencies.extend(dangling_dependencies.iter().map(|d|(package.local_location.clone(), d.git_url.clone())));
+
+            let changed = !missing_modules.is_empty() || !missing_executables.is_empty() || !dangling_dependencies.is_empty();
+            if changed && !report_only {
+                missing_modules.iter().for_each(|m|manifest.remove_module(m));
+                missing_executables.iter().for_each(|e|manifest.remove_executable(e));
+                dangling_dependencies.iter().for_each(|d|manifest.remove_dependency(d));
+                manifest.save(package.manifest_location());
+            }
+        }
+
+        if !report_only && !vanished.is_empty() {
+            for package in &vanished {
+                self.packages.remove(package);
+                self.provenance.remove(&package.local_location);
+            }
+            self.persist();
+        }
+        if !report_only {
+            let result = format!(
+                "removed {} package(s), {} module(s), {} dependency(s)",
+                report.packages.len(),
+                report.modules.len(),
+                report.dependencies.len()
+            );
+            self.audit("prune", vec![], &result);
+        }
+        report
+    }
+
+    /// Registers a [`Language`] under the given `name` and saves the [`Registry`]
+    /// Registering a [`Language`] under an existing `name` replaces it
+    ///
+    /// # Arguments
+    /// * `name` - The name [`Package`]s and [`Module`]s use to refer to the [`Language`]
+    /// * `language` - The [`Language`] that needs to be registered
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_languages.json"));
+    /// let language = Language::create("sac2c", vec![]);
+    /// registry.register_language("sac", language.clone());
+    /// assert_eq!(registry.get_language("sac"), Some(&language));
+    /// assert!(registry.get_language("c").is_none());
+    /// ```
+    ///
+    pub fn register_language<S: Into<String>>(&mut self, name: S, language: Language) {
+        let name = name.into();
+        self.audit("register_language", vec![name.clone(), language.compiler.clone()], "ok");
+        self.languages.insert(name, language);
+        self.persist();
+    }
+
+    /// Retrieves the [`Language`] that is registered under the given `name`
+    ///
+    /// # Arguments
+    /// * `name` - The name of the [`Language`]
+    pub fn get_language(&self, name: &str) -> Option<&Language> {
+        self.languages.get(name)
+    }
+
+    /// Retrieves the [`Language`] that is registered under the given `name`,
+    /// with every setting it leaves empty inherited from the [`Language`]s it extends
+    ///
+    /// # Arguments
+    /// * `name` - The name of the [`Language`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_resolve_language.json"));
+    /// let mut base = Language::create("sac2c", vec![String::from("-O3")]);
+    /// base.set_include_template(Some(String::from("-I{path}")));
+    /// registry.register_language("sac", base);
+    ///
+    /// let mut debug = Language::extend("sac");
+    /// debug.set_flags(Some(vec![String::from("-g")]));
+    /// registry.register_language("sac-debug", debug);
+    ///
+    /// let resolved = registry.resolve_language("sac-debug").unwrap();
+    /// assert_eq!(resolved.compiler(), "sac2c");
+    /// assert_eq!(resolved.flags(), &[String::from("-g")]);
+    /// assert_eq!(resolved.include_template(), Some("-I{path}"));
+    /// ```
+    pub fn resolve_language(&self, name: &str) -> Option<Language> {
+        self.get_language(name).map(|language| self.resolve_inheritance(language.clone()))
+    }
+
+    fn resolve_inheritance(&self, mut language: Language) -> Language {
+        let mut seen = vec![];
+        while let Some(base) = language.extends.clone() {
+            if seen.contains(&base) {
+                break;
+            }
+            match self.get_language(&base) {
+                Some(parent) => language = language.inherit(parent),
+                None => break,
+            }
+            seen.push(base);
+        }
+        language
+    }
+
+    /// Retrieves the [`Language`] the given [`Package`] refers to
+    /// Settings the [`Package`] overrides take precedence over those of the registered [`Language`]
+    ///
+    /// # Arguments
+    /// * `package` - A reference to a [`Package`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_package_language.json"));
+    /// registry.register_language("sac", Language::create("sac2c", vec![String::from("-O3")]));
+    ///
+    /// let path = env::temp_dir().join("mock_package_language_override");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// package.set_language(Some(String::from("sac")));
+    /// let mut overrides = Language::create("", vec![]);
+    /// overrides.set_flags(Some(vec![String::from("-O1")]));
+    /// package.set_language_override(Some(overrides));
+    ///
+    /// let language = registry.get_package_language(&package).unwrap();
+    /// assert_eq!(language.compiler(), "sac2c");
+    /// assert_eq!(language.flags(), &[String::from("-O1")]);
+    /// ```
+    pub fn get_package_language(&self, package: &Package) -> Option<Language> {
+        let base = package.language().and_then(|name| self.resolve_language(&name));
+        self.apply_override(base, package.language_override())
+    }
+
+    /// Retrieves the [`Language`] the given [`Module`] refers to
+    /// Falls back to the [`Language`] of the [`Package`] when the [`Module`] does not refer to one
+    /// Settings the [`Package`] overrides take precedence over those of that [`Language`],
+    /// and settings the [`Module`] overrides take precedence over both, see [`Module::set_language_override`]
+    ///
+    /// # Arguments
+    /// * `package` - A reference to the [`Package`] providing the [`Module`]
+    /// * `module` - A reference to a [`Module`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::language::Language;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_module_language.json"));
+    /// let mut base = Language::create("sac2c", vec![String::from("-O3")]);
+    /// base.set_include_template(Some(String::from("-I{path}")));
+    /// registry.register_language("sac", base);
+    ///
+    /// let path = env::temp_dir().join("mock_package_module_language");
+    /// # let _ = fs::remove_dir_all(&path);
+    /// Repository::init(&path);
+    /// let package = Package::create(&path);
+    /// package.set_language(Some(String::from("sac")));
+    /// let mut package_override = Language::extend("sac");
+    /// package_override.set_compiler("sac2c-dev");
+    /// package.set_language_override(Some(package_override));
+    ///
+    /// let mut module = Module::create("a.sac", None);
+    /// module.set_language_override(Some(Language::create("", vec![])));
+    ///
+    /// let language = registry.get_module_language(&package, &module).unwrap();
+    /// assert_eq!(language.compiler(), "sac2c-dev");
+    /// assert!(language.flags().is_empty());
+    /// assert_eq!(language.include_template(), Some("-I{path}"));
+    /// ```
+    pub fn get_module_language(&self, package: &Package, module: &Module) -> Option<Language> {
+        let base = match &module.language {
+            Some(name) => {
+                let base = self.resolve_language(name);
+                match package.language_override() {
+                    Some(language) => base.map(|base| language.inherit(&base)),
+                    None => base,
+                }
+            }
+            None => self.get_package_language(package),
+        };
+        self.apply_override(base, module.language_override.clone())
+    }
+
+    /// Applies overriding [`Language`] settings to the given base [`Language`]
+    /// An override extending a [`Language`] is resolved against that [`Language`] instead
+    fn apply_override(&self, base: Option<Language>, language: Option<Language>) -> Option<Language> {
+        match language {
+            Some(language) if language.extends.is_some() => Some(self.resolve_inheritance(language)),
+            Some(language) => base.map(|base| language.inherit(&base)),
+            None => base,
+        }
+    }
+
+    /// Sets the [`AuditLog`] every operation that changes the [`Registry`] is appended to
+    /// The [`AuditLog`] is not stored in the [`Registry`] and needs to be set again after loading it
+    ///
+    /// # Arguments
+    /// * `audit_log` - The [`AuditLog`] to append to, or `None` to stop auditing
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::audit::AuditLog;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let log_path = env::temp_dir().join("audit_registry.jsonl");
+    /// # let _ = fs::remove_file(&log_path);
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_audit.json"));
+    /// registry.set_audit_log(Some(AuditLog::open(&log_path)));
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_audit");
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// registry.add(package.clone());
+    /// registry.remove(&package);
+    ///
+    /// let log = AuditLog::open(&log_path);
+    /// let operations: Vec<String> = log.entries().unwrap().iter().map(|e| e.operation().to_string()).collect();
+    /// assert_eq!(operations, vec!["add", "remove"]);
+    /// assert!(log.verify().is_ok());
+    /// ```
+    pub fn set_audit_log(&mut self, audit_log: Option<AuditLog>) {
+        self.audit_log = audit_log;
+    }
+
+    /// Sets the [`ProgressHandler`] notified while the [`Registry`] performs long running work, see [`ProgressHandler`]
+    /// The [`ProgressHandler`] is not stored in the [`Registry`] and needs to be set again after loading it
+    ///
+    /// # Arguments
+    /// * `handler` - The [`ProgressHandler`] to notify, or `None` to stop reporting progress
+    pub fn set_progress_handler(&mut self, handler: Option<Box<dyn ProgressHandler + Send + Sync>>) {
+        self.progress = Progress(handler.map(Arc::from));
+    }
+
+    /// Sets the [`DocExtractor`] used to describe [`Module`]s without a description when they are registered,
+    /// by [`Registry::add`] and [`Registry::add_items_matching`]
+    /// The [`DocExtractor`] is not stored in the [`Registry`] and needs to be set again after loading it
+    ///
+    /// # Arguments
+    /// * `extractor` - The [`DocExtractor`] to use, or `None` to leave descriptions unset
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::docs::CommentBlockExtractor;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_doc_extractor.json"));
+    /// let package_path = env::temp_dir().join("mock_package_doc_extractor");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("json.sac"), "// Parses JSON\nmodule JSON;\n").unwrap();
+    /// registry.add(Package::create(&package_path));
+    ///
+    /// registry.set_doc_extractor(Some(Box::new(CommentBlockExtractor)));
+    /// let added = registry.add_items_matching(&package_path, "*.sac", None::<&str>);
+    /// assert_eq!(added[0].description(), Some("Parses JSON"));
+    /// ```
+    ///
+    /// [`DocExtractor`]: crate::docs::DocExtractor
+    pub fn set_doc_extractor(&mut self, extractor: Option<Box<dyn DocExtractor + Send + Sync>>) {
+        self.doc_extractor = Extractor(extractor.map(Arc::from));
+    }
+
+    /// Sets the description of the given [`Module`] using the [`DocExtractor`], if one is set and the [`Module`] has none yet
+    /// Returns whether the description changed
+    fn describe(&self, package: &Package, module: &mut Module) -> bool {
+        let Some(extractor) = &self.doc_extractor.0 else { return false };
+        if module.description.is_some() {
+            return false;
+        }
+        let Ok(source) = read_to_string(package.local_location.join(&module.location)) else { return false };
+        module.description = extractor.extract(&source);
+        module.description.is_some()
+    }
+
+    pub(crate) fn audit(&self, operation: &str, arguments: Vec<String>, result: &str) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.append(operation, arguments, result);
+        }
+    }
+
+    /// Reads the manifests of the given [`Package`]s before they are changed, see [`Registry::journal_manifests`]
+    /// Nothing is read when no [`Journal`] is set
+    fn manifest_contents(&self, packages: &[&Package]) -> Vec<(PathBuf, String)> {
+        if self.journal.is_none() || self.in_memory {
+            return vec![];
+        }
+        packages
+            .iter()
+            .map(|p| p.manifest_location())
+            .filter_map(|location| read_to_string(&location).ok().map(|contents| (location, contents)))
+            .collect()
+    }
+
+    /// Records a change that only touched the manifests read by [`Registry::manifest_contents`] in the [`Journal`],
+    /// so [`Registry::undo`] reverts it in order with the changes to the [`Registry`] itself
+    fn journal_manifests(&self, manifests: Vec<(PathBuf, String)>) {
+        if let (Some(journal), false) = (&self.journal, manifests.is_empty()) {
+            let snapshot = self.journal_baseline.clone().unwrap_or_else(|| serde_json::to_string(self).unwrap());
+            journal.record(snapshot, manifests);
+        }
+    }
+
+    /// Sets the [`Journal`] the state of the [`Registry`] is recorded in before every change, so changes can be undone
+    /// See [`Registry::undo`]
+    ///
+    /// # Arguments
+    /// * `journal` - The [`Journal`] to record in, or `None` to stop recording
+    pub fn set_journal(&mut self, journal: Option<Journal>) {
+        let argument = journal.as_ref().map(|j| j.location.display().to_string()).unwrap_or_default();
+        self.journal = journal;
+        self.journal_baseline = None;
+        self.audit("set_journal", vec![argument], "ok");
+        self.persist();
+    }
+
+    /// Returns the [`Journal`] the state of the [`Registry`] is recorded in, if set
+    pub fn journal(&self) -> Option<&Journal> {
+        self.journal.as_ref()
+    }
+
+    /// Reverts the most recent change recorded in the [`Journal`] and saves the [`Registry`]
+    /// Returns whether there was a change to revert
+    /// Changes the [`Registry`] made to the manifests of its [`Package`]s, e.g. through [`Registry::set_output`],
+    /// are reverted in the same order; changes made directly through a [`Package`] are not
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::journal::Journal;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let journal_path = env::temp_dir().join("registry_undo.journal");
+    /// # let _ = fs::remove_file(&journal_path);
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_undo.json"));
+    /// registry.set_journal(Some(Journal::create(&journal_path, 10)));
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_undo");
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// registry.add(package.clone());
+    /// registry.remove(&package);
+    /// assert!(registry.is_empty());
+    ///
+    /// assert!(registry.undo());
+    /// assert!(registry.contains(&package));
+    /// assert!(registry.undo());
+    /// assert!(registry.is_empty());
+    /// assert!(!registry.undo());
+    /// ```
+    ///
+    /// Changes to [`Module`]s are undone as well
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::journal::Journal;
+    /// # use knapsac_lib::module::Module;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    ///
+    /// let journal_path = env::temp_dir().join("registry_undo_module.journal");
+    /// # let _ = fs::remove_file(&journal_path);
+    /// let mut registry = Registry::initialize(env::temp_dir().join("registry_undo_module.json"));
+    /// registry.set_journal(Some(Journal::create(&journal_path, 10)));
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_undo_module");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// fs::write(package_path.join("a.sac"), "").unwrap();
+    /// let package = Package::create(&package_path);
+    /// package.add_module(Module::create("a.sac", None));
+    /// registry.add(package.clone());
+    ///
+    /// let source = package_path.join("a.sac");
+    /// registry.set_description(&source, Some("Parses JSON".to_string())).unwrap();
+    /// assert!(registry.undo());
+    /// assert!(registry.get_module(&source).unwrap().description().is_none());
+    /// assert!(registry.contains(&package));
+    /// assert!(registry.undo());
+    /// assert!(registry.is_empty());
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        let entry = match self.journal.as_ref().and_then(|j| j.pop()) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        for (location, contents) in &entry.manifests {
+            write_atomic(location, contents).unwrap_or_else(|e| panic!("Cannot restore {}: {}", location.display(), e));
+            if let Some(root) = location.parent() {
+                git_cache::invalidate(root);
+            }
+        }
+        let mut restored: Registry = serde_json::from_str(&entry.snapshot).unwrap();
+        restored.location = std::mem::take(&mut self.location);
+        restored.journal = self.journal.take();
+        restored.audit_log = self.audit_log.take();
+        restored.progress = std::mem::take(&mut self.progress);
+        restored.doc_extractor = std::mem::take(&mut self.doc_extractor);
+        restored.autosave = self.autosave;
+        restored.last_saved = self.last_saved;
+        restored.in_memory = self.in_memory;
+        restored.origin = std::mem::take(&mut self.origin);
+        self.dirty = false;
+        *self = restored;
+
+        self.journal_baseline = Some(serde_json::to_string(self).unwrap());
+        self.audit("undo", vec![entry.created.to_string()], "ok");
+        self.persist();
+        true
+    }
+
+    /// Sets when the [`Registry`] writes its changes to disk
+    /// Pending changes are written before switching
+    ///
+    /// # Arguments
+    /// * `autosave` - The [`AutoSave`] mode to use from now on
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::env;
+    /// # use std::time::Duration;
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::{AutoSave, Registry};
+    ///
+    /// let path = env::temp_dir().join("registry_debounced.json");
+    /// let mut registry = Registry::initialize(&path);
+    /// registry.set_autosave(AutoSave::Debounced(Duration::from_secs(60)));
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_debounced");
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// registry.add(package.clone());
+    /// registry.remove(&package);
+    /// registry.add(package.clone());
+    /// registry.flush();
+    ///
+    /// assert!(Registry::load(&path).contains(&package));
+    /// ```
+    pub fn set_autosave(&mut self, autosave: AutoSave) {
+        self.flush();
+        self.autosave = autosave;
+    }
+
+    /// Writes pending changes to disk, if any
+    /// With [`AutoSave::Debounced`] this is what writes the last changes of a batch, as no timer writes them
+    pub fn flush(&mut self) {
+        if self.dirty {
+            self.save().unwrap_or_else(|e| panic!("{}", e));
+            self.dirty = false;
+            self.last_saved = Some(Instant::now());
+        }
+    }
+
+    /// Records a change and writes it to disk according to the [`Registry`]'s [`AutoSave`] mode
+    pub(crate) fn persist(&mut self) {
+        if self.in_memory {
+            return;
+        }
+        if let Some(journal) = &self.journal {
+            let current = serde_json::to_string(self).unwrap();
+            match self.journal_baseline.replace(current.clone()) {
+                Some(previous) if previous != current => journal.record(previous, vec![]),
+                _ => {}
+            }
+        }
+        self.dirty = true;
+        match (self.autosave, self.last_saved) {
+            (AutoSave::Debounced(window), Some(last_saved)) if last_saved.elapsed() < window => {}
+            _ => self.flush(),
+        }
+    }
+
+    /// Serializes the [`Registry`] to the file located at the [`Registry`]'s `location`
+    /// The [`Format`] is selected by the file's extension
+    /// The file is replaced atomically, after the previous version was copied to a backup when it was written completely,
+    /// and its checksum is written next to it, see [`Registry::try_load`]
+    /// The lock file used by [`SharedRegistry`] is held while writing, so other processes never see a mix of two saves
+    ///
+    /// [`SharedRegistry`]: crate::shared::SharedRegistry
+    pub(crate) fn save(&self) -> Result<(), RegistryError> {
+        if self.in_memory {
+            return Ok(());
+        }
+        let path = self.location.as_path();
+        let unwritable = |reason: &str| RegistryError::Unwritable(path.to_path_buf(), reason.to_string());
+
+        if path.is_relative() {
+            return Err(unwritable("path is relative"));
+        }
+
+        let format = match Format::from_path(path) {
+            Some(format) => format,
+            #[cfg(not(feature = "toml"))]
+            None if path.extension().is_some_and(|e| e == "toml") => return Err(unwritable("TOML support is not enabled, enable the `toml` feature")),
+            None if path.extension().is_some() => return Err(unwritable("path does not point to a JSON file")),
+            None => return Err(unwritable("path does not point to a file")),
+        };
+
+        let serialized = match (self.relative_paths, path.parent()) {
+            (true, Some(base)) => {
+                let mut copy = self.clone_in_memory();
+                copy.map_paths(|p| PathBuf::from(to_portable(relative_to(p, base))));
+                format.serialize(&copy, self.style)
+            }
+            _ => format.serialize(self, self.style),
+        };
+
+        let _lock = FileLock::acquire(path)?;
+        let io_error = |error: std::io::Error| unwritable(&error.to_string());
+        let checksum_location = Registry::checksum_location(path);
+        if let Ok(current) = read_to_string(path) {
+            let complete = match read_to_string(&checksum_location) {
+                Ok(checksum) => checksum.trim() == sha256_hex(&current),
+                Err(_) => true,
+            };
+            if complete {
+                write_atomic(Registry::backup_location(path), current).map_err(io_error)?;
+            }
+        }
+        write_atomic(path, &serialized).map_err(io_error)?;
+        write_atomic(checksum_location, sha256_hex(&serialized)).map_err(io_error)
+    }
+}
+
+impl PartialEq for Registry {
+    fn eq(&self, other: &Self) -> bool {
+        self.packages == other.packages
+            && self.languages == other.languages
+            && self.provenance == other.provenance
+            && self.artifacts_root == other.artifacts_root
+            && self.output_policy == other.output_policy
+            && self.allowed_roots == other.allowed_roots
+            && self.remote_index == other.remote_index
+            && self.trust_store == other.trust_store
+            && self.cache_directory == other.cache_directory
+            && self.offline == other.offline
+            && self.journal == other.journal
+            && self.hook_policy == other.hook_policy
+            && self.style == other.style
+            && self.relative_paths == other.relative_paths
+            && self.target == other.target
+            && self.ignore_patterns == other.ignore_patterns
+    }
+}
+
+impl Clone for Registry {
+    fn clone(&self) -> Self {
+        Registry {
+            location: self.location.clone(),
+            packages: self.packages.clone(),
+            languages: self.languages.clone(),
+            provenance: self.provenance.clone(),
+            artifacts_root: self.artifacts_root.clone(),
+            output_policy: self.output_policy,
+            allowed_roots: self.allowed_roots.clone(),
+            remote_index: self.remote_index.clone(),
+            trust_store: self.trust_store.clone(),
+            cache_directory: self.cache_directory.clone(),
+            offline: self.offline,
+            journal: self.journal.clone(),
+            hook_policy: self.hook_policy,
+            style: self.style,
+            relative_paths: self.relative_paths,
+            target: self.target.clone(),
+            ignore_patterns: self.ignore_patterns.clone(),
+            audit_log: self.audit_log.clone(),
+            progress: self.progress.clone(),
+            doc_extractor: self.doc_extractor.clone(),
+            autosave: self.autosave,
+            last_saved: self.last_saved,
+            dirty: false,
+            recovered: self.recovered,
+            journal_baseline: self.journal_baseline.clone(),
+            in_memory: self.in_memory,
+            origin: self.origin.clone(),
+        }
+    }
+}
+
+impl Drop for Registry {
+    fn drop(&mut self) {
+        if self.dirty {
+            let _ = self.save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use git2::Repository;
+    use url::Url;
+    use crate::dependency::{Dependency, DependencyError, DependencyKind};
+    use crate::depfile::DepfileFormat;
+    use crate::diagnostics::Diagnostic;
+    use crate::error::{ManifestError, RegistryError};
+    use crate::executable::Executable;
+    use crate::language::Language;
+    use crate::module::{Module, ModuleError};
+    use crate::package::{Package, VENDORED_DIRECTORY};
+    use crate::registry::{AutoSave, OutputPolicy, Registry};
+    use crate::artifacts::{ArtifactMode, ARTIFACTS_DIRECTORY};
+    use crate::backend::{ArchiveFetcher, RegistryBackend};
+    use crate::build::BuildPlanFormat;
+    use crate::fetcher::{GitFetcher, ShallowGitFetcher};
+    use crate::index::{IndexError, RemoteIndex};
+    use crate::install::InstallError;
+    use crate::item::ImportKind;
+    use crate::integrity::IntegrityIssue;
+    use crate::operation::{OperationEvent, OperationStatus};
+    use crate::packaging::{ExecutablePolicy, PackagingError};
+    use crate::progress::ProgressHandler;
+    use crate::removal::RemovalPolicy;
+    use crate::run::RunError;
+    use crate::signing::{Ed25519Signer, SignatureError, TrustStore};
+    use crate::sync::{Discrepancy, SchemaViolation, SyncError};
+    use crate::vendor::Lockfile;
+    use crate::version::{SemVerIncrement, Version, VersionReq};
+
+    #[test]
+    fn test_save() {
+        let path = env::temp_dir().join("registry.json");
+
+        let res = fs::remove_file(&path);
+
+        assert!(res.is_ok());
+
+        let registry = Registry::new(path);
+        assert!(registry.save().is_ok());
+    }
+
+    #[test]
+    /// When [`Registry`]'s `location` points to an existing file, overwrite it.
+    fn test_save_overwrite() {
+        let path = env::temp_dir().join("registry.json");
+        let res = fs::write(&path, "hello");
+
+        assert!(res.is_ok());
+        assert!(path.is_file());
+
+        let registry = Registry::new(path);
+
+        assert!(registry.save().is_ok());
+    }
+
+    #[test]
+    /// Should panic when [`Registry`]´s `location` does not point to a JSON file
+    fn test_save_panic_not_json() {
+        let path = env::temp_dir().join("registry.txt");
+
+        fs::write(&path, "test").unwrap();
+
+        assert!(path.exists());
+
+        let registry = Registry::new(&path);
+        assert_eq!(registry.save(), Err(RegistryError::Unwritable(path, "path does not point to a JSON file".to_string())));
+    }
+
+    #[test]
+    /// Should panic when [`Registry`]´s `location` points to a directory
+    fn test_save_panic_is_a_file() {
+        let path = env::temp_dir().join("registry");
+
+        fs::create_dir_all(&path).unwrap();
+
+        assert!(path.is_dir());
+
+        let registry = Registry::new(&path);
+        let res = registry.save();
+        assert_eq!(res, Err(RegistryError::Unwritable(path, "path does not point to a file".to_string())));
+    }
+
+    #[test]
+    /// Should panic when [`Registry`]'s `location` is a relative [`Path`]
+    fn test_save_panic_is_relative() {
+        let path = PathBuf::from("./registry.json");
+
+        let registry = Registry::new(&path);
+        assert_eq!(registry.save(), Err(RegistryError::Unwritable(path, "path is relative".to_string())));
+    }
+
+    #[test]
+    /// Packages that depend on each other are reported as a cycle
+    fn test_check_cycle() {
+        let mut registry = Registry::new(env::temp_dir().join("registry_check_cycle.json"));
+        let a_url = Url::parse("https://example.com/a").unwrap();
+        let b_url = Url::parse("https://example.com/b").unwrap();
+
+        let a_path = env::temp_dir().join("mock_package_cycle_a");
+        let b_path = env::temp_dir().join("mock_package_cycle_b");
+        let _ = fs::remove_dir_all(&a_path);
+        let _ = fs::remove_dir_all(&b_path);
+        Repository::init(&a_path).unwrap().remote("origin", a_url.as_str()).unwrap();
+        Repository::init(&b_path).unwrap().remote("origin", b_url.as_str()).unwrap();
+        fs::write(a_path.join("a.sac"), "").unwrap();
+
+        let a = Package::create(&a_path);
+        let b = Package::create(&b_path);
+        a.add_module(Module::create("a.sac", None));
+        a.add_dependency(Dependency::create(b_url));
+        b.add_dependency(Dependency::create(a_url));
+        registry.add(a.clone());
+        registry.add(b.clone());
+
+        let report = registry.check(a_path.join("a.sac"));
+        assert_eq!(report.errors(), &[Diagnostic::DependencyCycle(vec![
+            a.local_location.clone(),
+            b.local_location.clone(),
+            a.local_location,
+        ])]);
+    }
+
+    #[test]
+    /// Only dependencies no remaining package depends on are transferred along
+    fn test_transfer_package_exclusive_dependencies() {
+        let mut workspace = Registry::new(env::temp_dir().join("registry_transfer_workspace.json"));
+        let mut global = Registry::new(env::temp_dir().join("registry_transfer_global.json"));
+
+        let mut packages = vec![];
+        for name in ["a", "b", "c", "d"] {
+            let path = env::temp_dir().join(format!("mock_package_transfer_{}", name));
+            let _ = fs::remove_dir_all(&path);
+            let url = format!("https://example.com/{}", name);
+            Repository::init(&path).unwrap().remote("origin", &url).unwrap();
+            let package = Package::create(&path);
+            workspace.add(package.clone());
+            packages.push((path, package, Url::parse(&url).unwrap()));
+        }
+        let (a_path, a, _) = &packages[0];
+        let (_, b, b_url) = &packages[1];
+        let (_, c, c_url) = &packages[2];
+        let (_, d, _) = &packages[3];
+        a.add_dependency(Dependency::create(b_url.clone()));
+        a.add_dependency(Dependency::create(c_url.clone()));
+        d.add_dependency(Dependency::create(c_url.clone()));
+
+        let moved = workspace.transfer_package(&mut global, a_path, true);
+        assert_eq!(moved.len(), 2);
+        assert!(global.contains(a) && global.contains(b));
+        assert!(workspace.contains(c) && workspace.contains(d));
+        assert_eq!(workspace.count_packages(), 2);
+    }
+
+    #[test]
+    /// Changes within the debounce window are only written on flush
+    fn test_persist_debounced() {
+        let path = env::temp_dir().join("registry_persist_debounced.json");
+        let mut registry = Registry::initialize(&path);
+        registry.set_autosave(AutoSave::Debounced(Duration::from_secs(60)));
+
+        let package_path = env::temp_dir().join("mock_package_persist_debounced");
+        Repository::init(&package_path).unwrap();
+        let package = Package::create(&package_path);
+
+        registry.add(package.clone());
+        assert!(Registry::load(&path).contains(&package));
+
+        registry.remove(&package);
+        assert!(registry.dirty);
+        assert!(Registry::load(&path).contains(&package));
+
+        registry.flush();
+        assert!(!registry.dirty);
+        assert!(Registry::load(&path).is_empty());
+    }
+
+    #[test]
+    /// Vanished packages are removed along with dependencies on them
+    fn test_prune_vanished_package() {
+        let mut registry = Registry::new(env::temp_dir().join("registry_prune_vanished.json"));
+        let gone_url = Url::parse("https://example.com/gone").unwrap();
+        let gone_path = env::temp_dir().join("mock_package_prune_gone");
+        let kept_path = env::temp_dir().join("mock_package_prune_kept");
+        let _ = fs::remove_dir_all(&kept_path);
+        Repository::init(&gone_path).unwrap().remote("origin", gone_url.as_str()).unwrap();
+        Repository::init(&kept_path).unwrap();
+
+        let gone = Package::create(&gone_path);
+        let kept = Package::create(&kept_path);
+        let dependency = Dependency::create(gone_url.clone());
+        kept.add_dependency(dependency.clone());
+        registry.add(gone.clone());
+        registry.add(kept.clone());
+
+        fs::remove_dir_all(&gone_path).unwrap();
+        let report = registry.prune(false);
+
+        assert_eq!(report.packages().to_vec(), vec![gone.local_location.clone()]);
+        assert_eq!(report.dependencies(), &[(kept.local_location.clone(), gone_url)]);
+        assert!(!registry.contains(&gone));
+        assert!(registry.contains(&kept));
+        assert!(!kept.has_dependency(&dependency));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// A compiler version outside the recorded range is reported as a warning
+    fn test_check_incompatible_compiler() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let compiler = env::temp_dir().join("mock_compiler_check");
+        fs::write(&compiler, "#!/bin/sh\necho 'mock compiler 2.1.0'\n").unwrap();
+        fs::set_permissions(&compiler, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_check_compiler.json"));
+        registry.languages.insert("mock".to_string(), Language::create(compiler.to_str().unwrap(), vec![]));
+
+        let package_path = env::temp_dir().join("mock_package_check_compiler");
+        Repository::init(&package_path).unwrap();
+        fs::write(package_path.join("a.sac"), "").unwrap();
+        let package = Package::create(&package_path);
+        package.set_language(Some("mock".to_string()));
+        package.add_module(Module::create("a.sac", None));
+        package.record_compiler_version("1.9.0");
+        package.record_compiler_version("2.0.5");
+        registry.add(package.clone());
+
+        let report = registry.check(package_path.join("a.sac"));
+        assert!(report.is_ok());
+        assert_eq!(report.warnings(), &[Diagnostic::IncompatibleCompiler(package.local_location.clone(), "2.1.0".to_string())]);
+
+        package.record_compiler_version("2.1.0");
+        assert!(registry.check(package_path.join("a.sac")).warnings().is_empty());
+    }
+
+    #[test]
+    /// Cascading removal also removes indirect dependents
+    fn test_remove_with_policy_cascade() {
+        let mut registry = Registry::new(env::temp_dir().join("registry_remove_cascade.json"));
+        let mut packages = vec![];
+        for name in ["json", "parser", "app", "other"] {
+            let path = env::temp_dir().join(format!("mock_package_remove_cascade_{}", name));
+            let _ = fs::remove_dir_all(&path);
+            let url = format!("https://example.com/cascade/{}", name);
+            Repository::init(&path).unwrap().remote("origin", &url).unwrap();
+            let package = Package::create(&path);
+            registry.add(package.clone());
+            packages.push((package, Url::parse(&url).unwrap()));
+        }
+        packages[1].0.add_dependency(Dependency::create(packages[0].1.clone()));
+        packages[2].0.add_dependency(Dependency::create(packages[1].1.clone()));
+
+        let removed = registry.remove_with_policy(&packages[0].0, RemovalPolicy::Cascade).unwrap();
+        assert_eq!(removed.len(), 3);
+        assert_eq!(registry.count_packages(), 1);
+        assert!(registry.contains(&packages[3].0));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// Executables in a swept directory are skipped, rejected or included as binaries depending on the policy
+    fn test_package_mixed_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_package_mixed.json"));
+        let package_path = env::temp_dir().join("mock_package_mixed");
+        let _ = fs::remove_dir_all(&package_path);
+        Repository::init(&package_path).unwrap();
+        fs::create_dir_all(package_path.join("src").join("nested")).unwrap();
+        fs::write(package_path.join("src").join("a.sac"), "").unwrap();
+        fs::write(package_path.join("src").join("nested").join("b.sac"), "").unwrap();
+        let tool = package_path.join("src").join("tool");
+        fs::write(&tool, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+        let package = Package::create(&package_path);
+        registry.add(package.clone());
+
+        assert_eq!(
+            registry.package(package_path.join("src"), ExecutablePolicy::Error),
+            Err(PackagingError::ExecutablesFound(vec![tool.clone()]))
+        );
+        assert!(package.get_module_by_location("src/a.sac").is_none());
+
+        let added = registry.package(package_path.join("src"), ExecutablePolicy::Skip).unwrap();
+        assert_eq!(added.len(), 2);
+        assert!(added.iter().all(|i| i.as_module().is_some()));
+        assert!(package.get_module_by_location("src/tool").is_none());
+
+        let added = registry.package(package_path.join("src"), ExecutablePolicy::IncludeAsBinary).unwrap();
+        assert_eq!(added.len(), 1);
+        assert!(package.get_module_by_location("src/tool").is_none());
+        assert!(package.get_executable_by_location("src/tool").unwrap().is_prebuilt());
+    }
+
+    #[test]
+    /// Sweeping a directory leaves out what the ignore file of the package and the registry's patterns exclude
+    fn test_package_respects_ignore_rules() {
+        let mut registry = Registry::new(env::temp_dir().join("registry_package_ignore.json"));
+        registry.set_ignore_patterns(vec!["*.bak".to_string()]);
+        let package_path = env::temp_dir().join("mock_package_ignore");
+        let _ = fs::remove_dir_all(&package_path);
+        Repository::init(&package_path).unwrap();
+        fs::create_dir_all(package_path.join("src").join("third_party")).unwrap();
+        fs::write(package_path.join("src").join("a.sac"), "").unwrap();
+        fs::write(package_path.join("src").join("a.sac.bak"), "").unwrap();
+        fs::write(package_path.join("src").join(".#a.sac"), "").unwrap();
+        fs::write(package_path.join("src").join("third_party").join("json.sac"), "").unwrap();
+        fs::write(package_path.join("src").join("third_party").join("keep.sac"), "").unwrap();
+        fs::write(package_path.join(".knapsacignore"), ".#*\nthird_party/\n!src/third_party/keep.sac\n").unwrap();
+        let package = Package::create(&package_path);
+        registry.add(package.clone());
+
+        let added = registry.package(package_path.join("src"), ExecutablePolicy::Error).unwrap();
+        assert_eq!(added.len(), 2);
+        assert!(package.get_module_by_location("src/a.sac").is_some());
+        assert!(package.get_module_by_location("src/third_party/keep.sac").is_some());
+        assert!(package.get_module_by_location("src/a.sac.bak").is_none());
+        assert!(package.get_module_by_location("src/third_party/json.sac").is_none());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// Artifacts of dependencies compiled by another compiler version are reported as needing a rebuild
+    fn test_check_incompatible_artifact() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_check_artifact.json"));
+        let compiler = env::temp_dir().join("mock_compiler_artifact");
+        fs::write(&compiler, "#!/bin/sh\necho 'sac2c 1.3.3'\n").unwrap();
+        fs::set_permissions(&compiler, fs::Permissions::from_mode(0o755)).unwrap();
+        let language = Language::create(compiler.display().to_string(), vec![]);
+        registry.register_language("sac", language.clone());
+
+        let lib_url = Url::parse("https://example.com/lib").unwrap();
+        let lib_path = env::temp_dir().join("mock_package_check_artifact_lib");
+        let app_path = env::temp_dir().join("mock_package_check_artifact_app");
+        let mut packages = vec![];
+        for path in [&lib_path, &app_path] {
+            let _ = fs::remove_dir_all(path);
+            let repository = Repository::init(path).unwrap();
+            if path == &lib_path {
+                repository.remote("origin", lib_url.as_str()).unwrap();
+            }
+            fs::write(path.join("a.sac"), "").unwrap();
+            let package = Package::create(path);
+            package.set_language(Some("sac".to_string()));
+            package.add_module(Module::create("a.sac", None));
+            registry.add(package.clone());
+            packages.push(package);
+        }
+        packages[1].add_dependency(Dependency::create(lib_url));
+
+        let expected = registry.abi_tag(app_path.join("a.sac")).unwrap();
+        assert_eq!(expected, language.abi_tag("1.3.3"));
+        registry.set_artifact_tag(lib_path.join("a.sac"), Some(expected.clone()));
+        assert!(registry.check(app_path.join("a.sac")).is_ok());
+
+        let stale = language.abi_tag("1.2.0");
+        registry.set_artifact_tag(lib_path.join("a.sac"), Some(stale.clone()));
+        assert_eq!(
+            registry.check(app_path.join("a.sac")).errors(),
+            &[Diagnostic::IncompatibleArtifact(lib_path.join("a.sac"), stale, expected)]
+        );
+    }
+
+    fn commit_all(repository: &Repository) {
+        let mut index = repository.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("knapsac", "knapsac@example.com").unwrap();
+        let parent = repository.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repository.commit(Some("HEAD"), &signature, &signature, "commit", &tree, &parents).unwrap();
+    }
+
+    #[test]
+    /// Installing a [`Package`] also installs the [`Package`]s its manifest declares as [`Dependency`]s
+    fn test_install_declared_dependencies() {
+        let json_path = env::temp_dir().join("mock_source_install_declared_json");
+        let app_path = env::temp_dir().join("mock_source_install_declared_app");
+        let install_path = env::temp_dir().join("mock_install_declared");
+        for path in [&json_path, &app_path, &install_path] {
+            let _ = fs::remove_dir_all(path);
+        }
+        fs::create_dir_all(&install_path).unwrap();
+
+        let json_repository = Repository::init(&json_path).unwrap();
+        fs::write(json_path.join("json.sac"), "").unwrap();
+        Package::create(&json_path).add_module(Module::create("json.sac", None));
+        commit_all(&json_repository);
+        let json_url = Url::from_directory_path(&json_path).unwrap();
+
+        let app_repository = Repository::init(&app_path).unwrap();
+        let app = Package::create(&app_path);
+        app.add_dependency(Dependency::declare(json_url.clone(), "json", None));
+        commit_all(&app_repository);
+        let app_url = Url::from_directory_path(&app_path).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_declared.json"));
+        let installed = registry.install(&GitFetcher, app_url, &install_path, None);
+        assert_eq!(registry.count_packages(), 2);
+        assert_eq!(installed.dependencies()[0].identifier(), Some("json"));
+
+        let json = registry.resolve_dependency(&Dependency::create(json_url)).unwrap();
+        assert!(json.get_module_by_location("json.sac").is_some());
+    }
+
+    #[test]
+    /// Incompatible requirements on the same [`Package`] make the whole installation fail and roll back
+    fn test_try_install_conflicting_requirement() {
+        let root = env::temp_dir().join("mock_try_install_conflict");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+
+        let mut urls = vec![];
+        for (name, dependencies) in [("json", vec![]), ("lib", vec![(0, "^2.0")]), ("app", vec![(0, "^1.0"), (1, "")])] {
+            let path = root.join(name);
+            let repository = Repository::init(&path).unwrap();
+            let package = Package::create(&path);
+            for (index, requirement) in dependencies {
+                let (identifier, url): &(&str, Url) = &urls[index];
+                let requirement = Some(requirement).filter(|r| !r.is_empty()).map(|r| r.parse().unwrap());
+                package.add_dependency(Dependency::declare(url.clone(), *identifier, requirement));
+            }
+            commit_all(&repository);
+            let head = repository.head().unwrap().peel(git2::ObjectType::Commit).unwrap();
+            repository.tag_lightweight("v1.0.0", &head, false).unwrap();
+            urls.push((name, Url::from_directory_path(&path).unwrap()));
+        }
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_try_install_conflict.json"));
+        let result = registry.try_install(&GitFetcher, urls[2].1.clone(), &install_path, None);
+        assert_eq!(
+            result.err(),
+            Some(InstallError::ConflictingRequirement("json".to_string(), "^2.0".to_string(), Some("1.0.0".to_string())))
+        );
+        assert!(registry.is_empty());
+        assert_eq!(fs::read_dir(&install_path).unwrap().count(), 0);
+
+        let package = registry.try_install(&GitFetcher, urls[1].1.clone(), &install_path, None);
+        assert!(package.is_err());
+        let package = registry.try_install(&GitFetcher, urls[0].1.clone(), &install_path, None).unwrap();
+        assert_eq!(package.version(), Some("1.0.0".parse().unwrap()));
+    }
+
+    #[test]
+    /// Dependencies on different versions of a package install those versions side by side
+    fn test_install_multiple_versions() {
+        let root = env::temp_dir().join("mock_install_versions");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        let lib = Package::create(&lib_path);
+        lib.publish(SemVerIncrement::Major, vec![]);
+        lib.publish(SemVerIncrement::Major, vec![]);
+        let lib_url = Url::from_directory_path(&lib_path).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_versions.json"));
+        for (name, requirement) in [("old", "^1.0"), ("new", "^2.0")] {
+            let path = root.join(name);
+            let repository = Repository::init(&path).unwrap();
+            let package = Package::create(&path);
+            package.add_dependency(Dependency::declare(lib_url.clone(), "lib", Some(requirement.parse().unwrap())));
+            commit_all(&repository);
+            registry.try_install(&GitFetcher, Url::from_directory_path(&path).unwrap(), &install_path, None).unwrap();
+        }
+
+        assert_eq!(registry.count_packages(), 4);
+        let v1: Version = "1.0.0".parse().unwrap();
+        let v2: Version = "2.0.0".parse().unwrap();
+        assert_eq!(registry.get_by_identifier("lib", Some(&v1)).unwrap().version(), Some(v1.clone()));
+        assert_eq!(registry.get_by_identifier("lib", None).unwrap().version(), Some(v2.clone()));
+
+        let old = registry.get_by_identifier("old", None).unwrap();
+        assert_eq!(registry.resolve_dependency(&old.dependencies()[0]).unwrap().version(), Some(v1));
+        let new = registry.get_by_identifier("new", None).unwrap();
+        assert_eq!(registry.resolve_dependency(&new.dependencies()[0]).unwrap().version(), Some(v2));
+    }
+
+    #[test]
+    /// Packages published in a remote index are found and installed by identifier
+    fn test_install_by_name() {
+        let root = env::temp_dir().join("mock_install_by_name");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        let lib = Package::create(&lib_path);
+        lib.publish(SemVerIncrement::Major, vec![]);
+        lib.publish(SemVerIncrement::Major, vec![]);
+        let lib_url = Url::from_directory_path(&lib_path).unwrap();
+
+        let index_path = root.join("index");
+        let index = Repository::init(&index_path).unwrap();
+        let entry = format!(r#"{{"identifier": "lib", "git_url": "{}", "versions": ["1.0.0", "2.0.0"]}}"#, lib_url);
+        fs::write(index_path.join("lib.json"), entry).unwrap();
+        commit_all(&index);
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_by_name.json"));
+        assert_eq!(
+            registry.install_by_name(&GitFetcher, "lib", None, &install_path).err(),
+            Some(InstallError::Index(IndexError::NoIndex))
+        );
+        registry.set_remote_index(Some(RemoteIndex::create(Url::from_directory_path(&index_path).unwrap(), root.join("checkout"))));
+        assert_eq!(registry.update_index(), Ok(1));
+
+        fs::write(index_path.join("json.json"), r#"{"identifier": "json", "git_url": "https://example.com/json.git"}"#).unwrap();
+        commit_all(&index);
+        assert_eq!(registry.update_index(), Ok(2));
+        let found: Vec<String> = registry.search_remote("l").iter().map(|e| e.identifier().to_string()).collect();
+        assert_eq!(found, vec!["lib".to_string()]);
+
+        let package = registry.install_by_name(&GitFetcher, "lib", Some("^1.0".parse().unwrap()), &install_path).unwrap();
+        assert_eq!(package.version(), Some("1.0.0".parse().unwrap()));
+        assert_eq!(
+            registry.install_by_name(&GitFetcher, "unknown", None, &install_path).err(),
+            Some(InstallError::UnknownPackage("unknown".to_string()))
+        );
+    }
+
+    #[test]
+    /// Packages uploaded through a registry backend are installed from their archive after checksum verification
+    fn test_upload_and_install_archive() {
+        struct MemoryBackend(RefCell<HashMap<String, Vec<u8>>>);
+
+        impl RegistryBackend for MemoryBackend {
+            fn name(&self) -> &str {
+                "memory"
+            }
+
+            fn upload(&self, identifier: &str, version: &Version, archive: &[u8], _manifest: &str) -> Result<(), String> {
+                self.0.borrow_mut().insert(format!("memory:{}/{}", identifier, version), archive.to_vec());
+                Ok(())
+            }
+
+            fn download(&self, source: &Url) -> Result<Vec<u8>, String> {
+                self.0.borrow().get(source.as_str()).cloned().ok_or_else(|| format!("{} not found", source))
+            }
+        }
+
+        let root = env::temp_dir().join("mock_upload_archive");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        fs::write(lib_path.join("a.sac"), "module a;").unwrap();
+        let lib = Package::create(&lib_path);
+        lib.add_module(Module::create("a.sac", None));
+
+        let backend = MemoryBackend(RefCell::new(HashMap::new()));
+        assert!(lib.upload(&backend).is_err());
+        lib.publish(SemVerIncrement::Minor, vec![]);
+        let checksum = lib.upload(&backend).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_upload_archive.json"));
+        let fetcher = ArchiveFetcher(backend);
+        let package = registry.install(&fetcher, Url::parse("memory:lib/0.1.0").unwrap(), &install_path, Some(&checksum));
+        assert!(package.get_module_by_location("a.sac").is_some());
+        assert_eq!(fs::read_to_string(package.local_location.join("a.sac")).unwrap(), "module a;");
+        assert_eq!(registry.get_provenance(&package).unwrap().fetcher(), "memory");
+    }
+
+    #[test]
+    /// Files changed after publishing are reported by verify and make installation fail
+    fn test_verify_integrity() {
+        let root = env::temp_dir().join("mock_verify_integrity");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+        let lib_path = root.join("lib");
+        let repository = Repository::init(&lib_path).unwrap();
+        fs::write(lib_path.join("a.sac"), "module a;").unwrap();
+        let lib = Package::create(&lib_path);
+        lib.add_module(Module::create("a.sac", None));
+        lib.publish(SemVerIncrement::Minor, vec![]);
+        let lib_url = Url::from_directory_path(&lib_path).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_verify_integrity.json"));
+        let package = registry.try_install(&GitFetcher, lib_url.clone(), &install_path, None).unwrap();
+        assert!(registry.verify("lib").is_empty());
+        fs::remove_file(package.local_location.join("a.sac")).unwrap();
+        assert_eq!(registry.verify("lib"), vec![IntegrityIssue::Missing(package.local_location.join("a.sac"))]);
+        registry.remove(&package);
+
+        fs::write(lib_path.join("a.sac"), "module evil;").unwrap();
+        commit_all(&repository);
+        let result = registry.try_install(&GitFetcher, lib_url, &install_path, None);
+        assert!(matches!(result, Err(InstallError::IntegrityViolation(identifier, _)) if identifier == "lib"));
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    /// With a trust store set only packages signed with a trusted key are installed
+    fn test_install_signed() {
+        let root = env::temp_dir().join("mock_install_signed");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+        let signer = Ed25519Signer::create("release", [1; 32]);
+        let intruder = Ed25519Signer::create("release", [2; 32]);
+
+        let mut urls = vec![];
+        for (name, signer) in [("unsigned", None), ("signed", Some(&signer)), ("forged", Some(&intruder))] {
+            let path = root.join(name);
+            Repository::init(&path).unwrap();
+            let package = Package::create(&path);
+            match signer {
+                Some(signer) => package.publish_signed(SemVerIncrement::Minor, vec![], signer),
+                None => package.publish(SemVerIncrement::Minor, vec![]),
+            };
+            urls.push(Url::from_directory_path(&path).unwrap());
+        }
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_signed.json"));
+        let mut trust_store = TrustStore::default();
+        trust_store.trust("release", &signer.public_key());
+        registry.set_trust_store(Some(trust_store));
+
+        assert_eq!(
+            registry.try_install(&GitFetcher, urls[0].clone(), &install_path, None).err(),
+            Some(InstallError::Signature("unsigned".to_string(), SignatureError::Unsigned))
+        );
+        assert!(registry.try_install(&GitFetcher, urls[1].clone(), &install_path, None).is_ok());
+        assert_eq!(
+            registry.try_install(&GitFetcher, urls[2].clone(), &install_path, None).err(),
+            Some(InstallError::Signature("forged".to_string(), SignatureError::Invalid))
+        );
+        assert_eq!(registry.count_packages(), 1);
+    }
+
+    #[test]
+    /// A shallow sparse install clones only the requested tag and checks out only the files the manifest refers to
+    fn test_install_shallow_sparse() {
+        let root = env::temp_dir().join("mock_install_shallow");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        fs::create_dir_all(lib_path.join("src").join("build")).unwrap();
+        fs::create_dir_all(lib_path.join("docs")).unwrap();
+        fs::write(lib_path.join("src").join("a.sac"), "module a;").unwrap();
+        fs::write(lib_path.join("src").join("build").join("a.so"), "binary").unwrap();
+        fs::write(lib_path.join("docs").join("manual.txt"), "large").unwrap();
+        let lib = Package::create(&lib_path);
+        let mut module = Module::create("src/a.sac", None);
+        module.set_output(Some("build"));
+        lib.add_module(module);
+        lib.publish(SemVerIncrement::Major, vec![]);
+        lib.publish(SemVerIncrement::Major, vec![]);
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_shallow.json"));
+        let fetcher = ShallowGitFetcher::create(Some("1.0.0".to_string()), true);
+        let package = registry.try_install(&fetcher, Url::from_directory_path(&lib_path).unwrap(), &install_path, None).unwrap();
+
+        assert_eq!(package.version(), Some("1.0.0".parse().unwrap()));
+        assert!(package.local_location.join("src").join("a.sac").is_file());
+        assert!(package.local_location.join("src").join("build").join("a.so").is_file());
+        assert!(!package.local_location.join("docs").exists());
+        let repository = Repository::open(&package.local_location).unwrap();
+        let mut revwalk = repository.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        assert_eq!(revwalk.count(), 1);
+    }
+
+    #[test]
+    /// Yanked versions are refused as dependencies and reported as warnings when installed directly
+    fn test_install_yanked_version() {
+        let root = env::temp_dir().join("mock_install_yanked");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        let lib = Package::create(&lib_path);
+        let version = lib.publish(SemVerIncrement::Major, vec![]);
+        lib.yank(&version);
+        lib.deprecate(&version, "broken");
+        let lib_url = Url::from_directory_path(&lib_path).unwrap();
+
+        let app_path = root.join("app");
+        let repository = Repository::init(&app_path).unwrap();
+        let app = Package::create(&app_path);
+        app.add_dependency(Dependency::declare(lib_url.clone(), "lib", None));
+        commit_all(&repository);
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_yanked.json"));
+        let result = registry.try_install(&GitFetcher, Url::from_directory_path(&app_path).unwrap(), &install_path, None);
+        assert_eq!(result.err(), Some(InstallError::YankedVersion("lib".to_string(), "1.0.0".to_string())));
+        assert!(registry.is_empty());
+
+        let mut handle = registry.begin_install(&GitFetcher, lib_url, &install_path, None);
+        handle.poll_status();
+        let warnings: Vec<OperationEvent> = handle.events().into_iter().filter(|e| matches!(e, OperationEvent::Warning(_))).collect();
+        assert_eq!(warnings, vec![
+            OperationEvent::Warning("version 1.0.0 of package `lib` was yanked".to_string()),
+            OperationEvent::Warning("version 1.0.0 of package `lib` is deprecated: broken".to_string()),
+        ]);
+        assert!(matches!(handle.status(), OperationStatus::Finished(_)));
+    }
+
+    #[test]
+    /// Installs consult the cache first, and offline installs of uncached packages are refused
+    fn test_install_cached_offline() {
+        let root = env::temp_dir().join("mock_install_cached");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        let version = Package::create(&lib_path).publish(SemVerIncrement::Major, vec![]);
+        let lib_url = Url::from_directory_path(&lib_path).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_cached.json"));
+        registry.set_cache_directory(Some(root.join("cache")));
+        let first = registry.install(&GitFetcher, lib_url.clone(), &install_path, None);
+        assert_eq!(registry.get_provenance(&first).unwrap().fetcher(), "git");
+        assert_eq!(registry.cache().unwrap().entries().len(), 1);
+
+        fs::remove_dir_all(&lib_path).unwrap();
+        registry.offline(true);
+        let second = registry.install(&GitFetcher, lib_url, &install_path, None);
+        assert_eq!(registry.get_provenance(&second).unwrap().fetcher(), "cache");
+        assert_eq!(second.version(), Some(version));
+        assert_eq!(second.identifier(), "lib");
+
+        let remote = Url::parse("https://example.com/json.git").unwrap();
+        let result = registry.try_install(&GitFetcher, remote, &install_path, None);
+        assert_eq!(result.err(), Some(InstallError::Offline("https://example.com/json.git".to_string())));
+    }
+
+    #[test]
+    /// Vendoring copies the transitive dependencies with their outputs and writes a lockfile pointing at them
+    fn test_vendor_transitive_dependencies() {
+        let root = env::temp_dir().join("mock_vendor");
+        let _ = fs::remove_dir_all(&root);
+        let mut registry = Registry::new(env::temp_dir().join("registry_vendor.json"));
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashSet;
-    use std::{env, fs};
-    use std::path::PathBuf;
-    use crate::registry::Registry;
+        let mut previous: Option<Url> = None;
+        for name in ["base", "lib", "app"] {
+            let path = root.join(name);
+            Repository::init(&path).unwrap();
+            fs::write(path.join(format!("{}.sac", name)), name).unwrap();
+            fs::create_dir_all(path.join("build")).unwrap();
+            fs::write(path.join("build").join(format!("{}.so", name)), name).unwrap();
+            let mut package = Package::create(&path);
+            let mut module = Module::create(format!("{}.sac", name), None);
+            module.set_output(Some("build"));
+            package.add_module(module);
+            if let Some(url) = previous.take() {
+                package.add_dependency(Dependency::create(url));
+            }
+            let url = Url::parse(&format!("https://example.com/{}.git", name)).unwrap();
+            package.remote_location = Some(url.clone());
+            previous = Some(url);
+            registry.add(package);
+        }
+
+        let target = root.join("project");
+        let lockfile = registry.vendor(root.join("app").join("app.sac"), &target, false);
+        let identifiers: Vec<&str> = lockfile.packages().iter().map(|p| p.identifier()).collect();
+        assert_eq!(identifiers, vec!["lib", "base"]);
+        assert_eq!(Lockfile::load(target.join(Lockfile::FILE_NAME)), lockfile);
+
+        let base = target.join(lockfile.get("base").unwrap().path());
+        assert!(base.join("manifest.json").is_file());
+        assert!(base.join("output").join("base").join("base.so").is_file());
+        assert!(!base.join("base.sac").exists());
+
+        registry.vendor(root.join("app").join("app.sac"), &target, true);
+        assert!(base.join("base.sac").is_file());
+    }
 
     #[test]
-    fn test_save() {
-        let path = env::temp_dir().join("registry.json");
+    /// A progress handler is told about fetches and the events of the install operation
+    fn test_install_reports_progress() {
+        struct Recorder(Arc<Mutex<Vec<String>>>);
 
-        let res = fs::remove_file(&path);
+        impl ProgressHandler for Recorder {
+            fn fetch_started(&self, source: &Url) {
+                self.0.lock().unwrap().push(format!("started {}", source));
+            }
 
-        assert!(res.is_ok());
+            fn fetch_progress(&self, _source: &Url, received: usize, total: usize) {
+                assert!(received <= total);
+            }
 
-        let registry = Registry {
-            location: path,
-            packages: HashSet::new(),
-        };
-        assert!(registry.save().is_ok());
+            fn fetch_finished(&self, source: &Url) {
+                self.0.lock().unwrap().push(format!("finished {}", source));
+            }
+
+            fn event(&self, event: &OperationEvent) {
+                self.0.lock().unwrap().push(format!("{:?}", event));
+            }
+        }
+
+        let root = env::temp_dir().join("mock_install_progress");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        Package::create(&lib_path).publish(SemVerIncrement::Major, vec![]);
+        let lib_url = Url::from_directory_path(&lib_path).unwrap();
+
+        let log = Arc::new(Mutex::new(vec![]));
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_progress.json"));
+        registry.set_progress_handler(Some(Box::new(Recorder(log.clone()))));
+        let package = registry.install(&GitFetcher, lib_url.clone(), &install_path, None);
+
+        assert_eq!(*log.lock().unwrap(), vec![
+            format!("started {}", lib_url),
+            format!("finished {}", lib_url),
+            format!("Step(\"installed {}\")", package.local_location.display()),
+            String::from("Finished"),
+        ]);
     }
 
     #[test]
-    /// When [`Registry`]'s `location` points to an existing file, overwrite it.
-    fn test_save_overwrite() {
-        let path = env::temp_dir().join("registry.json");
-        let res = fs::write(&path, "hello");
+    /// Dependency outputs are taken from the requested profile, falling back to the default output
+    fn test_dependency_outputs_per_profile() {
+        let root = env::temp_dir().join("mock_dependency_profiles");
+        let _ = fs::remove_dir_all(&root);
+        let mut registry = Registry::new(env::temp_dir().join("registry_dependency_profiles.json"));
+        registry.set_output_policy(OutputPolicy::SourceDirectory);
 
-        assert!(res.is_ok());
-        assert!(path.is_file());
+        let lib_url = Url::parse("https://example.com/profiles/lib.git").unwrap();
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap().remote("origin", lib_url.as_str()).unwrap();
+        fs::write(lib_path.join("lib.sac"), "").unwrap();
+        let lib = Package::create(&lib_path);
+        let mut module = Module::create("lib.sac", None);
+        module.set_output(Some("build"));
+        module.set_profile_output("release", Some("build/release"));
+        lib.add_module(module);
+        registry.add(lib);
 
-        let registry = Registry {
-            location: path,
-            packages: HashSet::new(),
-        };
+        let app_path = root.join("app");
+        Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("app.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.add_module(Module::create("app.sac", None));
+        app.add_dependency(Dependency::create(lib_url));
+        registry.add(app);
 
-        assert!(registry.save().is_ok());
+        let source = app_path.join("app.sac");
+        assert_eq!(registry.dependency_outputs(&source, Some("release")), vec![lib_path.join("build/release")]);
+        assert_eq!(registry.dependency_outputs(&source, Some("debug")), vec![lib_path.join("build")]);
+        assert_eq!(registry.dependency_outputs(&source, None), vec![lib_path.join("build")]);
     }
 
     #[test]
-    /// Should panic when [`Registry`]´s `location` does not point to a JSON file
-    fn test_save_panic_not_json() {
-        let path = env::temp_dir().join("registry.txt");
+    #[cfg(unix)]
+    /// An executable is built once and run with the output directories of its dependencies on the search path
+    fn test_run_executable() {
+        use std::os::unix::fs::PermissionsExt;
+        use crate::executable::Executable;
+        use crate::run::SEARCH_PATH_VARIABLE;
 
-        fs::write(&path, "test").unwrap();
+        let root = env::temp_dir().join("mock_run_executable");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let compiler = root.join("compiler");
+        fs::write(&compiler, format!("#!/bin/sh\nwhile [ \"$1\" != \"-o\" ]; do shift; done\nprintf '#!/bin/sh\\necho \"${}\" \"$@\"\\n' > \"$2\"\nchmod +x \"$2\"\n", SEARCH_PATH_VARIABLE)).unwrap();
+        fs::set_permissions(&compiler, fs::Permissions::from_mode(0o755)).unwrap();
 
-        assert!(path.exists());
+        let mut registry = Registry::new(env::temp_dir().join("registry_run_executable.json"));
+        registry.set_output_policy(OutputPolicy::SourceDirectory);
+        registry.languages.insert("mock".to_string(), Language::create(compiler.to_str().unwrap(), vec![]));
 
-        let registry = Registry {
-            location: path,
-            packages: HashSet::new(),
-        };
-        assert_eq!(registry.save().err(), Some("Path does not point to a JSON file"));
+        let lib_url = Url::parse("https://example.com/run/lib.git").unwrap();
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap().remote("origin", lib_url.as_str()).unwrap();
+        fs::write(lib_path.join("lib.sac"), "").unwrap();
+        let lib = Package::create(&lib_path);
+        let mut module = Module::create("lib.sac", None);
+        module.set_output(Some("build"));
+        lib.add_module(module);
+        registry.add(lib);
+
+        let app_path = root.join("app");
+        Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("main.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.set_language(Some("mock".to_string()));
+        app.add_dependency(Dependency::create(lib_url));
+        let mut executable = Executable::create("main.sac");
+        executable.set_output(Some("bin"));
+        app.add_executable(executable);
+        registry.add(app);
+
+        let output = registry.run(app_path.join("main.sac"), &["hello"]).unwrap();
+        assert!(output.success());
+        assert!(output.built());
+        assert_eq!(output.search_path(), &[lib_path.join("build")]);
+        assert!(output.stdout().starts_with(&lib_path.join("build").display().to_string()));
+        assert!(output.stdout().trim_end().ends_with(" hello"));
+        assert!(app_path.join("bin/main").is_file());
+
+        let output = registry.run(app_path.join("main.sac"), &["again"]).unwrap();
+        assert!(!output.built());
+        assert!(output.stdout().trim_end().ends_with(" again"));
+
+        assert_eq!(registry.run(app_path.join("missing.sac"), &[] as &[&str]), Err(RunError::UnknownExecutable(app_path.join("missing.sac"))));
     }
 
     #[test]
-    /// Should panic when [`Registry`]´s `location` points to a directory
-    fn test_save_panic_is_a_file() {
-        let path = env::temp_dir().join("registry");
+    #[cfg(unix)]
+    /// Test modules are run per package or per module and their outcomes are collected in a report
+    fn test_run_tests() {
+        use std::os::unix::fs::PermissionsExt;
+        use crate::testing::TestOutcome;
 
-        fs::create_dir_all(&path).unwrap();
+        let package_path = env::temp_dir().join("mock_package_run_tests");
+        let _ = fs::remove_dir_all(&package_path);
+        Repository::init(&package_path).unwrap();
+        let compiler = package_path.join("compiler");
+        fs::write(&compiler, "#!/bin/sh\nwhile [ \"$2\" != \"-o\" ]; do shift; done\ncp \"$1\" \"$3\"\nchmod +x \"$3\"\n").unwrap();
+        fs::set_permissions(&compiler, fs::Permissions::from_mode(0o755)).unwrap();
 
-        assert!(path.is_dir());
+        let mut registry = Registry::new(env::temp_dir().join("registry_run_tests.json"));
+        registry.languages.insert("mock".to_string(), Language::create(compiler.to_str().unwrap(), vec![]));
 
-        let registry = Registry {
-            location: path,
-            packages: HashSet::new(),
-        };
-        let res = registry.save();
-        assert_eq!(res.err(), Some("Path does not point to a file"));
+        fs::write(package_path.join("a.sac"), "").unwrap();
+        fs::write(package_path.join("a_test.sac"), "#!/bin/sh\nexit 0\n").unwrap();
+        fs::write(package_path.join("all_test.sac"), "#!/bin/sh\nexit 1\n").unwrap();
+        let package = Package::create(&package_path);
+        package.set_language(Some("mock".to_string()));
+        package.add_module(Module::create("a.sac", None));
+        registry.add(package);
+
+        registry.add_test(package_path.join("a_test.sac"), Some(package_path.join("a.sac")));
+        registry.add_test(package_path.join("all_test.sac"), None::<PathBuf>);
+
+        let report = registry.test(package_path.join("a.sac"));
+        assert!(report.is_ok());
+        assert_eq!(report.results().len(), 1);
+        assert_eq!(report.results()[0].source(), package_path.join("a_test.sac"));
+
+        let report = registry.test(&package_path);
+        assert!(!report.is_ok());
+        assert_eq!(report.passed().len(), 1);
+        assert_eq!(report.failed()[0].source(), package_path.join("all_test.sac"));
+        assert!(matches!(report.failed()[0].outcome(), TestOutcome::Failed(output) if output.status().code() == Some(1)));
     }
 
     #[test]
-    /// Should panic when [`Registry`]'s `location` is a relative [`Path`]
-    fn test_save_panic_is_relative() {
-        let path = PathBuf::from("./registry.json");
+    /// Optional modules only contribute outputs when a dependent enables their feature
+    fn test_dependency_outputs_with_features() {
+        let root = env::temp_dir().join("mock_dependency_features");
+        let _ = fs::remove_dir_all(&root);
+        let mut registry = Registry::new(env::temp_dir().join("registry_dependency_features.json"));
+        registry.set_output_policy(OutputPolicy::SourceDirectory);
 
-        let registry = Registry {
-            location: path,
-            packages: HashSet::new(),
-        };
-        assert_eq!(registry.save().err(), Some("Path is relative"));
+        let lib_url = Url::parse("https://example.com/features/lib.git").unwrap();
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap().remote("origin", lib_url.as_str()).unwrap();
+        let lib = Package::create(&lib_path);
+        for name in ["core", "yaml"] {
+            fs::write(lib_path.join(format!("{}.sac", name)), "").unwrap();
+            let mut module = Module::create(format!("{}.sac", name), None);
+            module.set_output(Some(format!("build/{}", name)));
+            lib.add_module(module);
+        }
+        lib.add_feature("yaml", &["yaml.sac"]);
+        registry.add(lib);
+
+        let app_path = root.join("app");
+        Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("app.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.add_module(Module::create("app.sac", None));
+        app.add_dependency(Dependency::create(lib_url.clone()));
+        registry.add(app.clone());
+
+        let source = app_path.join("app.sac");
+        assert_eq!(registry.dependency_outputs(&source, None), vec![lib_path.join("build/core")]);
+
+        let mut dependency = Dependency::create(lib_url);
+        dependency.set_features(vec!["yaml".to_string()]);
+        app.add_dependency(dependency);
+        assert_eq!(registry.dependency_outputs(&source, None), vec![lib_path.join("build/core"), lib_path.join("build/yaml")]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// The linker is invoked with the link template applied to every transitive dependency output
+    fn test_link_executable() {
+        use std::os::unix::fs::PermissionsExt;
+        use crate::executable::Executable;
+
+        let root = env::temp_dir().join("mock_link_executable");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let linker = root.join("linker");
+        fs::write(&linker, "#!/bin/sh\nfor last; do :; done\necho \"$@\" > \"$last\"\n").unwrap();
+        fs::set_permissions(&linker, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_link_executable.json"));
+        registry.set_output_policy(OutputPolicy::SourceDirectory);
+        let mut language = Language::create("cc", vec!["-O2".to_string()]);
+        language.set_linker(Some(linker.display().to_string()));
+        language.set_link_template(Some("-L{path}".to_string()));
+        registry.languages.insert("mock".to_string(), language);
+
+        let mut previous: Option<Url> = None;
+        for name in ["base", "lib"] {
+            let url = Url::parse(&format!("https://example.com/link/{}.git", name)).unwrap();
+            let path = root.join(name);
+            Repository::init(&path).unwrap().remote("origin", url.as_str()).unwrap();
+            fs::write(path.join(format!("{}.sac", name)), "").unwrap();
+            let package = Package::create(&path);
+            let mut module = Module::create(format!("{}.sac", name), None);
+            module.set_output(Some("build"));
+            package.add_module(module);
+            if let Some(previous) = previous.replace(url) {
+                package.add_dependency(Dependency::create(previous));
+            }
+            registry.add(package);
+        }
+
+        let app_path = root.join("app");
+        Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("main.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.set_language(Some("mock".to_string()));
+        app.add_dependency(Dependency::create(previous.unwrap()));
+        let mut executable = Executable::create("main.sac");
+        executable.set_link_flags(vec!["-lm".to_string()]);
+        app.add_executable(executable);
+        registry.add(app);
+
+        let binary = registry.link(app_path.join("main.sac")).unwrap();
+        assert_eq!(binary, app_path.join("main"));
+        let arguments = fs::read_to_string(&binary).unwrap();
+        assert_eq!(arguments.trim_end(), format!(
+            "-O2 {} -L{} -L{} -lm -o {}",
+            app_path.join("main.sac").display(),
+            root.join("lib/build").display(),
+            root.join("base/build").display(),
+            binary.display(),
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// Build hooks run around a build with the package's variables set, and are skipped when the policy disables them
+    fn test_build_hooks() {
+        use std::os::unix::fs::PermissionsExt;
+        use crate::executable::Executable;
+        use crate::hooks::{HookKind, HookPolicy};
+        use crate::run::RunError;
+
+        let root = env::temp_dir().join("mock_build_hooks");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let compiler = root.join("compiler");
+        fs::write(&compiler, "#!/bin/sh\nwhile [ \"$1\" != \"-o\" ]; do shift; done\nprintf '#!/bin/sh\\n' > \"$2\"\nchmod +x \"$2\"\n").unwrap();
+        fs::set_permissions(&compiler, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_build_hooks.json"));
+        registry.set_output_policy(OutputPolicy::SourceDirectory);
+        registry.languages.insert("mock".to_string(), Language::create(compiler.to_str().unwrap(), vec![]));
+
+        let app_path = root.join("app");
+        Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("main.sac"), "").unwrap();
+        fs::write(app_path.join("lib.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.set_language(Some("mock".to_string()));
+        let mut module = Module::create("lib.sac", None);
+        module.set_output(Some("build"));
+        app.add_module(module);
+        app.add_executable(Executable::create("main.sac"));
+        let log = root.join("hooks.log");
+        let script = format!("echo \"$KNAPSAC_HOOK $KNAPSAC_PACKAGE_ROOT $KNAPSAC_OUTPUT_DIRS\" >> {}", log.display());
+        app.set_hook(HookKind::PreBuild, Some(vec!["sh".to_string(), "-c".to_string(), script.clone()]));
+        app.set_hook(HookKind::PostBuild, Some(vec!["sh".to_string(), "-c".to_string(), script]));
+        registry.add(app.clone());
+
+        assert!(registry.run(app_path.join("main.sac"), &[] as &[&str]).unwrap().built());
+        let expected = |kind: &str| format!("{} {} {}", kind, app.local_location.display(), app_path.join("build").display());
+        assert_eq!(fs::read_to_string(&log).unwrap(), format!("{}\n{}\n", expected("pre_build"), expected("post_build")));
+
+        fs::remove_file(app_path.join("main")).unwrap();
+        registry.set_hook_policy(HookPolicy::None);
+        registry.run(app_path.join("main.sac"), &[] as &[&str]).unwrap();
+        assert_eq!(fs::read_to_string(&log).unwrap().lines().count(), 2);
+
+        fs::remove_file(app_path.join("main")).unwrap();
+        registry.set_hook_policy(HookPolicy::All);
+        app.set_hook(HookKind::PreBuild, Some(vec!["false".to_string()]));
+        match registry.run(app_path.join("main.sac"), &[] as &[&str]) {
+            Err(RunError::Hook(error)) => assert_eq!(error.kind(), HookKind::PreBuild),
+            other => panic!("expected a failing hook, got {:?}", other),
+        }
+        assert!(!app_path.join("main").exists());
+    }
+
+    #[test]
+    /// Saving the same [`Package`]s produces the same file, regardless of the order they were added in
+    fn test_stable_serialization() {
+        let packages: Vec<Package> = (0..5).map(|i| {
+            let path = env::temp_dir().join(format!("mock_package_stable_{}", i));
+            let _ = fs::remove_dir_all(&path);
+            Repository::init(&path).unwrap();
+            Package::create(&path)
+        }).collect();
+
+        let first = env::temp_dir().join("registry_stable_first.json");
+        let second = env::temp_dir().join("registry_stable_second.json");
+        let mut registry = Registry::initialize(&first);
+        packages.iter().for_each(|p| registry.add(p.clone()));
+        let mut other = Registry::initialize(&second);
+        packages.iter().rev().for_each(|p| other.add(p.clone()));
+
+        assert_eq!(fs::read_to_string(&first).unwrap(), fs::read_to_string(&second).unwrap());
+    }
+
+    #[test]
+    /// Dev dependencies of an installed package are not installed, and are left out of vendoring when excluded
+    fn test_dev_dependencies_not_installed() {
+        let json_path = env::temp_dir().join("mock_source_dev_json");
+        let app_path = env::temp_dir().join("mock_source_dev_app");
+        let install_path = env::temp_dir().join("mock_install_dev");
+        for path in [&json_path, &app_path, &install_path] {
+            let _ = fs::remove_dir_all(path);
+        }
+        fs::create_dir_all(&install_path).unwrap();
+
+        let json_repository = Repository::init(&json_path).unwrap();
+        fs::write(json_path.join("json.sac"), "").unwrap();
+        Package::create(&json_path).add_module(Module::create("json.sac", None));
+        commit_all(&json_repository);
+        let json_url = Url::from_directory_path(&json_path).unwrap();
+
+        let app_repository = Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("main.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.add_dependency(Dependency::declare(json_url.clone(), "json", None));
+        let mut tester = Dependency::create(Url::from_directory_path(env::temp_dir().join("mock_source_dev_missing")).unwrap());
+        tester.set_kind(DependencyKind::Dev);
+        app.add_dependency(tester);
+        commit_all(&app_repository);
+        let app_url = Url::from_directory_path(&app_path).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_dev.json"));
+        let installed = registry.try_install(&GitFetcher, app_url, &install_path, None).unwrap();
+        assert_eq!(registry.count_packages(), 2);
+        assert_eq!(installed.dependencies().iter().filter(|d| d.kind() == DependencyKind::Dev).count(), 1);
+
+        let target = env::temp_dir().join("mock_vendor_dev");
+        let _ = fs::remove_dir_all(&target);
+        let lockfile = registry.vendor_kinds(installed.local_location.join("main.sac"), &target, false, &[DependencyKind::Normal]);
+        assert_eq!(lockfile.packages().len(), 1);
+    }
+
+    #[test]
+    /// Declaring a dependency on a registered package fails when none of its versions satisfies the requirement
+    fn test_add_dependency_to_item_requirement() {
+        let lib_path = env::temp_dir().join("mock_package_requirement_lib");
+        let app_path = env::temp_dir().join("mock_package_requirement_app");
+        for path in [&lib_path, &app_path] {
+            let _ = fs::remove_dir_all(path);
+        }
+        let lib_url = Url::parse("https://example.com/requirement/lib").unwrap();
+        Repository::init(&lib_path).unwrap().remote("origin", lib_url.as_str()).unwrap();
+        let lib = Package::create(&lib_path);
+        lib.publish(SemVerIncrement::Minor, vec![]);
+        Repository::init(&app_path).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_requirement.json"));
+        registry.add(lib);
+        registry.add(Package::create(&app_path));
+
+        let requirement: VersionReq = "^1.0".parse().unwrap();
+        assert_eq!(
+            registry.add_dependency_to_item(app_path.join("main.sac"), Dependency::declare(lib_url.clone(), "lib", Some(requirement.clone())), None),
+            Err(DependencyError::UnsatisfiedRequirement(lib_url.clone(), requirement)),
+        );
+        registry.add_dependency_to_item(app_path.join("main.sac"), Dependency::declare(lib_url.clone(), "lib", Some("^0.1".parse().unwrap())), None).unwrap();
+        let unknown = Url::parse("https://example.com/requirement/unknown").unwrap();
+        registry.add_dependency_to_item(app_path.join("main.sac"), Dependency::declare(unknown, "unknown", Some("^1.0".parse().unwrap())), None).unwrap();
+        assert_eq!(registry.get_by_source(app_path.join("main.sac")).unwrap().dependencies().len(), 2);
+    }
+
+    #[test]
+    /// Paths listed in a JSON dependency file that belong to a registered package become dependencies on it
+    fn test_import_json_depfile() {
+        let lib_path = env::temp_dir().join("mock_package_depfile_lib");
+        let app_path = env::temp_dir().join("mock_package_depfile_app");
+        for path in [&lib_path, &app_path] {
+            let _ = fs::remove_dir_all(path);
+        }
+        let lib_url = Url::parse("https://example.com/depfile/lib").unwrap();
+        Repository::init(&lib_path).unwrap().remote("origin", lib_url.as_str()).unwrap();
+        fs::write(lib_path.join("a.sac"), "").unwrap();
+        fs::write(lib_path.join("b.sac"), "").unwrap();
+        Repository::init(&app_path).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_depfile_json.json"));
+        registry.add(Package::create(&lib_path));
+        registry.add(Package::create(&app_path));
+
+        let depfile = app_path.join("main.json");
+        let listed = serde_json::json!({ "dependencies": [lib_path.join("a.sac"), lib_path.join("b.sac"), "missing.h"] });
+        fs::write(&depfile, listed.to_string()).unwrap();
+        let import = registry.import_depfile(app_path.join("main.sac"), &depfile, DepfileFormat::Json).unwrap();
+        assert_eq!(import.dependencies().to_vec(), vec![lib_url.clone()]);
+        assert!(import.strays().is_empty());
+        assert_eq!(import.skipped(), &[app_path.join("missing.h")]);
+        assert_eq!(registry.get_by_source(app_path.join("main.sac")).unwrap().dependencies(), vec![Dependency::create(lib_url)]);
+
+        fs::write(&depfile, "{}").unwrap();
+        assert!(matches!(
+            registry.import_depfile(app_path.join("main.sac"), &depfile, DepfileFormat::Json),
+            Err(DependencyError::Depfile(..))
+        ));
+    }
+
+    #[test]
+    /// Imported identifiers resolve to modules of dependencies and to libraries in stray output directories
+    fn test_resolve_import_across_packages() {
+        let lib_path = env::temp_dir().join("mock_package_import_lib");
+        let app_path = env::temp_dir().join("mock_package_import_app");
+        let stray = env::temp_dir().join("mock_stray_import");
+        for path in [&lib_path, &app_path, &stray] {
+            let _ = fs::remove_dir_all(path);
+        }
+        let lib_url = Url::parse("https://example.com/import/lib").unwrap();
+        Repository::init(&lib_path).unwrap().remote("origin", lib_url.as_str()).unwrap();
+        fs::write(lib_path.join("json.sac"), "module json;").unwrap();
+        let lib = Package::create(&lib_path);
+        lib.add_module(Module::create("json.sac", None));
+        Repository::init(&app_path).unwrap();
+        let app = Package::create(&app_path);
+        app.add_dependency(Dependency::create(lib_url));
+        fs::create_dir_all(&stray).unwrap();
+        fs::write(stray.join("libxml.so"), "").unwrap();
+        app.add_stray(&stray);
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_resolve_import_across.json"));
+        registry.add(lib);
+        registry.add(app);
+
+        let json = registry.resolve_import(app_path.join("main.sac"), "json").unwrap();
+        assert_eq!(json.kind(), ImportKind::Dependency);
+        assert_eq!(json.source(), Some(lib_path.join("json.sac").as_path()));
+        assert_eq!(json.package(), Some(lib_path.as_path()));
+
+        let xml = registry.resolve_import(app_path.join("main.sac"), "xml").unwrap();
+        assert_eq!(xml.kind(), ImportKind::Stray);
+        assert_eq!(xml.output(), Some(stray.as_path()));
+        assert!(xml.package().is_none());
+    }
+
+    #[test]
+    /// A build plan compiles the modules of dependencies before linking the executable depending on them
+    fn test_build_plan_orders_dependencies() {
+        let lib_path = env::temp_dir().join("mock_package_build_plan_lib");
+        let app_path = env::temp_dir().join("mock_package_build_plan_app");
+        for path in [&lib_path, &app_path] {
+            let _ = fs::remove_dir_all(path);
+        }
+        let lib_url = Url::parse("https://example.com/build-plan/lib").unwrap();
+        Repository::init(&lib_path).unwrap().remote("origin", lib_url.as_str()).unwrap();
+        fs::write(lib_path.join("json.sac"), "module json;").unwrap();
+        let lib = Package::create(&lib_path);
+        lib.set_language(Some("sac".to_string()));
+        lib.add_module(Module::create("json.sac", None));
+        Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("main.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.set_language(Some("sac".to_string()));
+        app.add_dependency(Dependency::create(lib_url));
+        app.add_executable(Executable::create("main.sac"));
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_build_plan_order.json"));
+        let mut language = Language::create("sac2c", vec![]);
+        language.set_output_template(Some("lib{identifier}.so".to_string()));
+        registry.register_language("sac", language);
+        registry.add(lib);
+        registry.add(app);
+        registry.set_output(lib_path.join("json.sac"), "out");
+
+        let main = app_path.join("main.sac");
+        let plan = registry.build_plan(&main).unwrap();
+        let ids: Vec<&Path> = plan.steps().iter().map(|s| s.id()).collect();
+        assert_eq!(ids, vec![lib_path.join("json.sac").as_path(), main.as_path()]);
+        assert_eq!(plan.steps()[0].outputs().to_vec(), vec![lib_path.join("out").join("libjson.so")]);
+        assert_eq!(plan.steps()[1].dependencies().to_vec(), vec![lib_path.join("json.sac")]);
+
+        let ninja = registry.emit_build_plan(&main, BuildPlanFormat::Ninja).unwrap();
+        assert!(ninja.contains(&format!("| {}", lib_path.join("out").join("libjson.so").display())));
+        assert_eq!(registry.build_plan(app_path.join("missing.sac")), Err(RunError::UnknownTarget(app_path.join("missing.sac"))));
+    }
+
+    #[test]
+    /// Packages using the directory of a standalone module as a stray depend on its new package instead
+    fn test_package_single_module_converts_dependents() {
+        let directory = env::temp_dir().join("mock_single_module_dependents");
+        let app_path = env::temp_dir().join("mock_package_single_module_app");
+        for path in [&directory, &app_path] {
+            let _ = fs::remove_dir_all(path);
+        }
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("json.sac"), "module json;").unwrap();
+        Repository::init(&app_path).unwrap();
+        let app = Package::create(&app_path);
+        app.add_stray(&directory);
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_single_module_dependents.json"));
+        registry.add(app.clone());
+        assert_eq!(
+            registry.package_single_module(directory.join("json.sac"), "not an identifier").err(),
+            Some(PackagingError::InvalidIdentifier("not an identifier".to_string()))
+        );
+        let json = registry.package_single_module(directory.join("json.sac"), "json").unwrap();
+
+        assert!(app.strays().is_empty());
+        let dependencies = app.dependencies();
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(registry.resolve_dependency(&dependencies[0]), Some(&json));
+        assert_eq!(
+            registry.package_single_module(json.local_location.join("json.sac"), "other").err(),
+            Some(PackagingError::NotStandalone(json.local_location.join("json.sac")))
+        );
+    }
+
+    #[test]
+    /// Hand-edited manifests that parse but cannot be right are rejected, other edits are reported
+    fn test_sync_package_validates_manifest() {
+        let path = env::temp_dir().join("mock_package_sync_validates");
+        let _ = fs::remove_dir_all(&path);
+        Repository::init(&path).unwrap();
+        fs::write(path.join("a.sac"), "module a;").unwrap();
+        let package = Package::create(&path);
+        package.add_module(Module::create("a.sac", None));
+        let mut registry = Registry::new(env::temp_dir().join("registry_sync_validates.json"));
+        registry.add(package.clone());
+
+        let manifest = fs::read_to_string(package.manifest_location()).unwrap();
+        fs::write(package.manifest_location(), manifest.replace("\"a.sac\"", "\"../a.sac\"")).unwrap();
+        assert_eq!(
+            registry.sync_package(&path).err(),
+            Some(SyncError::Manifest(ManifestError::Invalid(
+                package.manifest_location(),
+                vec![SchemaViolation::ModuleOutsidePackage(PathBuf::from("../a.sac"))]
+            )))
+        );
+
+        fs::write(package.manifest_location(), manifest).unwrap();
+        fs::remove_file(path.join("a.sac")).unwrap();
+        let url = Url::parse("https://example.com/unknown.git").unwrap();
+        package.add_dependency(Dependency::create(url.clone()));
+        let report = registry.sync_package(&path).unwrap();
+        assert_eq!(
+            report.discrepancies().to_vec(),
+            vec![Discrepancy::MissingModuleFile(path.join("a.sac")), Discrepancy::UnresolvedDependency(url)]
+        );
+        assert_eq!(
+            registry.sync_package(env::temp_dir().join("mock_package_sync_unknown")).err(),
+            Some(SyncError::UnknownPackage(env::temp_dir().join("mock_package_sync_unknown")))
+        );
+    }
+
+    #[test]
+    /// Installing a package with a broken manifest fails with the position of the problem and leaves nothing behind
+    fn test_install_rejects_invalid_manifest() {
+        let root = env::temp_dir().join("mock_install_invalid_manifest");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+        let lib_path = root.join("lib");
+        let repository = Repository::init(&lib_path).unwrap();
+        let lib = Package::create(&lib_path);
+        let manifest = fs::read_to_string(lib.manifest_location()).unwrap();
+        fs::write(lib.manifest_location(), manifest.replace("\"mirrors\": []", "\"mirrors\": [\"not a url\"]")).unwrap();
+        commit_all(&repository);
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_install_invalid_manifest.json"));
+        let result = registry.try_install(&GitFetcher, Url::from_directory_path(&lib_path).unwrap(), &install_path, None);
+        match result {
+            Err(InstallError::Manifest(ManifestError::InvalidUrl(_, line, _, reason))) => {
+                assert_eq!(line, 25);
+                assert!(reason.starts_with("relative URL without a base"));
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert!(registry.packages.is_empty());
+        assert_eq!(fs::read_dir(&install_path).unwrap().count(), 0);
+    }
+
+    #[test]
+    /// Installed packages that vendored their stray dependencies provide the copies once the strays are gone
+    fn test_vendored_strays_provided_to_dependents() {
+        let root = env::temp_dir().join("mock_vendored_strays");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        let stray = root.join("standalone");
+        fs::create_dir_all(&install_path).unwrap();
+        fs::create_dir_all(&stray).unwrap();
+        fs::write(stray.join("json.o"), "").unwrap();
+
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        fs::write(lib_path.join("lib.sac"), "").unwrap();
+        let lib = Package::create(&lib_path);
+        lib.add_module(Module::create("lib.sac", None));
+        lib.add_stray(&stray);
+        lib.set_vendor_strays(true);
+        lib.publish(SemVerIncrement::Minor, vec![]);
+        let lib_url = Url::from_directory_path(&lib_path).unwrap();
+
+        let app_path = root.join("app");
+        Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("main.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.add_module(Module::create("main.sac", None));
+        app.add_dependency(Dependency::create(lib_url.clone()));
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_vendored_strays.json"));
+        let installed = registry.try_install(&GitFetcher, lib_url, &install_path, None).unwrap();
+        registry.add(app);
+        fs::remove_dir_all(&stray).unwrap();
+
+        let outputs = registry.try_dependency_outputs(app_path.join("main.sac"), None).unwrap();
+        assert_eq!(outputs, vec![installed.local_location.join(VENDORED_DIRECTORY).join("standalone")]);
+        assert!(outputs[0].join("json.o").is_file());
+    }
+
+    #[test]
+    /// Installing prefers prebuilt artifacts matching the host and leaves other modules to be built from source
+    fn test_install_restores_prebuilt_artifacts() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = env::temp_dir().join("mock_prebuilt_artifacts");
+        let _ = fs::remove_dir_all(&root);
+        let install_path = root.join("installed");
+        fs::create_dir_all(&install_path).unwrap();
+        let compiler = root.join("sac2c");
+        fs::write(&compiler, "#!/bin/sh\necho 'sac2c 1.3.3'\n").unwrap();
+        fs::set_permissions(&compiler, fs::Permissions::from_mode(0o755)).unwrap();
+        let language = Language::create(compiler.display().to_string(), vec![]);
+
+        let lib_path = root.join("lib");
+        Repository::init(&lib_path).unwrap();
+        fs::write(lib_path.join(".gitignore"), "out/\n").unwrap();
+        let lib = Package::create(&lib_path);
+        lib.set_language(Some("sac".to_string()));
+        for (name, tag) in [("a", language.abi_tag("1.3.3")), ("b", language.abi_tag("1.2.0"))] {
+            fs::write(lib_path.join(format!("{}.sac", name)), "").unwrap();
+            fs::create_dir_all(lib_path.join("out").join(name)).unwrap();
+            fs::write(lib_path.join("out").join(name).join(format!("{}.so", name)), name).unwrap();
+            let mut module = Module::create(format!("{}.sac", name), None);
+            module.set_output(Some(format!("out/{}", name)));
+            module.artifact_tag = Some(tag);
+            lib.add_module(module);
+        }
+        lib.set_artifact_mode(Some(ArtifactMode::OrphanBranch));
+        lib.publish(SemVerIncrement::Minor, vec![]);
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_prebuilt_artifacts.json"));
+        registry.register_language("sac", language);
+        let installed = registry.try_install(&GitFetcher, Url::from_directory_path(&lib_path).unwrap(), &install_path, None).unwrap();
+        assert_eq!(fs::read_to_string(installed.local_location.join("out/a/a.so")).unwrap(), "a");
+        assert!(!installed.local_location.join("out/b").exists());
+        let report = registry.restore_prebuilt(&installed);
+        assert_eq!(report.restored().to_vec(), vec![installed.local_location.join("a.sac")]);
+        assert_eq!(report.from_source().to_vec(), vec![installed.local_location.join("b.sac")]);
+
+        lib.set_artifact_mode(Some(ArtifactMode::Archive));
+        let unpacked = Package::unpack(lib.pack(&root), root.join("unpacked"));
+        assert!(unpacked.local_location.join(ARTIFACTS_DIRECTORY).join("a.sac/a.so").is_file());
+    }
+
+    #[test]
+    /// Resolving and vendoring select the outputs of the requested target, falling back to the default ones
+    fn test_target_outputs_selected() {
+        let root = env::temp_dir().join("mock_target_outputs");
+        let _ = fs::remove_dir_all(&root);
+        let lib_url = Url::parse("https://example.com/target/lib").unwrap();
+        let lib_path = root.join("lib");
+        let app_path = root.join("app");
+        let mut registry = Registry::new(env::temp_dir().join("registry_target_outputs.json"));
+        for path in [&lib_path, &app_path] {
+            let repository = Repository::init(path).unwrap();
+            if path == &lib_path {
+                repository.remote("origin", lib_url.as_str()).unwrap();
+            }
+            fs::write(path.join("a.sac"), "").unwrap();
+            let package = Package::create(path);
+            package.add_module(Module::create("a.sac", None));
+            registry.add(package);
+        }
+        registry.get_by_local_location(&app_path).unwrap().add_dependency(Dependency::create(lib_url));
+        let source = lib_path.join("a.sac");
+        registry.set_output_policy(OutputPolicy::SourceDirectory);
+        registry.set_output(&source, "build");
+        registry.set_target_output(&source, "wasm32", "build/wasm32");
+        fs::create_dir_all(lib_path.join("build/wasm32")).unwrap();
+        fs::write(lib_path.join("build/a.o"), "").unwrap();
+        fs::write(lib_path.join("build/wasm32/a.wasm"), "").unwrap();
+
+        let outputs = |registry: &Registry| registry.try_dependency_outputs(app_path.join("a.sac"), None).unwrap();
+        assert_eq!(outputs(&registry), vec![lib_path.join("build")]);
+        registry.set_target(Some("x86_64-linux"));
+        assert_eq!(outputs(&registry), vec![lib_path.join("build")]);
+        registry.set_target(Some("wasm32"));
+        assert_eq!(outputs(&registry), vec![lib_path.join("build/wasm32")]);
+
+        let vendored = root.join("vendored");
+        registry.vendor(app_path.join("a.sac"), &vendored, false);
+        let output = vendored.join("vendor/lib/output/a");
+        assert!(output.join("a.wasm").is_file());
+        assert!(!output.join("a.o").exists());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    /// A [`Registry`] and its [`Package`]s' manifests survive a round trip from JSON to TOML and back
+    fn test_toml_round_trip() {
+        use crate::format::Format;
+
+        let package_path = env::temp_dir().join("mock_package_toml_round_trip");
+        let _ = fs::remove_dir_all(&package_path);
+        Repository::init(&package_path).unwrap();
+        let package = Package::create(&package_path);
+        package.set_language(Some(String::from("sac")));
+        let dependency = Dependency::create(Url::parse("https://example.com/dependency.git").unwrap());
+        package.add_dependency(dependency.clone());
+
+        let json_path = env::temp_dir().join("registry_round_trip.json");
+        let toml_path = env::temp_dir().join("registry_round_trip.toml");
+        let _ = fs::remove_file(&json_path);
+        let _ = fs::remove_file(&toml_path);
+
+        let mut registry = Registry::initialize(&json_path);
+        registry.register_language("sac", Language::create("sac2c", vec![String::from("-O3")]));
+        registry.add(package.clone());
+
+        package.set_manifest_format(Format::Toml);
+        assert_eq!(package.manifest_format(), Format::Toml);
+        assert!(package_path.join("manifest.toml").is_file());
+        assert!(!package_path.join("manifest.json").exists());
+        assert_eq!(package.language(), Some(String::from("sac")));
+        assert!(package.has_dependency(&dependency));
+
+        registry.location = toml_path.clone();
+        registry.save().unwrap();
+        let from_toml = Registry::load(&toml_path);
+        assert!(from_toml.contains(&package));
+        assert_eq!(from_toml.get_language("sac"), registry.get_language("sac"));
+
+        package.set_manifest_format(Format::Json);
+        assert_eq!(package.manifest_format(), Format::Json);
+        assert_eq!(package.language(), Some(String::from("sac")));
+
+        let mut back = from_toml;
+        back.location = json_path.clone();
+        back.save().unwrap();
+        let from_json = Registry::load(&json_path);
+        assert_eq!(from_json.packages, registry.packages);
+        assert_eq!(from_json.languages, registry.languages);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// A package reached through a symbolic link is registered once and its items are found through the link
+    fn test_symlinked_package_is_registered_once() {
+        let package_path = env::temp_dir().join("mock_package_symlinked");
+        let link = env::temp_dir().join("mock_package_symlinked_link");
+        let _ = fs::remove_dir_all(&package_path);
+        let _ = fs::remove_file(&link);
+        Repository::init(&package_path).unwrap();
+        fs::write(package_path.join("a.sac"), "").unwrap();
+        std::os::unix::fs::symlink(&package_path, &link).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_symlinked.json"));
+        let package = Package::create(&package_path);
+        package.add_module(Module::create("a.sac", None));
+        registry.add(package.clone());
+        registry.add(Package { local_location: link.clone(), remote_location: None });
+
+        assert_eq!(registry.count_packages(), 1);
+        assert_eq!(registry.get_by_source(link.join("a.sac")), Some(&package));
+        assert!(registry.get_item(link.join("a.sac")).is_some());
+        assert_eq!(registry.get_by_local_location(&link), Some(&package));
+    }
+
+    #[test]
+    /// A project-local registry written on Windows, with backslash separators, can be read on every platform
+    fn test_relative_paths_with_foreign_separators() {
+        let project = env::temp_dir().join("mock_project_foreign_separators");
+        let _ = fs::remove_dir_all(&project);
+        fs::create_dir_all(project.join(".knapsac")).unwrap();
+        Repository::init(project.join("libs").join("json")).unwrap();
+        let package = Package::create(project.join("libs").join("json"));
+        let path = project.join(".knapsac").join("registry.json");
+        fs::write(&path, r#"{"packages": [{"local_location": "..\\libs\\json", "remote_location": null}], "relative_paths": true}"#).unwrap();
+
+        let registry = Registry::load(&path);
+        assert_eq!(registry.get_by_local_location(project.join("libs").join("json")), Some(&package));
+
+        registry.save().unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("\"../libs/json\""));
+    }
+
+    #[test]
+    /// Renaming a module is refused when a dependent has a module with that name, and renames its compiled library otherwise
+    fn test_set_identifier_checks_dependents_and_renames_outputs() {
+        let lib_path = env::temp_dir().join("mock_package_set_identifier_lib");
+        let app_path = env::temp_dir().join("mock_package_set_identifier_app");
+        for path in [&lib_path, &app_path] {
+            let _ = fs::remove_dir_all(path);
+        }
+        let lib_url = Url::parse("https://example.com/set-identifier/lib").unwrap();
+        Repository::init(&lib_path).unwrap().remote("origin", lib_url.as_str()).unwrap();
+        fs::write(lib_path.join("json.sac"), "module json;").unwrap();
+        let lib = Package::create(&lib_path);
+        lib.set_language(Some("sac".to_string()));
+        lib.add_module(Module::create("json.sac", None));
+        Repository::init(&app_path).unwrap();
+        fs::write(app_path.join("parser.sac"), "module parser;").unwrap();
+        let app = Package::create(&app_path);
+        app.add_module(Module::create("parser.sac", None));
+        app.add_dependency(Dependency::create(lib_url));
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_set_identifier_outputs.json"));
+        let mut language = Language::create("sac2c", vec![]);
+        language.set_output_template(Some("lib{identifier}.so".to_string()));
+        registry.register_language("sac", language);
+        registry.add(lib);
+        registry.add(app);
+        let source = lib_path.join("json.sac");
+        registry.set_output(&source, "out");
+        fs::create_dir_all(lib_path.join("out")).unwrap();
+        fs::write(lib_path.join("out").join("libjson.so"), "").unwrap();
+
+        assert_eq!(
+            registry.set_identifier(&source, "parser"),
+            Err(ModuleError::IdentifierTaken("parser".to_string(), app_path.join("parser.sac"))),
+        );
+        assert_eq!(registry.get_module(&source).unwrap().identifier, "json");
+
+        registry.set_identifier(&source, "json5").unwrap();
+        assert_eq!(registry.get_module(&source).unwrap().identifier, "json5");
+        assert!(!lib_path.join("out").join("libjson.so").exists());
+        assert!(lib_path.join("out").join("libjson5.so").exists());
+        assert_eq!(registry.resolve_import(app_path.join("parser.sac"), "json5").unwrap().source(), Some(source.as_path()));
+    }
+
+    #[test]
+    /// The registry file holds plain JSON, its checksum is kept next to it, and a file edited by hand is still loaded
+    fn test_saved_registry_is_plain_json() {
+        let path = env::temp_dir().join("registry_plain_json.json");
+        let _ = fs::remove_file(&path);
+        let mut registry = Registry::initialize(&path);
+        registry.offline(true);
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&contents).is_ok());
+        let mut checksum = path.as_os_str().to_os_string();
+        checksum.push(".sha256");
+        assert_eq!(fs::read_to_string(&checksum).unwrap(), crate::utils::sha256_hex(&contents));
+
+        fs::write(&path, contents.replace("\"offline\": true", "\"offline\": false")).unwrap();
+        let edited = Registry::try_load(&path).unwrap();
+        assert!(!edited.recovered_from_backup());
+        assert!(!edited.is_offline());
+    }
+
+    #[test]
+    #[cfg(not(feature = "toml"))]
+    /// Saving to a TOML file without the `toml` feature explains that the feature is missing
+    fn test_save_toml_without_feature() {
+        let path = env::temp_dir().join("registry_toml_disabled.toml");
+        let registry = Registry::new(&path);
+        assert_eq!(
+            registry.save(),
+            Err(RegistryError::Unwritable(path, "TOML support is not enabled, enable the `toml` feature".to_string())),
+        );
+    }
+
+    #[test]
+    /// Dropping a clone of a registry with pending changes does not overwrite what the original saves later
+    fn test_clone_does_not_save_on_drop() {
+        let path = env::temp_dir().join("registry_clone_drop.json");
+        let mut registry = Registry::initialize(&path);
+        registry.set_autosave(AutoSave::Debounced(Duration::from_secs(60)));
+
+        let package_path = env::temp_dir().join("mock_package_clone_drop");
+        Repository::init(&package_path).unwrap();
+        let package = Package::create(&package_path);
+
+        registry.add(package.clone());
+        registry.remove(&package);
+        assert!(registry.dirty);
+
+        let copy = registry.clone();
+        assert!(!copy.dirty);
+        registry.add(package.clone());
+        registry.flush();
+        drop(copy);
+        assert!(Registry::load(&path).contains(&package));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    /// Saving breaks a lock file left behind by a process that no longer runs
+    fn test_save_breaks_stale_lock() {
+        let path = env::temp_dir().join("registry_stale_lock.json");
+        let mut registry = Registry::initialize(&path);
+        let mut lock = path.as_os_str().to_os_string();
+        lock.push(".lock");
+        fs::write(&lock, u32::MAX.to_string()).unwrap();
+
+        registry.offline(true);
+        assert!(!Path::new(&lock).exists());
+        assert!(Registry::load(&path).is_offline());
+    }
+
+    #[test]
+    /// Moving a module to a file that does not exist fails without unregistering it
+    fn test_move_module_missing_destination() {
+        let mut registry = Registry::initialize(env::temp_dir().join("registry_move_missing.json"));
+        let package_path = env::temp_dir().join("mock_package_move_missing");
+        let _ = fs::remove_dir_all(&package_path);
+        Repository::init(&package_path).unwrap();
+        fs::write(package_path.join("a.sac"), "").unwrap();
+        let package = Package::create(&package_path);
+        package.add_module(Module::create("a.sac", None));
+        registry.add(package.clone());
+
+        let destination = package_path.join("src").join("a.sac");
+        assert_eq!(
+            registry.try_move_module(package_path.join("a.sac"), &destination),
+            Err(crate::Error::Module(ModuleError::MissingSource(destination))),
+        );
+        assert!(package.get_module_by_location("a.sac").is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "identifier `b` is already used by")]
+    /// Renaming a module to the identifier of another module of the same package is refused
+    fn test_rename_module_taken() {
+        let mut registry = Registry::initialize(env::temp_dir().join("registry_rename_taken.json"));
+        let package_path = env::temp_dir().join("mock_package_rename_taken");
+        let _ = fs::remove_dir_all(&package_path);
+        Repository::init(&package_path).unwrap();
+        fs::write(package_path.join("a.sac"), "").unwrap();
+        fs::write(package_path.join("b.sac"), "").unwrap();
+        let package = Package::create(&package_path);
+        package.add_module(Module::create("a.sac", None));
+        package.add_module(Module::create("b.sac", None));
+        registry.add(package);
+
+        registry.rename_module(package_path.join("a.sac"), "b");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// A compiled file that cannot be renamed is reported and the identifier is restored
+    fn test_set_identifier_restores_on_io_error() {
+        let package_path = env::temp_dir().join("mock_package_set_identifier_io");
+        let _ = fs::remove_dir_all(&package_path);
+        Repository::init(&package_path).unwrap();
+        fs::write(package_path.join("json.sac"), "module json;").unwrap();
+        let package = Package::create(&package_path);
+        package.set_language(Some("sac".to_string()));
+        package.add_module(Module::create("json.sac", None));
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_set_identifier_io.json"));
+        let mut language = Language::create("sac2c", vec![]);
+        language.set_output_template(Some("lib{identifier}.so".to_string()));
+        registry.register_language("sac", language);
+        registry.add(package);
+        let source = package_path.join("json.sac");
+        registry.set_output(&source, "out");
+        fs::create_dir_all(package_path.join("out").join("libjson5.so").join("taken")).unwrap();
+        fs::write(package_path.join("out").join("libjson.so"), "").unwrap();
+
+        let error = registry.set_identifier(&source, "json5").unwrap_err();
+        assert!(matches!(error, ModuleError::Io(path, _) if path == package_path.join("out").join("libjson.so")));
+        assert_eq!(registry.get_module(&source).unwrap().identifier, "json");
+        assert!(package_path.join("out").join("libjson.so").exists());
+    }
+
+    #[test]
+    /// Transferred packages are stored the way the destination stores its paths, and its allowed roots are respected
+    fn test_transfer_package_destination_paths() {
+        let root = env::temp_dir().join("mock_transfer_destination");
+        let _ = fs::remove_dir_all(&root);
+        let package_path = root.join("packages").join("json");
+        Repository::init(&package_path).unwrap();
+        let package = Package::create(&package_path);
+
+        let mut workspace = Registry::new(env::temp_dir().join("registry_transfer_destination_workspace.json"));
+        workspace.add(package.clone());
+        let mut restricted = Registry::new(env::temp_dir().join("registry_transfer_destination_restricted.json"));
+        restricted.set_allowed_roots(vec![root.join("elsewhere")]);
+        assert!(matches!(
+            workspace.try_transfer_package(&mut restricted, &package_path, false),
+            Err(crate::Error::OutsideAllowedRoots(_)),
+        ));
+        assert!(workspace.contains(&package) && restricted.is_empty());
+
+        let global_path = root.join("registry.json");
+        let mut global = Registry::new(&global_path);
+        global.set_relative_paths(true);
+        workspace.transfer_package(&mut global, &package_path, false);
+        assert!(fs::read_to_string(&global_path).unwrap().contains("\"packages/json\""));
+        assert!(Registry::load(&global_path).contains(&package));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// Hooks shipped inside an unpacked archive do not run under the default policy
+    fn test_unpacked_hooks_do_not_run() {
+        use std::os::unix::fs::PermissionsExt;
+        use crate::executable::Executable;
+        use crate::hooks::HookKind;
+
+        let root = env::temp_dir().join("mock_unpacked_hooks");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("archives")).unwrap();
+        let compiler = root.join("compiler");
+        fs::write(&compiler, "#!/bin/sh\nwhile [ \"$1\" != \"-o\" ]; do shift; done\nprintf '#!/bin/sh\\n' > \"$2\"\nchmod +x \"$2\"\n").unwrap();
+        fs::set_permissions(&compiler, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let original = root.join("original");
+        Repository::init(&original).unwrap();
+        fs::write(original.join("main.sac"), "").unwrap();
+        let package = Package::create(&original);
+        package.set_language(Some("mock".to_string()));
+        package.add_executable(Executable::create("main.sac"));
+        let marker = root.join("hook_ran");
+        package.set_hook(HookKind::PreBuild, Some(vec!["touch".to_string(), marker.display().to_string()]));
+        let archive = package.pack(root.join("archives"));
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_unpacked_hooks.json"));
+        registry.languages.insert("mock".to_string(), Language::create(compiler.to_str().unwrap(), vec![]));
+        let unpacked = registry.unpack(&archive, root.join("unpacked"));
+        assert_eq!(registry.get_provenance(&unpacked).unwrap().fetcher(), "archive");
+
+        assert!(registry.run(root.join("unpacked").join("main.sac"), &[] as &[&str]).unwrap().built());
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    /// A path stepping out of an allowed root through `..` is refused
+    fn test_allowed_roots_traversal() {
+        let root = env::temp_dir().join("mock_allowed_traversal");
+        let outside = env::temp_dir().join("mock_allowed_traversal_outside");
+        let _ = fs::remove_dir_all(&outside);
+        Repository::init(&outside).unwrap();
+
+        let mut registry = Registry::new(env::temp_dir().join("registry_allowed_traversal.json"));
+        registry.set_allowed_roots(vec![root.clone()]);
+        let traversal = root.join("..").join("mock_allowed_traversal_outside");
+        assert!(!registry.is_allowed(&traversal));
+        assert!(matches!(registry.try_add(Package::create(&traversal)), Err(crate::Error::OutsideAllowedRoots(_))));
+        assert!(registry.is_allowed(root.join("nested").join("..").join("json")));
+    }
+
+    #[test]
+    /// The lock file can be acquired again by the thread holding it, while other threads wait for it
+    fn test_file_lock_is_held_per_thread() {
+        let path = env::temp_dir().join("registry_file_lock_threads.json");
+        let outer = crate::shared::FileLock::acquire(&path).unwrap();
+        let inner = crate::shared::FileLock::acquire(&path).unwrap();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let waiting = path.clone();
+        let handle = std::thread::spawn(move || {
+            let lock = crate::shared::FileLock::acquire(&waiting).unwrap();
+            sender.send(()).unwrap();
+            drop(lock);
+        });
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+
+        drop(inner);
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+        drop(outer);
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        handle.join().unwrap();
+
+        let mut lock = path.into_os_string();
+        lock.push(".lock");
+        assert!(!Path::new(&lock).exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// Symbolic links to directories are not descended into, so a link back to the package does not loop
+    fn test_checksum_skips_symlinked_directories() {
+        let path = env::temp_dir().join("mock_share_symlink_loop");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("a.sac"), "module a;").unwrap();
+        let checksum = Package::checksum(&path);
+
+        std::os::unix::fs::symlink(&path, path.join("loop")).unwrap();
+        assert_eq!(Package::checksum(&path), checksum);
+    }
+
+    #[test]
+    /// Dev dependencies of dependencies are not checked and unresolved optional dependencies are warnings
+    fn test_check_dependency_kinds() {
+        let mut registry = Registry::initialize(env::temp_dir().join("registry_check_kinds.json"));
+        let app_path = env::temp_dir().join("mock_package_check_kinds_app");
+        let library_path = env::temp_dir().join("mock_package_check_kinds_library");
+        let _ = fs::remove_dir_all(&app_path);
+        let _ = fs::remove_dir_all(&library_path);
+        Repository::init(&app_path).unwrap();
+        let library_url = Url::parse("https://example.com/check_kinds_library").unwrap();
+        Repository::init(&library_path).unwrap().remote("origin", library_url.as_str()).unwrap();
+        fs::write(app_path.join("main.sac"), "").unwrap();
+        let app = Package::create(&app_path);
+        app.add_module(Module::create("main.sac", None));
+        app.add_dependency(Dependency::create(library_url));
+        let optional_url = Url::parse("https://example.com/check_kinds_optional").unwrap();
+        let mut optional = Dependency::create(optional_url.clone());
+        optional.set_kind(DependencyKind::Optional);
+        app.add_dependency(optional);
+        let library = Package::create(&library_path);
+        let mut tester = Dependency::create(Url::parse("https://example.com/check_kinds_tester").unwrap());
+        tester.set_kind(DependencyKind::Dev);
+        library.add_dependency(tester);
+        registry.add(app);
+        registry.add(library);
+
+        let report = registry.check(app_path.join("main.sac"));
+        assert!(report.errors().is_empty());
+        assert_eq!(report.warnings(), &[Diagnostic::UnresolvedDependency(optional_url)]);
     }
 }
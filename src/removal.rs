@@ -0,0 +1,177 @@
+use crate::package::Package;
+
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`RemovalPolicy`] determines what happens to the dependents of a [`Package`] that is removed from a [`Registry`]
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry`]: crate::registry::Registry
+pub enum RemovalPolicy {
+    /// Refuse to remove a [`Package`] other [`Package`]s depend on
+    ///
+    /// [`Package`]: crate::package::Package
+    Forbid,
+    /// Remove every [`Package`] that depends on the removed [`Package`], directly or indirectly
+    ///
+    /// [`Package`]: crate::package::Package
+    Cascade,
+    /// Remove the [`Dependency`] on the removed [`Package`] from every dependent
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    Detach,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`RemovalError`] explains why a [`Package`] could not be removed
+///
+/// [`Package`]: crate::package::Package
+pub enum RemovalError {
+    /// The [`Package`]s located at the given [`PathBuf`]s still depend on the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    HasDependents(Vec<PathBuf>),
+    /// No [`Package`] with the given identifier is registered
+    ///
+    /// [`Package`]: crate::package::Package
+    UnknownPackage(String),
+    /// The files of the [`Package`] at the given [`PathBuf`] would be deleted, but the given files have uncommitted changes
+    ///
+    /// [`Package`]: crate::package::Package
+    UncommittedChanges(PathBuf, Vec<PathBuf>),
+}
+
+impl Display for RemovalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemovalError::HasDependents(dependents) => {
+                let paths: Vec<String> = dependents.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "package is still depended on by: {}", paths.join(", "))
+            }
+            RemovalError::UnknownPackage(identifier) => write!(f, "no package `{}` is registered", identifier),
+            RemovalError::UncommittedChanges(package, files) => {
+                let paths: Vec<String> = files.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "{} has uncommitted changes: {}", package.display(), paths.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for RemovalError {}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`RemovalPlan`] lists the changes removing a [`Package`] with a [`RemovalPolicy`] makes, see [`Registry::plan_removal`]
+///
+/// [`Registry::plan_removal`]: crate::registry::Registry::plan_removal
+pub struct RemovalPlan {
+    pub(crate) packages: Vec<Package>,
+    pub(crate) detached: Vec<(PathBuf, Url)>,
+}
+
+impl RemovalPlan {
+    /// Returns every [`Package`] that is removed from the [`Registry`]
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    pub fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+
+    /// Returns the [`Dependency`]s that are removed, paired with the `local_location` of the dependent [`Package`]
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    pub fn detached(&self) -> &[(PathBuf, Url)] {
+        &self.detached
+    }
+}
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// [`UninstallOptions`] control what [`Registry::uninstall_package`] does besides removing the [`Package`] from the [`Registry`]
+/// By default dependents are protected with [`RemovalPolicy::Forbid`] and no files are deleted
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::uninstall_package`]: crate::registry::Registry::uninstall_package
+pub struct UninstallOptions {
+    pub(crate) policy: RemovalPolicy,
+    pub(crate) delete_files: bool,
+    pub(crate) force: bool,
+}
+
+impl Default for UninstallOptions {
+    fn default() -> Self {
+        UninstallOptions {
+            policy: RemovalPolicy::Forbid,
+            delete_files: false,
+            force: false,
+        }
+    }
+}
+
+impl UninstallOptions {
+    /// Sets the [`RemovalPolicy`] that determines what happens to the dependents of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_policy(&mut self, policy: RemovalPolicy) {
+        self.policy = policy;
+    }
+
+    /// Sets whether the directories of the removed [`Package`]s are deleted
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn set_delete_files(&mut self, delete_files: bool) {
+        self.delete_files = delete_files;
+    }
+
+    /// Sets whether directories are deleted even when they contain uncommitted changes
+    pub fn set_force(&mut self, force: bool) {
+        self.force = force;
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// An [`UninstallReport`] lists what [`Registry::uninstall_package`] removed
+///
+/// [`Registry::uninstall_package`]: crate::registry::Registry::uninstall_package
+pub struct UninstallReport {
+    pub(crate) packages: Vec<PathBuf>,
+    pub(crate) detached: Vec<(PathBuf, Url)>,
+    pub(crate) deleted: Vec<PathBuf>,
+}
+
+impl UninstallReport {
+    /// Returns the `local_location`s of the [`Package`]s removed from the [`Registry`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Registry`]: crate::registry::Registry
+    pub fn packages(&self) -> &[PathBuf] {
+        &self.packages
+    }
+
+    /// Returns the [`Dependency`]s that were removed, paired with the `local_location` of the dependent [`Package`]
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    pub fn detached(&self) -> &[(PathBuf, Url)] {
+        &self.detached
+    }
+
+    /// Returns the directories that were deleted from disk
+    pub fn deleted(&self) -> &[PathBuf] {
+        &self.deleted
+    }
+}
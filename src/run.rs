@@ -0,0 +1,129 @@
+use crate::dependency::DependencyError;
+use crate::hooks::HookError;
+
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+/// The environment variable the dynamic loader of the current target searches libraries in
+#[cfg(target_os = "macos")]
+pub(crate) const SEARCH_PATH_VARIABLE: &str = "DYLD_LIBRARY_PATH";
+/// The environment variable the dynamic loader of the current target searches libraries in
+#[cfg(target_os = "windows")]
+pub(crate) const SEARCH_PATH_VARIABLE: &str = "PATH";
+/// The environment variable the dynamic loader of the current target searches libraries in
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) const SEARCH_PATH_VARIABLE: &str = "LD_LIBRARY_PATH";
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`RunOutput`] holds the result of running an [`Executable`] with [`Registry::run`]
+///
+/// [`Executable`]: crate::executable::Executable
+/// [`Registry::run`]: crate::registry::Registry::run
+pub struct RunOutput {
+    pub(crate) status: ExitStatus,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) built: bool,
+    pub(crate) search_path: Vec<PathBuf>,
+}
+
+impl RunOutput {
+    /// Returns the [`ExitStatus`] the process exited with
+    pub fn status(&self) -> ExitStatus {
+        self.status
+    }
+
+    /// Checks whether the process exited successfully
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+
+    /// Returns what the process wrote to its standard output
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    /// Returns what the process wrote to its standard error
+    pub fn stderr(&self) -> &str {
+        &self.stderr
+    }
+
+    /// Checks whether the [`Executable`] was built before it was run
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    pub fn built(&self) -> bool {
+        self.built
+    }
+
+    /// Returns the directories that were prepended to the runtime search path of the process
+    pub fn search_path(&self) -> &[PathBuf] {
+        &self.search_path
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`RunError`] explains why [`Registry::run`] could not run an [`Executable`]
+///
+/// [`Executable`]: crate::executable::Executable
+/// [`Registry::run`]: crate::registry::Registry::run
+pub enum RunError {
+    /// No [`Executable`] is registered at the given source [`PathBuf`]
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    UnknownExecutable(PathBuf),
+    /// No [`Module`] or [`Executable`] is registered at the given source [`PathBuf`]
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Module`]: crate::module::Module
+    UnknownTarget(PathBuf),
+    /// The [`Executable`] at the given source [`PathBuf`] needs to be built, but its [`Language`] is unknown
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Language`]: crate::language::Language
+    UnknownLanguage(PathBuf),
+    /// Building the [`Executable`] at the given source [`PathBuf`] failed with the given compiler output
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    BuildFailed(PathBuf, String),
+    /// The given command could not be started for the given reason
+    Spawn(String, String),
+    /// A [`HookKind::PreBuild`] or [`HookKind::PostBuild`] hook of the [`Package`] failed
+    ///
+    /// [`HookKind::PreBuild`]: crate::hooks::HookKind::PreBuild
+    /// [`HookKind::PostBuild`]: crate::hooks::HookKind::PostBuild
+    /// [`Package`]: crate::package::Package
+    Hook(HookError),
+    /// The output directories the [`Executable`] depends on could not be resolved
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    Dependency(DependencyError),
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::UnknownExecutable(source) => write!(f, "no executable registered @ {}", source.display()),
+            RunError::UnknownTarget(source) => write!(f, "no module or executable registered @ {}", source.display()),
+            RunError::UnknownLanguage(source) => write!(f, "no language known for {}", source.display()),
+            RunError::BuildFailed(source, output) => write!(f, "building {} failed: {}", source.display(), output),
+            RunError::Spawn(command, reason) => write!(f, "could not start `{}`: {}", command, reason),
+            RunError::Hook(error) => error.fmt(f),
+            RunError::Dependency(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RunError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RunError::Hook(error) => Some(error),
+            RunError::Dependency(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::metadata::PackageMetadata;
+use crate::module::Module;
+use crate::package::Package;
+
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(PartialOrd, Ord)]
+/// How a search query matched an identifier, ordered from weakest to strongest
+pub enum MatchKind {
+    /// The characters of the query appear in order in the identifier
+    Fuzzy,
+    /// The identifier contains the query, only produced by [`Registry::search`]
+    ///
+    /// [`Registry::search`]: crate::registry::Registry::search
+    Substring,
+    /// The identifier starts with the query
+    Prefix,
+    /// The identifier equals the query
+    Exact,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+/// A [`SearchResult`] is a [`Module`] matching a search query, together with the [`Package`] providing it
+pub struct SearchResult<'a> {
+    pub(crate) package: &'a Package,
+    pub(crate) module: Module,
+    pub(crate) metadata: PackageMetadata,
+    pub(crate) kind: MatchKind,
+    pub(crate) score: u32,
+}
+
+impl<'a> SearchResult<'a> {
+    /// Returns the [`Package`] providing the matched [`Module`]
+    pub fn package(&self) -> &'a Package {
+        self.package
+    }
+
+    /// Returns the matched [`Module`]
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Returns the [`PackageMetadata`] of the [`Package`] providing the matched [`Module`]
+    pub fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    /// Returns how the query matched the [`Module`]'s identifier
+    pub fn kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    /// Returns the relevance of the match, higher is more relevant
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+}
+
+/// Matches `query` against `candidate`, returning how it matched and a relevance score
+/// Exact matches score highest, followed by prefix matches and fuzzy matches; closer matches score higher within each kind
+pub(crate) fn rank(query: &str, candidate: &str) -> Option<(MatchKind, u32)> {
+    if candidate == query {
+        return Some((MatchKind::Exact, 3000));
+    }
+    if candidate.starts_with(query) {
+        let extra = (candidate.len() - query.len()) as u32;
+        return Some((MatchKind::Prefix, 2000 - extra.min(999)));
+    }
+
+    let mut gaps = 0;
+    let mut characters = candidate.chars();
+    for wanted in query.chars() {
+        loop {
+            match characters.next() {
+                Some(c) if c == wanted => break,
+                Some(_) => gaps += 1,
+                None => return None,
+            }
+        }
+    }
+    Some((MatchKind::Fuzzy, 1000 - gaps.min(999)))
+}
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+#[derive(PartialOrd, Ord)]
+/// The text a [`SearchMatch`] was found in, ordered from most to least relevant
+pub enum MatchField {
+    /// The identifier of a [`Module`]
+    ModuleIdentifier,
+    /// The identifier of a [`Package`]
+    PackageIdentifier,
+    /// The name in the [`PackageMetadata`]
+    Name,
+    /// One of the keywords in the [`PackageMetadata`]
+    Keyword,
+    /// One of the authors in the [`PackageMetadata`]
+    Author,
+    /// The description in the [`PackageMetadata`]
+    Description,
+    /// The description of a [`Module`]
+    ModuleDescription,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// [`SearchOptions`] control what [`Registry::search`] looks at and which matches it returns
+/// By default [`Module`] identifiers, [`Package`] identifiers and [`PackageMetadata`] are searched case insensitively,
+/// every [`MatchKind`] is accepted and the first 20 results are returned
+///
+/// [`Registry::search`]: crate::registry::Registry::search
+pub struct SearchOptions {
+    pub(crate) modules: bool,
+    pub(crate) packages: bool,
+    pub(crate) metadata: bool,
+    pub(crate) case_sensitive: bool,
+    pub(crate) minimum_kind: MatchKind,
+    pub(crate) offset: usize,
+    pub(crate) limit: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            modules: true,
+            packages: true,
+            metadata: true,
+            case_sensitive: false,
+            minimum_kind: MatchKind::Fuzzy,
+            offset: 0,
+            limit: 20,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Sets whether [`Module`] identifiers and descriptions are searched
+    pub fn set_modules(&mut self, modules: bool) {
+        self.modules = modules;
+    }
+
+    /// Sets whether [`Package`] identifiers are searched
+    pub fn set_packages(&mut self, packages: bool) {
+        self.packages = packages;
+    }
+
+    /// Sets whether the name, keywords, authors and description in the [`PackageMetadata`] are searched
+    pub fn set_metadata(&mut self, metadata: bool) {
+        self.metadata = metadata;
+    }
+
+    /// Sets whether letters only match when their case is equal
+    pub fn set_case_sensitive(&mut self, case_sensitive: bool) {
+        self.case_sensitive = case_sensitive;
+    }
+
+    /// Sets the weakest [`MatchKind`] that is returned, e.g. [`MatchKind::Substring`] to leave out fuzzy matches
+    pub fn set_minimum_kind(&mut self, kind: MatchKind) {
+        self.minimum_kind = kind;
+    }
+
+    /// Sets the number of ranked results to skip and the maximum number of results to return
+    pub fn set_page(&mut self, offset: usize, limit: usize) {
+        self.offset = offset;
+        self.limit = limit;
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`SearchMatch`] is a piece of text of a registered [`Package`] that matched the query of [`Registry::search`]
+///
+/// [`Registry::search`]: crate::registry::Registry::search
+pub struct SearchMatch {
+    pub(crate) package: PathBuf,
+    pub(crate) module: Option<Module>,
+    pub(crate) field: MatchField,
+    pub(crate) text: String,
+    pub(crate) positions: Vec<usize>,
+    pub(crate) kind: MatchKind,
+    pub(crate) score: u32,
+}
+
+impl SearchMatch {
+    /// Returns the `local_location` of the [`Package`] the match was found in
+    pub fn package(&self) -> &Path {
+        &self.package
+    }
+
+    /// Returns the [`Module`] whose identifier matched, if the match was found in a [`Module`] identifier
+    pub fn module(&self) -> Option<&Module> {
+        self.module.as_ref()
+    }
+
+    /// Returns the kind of text the match was found in
+    pub fn field(&self) -> MatchField {
+        self.field
+    }
+
+    /// Returns the text the match was found in
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the indices of the characters of the text that matched the query, e.g. to highlight them
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    /// Returns how the query matched the text
+    pub fn kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    /// Returns the relevance of the match, higher is more relevant
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+}
+
+/// Matches `query` against `candidate` like [`rank`], but also recognises substrings and reports the matched characters
+/// Substring matches score higher the earlier they start
+pub(crate) fn find(query: &str, candidate: &str, case_sensitive: bool) -> Option<(MatchKind, u32, Vec<usize>)> {
+    let fold = |text: &str| -> Vec<char> {
+        match case_sensitive {
+            true => text.chars().collect(),
+            false => text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect(),
+        }
+    };
+    let query = fold(query);
+    let candidate = fold(candidate);
+    if query.is_empty() {
+        return None;
+    }
+    if candidate == query {
+        return Some((MatchKind::Exact, 3000, (0..query.len()).collect()));
+    }
+    if candidate.starts_with(&query) {
+        let extra = (candidate.len() - query.len()) as u32;
+        return Some((MatchKind::Prefix, 2000 - extra.min(999), (0..query.len()).collect()));
+    }
+    if let Some(start) = candidate.windows(query.len()).position(|w| w == query.as_slice()) {
+        return Some((MatchKind::Substring, 1500 - (start as u32).min(499), (start..start + query.len()).collect()));
+    }
+
+    let mut positions = vec![];
+    let mut characters = candidate.iter().enumerate();
+    for wanted in &query {
+        positions.push(characters.find(|(_, c)| *c == wanted)?.0);
+    }
+    let gaps = (positions.last().unwrap() + 1 - positions.len()) as u32;
+    Some((MatchKind::Fuzzy, 1000 - gaps.min(999), positions))
+}
@@ -0,0 +1,362 @@
+use crate::error::RegistryError;
+#[cfg(feature = "tokio")]
+use crate::error::Error;
+#[cfg(feature = "tokio")]
+use crate::fetcher::Fetcher;
+#[cfg(feature = "tokio")]
+use crate::package::Package;
+use crate::registry::Registry;
+use crate::utils::sha256_hex;
+#[cfg(feature = "tokio")]
+use crate::utils::spawn_blocking;
+#[cfg(feature = "tokio")]
+use crate::version::VersionReq;
+
+use std::collections::HashMap;
+use std::fs::{metadata, read, read_to_string, remove_file, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant, SystemTime};
+#[cfg(feature = "tokio")]
+use url::Url;
+
+/// How long [`SharedRegistry::write`] waits for another process to release the lock file
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How old a lock file may get before it is considered left behind by a process that never released it
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// The state of the registry file as last seen by a [`SharedRegistry`]
+struct FileState {
+    modified: Option<SystemTime>,
+    checksum: Option<String>,
+}
+
+impl FileState {
+    fn of(path: &Path) -> Self {
+        FileState {
+            modified: metadata(path).and_then(|m| m.modified()).ok(),
+            checksum: read(path).ok().map(sha256_hex),
+        }
+    }
+}
+
+/// Returns the lock files held by this process, with the thread holding each one and how often it acquired it
+fn held_locks() -> MutexGuard<'static, HashMap<PathBuf, (ThreadId, usize)>> {
+    static HELD: OnceLock<Mutex<HashMap<PathBuf, (ThreadId, usize)>>> = OnceLock::new();
+    HELD.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Holds the lock file next to the registry file, removing it when dropped
+/// The lock file records the id of the process holding it, so a lock left behind by a process that exited without
+/// releasing it is broken instead of blocking every later writer, see [`FileLock::is_stale`]
+/// The thread holding the lock can acquire it again, e.g. to save the [`Registry`] within [`SharedRegistry::write`],
+/// the lock file is then only removed when the outermost [`FileLock`] is dropped
+/// Other threads of the same process wait for the lock just like other processes do
+pub(crate) struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    pub(crate) fn acquire(registry: &Path) -> Result<Self, RegistryError> {
+        let mut path = registry.as_os_str().to_os_string();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+        let current = thread::current().id();
+        let started = Instant::now();
+        loop {
+            {
+                let mut held = held_locks();
+                match held.get_mut(&path) {
+                    Some((owner, count)) if *owner == current => {
+                        *count += 1;
+                        return Ok(FileLock { path });
+                    }
+                    Some(_) => {}
+                    None => match OpenOptions::new().write(true).create_new(true).open(&path) {
+                        Ok(mut file) => {
+                            let _ = write!(file, "{}", process::id());
+                            held.insert(path.clone(), (current, 1));
+                            return Ok(FileLock { path });
+                        }
+                        Err(e) if e.kind() != ErrorKind::AlreadyExists => {
+                            return Err(RegistryError::Unwritable(registry.to_path_buf(), e.to_string()))
+                        }
+                        Err(_) if FileLock::is_stale(&path) => {
+                            let _ = remove_file(&path);
+                            continue;
+                        }
+                        Err(_) => {}
+                    },
+                }
+            }
+            match started.elapsed() < LOCK_TIMEOUT {
+                true => thread::sleep(Duration::from_millis(10)),
+                false => return Err(RegistryError::Locked(path)),
+            }
+        }
+    }
+
+    /// Returns the id of the process holding the lock file, `None` while it is still being written
+    fn owner(path: &Path) -> Option<u32> {
+        read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    /// Checks whether the lock file was left behind, either because the process holding it no longer runs,
+    /// because it names this process while no thread of it holds the lock,
+    /// or because it is older than [`STALE_LOCK_AGE`]
+    /// Must be called while holding [`held_locks`]
+    fn is_stale(path: &Path) -> bool {
+        let expired = metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|m| m.elapsed().ok())
+            .is_some_and(|age| age > STALE_LOCK_AGE);
+        expired || FileLock::owner(path).is_some_and(|pid| pid == process::id() || !is_running(pid))
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let mut held = held_locks();
+        if let Some((_, count)) = held.get_mut(&self.path) {
+            *count -= 1;
+            if *count == 0 {
+                held.remove(&self.path);
+                let _ = remove_file(&self.path);
+            }
+        }
+    }
+}
+
+/// Checks whether the process with the given id still runs
+/// Where this cannot be checked through `/proc` the process is assumed to run, the lock then only expires with age
+fn is_running(pid: u32) -> bool {
+    match Path::new("/proc/self").exists() {
+        true => Path::new("/proc").join(pid.to_string()).exists(),
+        false => true,
+    }
+}
+
+#[derive(Clone)]
+/// A [`SharedRegistry`] is a handle to a [`Registry`] that can be shared between threads
+/// Any number of readers can use the [`Registry`] at the same time, while writers are serialized,
+/// both within the process and, through a lock file next to the registry file, with other processes
+/// Cloning a [`SharedRegistry`] returns another handle to the same [`Registry`]
+///
+/// # Examples
+/// ```
+/// # use std::{env, fs, thread};
+/// # use git2::Repository;
+/// # use knapsac_lib::package::Package;
+/// # use knapsac_lib::registry::Registry;
+/// # use knapsac_lib::shared::SharedRegistry;
+///
+/// let path = env::temp_dir().join("registry_shared.json");
+/// let shared = SharedRegistry::from(Registry::initialize(&path));
+///
+/// let package_path = env::temp_dir().join("mock_package_shared");
+/// # let _ = fs::remove_dir_all(&package_path);
+/// Repository::init(&package_path);
+/// let package = Package::create(&package_path);
+///
+/// let writer = shared.clone();
+/// let added = package.clone();
+/// thread::spawn(move || writer.write(|r| r.add(added))).join().unwrap().unwrap();
+/// assert!(shared.read(|r| r.contains(&package)));
+/// assert!(Registry::load(&path).contains(&package));
+/// ```
+pub struct SharedRegistry {
+    registry: Arc<RwLock<Registry>>,
+    seen: Arc<Mutex<FileState>>,
+}
+
+impl SharedRegistry {
+    /// Loads the [`Registry`] at the given [`Path`] and shares it, see [`Registry::try_load`]
+    ///
+    /// # Arguments
+    /// * `path` - A [`Path`] pointing to a saved [`Registry`]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, RegistryError> {
+        Ok(SharedRegistry::from(Registry::try_load(path)?))
+    }
+
+    /// Returns the [`Path`] of the file the shared [`Registry`] is saved to
+    pub fn location(&self) -> PathBuf {
+        self.read(|r| r.location.clone())
+    }
+
+    /// Runs the given closure with shared access to the [`Registry`] and returns its result
+    /// Other readers are not blocked, writers wait until the closure returns
+    ///
+    /// # Arguments
+    /// * `f` - The closure reading the [`Registry`]
+    pub fn read<R, F: FnOnce(&Registry) -> R>(&self, f: F) -> R {
+        let registry = self.registry.read().unwrap_or_else(|e| e.into_inner());
+        f(&registry)
+    }
+
+    /// Runs the given closure with exclusive access to the [`Registry`] and returns its result
+    /// The lock file is held while the closure runs, changes made by other processes are loaded first,
+    /// see [`SharedRegistry::refresh`], and pending changes are written to disk afterwards
+    ///
+    /// # Arguments
+    /// * `f` - The closure changing the [`Registry`]
+    ///
+    /// # Errors
+    /// Returns [`RegistryError::Locked`] when another running process holds the lock file for too long,
+    /// or the error of reloading the [`Registry`] when another process left it unreadable
+    /// A lock file left behind by a process that no longer runs is removed first
+    pub fn write<R, F: FnOnce(&mut Registry) -> R>(&self, f: F) -> Result<R, RegistryError> {
+        let mut registry = self.registry.write().unwrap_or_else(|e| e.into_inner());
+        let _lock = FileLock::acquire(&registry.location)?;
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        SharedRegistry::reload_if_changed(&mut registry, &mut seen)?;
+        let result = f(&mut registry);
+        registry.flush();
+        *seen = FileState::of(&registry.location);
+        Ok(result)
+    }
+
+    /// Reloads the [`Registry`] from disk when another process changed the file since it was last read or written
+    /// Changes are detected through the modification time of the file and, when that differs, its checksum
+    /// The [`ProgressHandler`], [`DocExtractor`], [`AuditLog`] and [`AutoSave`] mode of the [`Registry`] are kept,
+    /// changes that were not written to disk yet are discarded
+    /// Returns whether the [`Registry`] was reloaded
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use git2::Repository;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::shared::SharedRegistry;
+    ///
+    /// let path = env::temp_dir().join("registry_shared_refresh.json");
+    /// let shared = SharedRegistry::from(Registry::initialize(&path));
+    /// assert_eq!(shared.refresh(), Ok(false));
+    ///
+    /// let package_path = env::temp_dir().join("mock_package_shared_refresh");
+    /// # let _ = fs::remove_dir_all(&package_path);
+    /// Repository::init(&package_path);
+    /// let package = Package::create(&package_path);
+    /// let mut other = Registry::load(&path);
+    /// other.add(package.clone());
+    /// drop(other);
+    ///
+    /// assert_eq!(shared.refresh(), Ok(true));
+    /// assert!(shared.read(|r| r.contains(&package)));
+    /// ```
+    ///
+    /// [`AuditLog`]: crate::audit::AuditLog
+    /// [`AutoSave`]: crate::registry::AutoSave
+    /// [`DocExtractor`]: crate::docs::DocExtractor
+    /// [`ProgressHandler`]: crate::progress::ProgressHandler
+    pub fn refresh(&self) -> Result<bool, RegistryError> {
+        let mut registry = self.registry.write().unwrap_or_else(|e| e.into_inner());
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        SharedRegistry::reload_if_changed(&mut registry, &mut seen)
+    }
+
+    fn reload_if_changed(registry: &mut Registry, seen: &mut FileState) -> Result<bool, RegistryError> {
+        let modified = metadata(&registry.location).and_then(|m| m.modified()).ok();
+        if modified == seen.modified {
+            return Ok(false);
+        }
+        let current = FileState::of(&registry.location);
+        if current.checksum == seen.checksum {
+            seen.modified = current.modified;
+            return Ok(false);
+        }
+        let mut loaded = Registry::try_load(&registry.location)?;
+        registry.dirty = false;
+        loaded.audit_log = registry.audit_log.take();
+        loaded.progress = mem::take(&mut registry.progress);
+        loaded.doc_extractor = mem::take(&mut registry.doc_extractor);
+        loaded.autosave = registry.autosave;
+        *registry = loaded;
+        *seen = current;
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl SharedRegistry {
+    /// Like [`Registry::try_install`], running on the blocking thread pool so the async runtime is not blocked
+    /// The [`Registry`] is locked for writing until the installation finishes, see [`SharedRegistry::write`]
+    ///
+    /// # Arguments
+    /// * `fetcher` - The [`Fetcher`] used to obtain the [`Package`]
+    /// * `source` - An [`Url`] pointing to the contents of the [`Package`]
+    /// * `path` - A [`Path`] pointing to where packages need to be downloaded to
+    /// * `checksum` - The expected checksum of the contents, see [`Package::checksum`]
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::{env, fs};
+    /// # use url::Url;
+    /// # use knapsac_lib::fetcher::LocalFetcher;
+    /// # use knapsac_lib::package::Package;
+    /// # use knapsac_lib::registry::Registry;
+    /// # use knapsac_lib::shared::SharedRegistry;
+    ///
+    /// let shared = SharedRegistry::from(Registry::initialize(env::temp_dir().join("registry_install_async.json")));
+    /// let share = env::temp_dir().join("mock_share_install_async");
+    /// fs::create_dir_all(&share).unwrap();
+    /// fs::write(share.join("a.sac"), "module a;").unwrap();
+    /// let checksum = Package::checksum(&share);
+    ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// let source = Url::from_directory_path(&share).unwrap();
+    /// let package = runtime.block_on(shared.install(LocalFetcher, source, env::temp_dir(), Some(checksum))).unwrap();
+    /// assert!(shared.read(|r| r.contains(&package)));
+    /// ```
+    ///
+    /// [`Fetcher`]: crate::fetcher::Fetcher
+    /// [`Package`]: crate::package::Package
+    /// [`Package::checksum`]: crate::package::Package::checksum
+    pub async fn install<F: Fetcher + Send + 'static>(&self, fetcher: F, source: Url, path: PathBuf, checksum: Option<String>) -> Result<Package, Error> {
+        let shared = self.clone();
+        spawn_blocking(move || Ok(shared.write(|r| r.try_install(&fetcher, source, path, checksum.as_deref()))??)).await
+    }
+
+    /// Like [`Registry::install_by_name`], running on the blocking thread pool so the async runtime is not blocked
+    ///
+    /// # Arguments
+    /// * `fetcher` - The [`Fetcher`] used to obtain the [`Package`]s
+    /// * `identifier` - The identifier the [`Package`] is published under
+    /// * `requirement` - The [`VersionReq`] to satisfy, or `None` to install the latest [`Version`]
+    /// * `path` - A [`Path`] pointing to where packages need to be downloaded to
+    ///
+    /// [`Fetcher`]: crate::fetcher::Fetcher
+    /// [`Package`]: crate::package::Package
+    /// [`Version`]: crate::version::Version
+    pub async fn install_by_name<F: Fetcher + Send + 'static>(&self, fetcher: F, identifier: String, requirement: Option<VersionReq>, path: PathBuf) -> Result<Package, Error> {
+        let shared = self.clone();
+        spawn_blocking(move || Ok(shared.write(|r| r.install_by_name(&fetcher, &identifier, requirement, path))??)).await
+    }
+
+    /// Like [`Registry::update_index`], running on the blocking thread pool so the async runtime is not blocked
+    pub async fn update_index(&self) -> Result<usize, Error> {
+        let shared = self.clone();
+        spawn_blocking(move || Ok(shared.write(|r| r.update_index())??)).await
+    }
+}
+
+impl From<Registry> for SharedRegistry {
+    fn from(mut registry: Registry) -> Self {
+        registry.flush();
+        let seen = FileState::of(&registry.location);
+        SharedRegistry {
+            registry: Arc::new(RwLock::new(registry)),
+            seen: Arc::new(Mutex::new(seen)),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// A [`Signer`] signs the manifest of a [`Package`] when it is published
+/// Signatures are ed25519 signatures, so they can be checked against a [`TrustStore`]
+/// Implement it to sign with keys kept elsewhere, e.g. in a hardware token or by invoking minisign
+///
+/// [`Package`]: crate::package::Package
+pub trait Signer {
+    /// The identifier of the key, under which its public key is added to a [`TrustStore`]
+    fn key_id(&self) -> &str;
+
+    /// Signs the given message, returning the 64 byte ed25519 signature
+    ///
+    /// # Arguments
+    /// * `message` - The bytes to sign
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String>;
+}
+
+/// A [`Signer`] holding an ed25519 secret key in memory
+pub struct Ed25519Signer {
+    key_id: String,
+    key: SigningKey,
+}
+
+impl Ed25519Signer {
+    /// Creates a new [`Ed25519Signer`] from a 32 byte secret key
+    ///
+    /// # Arguments
+    /// * `key_id` - The identifier of the key
+    /// * `secret_key` - The 32 byte ed25519 secret key
+    ///
+    /// # Examples
+    /// ```
+    /// # use knapsac_lib::signing::{Ed25519Signer, TrustStore};
+    ///
+    /// let signer = Ed25519Signer::create("release", [7; 32]);
+    /// let mut trust_store = TrustStore::default();
+    /// trust_store.trust("release", &signer.public_key());
+    /// assert!(trust_store.is_trusted("release"));
+    /// ```
+    pub fn create<S: Into<String>>(key_id: S, secret_key: [u8; 32]) -> Self {
+        Ed25519Signer {
+            key_id: key_id.into(),
+            key: SigningKey::from_bytes(&secret_key),
+        }
+    }
+
+    /// Returns the public key belonging to the secret key, hex encoded
+    pub fn public_key(&self) -> String {
+        to_hex(self.key.verifying_key().as_bytes())
+    }
+}
+
+impl Signer for Ed25519Signer {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String> {
+        Ok(self.key.sign(message).to_bytes().to_vec())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`PackageSignature`] is stored next to the manifest of a signed [`Package`], as `<manifest>.sig`
+///
+/// [`Package`]: crate::package::Package
+pub struct PackageSignature {
+    pub(crate) key_id: String,
+    pub(crate) signature: String,
+}
+
+impl PackageSignature {
+    /// Returns the identifier of the key the manifest was signed with
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the hex encoded ed25519 signature over the manifest
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`SignatureError`] explains why the signature of a [`Package`] was rejected
+///
+/// [`Package`]: crate::package::Package
+pub enum SignatureError {
+    /// The [`Package`] is not signed
+    ///
+    /// [`Package`]: crate::package::Package
+    Unsigned,
+    /// The [`Package`] is signed with the key with the given identifier, which is not in the [`TrustStore`]
+    ///
+    /// [`Package`]: crate::package::Package
+    UntrustedKey(String),
+    /// The signature does not match the manifest, it was tampered with or signed with another key
+    Invalid,
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureError::Unsigned => write!(f, "package is not signed"),
+            SignatureError::UntrustedKey(key_id) => write!(f, "package is signed with untrusted key `{}`", key_id),
+            SignatureError::Invalid => write!(f, "signature does not match the manifest"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`TrustStore`] holds the hex encoded ed25519 public keys [`Package`]s must be signed with, by key identifier
+///
+/// [`Package`]: crate::package::Package
+pub struct TrustStore {
+    pub(crate) keys: BTreeMap<String, String>,
+}
+
+impl TrustStore {
+    /// Trusts the given public key under the given key identifier
+    ///
+    /// # Arguments
+    /// * `key_id` - The identifier of the key
+    /// * `public_key` - The hex encoded 32 byte ed25519 public key
+    pub fn trust<S: Into<String>>(&mut self, key_id: S, public_key: &str) {
+        self.keys.insert(key_id.into(), public_key.to_string());
+    }
+
+    /// Stops trusting the key with the given identifier
+    pub fn revoke(&mut self, key_id: &str) {
+        self.keys.remove(key_id);
+    }
+
+    /// Checks whether a key with the given identifier is trusted
+    pub fn is_trusted(&self, key_id: &str) -> bool {
+        self.keys.contains_key(key_id)
+    }
+
+    /// Verifies the [`PackageSignature`] over the given manifest against the trusted keys
+    ///
+    /// # Arguments
+    /// * `manifest` - The contents of the manifest
+    /// * `signature` - The [`PackageSignature`] stored next to the manifest, `None` when there is none
+    pub fn verify(&self, manifest: &[u8], signature: Option<&PackageSignature>) -> Result<(), SignatureError> {
+        let signature = signature.ok_or(SignatureError::Unsigned)?;
+        let public_key = self.keys
+            .get(&signature.key_id)
+            .ok_or_else(|| SignatureError::UntrustedKey(signature.key_id.clone()))?;
+        let key = from_hex(public_key)
+            .and_then(|k| <[u8; 32]>::try_from(k).ok())
+            .and_then(|k| VerifyingKey::from_bytes(&k).ok())
+            .ok_or(SignatureError::Invalid)?;
+        let bytes = from_hex(&signature.signature)
+            .and_then(|s| <[u8; 64]>::try_from(s).ok())
+            .ok_or(SignatureError::Invalid)?;
+        key.verify(manifest, &Signature::from_bytes(&bytes)).map_err(|_| SignatureError::Invalid)
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
@@ -0,0 +1,277 @@
+use crate::dependency::DependencyKind;
+use crate::metadata::PackageMetadata;
+use crate::package_id::PackageId;
+use crate::version::{Version, VersionReq};
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// The kind of a registered file described by an [`ItemView`]
+pub enum ItemKind {
+    /// A [`Module`] other [`Package`]s can import
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    Module,
+    /// An [`Executable`] that can be run
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    Executable,
+    /// A [`TestModule`] testing a [`Module`] or its [`Package`]
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    /// [`TestModule`]: crate::testing::TestModule
+    Test,
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// An [`ItemView`] is an owned description of a registered [`Module`], [`Executable`] or [`TestModule`]
+///
+/// [`Executable`]: crate::executable::Executable
+/// [`Module`]: crate::module::Module
+/// [`TestModule`]: crate::testing::TestModule
+pub struct ItemView {
+    pub(crate) kind: ItemKind,
+    pub(crate) source: PathBuf,
+    pub(crate) name: String,
+    pub(crate) language: Option<String>,
+    pub(crate) output: Option<PathBuf>,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+}
+
+impl ItemView {
+    /// Returns what kind of file the [`ItemView`] describes
+    pub fn kind(&self) -> ItemKind {
+        self.kind
+    }
+
+    /// Returns the absolute [`Path`] of the file
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Returns the identifier of a [`Module`] or the binary name of an [`Executable`] or [`TestModule`]
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Module`]: crate::module::Module
+    /// [`TestModule`]: crate::testing::TestModule
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name of the [`Language`] the file is written in, if it differs from its [`Package`]'s
+    ///
+    /// [`Language`]: crate::language::Language
+    /// [`Package`]: crate::package::Package
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Returns the absolute directory a [`Module`] is compiled to or the absolute [`Path`] of the binary of an [`Executable`], if known
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Module`]: crate::module::Module
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// Returns the description of a [`Module`], if set
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`DependencyView`] is an owned description of a declared [`Dependency`]
+///
+/// [`Dependency`]: crate::dependency::Dependency
+pub struct DependencyView {
+    pub(crate) git_url: Url,
+    pub(crate) identifier: Option<PackageId>,
+    pub(crate) requirement: Option<VersionReq>,
+    #[serde(default)]
+    pub(crate) alias: Option<String>,
+    #[serde(default)]
+    pub(crate) kind: DependencyKind,
+    pub(crate) resolved: Option<PathBuf>,
+}
+
+impl DependencyView {
+    /// Returns the [`Url`] pointing to the git repository of the [`Package`] depended on
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn git_url(&self) -> &Url {
+        &self.git_url
+    }
+
+    /// Returns the declared identifier of the [`Package`] depended on, if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn identifier(&self) -> Option<&PackageId> {
+        self.identifier.as_ref()
+    }
+
+    /// Returns the declared [`VersionReq`], if any
+    pub fn requirement(&self) -> Option<&VersionReq> {
+        self.requirement.as_ref()
+    }
+
+    /// Returns the local name the [`Package`] depended on is referred to by, if aliased
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn alias(&self) -> Option<&str> {
+        self.alias.as_deref()
+    }
+
+    /// Returns the [`DependencyKind`] of the [`Dependency`]
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    pub fn kind(&self) -> DependencyKind {
+        self.kind
+    }
+
+    /// Returns the `local_location` of the registered [`Package`] the [`Dependency`] resolves to, if any
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    pub fn resolved(&self) -> Option<&Path> {
+        self.resolved.as_deref()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`PackageView`] is an owned description of a registered [`Package`] and everything it declares
+///
+/// [`Package`]: crate::package::Package
+pub struct PackageView {
+    pub(crate) identifier: String,
+    pub(crate) local_location: PathBuf,
+    pub(crate) remote_location: Option<Url>,
+    pub(crate) version: Option<Version>,
+    pub(crate) language: Option<String>,
+    pub(crate) metadata: PackageMetadata,
+    pub(crate) items: Vec<ItemView>,
+    pub(crate) dependencies: Vec<DependencyView>,
+    pub(crate) strays: Vec<PathBuf>,
+}
+
+impl PackageView {
+    /// Returns the identifier of the [`Package`]: its name, or else the last segment of its location
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Returns the absolute [`Path`] of the [`Package`]'s directory
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn local_location(&self) -> &Path {
+        &self.local_location
+    }
+
+    /// Returns the [`Url`] the [`Package`] was obtained from, if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn remote_location(&self) -> Option<&Url> {
+        self.remote_location.as_ref()
+    }
+
+    /// Returns the [`Version`] of the [`Package`], if it has one
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// Returns the name of the [`Language`] of the [`Package`], if set
+    ///
+    /// [`Language`]: crate::language::Language
+    /// [`Package`]: crate::package::Package
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Returns the [`PackageMetadata`] of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    /// Returns an [`ItemView`] of every registered file of the [`Package`], ordered by source
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn items(&self) -> &[ItemView] {
+        &self.items
+    }
+
+    /// Returns a [`DependencyView`] of every [`Dependency`] of the [`Package`], ordered by [`Url`]
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    pub fn dependencies(&self) -> &[DependencyView] {
+        &self.dependencies
+    }
+
+    /// Returns the output directories of the stray dependencies of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn strays(&self) -> &[PathBuf] {
+        &self.strays
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`RegistrySnapshot`] is an owned, read-only description of everything in a [`Registry`]
+/// taken by [`Registry::snapshot`]
+///
+/// It does not borrow the [`Registry`], so it can be handed to front-ends or serialized, but does not follow later changes
+///
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::snapshot`]: crate::registry::Registry::snapshot
+pub struct RegistrySnapshot {
+    pub(crate) packages: Vec<PackageView>,
+}
+
+impl RegistrySnapshot {
+    /// Returns a [`PackageView`] of every registered [`Package`], ordered by `local_location`
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn packages(&self) -> &[PackageView] {
+        &self.packages
+    }
+
+    /// Returns the [`PackageView`] of the [`Package`] whose directory contains the given [`Path`], if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn get_package<P: AsRef<Path>>(&self, path: P) -> Option<&PackageView> {
+        self.packages.iter().find(|p| path.as_ref().starts_with(&p.local_location))
+    }
+
+    /// Returns the [`ItemView`] of the file at the given absolute [`Path`], if it is registered
+    pub fn get_item<P: AsRef<Path>>(&self, source: P) -> Option<&ItemView> {
+        self.get_package(&source)?.items.iter().find(|i| i.source == source.as_ref())
+    }
+}
@@ -0,0 +1,220 @@
+use crate::dependency::Dependency;
+use crate::error::RegistryError;
+use crate::fetcher::Provenance;
+use crate::item::RegistryItem;
+use crate::language::Language;
+use crate::package::Package;
+use crate::registry::{Registry, RegistryOrigin};
+use crate::version::Version;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The read-only lookups shared by a [`Registry`] and a [`RegistryStack`],
+/// so code resolving [`Package`]s and [`Dependency`]s works with either
+pub trait RegistryQuery {
+    /// Retrieves the [`Package`] registered at the given `local_location`, see [`Registry::get_by_local_location`]
+    fn get_by_local_location<P: AsRef<Path>>(&self, local_location: P) -> Option<&Package>;
+
+    /// Retrieves the [`Package`] containing the given source file, see [`Registry::get_by_source`]
+    fn get_by_source<P: AsRef<Path>>(&self, source: P) -> Option<&Package>;
+
+    /// Retrieves the item at the given source file, see [`Registry::get_item`]
+    fn get_item<P: AsRef<Path>>(&self, source: P) -> Option<RegistryItem>;
+
+    /// Retrieves a [`Package`] by identifier and optionally [`Version`], see [`Registry::get_by_identifier`]
+    fn get_by_identifier(&self, identifier: &str, version: Option<&Version>) -> Option<&Package>;
+
+    /// Retrieves the [`Package`] the given [`Dependency`] resolves to, see [`Registry::resolve_dependency`]
+    fn resolve_dependency(&self, dependency: &Dependency) -> Option<&Package>;
+
+    /// Retrieves the [`Provenance`] of the given [`Package`], see [`Registry::get_provenance`]
+    fn get_provenance(&self, package: &Package) -> Option<&Provenance>;
+
+    /// Retrieves the [`Language`] registered under the given name, see [`Registry::get_language`]
+    fn get_language(&self, name: &str) -> Option<&Language>;
+
+    /// Checks whether the given [`Package`] is registered, see [`Registry::contains`]
+    fn contains(&self, package: &Package) -> bool;
+
+    /// Returns every registered [`Package`]
+    fn packages(&self) -> Vec<&Package>;
+}
+
+impl RegistryQuery for Registry {
+    fn get_by_local_location<P: AsRef<Path>>(&self, local_location: P) -> Option<&Package> {
+        Registry::get_by_local_location(self, local_location)
+    }
+
+    fn get_by_source<P: AsRef<Path>>(&self, source: P) -> Option<&Package> {
+        Registry::get_by_source(self, source)
+    }
+
+    fn get_item<P: AsRef<Path>>(&self, source: P) -> Option<RegistryItem> {
+        Registry::get_item(self, source)
+    }
+
+    fn get_by_identifier(&self, identifier: &str, version: Option<&Version>) -> Option<&Package> {
+        Registry::get_by_identifier(self, identifier, version)
+    }
+
+    fn resolve_dependency(&self, dependency: &Dependency) -> Option<&Package> {
+        Registry::resolve_dependency(self, dependency)
+    }
+
+    fn get_provenance(&self, package: &Package) -> Option<&Provenance> {
+        Registry::get_provenance(self, package)
+    }
+
+    fn get_language(&self, name: &str) -> Option<&Language> {
+        Registry::get_language(self, name)
+    }
+
+    fn contains(&self, package: &Package) -> bool {
+        Registry::contains(self, package)
+    }
+
+    fn packages(&self) -> Vec<&Package> {
+        self.iter_packages().collect()
+    }
+}
+
+#[derive(Debug)]
+/// A [`RegistryStack`] layers [`Registry`]s over each other, e.g. a project registry over the global one
+/// Lookups consult the layers from the top down and return the first answer, changes go to the top layer
+///
+/// # Examples
+/// ```
+/// # use std::{env, fs};
+/// # use git2::Repository;
+/// # use knapsac_lib::package::Package;
+/// # use knapsac_lib::registry::Registry;
+/// # use knapsac_lib::stack::{RegistryQuery, RegistryStack};
+///
+/// let mut global = Registry::initialize(env::temp_dir().join("registry_stack_global.json"));
+/// let shared_path = env::temp_dir().join("mock_package_stack_shared");
+/// # let _ = fs::remove_dir_all(&shared_path);
+/// Repository::init(&shared_path);
+/// let shared = Package::create(&shared_path);
+/// global.add(shared.clone());
+///
+/// let project = Registry::initialize(env::temp_dir().join("registry_stack_project.json"));
+/// let mut stack = RegistryStack::create(project);
+/// stack.push_layer(global);
+///
+/// let local_path = env::temp_dir().join("mock_package_stack_local");
+/// # let _ = fs::remove_dir_all(&local_path);
+/// Repository::init(&local_path);
+/// let local = Package::create(&local_path);
+/// stack.top_mut().add(local.clone());
+///
+/// assert!(stack.contains(&shared) && stack.contains(&local));
+/// assert_eq!(stack.layer_of(&shared), Some(1));
+/// assert_eq!(stack.layer_of(&local), Some(0));
+/// assert!(!stack.layers()[1].contains(&local));
+/// ```
+pub struct RegistryStack {
+    pub(crate) layers: Vec<Registry>,
+}
+
+impl RegistryStack {
+    /// Creates a [`RegistryStack`] holding only the given top layer, the [`Registry`] changes go to
+    pub fn create(top: Registry) -> Self {
+        RegistryStack { layers: vec![top] }
+    }
+
+    /// Finds the [`Registry`]s to use for the given directory, see [`Registry::discover`]
+    /// Every project-local registry in the directory and its ancestors becomes a layer, the nearest one on top,
+    /// and the global registry is the bottom layer, see [`Registry::global`]
+    ///
+    /// # Arguments
+    /// * `start` - A [`Path`] pointing to the directory to start searching from
+    pub fn discover<P: AsRef<Path>>(start: P) -> Result<Self, RegistryError> {
+        let mut stack = RegistryStack::create(Registry::discover(start)?);
+        while let RegistryOrigin::Project(project) = stack.layers[stack.layers.len() - 1].origin().clone() {
+            let layer = match project.parent() {
+                Some(parent) => Registry::discover(parent)?,
+                None => Registry::global()?,
+            };
+            stack.push_layer(layer);
+        }
+        Ok(stack)
+    }
+
+    /// Adds a [`Registry`] below the existing layers, consulted after all of them
+    pub fn push_layer(&mut self, registry: Registry) {
+        self.layers.push(registry);
+    }
+
+    /// Returns the layers, from the top down
+    pub fn layers(&self) -> &[Registry] {
+        &self.layers
+    }
+
+    /// Returns the top layer
+    pub fn top(&self) -> &Registry {
+        &self.layers[0]
+    }
+
+    /// Returns the top layer, the [`Registry`] changes go to
+    pub fn top_mut(&mut self) -> &mut Registry {
+        &mut self.layers[0]
+    }
+
+    /// Returns the index of the highest layer registering the given [`Package`], `0` being the top layer
+    pub fn layer_of(&self, package: &Package) -> Option<usize> {
+        self.layers.iter().position(|l| l.contains(package))
+    }
+
+    /// Returns the locations of the files of the layers, from the top down
+    pub fn locations(&self) -> Vec<PathBuf> {
+        self.layers.iter().map(|l| l.location().to_path_buf()).collect()
+    }
+
+    fn first<'a, T, F: Fn(&'a Registry) -> Option<T>>(&'a self, lookup: F) -> Option<T> {
+        self.layers.iter().find_map(lookup)
+    }
+}
+
+impl RegistryQuery for RegistryStack {
+    fn get_by_local_location<P: AsRef<Path>>(&self, local_location: P) -> Option<&Package> {
+        self.first(|l| l.get_by_local_location(&local_location))
+    }
+
+    fn get_by_source<P: AsRef<Path>>(&self, source: P) -> Option<&Package> {
+        self.first(|l| l.get_by_source(&source))
+    }
+
+    fn get_item<P: AsRef<Path>>(&self, source: P) -> Option<RegistryItem> {
+        self.first(|l| l.get_item(&source))
+    }
+
+    fn get_by_identifier(&self, identifier: &str, version: Option<&Version>) -> Option<&Package> {
+        self.first(|l| l.get_by_identifier(identifier, version))
+    }
+
+    fn resolve_dependency(&self, dependency: &Dependency) -> Option<&Package> {
+        self.first(|l| l.resolve_dependency(dependency))
+    }
+
+    fn get_provenance(&self, package: &Package) -> Option<&Provenance> {
+        self.first(|l| l.get_provenance(package))
+    }
+
+    fn get_language(&self, name: &str) -> Option<&Language> {
+        self.first(|l| l.get_language(name))
+    }
+
+    fn contains(&self, package: &Package) -> bool {
+        self.layers.iter().any(|l| l.contains(package))
+    }
+
+    /// Returns every registered [`Package`], a [`Package`] registered in several layers only once, from its highest layer
+    fn packages(&self) -> Vec<&Package> {
+        let mut seen: HashSet<&Path> = HashSet::new();
+        self.layers.iter()
+            .flat_map(|l| l.iter_packages())
+            .filter(|p| seen.insert(p.local_location.as_path()))
+            .collect()
+    }
+}
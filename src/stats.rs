@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// [`RegistryStats`] summarize the contents and health of a [`Registry`], see [`Registry::stats`]
+///
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::stats`]: crate::registry::Registry::stats
+pub struct RegistryStats {
+    pub(crate) packages: usize,
+    pub(crate) modules: usize,
+    pub(crate) executables: usize,
+    pub(crate) dependency_edges: usize,
+    pub(crate) dangling_dependencies: usize,
+    pub(crate) missing_files: usize,
+    pub(crate) missing_strays: usize,
+    pub(crate) output_bytes: u64,
+}
+
+impl RegistryStats {
+    /// Returns the number of registered [`Package`]s
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn packages(&self) -> usize {
+        self.packages
+    }
+
+    /// Returns the number of registered items, [`Module`]s and [`Executable`]s together
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Module`]: crate::module::Module
+    pub fn items(&self) -> usize {
+        self.modules + self.executables
+    }
+
+    /// Returns the number of [`Module`]s provided by registered [`Package`]s
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    pub fn modules(&self) -> usize {
+        self.modules
+    }
+
+    /// Returns the number of [`Executable`]s provided by registered [`Package`]s
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Package`]: crate::package::Package
+    pub fn executables(&self) -> usize {
+        self.executables
+    }
+
+    /// Returns the number of [`Dependency`]s declared by registered [`Package`]s
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    pub fn dependency_edges(&self) -> usize {
+        self.dependency_edges
+    }
+
+    /// Returns the number of [`Dependency`]s that do not resolve to a registered [`Package`]
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    pub fn dangling_dependencies(&self) -> usize {
+        self.dangling_dependencies
+    }
+
+    /// Returns the number of registered [`Package`] directories and item files that no longer exist
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn missing_files(&self) -> usize {
+        self.missing_files
+    }
+
+    /// Returns the number of stray dependencies whose output directory no longer exists, see [`Registry::audit_strays`]
+    ///
+    /// [`Registry::audit_strays`]: crate::registry::Registry::audit_strays
+    pub fn missing_strays(&self) -> usize {
+        self.missing_strays
+    }
+
+    /// Returns the number of dangling references of any kind
+    pub fn dangling(&self) -> usize {
+        self.dangling_dependencies + self.missing_files + self.missing_strays
+    }
+
+    /// Returns the total size in bytes of the files in the output directories of registered [`Module`]s
+    /// Output directories shared by several [`Module`]s are counted once
+    ///
+    /// [`Module`]: crate::module::Module
+    pub fn output_bytes(&self) -> u64 {
+        self.output_bytes
+    }
+
+    /// Checks whether the [`Registry`] holds no dangling references
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    pub fn is_healthy(&self) -> bool {
+        self.dangling() == 0
+    }
+}
@@ -0,0 +1,156 @@
+use crate::error::ManifestError;
+
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`SchemaViolation`] is an entry of a manifest that parses, but cannot be right
+pub enum SchemaViolation {
+    /// The location of a [`Module`] is absolute, while locations are relative to the [`Package`]
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    AbsoluteModuleLocation(PathBuf),
+    /// The location of a [`Module`] points outside of the [`Package`]
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    ModuleOutsidePackage(PathBuf),
+    /// Several [`Module`]s are listed at the same location
+    ///
+    /// [`Module`]: crate::module::Module
+    DuplicateModuleLocation(PathBuf),
+    /// The feature with the given name lists a location no [`Module`] is listed at
+    ///
+    /// [`Module`]: crate::module::Module
+    UnknownFeatureModule(String, PathBuf),
+    /// A stray dependency is a relative path, while stray dependencies are absolute directories
+    RelativeStray(PathBuf),
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaViolation::AbsoluteModuleLocation(path) => write!(f, "module location {} is absolute", path.display()),
+            SchemaViolation::ModuleOutsidePackage(path) => write!(f, "module location {} lies outside the package", path.display()),
+            SchemaViolation::DuplicateModuleLocation(path) => write!(f, "several modules are listed at {}", path.display()),
+            SchemaViolation::UnknownFeatureModule(feature, path) => write!(
+                f,
+                "feature `{}` lists {}, which is not a module",
+                feature,
+                path.display()
+            ),
+            SchemaViolation::RelativeStray(path) => write!(f, "stray dependency {} is not absolute", path.display()),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`Discrepancy`] is a difference [`Registry::sync_package`] found between a manifest and the state of the [`Registry`]
+///
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::sync_package`]: crate::registry::Registry::sync_package
+pub enum Discrepancy {
+    /// The remote location registered for the [`Package`] is the first one, while its git repository points to the second one
+    /// The [`Registry`] takes over the second one
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Registry`]: crate::registry::Registry
+    RemoteLocation(Option<Url>, Option<Url>),
+    /// The source file of a [`Module`] at the given [`PathBuf`] does not exist
+    ///
+    /// [`Module`]: crate::module::Module
+    MissingModuleFile(PathBuf),
+    /// The [`Module`] with the source file at the given [`PathBuf`] changed since its content hash was recorded,
+    /// the recorded content hash is updated, see [`Module::content_hash`]
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Module::content_hash`]: crate::module::Module::content_hash
+    ChangedModule(PathBuf),
+    /// No registered [`Package`] is located at the remote [`Url`] of a [`Dependency`]
+    ///
+    /// [`Dependency`]: crate::dependency::Dependency
+    /// [`Package`]: crate::package::Package
+    UnresolvedDependency(Url),
+    /// The [`Package`] located at the given [`PathBuf`] is registered under the same identifier and [`Version`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Version`]: crate::version::Version
+    IdentifierConflict(String, PathBuf),
+}
+
+impl Display for Discrepancy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Discrepancy::RemoteLocation(registered, actual) => {
+                let display = |url: &Option<Url>| url.as_ref().map_or("none".to_string(), Url::to_string);
+                write!(f, "remote location changed from {} to {}", display(registered), display(actual))
+            }
+            Discrepancy::MissingModuleFile(path) => write!(f, "{} is missing", path.display()),
+            Discrepancy::ChangedModule(path) => write!(f, "{} changed since its content hash was recorded", path.display()),
+            Discrepancy::UnresolvedDependency(url) => write!(f, "no registered package is located at {}", url),
+            Discrepancy::IdentifierConflict(identifier, path) => write!(
+                f,
+                "{} is registered as `{}` with the same version",
+                path.display(),
+                identifier
+            ),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`SyncReport`] lists the [`Discrepancy`]s [`Registry::sync_package`] found
+///
+/// [`Registry::sync_package`]: crate::registry::Registry::sync_package
+pub struct SyncReport {
+    pub(crate) discrepancies: Vec<Discrepancy>,
+}
+
+impl SyncReport {
+    /// Returns the [`Discrepancy`]s found
+    pub fn discrepancies(&self) -> &[Discrepancy] {
+        &self.discrepancies
+    }
+
+    /// Whether the manifest agreed with the [`Registry`]
+    ///
+    /// [`Registry`]: crate::registry::Registry
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`SyncError`] explains why [`Registry::sync_package`] could not reconcile a manifest
+///
+/// [`Registry::sync_package`]: crate::registry::Registry::sync_package
+pub enum SyncError {
+    /// No [`Package`] is registered at the given [`PathBuf`]
+    ///
+    /// [`Package`]: crate::package::Package
+    UnknownPackage(PathBuf),
+    /// The manifest is not valid, see [`ManifestError`]
+    Manifest(ManifestError),
+}
+
+impl Display for SyncError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncError::UnknownPackage(path) => write!(f, "no package registered @ {}", path.display()),
+            SyncError::Manifest(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
@@ -0,0 +1,141 @@
+use crate::run::{RunError, RunOutput};
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Serialize)]
+#[derive(Hash)]
+#[derive(Eq, PartialEq)]
+#[derive(Clone)]
+#[derive(Debug)]
+/// A [`TestModule`] is a program that tests a [`Module`] or the [`Package`] as a whole
+/// It is built like an [`Executable`] and passes when it exits successfully
+///
+/// [`Executable`]: crate::executable::Executable
+/// [`Module`]: crate::module::Module
+/// [`Package`]: crate::package::Package
+pub struct TestModule {
+    pub(crate) location: PathBuf,
+    pub(crate) target: Option<PathBuf>,
+}
+
+impl TestModule {
+    /// Creates a new [`TestModule`]
+    ///
+    /// # Arguments
+    /// * `path` - A relative [`Path`] that points to the source file of the [`TestModule`] within a [`Package`]
+    /// * `target` - A relative [`Path`] that points to the [`Module`] being tested, `None` to test the [`Package`] as a whole
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::path::Path;
+    /// # use knapsac_lib::testing::TestModule;
+    ///
+    /// let test = TestModule::create("tests/a.sac", Some("src/a.sac"));
+    /// assert_eq!(test.location(), Path::new("tests/a.sac"));
+    /// assert_eq!(test.target(), Some(Path::new("src/a.sac")));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics when one of the received paths is absolute
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    pub fn create<P: AsRef<Path>, Q: AsRef<Path>>(path: P, target: Option<Q>) -> Self {
+        if path.as_ref().is_absolute() || target.as_ref().is_some_and(|t| t.as_ref().is_absolute()) {
+            panic!("Path is absolute")
+        }
+        TestModule {
+            location: path.as_ref().to_path_buf(),
+            target: target.map(|t| t.as_ref().to_path_buf()),
+        }
+    }
+
+    /// Returns the [`Path`] of the source file, relative to the [`Package`]'s `local_location`
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn location(&self) -> &Path {
+        &self.location
+    }
+
+    /// Returns the [`Path`] of the [`Module`] being tested, relative to the [`Package`]'s `local_location`
+    /// Returns `None` when the [`TestModule`] tests the [`Package`] as a whole
+    ///
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    pub fn target(&self) -> Option<&Path> {
+        self.target.as_deref()
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`TestOutcome`] tells how running a single [`TestModule`] went
+pub enum TestOutcome {
+    /// The [`TestModule`] exited successfully
+    Passed(RunOutput),
+    /// The [`TestModule`] exited with a failure
+    Failed(RunOutput),
+    /// The [`TestModule`] could not be built or started, see [`RunError`]
+    Error(RunError),
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`TestResult`] pairs the absolute source file of a [`TestModule`] with its [`TestOutcome`]
+pub struct TestResult {
+    pub(crate) source: PathBuf,
+    pub(crate) outcome: TestOutcome,
+}
+
+impl TestResult {
+    /// Returns the absolute [`Path`] of the source file of the [`TestModule`]
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Returns the [`TestOutcome`] of the [`TestModule`]
+    pub fn outcome(&self) -> &TestOutcome {
+        &self.outcome
+    }
+
+    /// Checks whether the [`TestModule`] passed
+    pub fn passed(&self) -> bool {
+        matches!(self.outcome, TestOutcome::Passed(_))
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`TestReport`] collects the [`TestResult`]s of a [`Registry::test`] run
+///
+/// [`Registry::test`]: crate::registry::Registry::test
+pub struct TestReport {
+    pub(crate) results: Vec<TestResult>,
+}
+
+impl TestReport {
+    /// Returns the [`TestResult`] of every [`TestModule`] that was run, in the order they were run
+    pub fn results(&self) -> &[TestResult] {
+        &self.results
+    }
+
+    /// Returns the [`TestResult`]s of the [`TestModule`]s that passed
+    pub fn passed(&self) -> Vec<&TestResult> {
+        self.results.iter().filter(|r| r.passed()).collect()
+    }
+
+    /// Returns the [`TestResult`]s of the [`TestModule`]s that failed or could not be run
+    pub fn failed(&self) -> Vec<&TestResult> {
+        self.results.iter().filter(|r| !r.passed()).collect()
+    }
+
+    /// Checks whether every [`TestModule`] passed
+    pub fn is_ok(&self) -> bool {
+        self.results.iter().all(|r| r.passed())
+    }
+}
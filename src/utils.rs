@@ -1,5 +1,16 @@
+use crate::git_cache;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use git2::Repository;
+use serde::{Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{copy, create_dir_all, read, read_dir, rename, write};
 use std::path::{Path, PathBuf};
+use tar::{Archive, Builder, Header};
 
 pub(crate) fn discover_git_repository<P: AsRef<Path>>(path: P) -> Repository {
     match Repository::discover(&path) {
@@ -11,11 +22,196 @@ pub(crate) fn discover_git_repository<P: AsRef<Path>>(path: P) -> Repository {
 }
 
 pub(crate) fn infer_working_directory<P: AsRef<Path>>(path: P) -> PathBuf {
-    discover_git_repository(&path)
-        .workdir()
-        .expect(&*format!(
-            "Failed to find root of local repository for path '{}'",
-            path.as_ref().display(),
-        ))
-        .to_path_buf()
+    git_cache::workdir(path)
+}
+
+/// Compares two dot separated version strings component by component, numerically where possible
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+    loop {
+        let ordering = match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Lists the paths of all files in the given directory relative to it, sorted
+/// The `.git` directory is skipped, as are symbolic links to directories, which are not descended into
+pub(crate) fn list_files<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
+    collect_files(path, false)
+}
+
+/// Lists the paths of all files in the given directory relative to it, sorted, including the `.git` directory
+pub(crate) fn list_all_files<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
+    collect_files(path, true)
+}
+
+fn collect_files<P: AsRef<Path>>(path: P, include_git: bool) -> Vec<PathBuf> {
+    fn collect(root: &Path, dir: &Path, include_git: bool, files: &mut Vec<PathBuf>) {
+        for entry in read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if !include_git && entry.file_name() == ".git" {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                collect(root, &path, include_git, files);
+            } else if !(file_type.is_symlink() && path.is_dir()) {
+                files.push(path.strip_prefix(root).unwrap().to_path_buf());
+            }
+        }
+    }
+
+    let mut files = vec![];
+    collect(path.as_ref(), path.as_ref(), include_git, &mut files);
+    files.sort();
+    files
+}
+
+/// Checks whether a relative [`Path`] matches a glob pattern of `/` separated components
+/// Within a component `*` matches any run of characters and `?` matches one character,
+/// a component that is `**` matches any number of directories
+pub(crate) fn glob_matches<P: AsRef<Path>>(pattern: &str, path: P) -> bool {
+    fn component(pattern: &[char], name: &[char]) -> bool {
+        match (pattern.first(), name.first()) {
+            (None, None) => true,
+            (Some('*'), _) => component(&pattern[1..], name) || (!name.is_empty() && component(pattern, &name[1..])),
+            (Some('?'), Some(_)) => component(&pattern[1..], &name[1..]),
+            (Some(p), Some(n)) if p == n => component(&pattern[1..], &name[1..]),
+            _ => false,
+        }
+    }
+    fn components(pattern: &[&str], names: &[String]) -> bool {
+        match (pattern.first(), names.first()) {
+            (None, None) => true,
+            (Some(&"**"), _) => components(&pattern[1..], names) || (!names.is_empty() && components(pattern, &names[1..])),
+            (Some(p), Some(n)) => {
+                let p: Vec<char> = p.chars().collect();
+                let n: Vec<char> = n.chars().collect();
+                component(&p, &n) && components(&pattern[1..], &names[1..])
+            }
+            _ => false,
+        }
+    }
+
+    let pattern: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let names: Vec<String> = path.as_ref().components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+    components(&pattern, &names)
+}
+
+/// Copies all files in the given directory to another directory, creating it when needed
+/// The `.git` directory is skipped
+pub(crate) fn copy_directory<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) {
+    for file in list_files(&from) {
+        let destination = to.as_ref().join(&file);
+        create_dir_all(destination.parent().unwrap()).unwrap();
+        copy(from.as_ref().join(&file), destination).unwrap();
+    }
+}
+
+/// Computes a SHA-256 checksum over the relative paths and contents of all files in the given directory
+/// The `.git` directory is skipped
+pub(crate) fn hash_directory<P: AsRef<Path>>(path: P) -> String {
+    let mut hasher = Sha256::new();
+    for file in list_files(&path) {
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update(read(path.as_ref().join(&file)).unwrap());
+    }
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Checks whether the file at the given [`Path`] is an executable
+/// On unix this means any execute permission bit is set, elsewhere the file has an `exe` extension
+pub(crate) fn is_executable<P: AsRef<Path>>(path: P) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path).map(|m| m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.as_ref().extension().map_or(false, |e| e.eq_ignore_ascii_case("exe"))
+    }
+}
+
+/// Runs blocking work, like git and network operations, on the blocking thread pool of the tokio runtime
+/// A panic of the work is resumed on the awaiting task
+#[cfg(feature = "tokio")]
+pub(crate) async fn spawn_blocking<R: Send + 'static, F: FnOnce() -> R + Send + 'static>(work: F) -> R {
+    tokio::task::spawn_blocking(work).await.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+/// Serializes a [`HashSet`] as a sequence sorted by the JSON representation of its elements,
+/// so saving the same contents always produces the same file
+pub(crate) fn sorted_set<T: Serialize, S: Serializer>(set: &HashSet<T>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut elements: Vec<(String, &T)> = set.iter().map(|e| (serde_json::to_string(e).unwrap_or_default(), e)).collect();
+    elements.sort_by(|a, b| a.0.cmp(&b.0));
+    serializer.collect_seq(elements.into_iter().map(|(_, e)| e))
+}
+
+/// Serializes a [`HashMap`] with its keys sorted, so saving the same contents always produces the same file
+pub(crate) fn sorted_map<K: Serialize + Ord, V: Serialize, S: Serializer>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().collect::<BTreeMap<&K, &V>>())
+}
+
+/// Computes the SHA-256 hash of the given data as a lowercase hexadecimal string
+pub(crate) fn sha256_hex<D: AsRef<[u8]>>(data: D) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Packs the given files in the given directory into a gzipped tarball, see [`list_files`]
+/// Entries are sorted and carry no timestamps or owners, so equal contents produce equal archives
+pub(crate) fn pack_directory<P: AsRef<Path>>(path: P, files: Vec<PathBuf>) -> Vec<u8> {
+    pack_files(files.into_iter().map(|f| (path.as_ref().join(&f), f)).collect())
+}
+
+/// Packs the given files into a deterministic gzipped tarball, each stored under the archive path it is paired with
+pub(crate) fn pack_files(files: Vec<(PathBuf, PathBuf)>) -> Vec<u8> {
+    let mut builder = Builder::new(GzEncoder::new(vec![], Compression::default()));
+    for (file, archived) in files {
+        let contents = read(&file).unwrap();
+        let mut header = Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(match is_executable(&file) {
+            true => 0o755,
+            false => 0o644,
+        });
+        header.set_mtime(0);
+        builder.append_data(&mut header, &archived, contents.as_slice()).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+/// Writes the given contents to a temporary file next to `path` and moves it into place,
+/// so readers see either the old or the new contents but never a partially written file
+pub(crate) fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> std::io::Result<()> {
+    let mut temporary = path.as_ref().as_os_str().to_os_string();
+    temporary.push(".tmp");
+    write(&temporary, contents)?;
+    rename(&temporary, path)
+}
+
+/// Unpacks a gzipped tarball created by [`pack_directory`] into the given directory
+/// Entries pointing outside the directory are rejected
+pub(crate) fn unpack_archive<P: AsRef<Path>>(data: &[u8], destination: P) -> Result<(), String> {
+    let mut archive = Archive::new(GzDecoder::new(data));
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        if !entry.unpack_in(&destination).map_err(|e| e.to_string())? {
+            return Err(format!("Archive entry {} lies outside the destination", entry.path().map_err(|e| e.to_string())?.display()));
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,111 @@
+use crate::version::Version;
+
+use serde::{Deserialize, Serialize};
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`LockedPackage`] pins a single vendored [`Package`] in a [`Lockfile`]
+///
+/// [`Package`]: crate::package::Package
+pub struct LockedPackage {
+    pub(crate) identifier: String,
+    pub(crate) version: Option<Version>,
+    pub(crate) source: Option<Url>,
+    pub(crate) checksum: String,
+    pub(crate) path: PathBuf,
+}
+
+impl LockedPackage {
+    /// Returns the identifier of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Returns the [`Version`] of the [`Package`] that was vendored, if it has one
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// Returns the remote location the [`Package`] was obtained from, if any
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn source(&self) -> Option<&Url> {
+        self.source.as_ref()
+    }
+
+    /// Returns the checksum of the registered [`Package`] at the time it was vendored, see [`Package::checksum`]
+    ///
+    /// [`Package`]: crate::package::Package
+    /// [`Package::checksum`]: crate::package::Package::checksum
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+
+    /// Returns the [`Path`] of the vendored copy, relative to the directory containing the [`Lockfile`]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq, Eq)]
+/// A [`Lockfile`] lists every [`Package`] copied into a `vendor` directory by [`Registry::vendor`],
+/// so a project can be built without a [`Registry`]
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry`]: crate::registry::Registry
+/// [`Registry::vendor`]: crate::registry::Registry::vendor
+pub struct Lockfile {
+    pub(crate) packages: Vec<LockedPackage>,
+}
+
+impl Lockfile {
+    /// The name of the file a [`Lockfile`] is written to
+    pub const FILE_NAME: &'static str = "knapsac.lock";
+
+    /// Loads a [`Lockfile`] that was written by [`Registry::vendor`]
+    ///
+    /// # Arguments
+    /// * `path` - A [`Path`] pointing to the file of the [`Lockfile`]
+    ///
+    /// # Panics
+    /// Panics when the file does not exist or does not contain a [`Lockfile`]
+    ///
+    /// [`Registry::vendor`]: crate::registry::Registry::vendor
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let data = read_to_string(&path)
+            .unwrap_or_else(|_| panic!("No lockfile found @ {}", path.as_ref().display()));
+        serde_json::from_str(&data).unwrap()
+    }
+
+    pub(crate) fn save<P: AsRef<Path>>(&self, path: P) {
+        write(path, serde_json::to_string_pretty(self).unwrap()).unwrap();
+    }
+
+    /// Returns every [`LockedPackage`], in the order the dependencies were resolved
+    pub fn packages(&self) -> &[LockedPackage] {
+        &self.packages
+    }
+
+    /// Retrieves the [`LockedPackage`] with the given identifier
+    ///
+    /// # Arguments
+    /// * `identifier` - The identifier of the [`Package`]
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn get(&self, identifier: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|p| p.identifier == identifier)
+    }
+}
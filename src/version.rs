@@ -0,0 +1,484 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`ParseVersionError`] explains why a string is not a valid [`Version`] or [`VersionReq`]
+pub struct ParseVersionError(pub(crate) String);
+
+impl Display for ParseVersionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid version: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseVersionError {}
+
+#[derive(Clone)]
+#[derive(Debug)]
+/// A [`Version`] is a semantic version, e.g. `1.2.3`, `1.3.0-alpha.1` or `1.3.0+build.5`
+///
+/// [`Version`]s are ordered by precedence as defined by semantic versioning:
+/// pre-release versions precede the release they belong to and build metadata is ignored
+/// Two [`Version`]s that differ only in build metadata are therefore equal
+///
+/// # Examples
+/// ```
+/// # use knapsac_lib::version::Version;
+///
+/// let mut versions: Vec<Version> = ["1.10.0", "1.2.0", "1.2.0-rc.1", "1.2.0-alpha"]
+///     .iter()
+///     .map(|v| v.parse().unwrap())
+///     .collect();
+/// versions.sort();
+/// let sorted: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+/// assert_eq!(sorted, vec!["1.2.0-alpha", "1.2.0-rc.1", "1.2.0", "1.10.0"]);
+/// assert_eq!("1.0.0+a".parse::<Version>(), "1.0.0+b".parse::<Version>());
+/// ```
+pub struct Version {
+    pub(crate) major: u64,
+    pub(crate) minor: u64,
+    pub(crate) patch: u64,
+    pub(crate) pre: Vec<String>,
+    pub(crate) build: Vec<String>,
+}
+
+impl Version {
+    /// Creates a new release [`Version`] without pre-release identifiers or build metadata
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: vec![],
+            build: vec![],
+        }
+    }
+
+    /// Returns the major version
+    pub fn major(&self) -> u64 {
+        self.major
+    }
+
+    /// Returns the minor version
+    pub fn minor(&self) -> u64 {
+        self.minor
+    }
+
+    /// Returns the patch version
+    pub fn patch(&self) -> u64 {
+        self.patch
+    }
+
+    /// Returns the dot separated pre-release identifiers, empty for a release
+    pub fn pre(&self) -> &[String] {
+        &self.pre
+    }
+
+    /// Returns the dot separated build metadata identifiers
+    pub fn build(&self) -> &[String] {
+        &self.build
+    }
+
+    /// Checks whether the [`Version`] is a pre-release
+    pub fn is_pre_release(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    fn parse_identifiers(identifiers: &str, input: &str) -> Result<Vec<String>, ParseVersionError> {
+        let identifiers: Vec<String> = identifiers.split('.').map(String::from).collect();
+        let valid = |i: &String| !i.is_empty() && i.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        match identifiers.iter().all(valid) {
+            true => Ok(identifiers),
+            false => Err(ParseVersionError(input.to_string())),
+        }
+    }
+
+    fn compare_pre(a: &[String], b: &[String]) -> Ordering {
+        match (a.is_empty(), b.is_empty()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Greater,
+            (false, true) => return Ordering::Less,
+            (false, false) => {}
+        }
+        for (a, b) in a.iter().zip(b) {
+            let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        a.len().cmp(&b.len())
+    }
+}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    /// Parses a [`Version`], a leading `v` is allowed
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || ParseVersionError(input.to_string());
+        let trimmed = input.trim().trim_start_matches('v');
+        let (rest, build) = match trimmed.split_once('+') {
+            Some((rest, build)) => (rest, Version::parse_identifiers(build, input)?),
+            None => (trimmed, vec![]),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, Version::parse_identifiers(pre, input)?),
+            None => (rest, vec![]),
+        };
+        let numbers: Vec<u64> = core
+            .split('.')
+            .map(|n| n.parse::<u64>().map_err(|_| error()))
+            .collect::<Result<_, _>>()?;
+        match numbers[..] {
+            [major, minor, patch] => Ok(Version { major, minor, patch, pre, build }),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl Hash for Version {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.major, self.minor, self.patch, &self.pre).hash(state);
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| Version::compare_pre(&self.pre, &other.pre))
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`SemVerIncrement`] describes how the next [`Version`] of a [`Package`] is derived from the current one
+///
+/// [`Package`]: crate::package::Package
+pub enum SemVerIncrement {
+    /// `1.2.3` becomes `2.0.0`
+    Major,
+    /// `1.2.3` becomes `1.3.0`
+    Minor,
+    /// `1.2.3` becomes `1.2.4`
+    Patch,
+    /// `1.2.3` becomes `2.0.0-<label>.1`
+    PreMajor(String),
+    /// `1.2.3` becomes `1.3.0-<label>.1`
+    PreMinor(String),
+    /// `1.2.3` becomes `1.2.4-<label>.1`
+    PrePatch(String),
+    /// `1.3.0-<label>.1` becomes `1.3.0-<label>.2`, other versions become a [`SemVerIncrement::PrePatch`]
+    PreRelease(String),
+    /// `1.3.0-<label>.2` is promoted to `1.3.0`, releases become a [`SemVerIncrement::Patch`]
+    Release,
+}
+
+impl Version {
+    /// Returns the [`Version`] following this [`Version`] according to the given [`SemVerIncrement`]
+    /// Build metadata is never carried over
+    ///
+    /// # Arguments
+    /// * `increment` - The [`SemVerIncrement`] to apply
+    ///
+    /// # Examples
+    /// ```
+    /// # use knapsac_lib::version::{SemVerIncrement, Version};
+    ///
+    /// let version = Version::new(1, 2, 0);
+    /// let alpha = version.increment(&SemVerIncrement::PreMinor("alpha".to_string()));
+    /// assert_eq!(alpha.to_string(), "1.3.0-alpha.1");
+    /// let alpha = alpha.increment(&SemVerIncrement::PreRelease("alpha".to_string()));
+    /// assert_eq!(alpha.to_string(), "1.3.0-alpha.2");
+    /// let beta = alpha.increment(&SemVerIncrement::PreRelease("beta".to_string()));
+    /// assert_eq!(beta.to_string(), "1.3.0-beta.1");
+    /// assert_eq!(beta.increment(&SemVerIncrement::Release).to_string(), "1.3.0");
+    /// ```
+    pub fn increment(&self, increment: &SemVerIncrement) -> Version {
+        let pre = |label: &String| vec![label.clone(), String::from("1")];
+        let (major, minor, patch) = (self.major, self.minor, self.patch);
+        match increment {
+            SemVerIncrement::Major => Version::new(major + 1, 0, 0),
+            SemVerIncrement::Minor => Version::new(major, minor + 1, 0),
+            SemVerIncrement::Patch => Version::new(major, minor, patch + 1),
+            SemVerIncrement::PreMajor(label) => Version { pre: pre(label), ..Version::new(major + 1, 0, 0) },
+            SemVerIncrement::PreMinor(label) => Version { pre: pre(label), ..Version::new(major, minor + 1, 0) },
+            SemVerIncrement::PrePatch(label) => Version { pre: pre(label), ..Version::new(major, minor, patch + 1) },
+            SemVerIncrement::PreRelease(label) if self.is_pre_release() => {
+                let next = match &self.pre[..] {
+                    [current, counter] if current == label => counter.parse::<u64>().ok().map(|c| c + 1),
+                    _ => None,
+                };
+                let pre = match next {
+                    Some(counter) => vec![label.clone(), counter.to_string()],
+                    None => pre(label),
+                };
+                Version { pre, ..Version::new(major, minor, patch) }
+            }
+            SemVerIncrement::PreRelease(label) => self.increment(&SemVerIncrement::PrePatch(label.clone())),
+            SemVerIncrement::Release if self.is_pre_release() => Version::new(major, minor, patch),
+            SemVerIncrement::Release => self.increment(&SemVerIncrement::Patch),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+enum Operator {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+struct Comparator {
+    operator: Operator,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<String>,
+}
+
+impl Comparator {
+    fn lower(&self) -> Version {
+        Version {
+            major: self.major,
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            pre: self.pre.clone(),
+            build: vec![],
+        }
+    }
+
+    /// The first version that no longer matches a `~` or `^` comparator
+    fn upper(&self) -> Version {
+        let (major, minor, patch) = (self.major, self.minor.unwrap_or(0), self.patch.unwrap_or(0));
+        let caret = self.operator == Operator::Caret;
+        let upper = match (self.minor, self.patch) {
+            (None, _) => Version::new(major + 1, 0, 0),
+            _ if caret && major > 0 => Version::new(major + 1, 0, 0),
+            (Some(_), None) => Version::new(major, minor + 1, 0),
+            _ if !caret || minor > 0 => Version::new(major, minor + 1, 0),
+            _ => Version::new(major, minor, patch + 1),
+        };
+        Version { pre: vec![String::from("0")], ..upper }
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let lower = self.lower();
+        let partial = self.minor.is_none() || self.patch.is_none();
+        match self.operator {
+            Operator::Exact if partial => version >= &lower && version < &self.upper_exact(),
+            Operator::Exact => version == &lower,
+            Operator::Greater if partial => version >= &self.upper_exact(),
+            Operator::Greater => version > &lower,
+            Operator::GreaterEq => version >= &lower,
+            Operator::Less => version < &lower,
+            Operator::LessEq if partial => version < &self.upper_exact(),
+            Operator::LessEq => version <= &lower,
+            Operator::Tilde | Operator::Caret => version >= &lower && version < &self.upper(),
+        }
+    }
+
+    /// The first version that no longer matches a partial version, e.g. `2.0.0-0` for `1`
+    fn upper_exact(&self) -> Version {
+        let upper = match (self.minor, self.patch) {
+            (None, _) => Version::new(self.major + 1, 0, 0),
+            (Some(minor), None) => Version::new(self.major, minor + 1, 0),
+            (Some(minor), Some(patch)) => Version::new(self.major, minor, patch + 1),
+        };
+        Version { pre: vec![String::from("0")], ..upper }
+    }
+}
+
+impl FromStr for Comparator {
+    type Err = ParseVersionError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || ParseVersionError(input.to_string());
+        let input = input.trim();
+        let split = input.find(|c: char| c.is_ascii_digit()).ok_or_else(error)?;
+        let (operator, version) = input.split_at(split);
+        let operator = match operator.trim().trim_end_matches('v') {
+            "=" => Operator::Exact,
+            ">" => Operator::Greater,
+            ">=" => Operator::GreaterEq,
+            "<" => Operator::Less,
+            "<=" => Operator::LessEq,
+            "~" => Operator::Tilde,
+            "^" | "" => Operator::Caret,
+            _ => return Err(error()),
+        };
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Version::parse_identifiers(pre, input)?),
+            None => (version, vec![]),
+        };
+        let mut numbers = core.split('.').map(|n| n.parse::<u64>().map_err(|_| error()));
+        let major = numbers.next().ok_or_else(error)??;
+        let minor = numbers.next().transpose()?;
+        let patch = numbers.next().transpose()?;
+        if numbers.next().is_some() || (!pre.is_empty() && patch.is_none()) {
+            return Err(error());
+        }
+        Ok(Comparator { operator, major, minor, patch, pre })
+    }
+}
+
+impl Display for Comparator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let operator = match self.operator {
+            Operator::Exact => "=",
+            Operator::Greater => ">",
+            Operator::GreaterEq => ">=",
+            Operator::Less => "<",
+            Operator::LessEq => "<=",
+            Operator::Tilde => "~",
+            Operator::Caret => "^",
+        };
+        write!(f, "{}{}", operator, self.major)?;
+        if let Some(minor) = self.minor {
+            write!(f, ".{}", minor)?;
+        }
+        if let Some(patch) = self.patch {
+            write!(f, ".{}", patch)?;
+        }
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`VersionReq`] describes the [`Version`]s that are accepted, e.g. `^1.2`, `~1.2.3` or `>=1.0, <2.0`
+///
+/// A requirement is a comma separated list of comparisons that all need to match
+/// A comparison is one of `=`, `>`, `>=`, `<`, `<=`, `~` or `^` followed by a possibly partial version,
+/// a version without an operator is treated as `^`
+/// Pre-release [`Version`]s only match when a comparison mentions a pre-release of the same release
+///
+/// # Examples
+/// ```
+/// # use knapsac_lib::version::{Version, VersionReq};
+///
+/// let requirement: VersionReq = "^1.2".parse().unwrap();
+/// assert!(requirement.matches(&"1.9.0".parse::<Version>().unwrap()));
+/// assert!(!requirement.matches(&"2.0.0".parse::<Version>().unwrap()));
+/// assert!(!requirement.matches(&"1.3.0-alpha".parse::<Version>().unwrap()));
+///
+/// let requirement: VersionReq = ">=1.3.0-alpha, <2".parse().unwrap();
+/// assert!(requirement.matches(&"1.3.0-beta".parse::<Version>().unwrap()));
+///
+/// let requirement: VersionReq = "^0.2.3".parse().unwrap();
+/// assert!(requirement.matches(&Version::new(0, 2, 9)));
+/// assert!(!requirement.matches(&Version::new(0, 3, 0)));
+///
+/// let requirement: VersionReq = "~1.2".parse().unwrap();
+/// assert!(requirement.matches(&Version::new(1, 2, 7)));
+/// assert!(!requirement.matches(&Version::new(1, 3, 0)));
+/// assert!("1.x".parse::<VersionReq>().is_err());
+/// ```
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    /// Checks whether the given [`Version`] satisfies the [`VersionReq`]
+    pub fn matches(&self, version: &Version) -> bool {
+        let all = self.comparators.iter().all(|c| c.matches(version));
+        let pre_release_allowed = !version.is_pre_release() || self.comparators.iter().any(|c| {
+            !c.pre.is_empty() && (c.major, c.minor, c.patch) == (version.major, Some(version.minor), Some(version.patch))
+        });
+        all && pre_release_allowed
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = ParseVersionError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let comparators: Vec<Comparator> = input
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(VersionReq { comparators })
+    }
+}
+
+impl Display for VersionReq {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let comparators: Vec<String> = self.comparators.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", comparators.join(", "))
+    }
+}
+
+impl Serialize for VersionReq {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionReq {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::git_cache;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// What happened to a file reported by a [`WatchEvent`]
+pub enum ChangeKind {
+    /// The file was created
+    Created,
+    /// The contents or metadata of the file changed
+    Modified,
+    /// The file was removed
+    Removed,
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`WatchEvent`] reports a change to a file within a registered [`Package`]
+///
+/// [`Package`]: crate::package::Package
+pub struct WatchEvent {
+    pub(crate) path: PathBuf,
+    pub(crate) package: PathBuf,
+    pub(crate) kind: ChangeKind,
+    pub(crate) registered: bool,
+}
+
+impl WatchEvent {
+    /// Returns the absolute [`Path`] of the file that changed
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the `local_location` of the [`Package`] containing the file
+    ///
+    /// [`Package`]: crate::package::Package
+    pub fn package(&self) -> &Path {
+        &self.package
+    }
+
+    /// Returns what happened to the file
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// Checks whether the file is a registered [`Module`], [`Executable`] or [`TestModule`], or the manifest of its [`Package`]
+    /// Other files, e.g. build outputs, do not require a rebuild
+    ///
+    /// [`Executable`]: crate::executable::Executable
+    /// [`Module`]: crate::module::Module
+    /// [`Package`]: crate::package::Package
+    /// [`TestModule`]: crate::testing::TestModule
+    pub fn is_registered(&self) -> bool {
+        self.registered
+    }
+}
+
+/// A callback passed to [`Registry::watch`], called for every [`WatchEvent`] on the watching thread
+///
+/// [`Registry::watch`]: crate::registry::Registry::watch
+pub type WatchCallback = Box<dyn FnMut(&WatchEvent) + Send>;
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq, Eq)]
+/// A [`WatchError`] explains why [`Registry::watch`] could not start watching
+///
+/// [`Registry::watch`]: crate::registry::Registry::watch
+pub struct WatchError(pub(crate) String);
+
+impl Display for WatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "could not watch the registry: {}", self.0)
+    }
+}
+
+impl std::error::Error for WatchError {}
+
+/// A [`RegistryWatcher`] monitors the directories of the [`Package`]s registered when it was started by [`Registry::watch`]
+/// Every [`WatchEvent`] is passed to the callback, if any, on the watching thread and queued for [`RegistryWatcher::next_event`]
+/// Watching stops when the [`RegistryWatcher`] is dropped
+///
+/// Cached git metadata of a [`Package`] is invalidated whenever one of its files changes
+///
+/// [`Package`]: crate::package::Package
+/// [`Registry::watch`]: crate::registry::Registry::watch
+pub struct RegistryWatcher {
+    _watcher: RecommendedWatcher,
+    events: Receiver<WatchEvent>,
+}
+
+impl RegistryWatcher {
+    pub(crate) fn start(
+        packages: Vec<PathBuf>,
+        sources: HashSet<PathBuf>,
+        mut callback: Option<WatchCallback>,
+    ) -> Result<Self, WatchError> {
+        let (sender, events) = channel();
+        let roots = packages.clone();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else { return };
+            let kind = match event.kind {
+                EventKind::Create(_) => ChangeKind::Created,
+                EventKind::Modify(_) => ChangeKind::Modified,
+                EventKind::Remove(_) => ChangeKind::Removed,
+                _ => return,
+            };
+            for path in event.paths {
+                if path.components().any(|c| c == Component::Normal(".git".as_ref())) {
+                    continue;
+                }
+                let Some(package) = roots.iter().filter(|r| path.starts_with(r)).max_by_key(|r| r.components().count()) else {
+                    continue;
+                };
+                git_cache::invalidate(package);
+                let manifest = path.parent() == Some(package.as_path()) && path.file_stem().is_some_and(|s| s == "manifest");
+                let registered = manifest || sources.contains(&path);
+                let event = WatchEvent { path, package: package.clone(), kind, registered };
+                if let Some(callback) = callback.as_mut() {
+                    callback(&event);
+                }
+                let _ = sender.send(event);
+            }
+        }).map_err(|e| WatchError(e.to_string()))?;
+        for package in &packages {
+            watcher.watch(package, RecursiveMode::Recursive).map_err(|e| WatchError(e.to_string()))?;
+        }
+        Ok(RegistryWatcher { _watcher: watcher, events })
+    }
+
+    /// Waits at most `timeout` for the next [`WatchEvent`] and returns it, if one arrived
+    pub fn next_event(&self, timeout: Duration) -> Option<WatchEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+}